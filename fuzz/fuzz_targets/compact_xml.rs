@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// The main crate is a binary, so the text processing code is included directly
+#[allow(dead_code)]
+#[path = "../../src/text_formatting.rs"]
+mod text_formatting;
+
+fuzz_target!(|xml: &str| {
+    text_formatting::compact_xml(xml);
+});
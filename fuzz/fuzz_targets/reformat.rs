@@ -0,0 +1,37 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// The main crate is a binary, so the text processing code is included directly
+#[allow(dead_code)]
+#[path = "../../src/text_formatting.rs"]
+mod text_formatting;
+
+use text_formatting::{normalize_typography, normalize_whitespace, reformat_and_correct_text, DashStyle, FormatOptions, QuoteStyle, TesseractExportMode, TypographySettings};
+
+fuzz_target!(|input: (u16, Vec<String>, &str)| {
+    let (flags, hyphenated_words, text) = input;
+    let format_options = FormatOptions {
+        reformat_and_correct: true,
+        maintain_newlines: flags & 1 != 0,
+        code_mode: flags & 2 != 0,
+        smart_paragraphs: flags & 4 != 0,
+        collapse_spaces: flags & 8 != 0,
+        trim_trailing_spaces: flags & 16 != 0,
+        normalize_tabs: flags & 32 != 0,
+        typography: TypographySettings {
+            quotes: if flags & 64 != 0 { QuoteStyle::Curly } else { QuoteStyle::Straight },
+            dashes: if flags & 128 != 0 { DashStyle::Ascii } else { DashStyle::Normalized },
+            expand_ligatures: true,
+        },
+        strip_noise: false,
+        programmer_mode: flags & 256 != 0,
+        csv_delimiter: ',',
+        pretty_json: true,
+        export_mode: TesseractExportMode::UTF8,
+    };
+
+    let text = reformat_and_correct_text(text.to_string(), &format_options, &hyphenated_words);
+    let text = normalize_whitespace(text, &format_options);
+    normalize_typography(text, &format_options.typography);
+});
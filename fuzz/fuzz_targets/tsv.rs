@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// The main crate is a binary, so the TSV code is included directly
+#[allow(dead_code)]
+#[path = "../../src/tsv.rs"]
+mod tsv;
+
+fuzz_target!(|tsv_text: &str| {
+    tsv::layout_preserving_text(tsv_text);
+});
@@ -0,0 +1,184 @@
+use std::{sync::{Arc, Mutex}, thread, time::Duration};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use image::{DynamicImage, ImageFormat};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::text_formatting::escape_html;
+
+/// What goes on one side of a flashcard made from a capture.
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
+pub enum AnkiCardContent {
+    Image,
+    Text,
+    ImageAndText,
+    Empty
+}
+
+impl AnkiCardContent {
+    fn has_image(&self) -> bool {
+        matches!(self, AnkiCardContent::Image | AnkiCardContent::ImageAndText)
+    }
+
+    fn has_text(&self) -> bool {
+        matches!(self, AnkiCardContent::Text | AnkiCardContent::ImageAndText)
+    }
+}
+
+/// Where and how captures are added to Anki through the AnkiConnect add-on.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AnkiSettings {
+    #[serde(default = "default_url")]
+    pub url: String,
+    #[serde(default = "default_deck")]
+    pub deck: String,
+    #[serde(default = "default_note_type")]
+    pub note_type: String,
+    #[serde(default = "default_front_field")]
+    pub front_field: String,
+    #[serde(default = "default_back_field")]
+    pub back_field: String,
+    #[serde(default = "default_front")]
+    pub front: AnkiCardContent,
+    #[serde(default = "default_back")]
+    pub back: AnkiCardContent,
+    #[serde(default = "default_tags")]
+    pub tags: Vec<String>,
+}
+
+fn default_url() -> String {
+    "http://127.0.0.1:8765".to_string()
+}
+
+fn default_deck() -> String {
+    "Default".to_string()
+}
+
+fn default_note_type() -> String {
+    "Basic".to_string()
+}
+
+fn default_front_field() -> String {
+    "Front".to_string()
+}
+
+fn default_back_field() -> String {
+    "Back".to_string()
+}
+
+fn default_front() -> AnkiCardContent {
+    AnkiCardContent::Image
+}
+
+fn default_back() -> AnkiCardContent {
+    AnkiCardContent::Text
+}
+
+fn default_tags() -> Vec<String> {
+    vec!["OnScreenOCR".to_string()]
+}
+
+impl Default for AnkiSettings {
+    fn default() -> Self {
+        Self {
+            url: default_url(),
+            deck: default_deck(),
+            note_type: default_note_type(),
+            front_field: default_front_field(),
+            back_field: default_back_field(),
+            front: default_front(),
+            back: default_back(),
+            tags: default_tags()
+        }
+    }
+}
+
+/// Adds notes on a background thread, since AnkiConnect can take a while to answer or not answer at all.
+pub struct AnkiSender {
+    note: Option<Arc<Mutex<Option<Result<String, String>>>>>,
+}
+
+impl AnkiSender {
+    pub fn new() -> Self {
+        AnkiSender { note: None }
+    }
+
+    pub fn is_sending(&self) -> bool {
+        self.note.is_some()
+    }
+
+    /// Starts adding a note. Only one note is added at a time.
+    pub fn start(&mut self, settings: AnkiSettings, text: String, image: DynamicImage) -> Result<(), String> {
+        if self.is_sending() {
+            return Err("A card is already being added".to_string());
+        }
+
+        let state = Arc::new(Mutex::new(None));
+        let thread_state = state.clone();
+        thread::spawn(move || {
+            let result = add_note(&settings, &text, &image).map(|()| settings.deck);
+            *thread_state.lock().unwrap() = Some(result);
+        });
+
+        self.note = Some(state);
+        Ok(())
+    }
+
+    /// Returns the deck the note was added to, or the error, once adding it finishes.
+    pub fn poll(&mut self) -> Option<Result<String, String>> {
+        let result = self.note.as_ref()?.lock().unwrap().take()?;
+        self.note = None;
+        Some(result)
+    }
+}
+
+/// Adds a note with the selection image and recognized text to Anki through AnkiConnect.
+/// Anki has to be running with the AnkiConnect add-on installed.
+fn add_note(settings: &AnkiSettings, text: &str, image: &DynamicImage) -> Result<(), String> {
+    // Fields are HTML, so the text is escaped and keeps its line breaks
+    let text_html = escape_html(text).replace('\n', "<br>");
+    let field = |content: AnkiCardContent| if content.has_text() { text_html.clone() } else { String::new() };
+
+    let mut fields = serde_json::Map::new();
+    fields.insert(settings.front_field.clone(), Value::String(field(settings.front)));
+    fields.insert(settings.back_field.clone(), Value::String(field(settings.back)));
+    let mut note = json!({
+        "deckName": settings.deck,
+        "modelName": settings.note_type,
+        "fields": fields,
+        "options": { "allowDuplicate": false },
+        "tags": settings.tags
+    });
+
+    // AnkiConnect stores the picture in Anki's media folder and appends it to the listed fields
+    let image_fields: Vec<&String> = [(settings.front, &settings.front_field), (settings.back, &settings.back_field)].into_iter()
+        .filter(|(content, _)| content.has_image())
+        .map(|(_, field)| field)
+        .collect();
+    if !image_fields.is_empty() {
+        let mut png = std::io::Cursor::new(Vec::new());
+        image.write_to(&mut png, ImageFormat::Png).map_err(|e| format!("Unable to encode the image: {}", e))?;
+        let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_millis();
+        note["picture"] = json!([{
+            "data": BASE64.encode(png.into_inner()),
+            "filename": format!("onscreenocr-{}.png", timestamp),
+            "fields": image_fields
+        }]);
+    }
+
+    let request = json!({ "action": "addNote", "version": 6, "params": { "note": note } });
+    let response = ureq::post(&settings.url)
+        .timeout(Duration::from_secs(10))
+        .set("Content-Type", "application/json")
+        .send_string(&request.to_string())
+        .map_err(|e| format!("Unable to reach AnkiConnect (is Anki running?): {}", e))?
+        .into_string()
+        .map_err(|e| format!("Unable to read the AnkiConnect response: {}", e))?;
+
+    let response: Value = serde_json::from_str(&response).map_err(|e| format!("Unexpected AnkiConnect response: {}", e))?;
+    match response.get("error") {
+        Some(Value::String(error)) => Err(format!("Anki couldn't add the note: {}", error)),
+        _ => Ok(())
+    }
+}
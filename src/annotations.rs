@@ -0,0 +1,127 @@
+use std::{ops::Range, path::{Path, PathBuf}};
+
+use serde_json::{json, Value};
+
+use crate::{export::unique_timestamped_path, geometry::{Point, Rect}, screenshot::Screenshot, settings::get_project_dirs, tsv::{parse_tsv, TSV_WORD_LEVEL}, word_choices::find_whole_word};
+
+/// A recognized word and where it is on the screenshot.
+#[derive(Debug, Clone)]
+pub(crate) struct WordBox {
+    pub text: String,
+    pub confidence: f32,
+    pub bounds: Rect,
+}
+
+/// Reads the word boxes out of a TSV export. Tesseract's positions are relative to the
+/// cropped selection, so they're moved by `origin` to line up with the full screenshot.
+pub(crate) fn word_boxes_from_tsv(tsv: &str, origin: Point) -> Vec<WordBox> {
+    parse_tsv(tsv).into_iter()
+        .filter(|row| row.level == TSV_WORD_LEVEL && !row.text.trim().is_empty())
+        .map(|row| WordBox {
+            text: row.text.trim().to_string(),
+            confidence: row.conf,
+            bounds: Rect::new(row.left as f32 + origin.x, row.top as f32 + origin.y, row.width as f32, row.height as f32)
+        })
+        .collect()
+}
+
+/// Finds each word box's word in the text, in order, as a range of characters. Words Tesseract's
+/// text doesn't contain where expected, like ones corrected since, are left out.
+pub(crate) fn locate_word_boxes(text: &str, word_boxes: &[WordBox]) -> Vec<(Range<usize>, Rect)> {
+    let mut located = Vec::new();
+    let mut search_start = 0;
+    let mut characters_before = 0;
+    for word in word_boxes {
+        let Some(offset) = find_whole_word(&text[search_start..], &word.text) else {
+            continue;
+        };
+        let start = characters_before + text[search_start..search_start + offset].chars().count();
+        let end = start + word.text.chars().count();
+        search_start += offset + word.text.len();
+        characters_before = end;
+        located.push((start..end, word.bounds));
+    }
+    located
+}
+
+pub(crate) fn get_annotations_directory() -> PathBuf {
+    get_project_dirs().data_dir().join("annotations")
+}
+
+/// Writes the screenshot along with a LabelMe-compatible JSON file containing the selection
+/// polygon and every word box, so the capture can be opened in annotation tools or used as
+/// training data. The screenshot is the one the word boxes were recognized from, rather than the copy
+/// saved to disk, which can lag behind. Returns the directory the files were written to.
+pub(crate) fn export_annotations(screenshot: &Screenshot, selection: &[Point], word_boxes: &[WordBox]) -> Result<PathBuf, String> {
+    let (width, height) = (screenshot.width, screenshot.height);
+    let directory = unique_timestamped_path(&get_annotations_directory(), "", "");
+    save_screenshot(screenshot, &directory)?;
+
+    let mut shapes = vec![shape("selection", selection.iter().map(|point| (point.x, point.y)).collect(), "polygon", Value::Null)];
+    for word in word_boxes {
+        let points = vec![(word.bounds.x, word.bounds.y), (word.bounds.x + word.bounds.width, word.bounds.y + word.bounds.height)];
+        shapes.push(shape(&word.text, points, "rectangle", json!(format!("confidence: {:.0}", word.confidence))));
+    }
+
+    let annotations = json!({
+        "version": "5.4.1",
+        "flags": {},
+        "shapes": shapes,
+        "imagePath": "screenshot.png",
+        "imageData": null,
+        "imageWidth": width,
+        "imageHeight": height
+    });
+    let encoded = serde_json::to_string_pretty(&annotations).map_err(|e| format!("Unable to encode annotations: {}", e))?;
+    std::fs::write(directory.join("screenshot.json"), encoded).map_err(|e| format!("Unable to write annotations: {}", e))?;
+
+    Ok(directory)
+}
+
+/// Writes the screenshot along with the selection's vertices and bounds, as an SVG path drawn over the
+/// screenshot and as JSON, so documentation and annotation tools can reuse the exact region.
+/// Returns the directory the files were written to.
+pub(crate) fn export_region(screenshot: &Screenshot, selection: &[Point], bounds: Rect) -> Result<PathBuf, String> {
+    let (width, height) = (screenshot.width, screenshot.height);
+    let directory = unique_timestamped_path(&get_annotations_directory(), "", "-region");
+    save_screenshot(screenshot, &directory)?;
+
+    let path = selection.iter().enumerate()
+        .map(|(index, point)| format!("{} {} {}", if index == 0 { "M" } else { "L" }, point.x, point.y))
+        .collect::<Vec<String>>()
+        .join(" ");
+    let svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n  <image href=\"screenshot.png\" width=\"{width}\" height=\"{height}\"/>\n  <path d=\"{path} Z\" fill=\"none\" stroke=\"#ff0000\" stroke-width=\"2\"/>\n</svg>\n"
+    );
+    std::fs::write(directory.join("region.svg"), svg).map_err(|e| format!("Unable to write the region SVG: {}", e))?;
+
+    let region = json!({
+        "bounds": { "x": bounds.x, "y": bounds.y, "width": bounds.width, "height": bounds.height },
+        "vertices": selection.iter().map(|point| [point.x, point.y]).collect::<Vec<[f32; 2]>>(),
+        "imagePath": "screenshot.png",
+        "imageWidth": width,
+        "imageHeight": height
+    });
+    let encoded = serde_json::to_string_pretty(&region).map_err(|e| format!("Unable to encode the region: {}", e))?;
+    std::fs::write(directory.join("region.json"), encoded).map_err(|e| format!("Unable to write the region JSON: {}", e))?;
+
+    Ok(directory)
+}
+
+/// Creates the directory and writes the screenshot to `screenshot.png` in it.
+fn save_screenshot(screenshot: &Screenshot, directory: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(directory).map_err(|e| format!("Unable to create annotations directory: {}", e))?;
+    image::save_buffer(directory.join("screenshot.png"), &screenshot.bytes, screenshot.width as u32, screenshot.height as u32, image::ExtendedColorType::Rgba8)
+        .map_err(|e| format!("Unable to save the screenshot: {}", e))
+}
+
+fn shape(label: &str, points: Vec<(f32, f32)>, shape_type: &str, description: Value) -> Value {
+    json!({
+        "label": label,
+        "points": points.into_iter().map(|(x, y)| [x, y]).collect::<Vec<[f32; 2]>>(),
+        "group_id": null,
+        "description": description,
+        "shape_type": shape_type,
+        "flags": {}
+    })
+}
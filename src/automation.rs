@@ -0,0 +1,361 @@
+use std::{io::{BufRead, BufReader, Read, Write}, sync::mpsc, thread, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use winit::event_loop::EventLoopProxy;
+
+use crate::AppEvent;
+
+/// How long a client waits for the application to handle a command before giving up
+const REPLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[cfg(windows)]
+const PIPE_NAME: &str = r"\\.\pipe\OnScreenOCR";
+
+#[cfg(target_os = "linux")]
+const DBUS_NAME: &str = "com.github.Glitch752.OnScreenOCR";
+#[cfg(target_os = "linux")]
+const DBUS_PATH: &str = "/com/github/Glitch752/OnScreenOCR";
+
+/// An action other programs can ask for. On Windows, these are sent over the named pipe, and on Unix
+/// over a socket, one per line: either a JSON object, e.g.
+/// `{"command": "capture_region", "x": 0, "y": 0, "width": 200, "height": 50}`, or a plain command,
+/// e.g. `ocr-region 0 0 200 50` (see `parse_line_command`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub(crate) enum AutomationCommand {
+    OpenOverlay,
+    CaptureRegion { x: i32, y: i32, width: u32, height: u32 },
+    GetLastResult,
+    SetLanguage { code: String },
+    /// An `onscreenocr://` link, forwarded by a second instance that was started to open it
+    OpenUri { uri: String },
+}
+
+/// Where the main thread sends the result of a command
+pub(crate) type AutomationReply = mpsc::Sender<Result<String, String>>;
+
+/// Starts listening for automation clients in the background.
+pub(crate) fn start(loop_proxy: EventLoopProxy<AppEvent>) {
+    #[cfg(unix)]
+    {
+        let loop_proxy = loop_proxy.clone();
+        thread::spawn(move || {
+            if let Err(error) = serve_socket(loop_proxy) {
+                eprintln!("Automation socket unavailable: {}", error);
+            }
+        });
+    }
+
+    thread::spawn(move || {
+        if let Err(error) = serve(loop_proxy) {
+            eprintln!("Automation interface unavailable: {}", error);
+        }
+    });
+}
+
+pub(crate) fn send_command(loop_proxy: &EventLoopProxy<AppEvent>, command: AutomationCommand) -> Result<String, String> {
+    let (tx, rx) = mpsc::channel();
+    loop_proxy.send_event(AppEvent::Automation(command, tx)).map_err(|_| "The application is shutting down".to_string())?;
+    rx.recv_timeout(REPLY_TIMEOUT).map_err(|_| "Timed out waiting for the application".to_string())?
+}
+
+#[cfg(windows)]
+fn serve(loop_proxy: EventLoopProxy<AppEvent>) -> Result<(), String> {
+    use std::os::windows::io::FromRawHandle;
+    use windows_sys::Win32::{
+        Foundation::{GetLastError, ERROR_PIPE_CONNECTED, INVALID_HANDLE_VALUE},
+        Security::SECURITY_ATTRIBUTES,
+        Storage::FileSystem::{FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_DUPLEX},
+        System::Pipes::{ConnectNamedPipe, CreateNamedPipeW, PIPE_READMODE_BYTE, PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT}
+    };
+
+    let name: Vec<u16> = PIPE_NAME.encode_utf16().chain(std::iter::once(0)).collect();
+    // Anyone who can connect can capture the screen and read the last result, so only the current user can
+    let security_attributes = SECURITY_ATTRIBUTES {
+        nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+        lpSecurityDescriptor: current_user_security_descriptor()?,
+        bInheritHandle: 0
+    };
+    println!("Listening for automation commands on {}", PIPE_NAME);
+
+    let mut first_instance = true;
+    loop {
+        // If another process already created the first instance, it would be the one receiving our clients' commands
+        let first_instance_flag = if first_instance { FILE_FLAG_FIRST_PIPE_INSTANCE } else { 0 };
+        let pipe = unsafe {
+            CreateNamedPipeW(
+                name.as_ptr(),
+                PIPE_ACCESS_DUPLEX | first_instance_flag,
+                PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+                PIPE_UNLIMITED_INSTANCES,
+                4096,
+                4096,
+                0,
+                &security_attributes
+            )
+        };
+        if pipe == INVALID_HANDLE_VALUE {
+            return Err(format!("Unable to create {}", PIPE_NAME));
+        }
+        first_instance = false;
+
+        // A client can connect between creating the pipe and waiting for it, which isn't an error
+        let connected = unsafe { ConnectNamedPipe(pipe, std::ptr::null_mut()) != 0 || GetLastError() == ERROR_PIPE_CONNECTED };
+        // Closes the pipe when dropped
+        let pipe = unsafe { std::fs::File::from_raw_handle(pipe as _) };
+        if connected {
+            // Each client gets its own thread so the next instance is created right away; a client holding
+            // its connection open would otherwise keep everyone else out
+            let loop_proxy = loop_proxy.clone();
+            thread::spawn(move || handle_client(&pipe, &pipe, &loop_proxy));
+        }
+    }
+}
+
+/// Creates a security descriptor that only grants the current user access. It's never freed, since the
+/// pipe server uses it for as long as the application runs.
+#[cfg(windows)]
+fn current_user_security_descriptor() -> Result<windows_sys::Win32::Security::PSECURITY_DESCRIPTOR, String> {
+    use windows_sys::Win32::{
+        Foundation::{CloseHandle, LocalFree},
+        Security::{GetTokenInformation, TokenUser, PSECURITY_DESCRIPTOR, TOKEN_QUERY, TOKEN_USER, Authorization::{ConvertSidToStringSidW, ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1}},
+        System::Threading::{GetCurrentProcess, OpenProcessToken}
+    };
+
+    unsafe {
+        let mut token = 0;
+        if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) == 0 {
+            return Err("Unable to open the process token".to_string());
+        }
+        let mut length = 0;
+        GetTokenInformation(token, TokenUser, std::ptr::null_mut(), 0, &mut length);
+        // TOKEN_USER holds pointers, so the buffer needs their alignment
+        let mut buffer = vec![0usize; (length as usize).div_ceil(std::mem::size_of::<usize>())];
+        let read = GetTokenInformation(token, TokenUser, buffer.as_mut_ptr() as _, length, &mut length);
+        CloseHandle(token);
+        if read == 0 {
+            return Err("Unable to get the current user".to_string());
+        }
+        let user = &*(buffer.as_ptr() as *const TOKEN_USER);
+
+        let mut sid_pointer = std::ptr::null_mut();
+        if ConvertSidToStringSidW(user.User.Sid, &mut sid_pointer) == 0 {
+            return Err("Unable to get the current user's SID".to_string());
+        }
+        let sid_length = (0..).take_while(|&i| *sid_pointer.add(i) != 0).count();
+        let sid = String::from_utf16_lossy(std::slice::from_raw_parts(sid_pointer, sid_length));
+        LocalFree(sid_pointer as _);
+
+        // A protected DACL with a single entry giving the user full access
+        let sddl: Vec<u16> = format!("D:P(A;;GA;;;{})", sid).encode_utf16().chain(std::iter::once(0)).collect();
+        let mut descriptor: PSECURITY_DESCRIPTOR = std::ptr::null_mut();
+        if ConvertStringSecurityDescriptorToSecurityDescriptorW(sddl.as_ptr(), SDDL_REVISION_1, &mut descriptor, std::ptr::null_mut()) == 0 {
+            return Err("Unable to create the pipe's security descriptor".to_string());
+        }
+        Ok(descriptor)
+    }
+}
+
+/// Listens on a Unix socket in the runtime directory, e.g. `/run/user/1000/onscreenocr/automation.sock`,
+/// for clients that would rather not use D-Bus.
+#[cfg(unix)]
+fn serve_socket(loop_proxy: EventLoopProxy<AppEvent>) -> Result<(), String> {
+    let path = socket_path();
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory).map_err(|error| error.to_string())?;
+    }
+    // A socket left behind by an earlier run would make binding fail
+    let _ = std::fs::remove_file(&path);
+
+    let listener = std::os::unix::net::UnixListener::bind(&path).map_err(|error| format!("Unable to bind {}: {}", path.display(), error))?;
+    println!("Listening for automation commands on {}", path.display());
+    for stream in listener.incoming().flatten() {
+        // Clients are handled on their own threads, like on the named pipe
+        let loop_proxy = loop_proxy.clone();
+        thread::spawn(move || handle_client(&stream, &stream, &loop_proxy));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn socket_path() -> std::path::PathBuf {
+    let project_dirs = crate::settings::get_project_dirs();
+    project_dirs.runtime_dir().unwrap_or(project_dirs.data_dir()).join("automation.sock")
+}
+
+/// Sends a command to an instance that's already running, as a client of its named pipe or socket.
+/// Returns None if no instance is listening.
+#[cfg(any(windows, unix))]
+pub(crate) fn send_to_running_instance(command: &AutomationCommand) -> Option<Result<String, String>> {
+    #[cfg(windows)]
+    let stream = match connect_to_pipe()? {
+        Ok(stream) => stream,
+        Err(error) => return Some(Err(error))
+    };
+    #[cfg(unix)]
+    let stream = std::os::unix::net::UnixStream::connect(socket_path()).ok()?;
+
+    let request = serde_json::to_string(command).map_err(|error| error.to_string());
+    let response = request.and_then(|request| {
+        writeln!(&stream, "{}", request).map_err(|error| format!("Unable to send the command: {}", error))?;
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line).map_err(|error| format!("Unable to read the reply: {}", error))?;
+        serde_json::from_str::<serde_json::Value>(&line).map_err(|error| format!("Invalid reply: {}", error))
+    });
+    Some(response.and_then(|response| match response["ok"].as_bool() {
+        Some(true) => Ok(response["result"].as_str().unwrap_or_default().to_string()),
+        _ => Err(response["error"].as_str().unwrap_or("Unknown error").to_string())
+    }))
+}
+
+/// Opens the client end of the named pipe. If every instance of it is busy, waits for one to be free,
+/// since that still means the application is running. Returns None if no instance is listening.
+#[cfg(windows)]
+fn connect_to_pipe() -> Option<Result<std::fs::File, String>> {
+    use windows_sys::Win32::{Foundation::ERROR_PIPE_BUSY, System::Pipes::WaitNamedPipeW};
+
+    let name: Vec<u16> = PIPE_NAME.encode_utf16().chain(std::iter::once(0)).collect();
+    let deadline = std::time::Instant::now() + REPLY_TIMEOUT;
+    loop {
+        match std::fs::OpenOptions::new().read(true).write(true).open(PIPE_NAME) {
+            Ok(pipe) => return Some(Ok(pipe)),
+            Err(error) if error.raw_os_error() == Some(ERROR_PIPE_BUSY as i32) => {
+                let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+                if remaining.is_zero() {
+                    return Some(Err("Timed out waiting for the running instance".to_string()));
+                }
+                // Another client can take the instance that frees up first, so this just retries either way
+                unsafe { WaitNamedPipeW(name.as_ptr(), remaining.as_millis() as u32) };
+            }
+            Err(_) => return None
+        }
+    }
+}
+
+#[cfg(not(any(windows, unix)))]
+pub(crate) fn send_to_running_instance(_command: &AutomationCommand) -> Option<Result<String, String>> {
+    None
+}
+
+/// Answers each command line from a client. JSON commands get a JSON response, and plain commands a
+/// plain one (see `plain_response`).
+#[cfg(any(windows, unix))]
+fn handle_client(reader: impl Read, mut writer: impl Write, loop_proxy: &EventLoopProxy<AppEvent>) {
+    let mut reader = BufReader::new(reader);
+    let mut line = String::new();
+    while reader.read_line(&mut line).is_ok_and(|read| read > 0) {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            line.clear();
+            continue;
+        }
+
+        let response = if trimmed.starts_with('{') {
+            let result = serde_json::from_str::<AutomationCommand>(trimmed)
+                .map_err(|error| format!("Invalid command: {}", error))
+                .and_then(|command| send_command(loop_proxy, command));
+            match result {
+                Ok(result) => serde_json::json!({ "ok": true, "result": result }).to_string(),
+                Err(error) => serde_json::json!({ "ok": false, "error": error }).to_string()
+            }
+        } else {
+            plain_response(parse_line_command(trimmed).and_then(|command| send_command(loop_proxy, command)))
+        };
+        if writeln!(writer, "{}", response).is_err() {
+            return;
+        }
+        line.clear();
+    }
+}
+
+/// Parses a plain command for simple clients like AutoHotkey scripts:
+/// `open-overlay`, `ocr-region <x> <y> <width> <height>`, `get-last-result`, or `set-language <code>`.
+fn parse_line_command(line: &str) -> Result<AutomationCommand, String> {
+    let mut words = line.split_whitespace();
+    let name = words.next().unwrap_or_default();
+    let arguments: Vec<&str> = words.collect();
+    let number = |index: usize| arguments.get(index)
+        .ok_or_else(|| format!("{} needs 4 arguments: x y width height", name))?
+        .parse::<i64>()
+        .map_err(|_| format!("'{}' isn't a number", arguments[index]));
+
+    match name {
+        "open-overlay" => Ok(AutomationCommand::OpenOverlay),
+        "ocr-region" | "capture-region" => Ok(AutomationCommand::CaptureRegion {
+            x: number(0)? as i32,
+            y: number(1)? as i32,
+            width: number(2)?.max(0) as u32,
+            height: number(3)?.max(0) as u32
+        }),
+        "get-last-result" => Ok(AutomationCommand::GetLastResult),
+        "set-language" => match arguments.first() {
+            Some(code) => Ok(AutomationCommand::SetLanguage { code: code.to_string() }),
+            None => Err("set-language needs a language code".to_string())
+        },
+        _ => Err(format!("Unknown command '{}'", name))
+    }
+}
+
+/// Replies to plain commands are one line: `ok <result>` or `error <message>`, with backslashes and line
+/// breaks escaped as `\\` and `\n` so multi-line results stay on one line.
+fn plain_response(result: Result<String, String>) -> String {
+    let escape = |text: String| text.replace('\\', "\\\\").replace('\r', "").replace('\n', "\\n");
+    match result {
+        Ok(result) if result.is_empty() => "ok".to_string(),
+        Ok(result) => format!("ok {}", escape(result)),
+        Err(error) => format!("error {}", escape(error))
+    }
+}
+
+#[cfg(target_os = "linux")]
+struct AutomationInterface {
+    loop_proxy: std::sync::Mutex<EventLoopProxy<AppEvent>>,
+}
+
+#[cfg(target_os = "linux")]
+impl AutomationInterface {
+    fn send(&self, command: AutomationCommand) -> zbus::fdo::Result<String> {
+        send_command(&self.loop_proxy.lock().unwrap(), command).map_err(zbus::fdo::Error::Failed)
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[zbus::interface(name = "com.github.Glitch752.OnScreenOCR1")]
+impl AutomationInterface {
+    fn open_overlay(&self) -> zbus::fdo::Result<()> {
+        self.send(AutomationCommand::OpenOverlay).map(|_| ())
+    }
+
+    fn capture_region(&self, x: i32, y: i32, width: u32, height: u32) -> zbus::fdo::Result<String> {
+        self.send(AutomationCommand::CaptureRegion { x, y, width, height })
+    }
+
+    fn get_last_result(&self) -> zbus::fdo::Result<String> {
+        self.send(AutomationCommand::GetLastResult)
+    }
+
+    fn set_language(&self, code: String) -> zbus::fdo::Result<()> {
+        self.send(AutomationCommand::SetLanguage { code }).map(|_| ())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn serve(loop_proxy: EventLoopProxy<AppEvent>) -> Result<(), String> {
+    let interface = AutomationInterface { loop_proxy: std::sync::Mutex::new(loop_proxy) };
+    // The connection serves requests on its own threads for as long as it's alive
+    let _connection = zbus::blocking::ConnectionBuilder::session()
+        .and_then(|builder| builder.name(DBUS_NAME))
+        .and_then(|builder| builder.serve_at(DBUS_PATH, interface))
+        .and_then(|builder| builder.build())
+        .map_err(|error| error.to_string())?;
+    println!("Listening for automation commands on D-Bus as {}", DBUS_NAME);
+
+    loop {
+        thread::park();
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+fn serve(_loop_proxy: EventLoopProxy<AppEvent>) -> Result<(), String> {
+    Err("Automation is not supported on this platform".to_string())
+}
@@ -0,0 +1,75 @@
+use crate::geometry::{Polygon, Rect};
+
+const FIELD_NAMES: [&str; 4] = ["x", "y", "w", "h"];
+
+/// A readout of the selection's bounds in which exact coordinates can be typed, opened with B in the overlay.
+#[derive(Debug, Clone)]
+pub(crate) struct BoundsEntry {
+    /// The text of the x, y, width, and height fields, in pixels
+    fields: [String; 4],
+    /// The field being typed in
+    active: usize,
+    /// Whether the active field still shows the selection's value, so typing replaces it instead of appending
+    replace_on_type: bool,
+    /// The selection before editing, restored if the entry is cancelled
+    pub original_bounds: Rect,
+    pub original_polygon: Polygon
+}
+
+impl BoundsEntry {
+    pub fn new(bounds: Rect, polygon: Polygon) -> Self {
+        let pixel_bounds = bounds.to_pixel_rect();
+        BoundsEntry {
+            fields: [pixel_bounds.x, pixel_bounds.y, pixel_bounds.width, pixel_bounds.height].map(|value| format!("{:.0}", value)),
+            active: 0,
+            replace_on_type: true,
+            original_bounds: bounds,
+            original_polygon: polygon
+        }
+    }
+
+    /// Types a character into the active field, ignoring anything but digits.
+    pub fn type_character(&mut self, character: char) {
+        if !character.is_ascii_digit() {
+            return;
+        }
+        if self.replace_on_type {
+            self.fields[self.active].clear();
+            self.replace_on_type = false;
+        }
+        self.fields[self.active].push(character);
+    }
+
+    pub fn backspace(&mut self) {
+        self.replace_on_type = false;
+        self.fields[self.active].pop();
+    }
+
+    /// Moves to the next field, or the previous one if `backwards` is set, wrapping around.
+    pub fn next_field(&mut self, backwards: bool) {
+        self.active = if backwards { (self.active + FIELD_NAMES.len() - 1) % FIELD_NAMES.len() } else { (self.active + 1) % FIELD_NAMES.len() };
+        self.replace_on_type = true;
+    }
+
+    /// The typed bounds, if every field holds a number and they fit on the screen.
+    pub fn bounds(&self, screen_size: (u32, u32)) -> Result<Rect, String> {
+        let mut values = [0f32; 4];
+        for (value, (field, name)) in values.iter_mut().zip(self.fields.iter().zip(FIELD_NAMES)) {
+            *value = field.parse::<u32>().map_err(|_| format!("{} needs a number", name))? as f32;
+        }
+        let [x, y, width, height] = values;
+
+        if width == 0. || height == 0. {
+            return Err("The selection needs a width and height".to_string());
+        }
+        if x + width > screen_size.0 as f32 || y + height > screen_size.1 as f32 {
+            return Err(format!("The selection has to fit on the screen ({}×{})", screen_size.0, screen_size.1));
+        }
+        Ok(Rect::new(x, y, width, height))
+    }
+
+    /// Each field's label and text, along with whether it's the one being typed in.
+    pub fn fields(&self) -> impl Iterator<Item = (String, bool)> + '_ {
+        self.fields.iter().zip(FIELD_NAMES).enumerate().map(|(index, (field, name))| (format!("{}: {}", name, field), index == self.active))
+    }
+}
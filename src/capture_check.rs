@@ -0,0 +1,180 @@
+use std::time::{Duration, Instant};
+
+use pixels::{Pixels, PixelsBuilder, SurfaceTexture};
+use winit::{application::ApplicationHandler, event::WindowEvent, event_loop::{ActiveEventLoop, ControlFlow, EventLoop}, window::{Window, WindowId, WindowLevel}};
+
+use crate::{screenshot::Screenshot, settings::get_project_dirs};
+
+/// Passing this argument runs the capture check instead of the normal application.
+pub(crate) const CAPTURE_CHECK_ARG: &str = "--check-capture";
+
+type CaptureFn = fn((i32, i32), (u32, u32)) -> Result<Screenshot, &'static str>;
+
+/// Every screenshot backend on this platform. New backends should be added here so they're checked against the same pattern.
+#[cfg(target_os = "windows")]
+const CAPTURE_BACKENDS: &[(&str, CaptureFn)] = &[
+    ("GDI", crate::screenshot::screenshot_region),
+];
+/// Region capture is only implemented with GDI, so other platforms have nothing to check yet.
+#[cfg(not(target_os = "windows"))]
+const CAPTURE_BACKENDS: &[(&str, CaptureFn)] = &[];
+
+/// The test pattern is a grid of solid colors, which survives scaling and is easy to compare
+const PATTERN_COLORS: [[u8; 3]; 8] = [
+    [255, 0, 0], [0, 255, 0], [0, 0, 255], [255, 255, 255],
+    [0, 0, 0], [0, 255, 255], [255, 0, 255], [255, 255, 0]
+];
+const PATTERN_COLUMNS: u32 = 4;
+const PATTERN_ROWS: u32 = 2;
+const PATTERN_SIZE: (u32, u32) = (400, 200);
+/// The maximum per-channel difference between a captured and expected color
+const COLOR_TOLERANCE: u8 = 12;
+/// Compositors can take a few frames to show a new window, so wait before capturing
+const SETTLE_TIME: Duration = Duration::from_millis(500);
+
+/// Shows a window with a known pattern, captures it with every screenshot backend, and compares the
+/// results. Returns whether every backend passed; a report is printed and written to the data directory.
+pub(crate) fn run() -> bool {
+    let event_loop = EventLoop::new().expect("Unable to create event loop");
+    let mut check = CaptureCheck {
+        window_state: None,
+        shown_at: None,
+        report: Vec::new(),
+        passed: false
+    };
+    event_loop.run_app(&mut check).expect("Unable to run event loop");
+
+    let report = check.report.join("\n");
+    println!("{}", report);
+    let report_path = get_project_dirs().data_dir().join("capture_check.txt");
+    if std::fs::create_dir_all(get_project_dirs().data_dir()).and_then(|_| std::fs::write(&report_path, &report)).is_ok() {
+        println!("Report written to {}", report_path.to_string_lossy());
+    }
+
+    check.passed
+}
+
+struct CaptureCheck {
+    window_state: Option<(Window, Pixels)>,
+    shown_at: Option<Instant>,
+    report: Vec<String>,
+    passed: bool
+}
+
+impl CaptureCheck {
+    fn check_backends(&mut self) {
+        let Some((window, _)) = &self.window_state else {
+            return;
+        };
+        let Ok(position) = window.inner_position() else {
+            self.report.push("Unable to get the position of the pattern window".to_string());
+            return;
+        };
+        let size = window.inner_size();
+
+        if CAPTURE_BACKENDS.is_empty() {
+            self.report.push(format!("No screenshot backend is available on {}", std::env::consts::OS));
+            return;
+        }
+
+        self.passed = true;
+        for (name, capture) in CAPTURE_BACKENDS {
+            let result = capture((position.x, position.y), (size.width, size.height))
+                .map_err(|e| e.to_string())
+                .and_then(|screenshot| compare_to_pattern(&screenshot, (size.width, size.height)));
+            match result {
+                Ok(()) => self.report.push(format!("{}: passed", name)),
+                Err(error) => {
+                    self.report.push(format!("{}: FAILED - {}", name, error));
+                    self.passed = false;
+                }
+            }
+        }
+    }
+}
+
+impl ApplicationHandler for CaptureCheck {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window_state.is_some() {
+            return;
+        }
+
+        let window = event_loop
+            .create_window(
+                Window::default_attributes()
+                    .with_title("OnScreenOCR capture check")
+                    .with_decorations(false)
+                    .with_resizable(false)
+                    .with_window_level(WindowLevel::AlwaysOnTop)
+                    .with_inner_size(winit::dpi::PhysicalSize::new(PATTERN_SIZE.0, PATTERN_SIZE.1)),
+            )
+            .expect("Unable to create pattern window");
+
+        let surface_texture = SurfaceTexture::new(PATTERN_SIZE.0, PATTERN_SIZE.1, &window);
+        let mut pixels = PixelsBuilder::new(PATTERN_SIZE.0, PATTERN_SIZE.1, surface_texture).build().expect("Unable to create pixels");
+        for (i, pixel) in pixels.frame_mut().chunks_exact_mut(4).enumerate() {
+            let (x, y) = (i as u32 % PATTERN_SIZE.0, i as u32 / PATTERN_SIZE.0);
+            let [r, g, b] = expected_color((x, y), PATTERN_SIZE);
+            pixel.copy_from_slice(&[r, g, b, 255]);
+        }
+
+        window.request_redraw();
+        self.window_state = Some((window, pixels));
+        self.shown_at = Some(Instant::now());
+        event_loop.set_control_flow(ControlFlow::WaitUntil(Instant::now() + SETTLE_TIME));
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _window_id: WindowId, event: WindowEvent) {
+        match event {
+            WindowEvent::RedrawRequested => {
+                if let Some((_, pixels)) = &self.window_state {
+                    if let Err(error) = pixels.render() {
+                        self.report.push(format!("Unable to draw the test pattern: {}", error));
+                        event_loop.exit();
+                    }
+                }
+            }
+            WindowEvent::CloseRequested => event_loop.exit(),
+            _ => ()
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        if self.shown_at.is_some_and(|shown_at| shown_at.elapsed() >= SETTLE_TIME) {
+            self.check_backends();
+            event_loop.exit();
+        }
+    }
+}
+
+/// The color the pattern has at `position` when it's drawn at `size`.
+fn expected_color(position: (u32, u32), size: (u32, u32)) -> [u8; 3] {
+    let column = (position.0 * PATTERN_COLUMNS / size.0).min(PATTERN_COLUMNS - 1);
+    let row = (position.1 * PATTERN_ROWS / size.1).min(PATTERN_ROWS - 1);
+    PATTERN_COLORS[(row * PATTERN_COLUMNS + column) as usize]
+}
+
+fn compare_to_pattern(screenshot: &Screenshot, expected_size: (u32, u32)) -> Result<(), String> {
+    if (screenshot.width as u32, screenshot.height as u32) != expected_size {
+        return Err(format!(
+            "Captured {}×{}, expected {}×{}",
+            screenshot.width, screenshot.height, expected_size.0, expected_size.1
+        ));
+    }
+
+    // Only compare the center of each cell, since edges can be blended by scaling
+    for row in 0..PATTERN_ROWS {
+        for column in 0..PATTERN_COLUMNS {
+            let x = (column * 2 + 1) * expected_size.0 / (PATTERN_COLUMNS * 2);
+            let y = (row * 2 + 1) * expected_size.1 / (PATTERN_ROWS * 2);
+            let idx = (y as usize * screenshot.width + x as usize) * 4;
+            let captured = &screenshot.bytes[idx..idx + 3];
+            let expected = expected_color((x, y), expected_size);
+            if captured.iter().zip(expected.iter()).any(|(a, b)| a.abs_diff(*b) > COLOR_TOLERANCE) {
+                return Err(format!("Pixel at ({}, {}) is {:?}, expected {:?}", x, y, captured, expected));
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use std::{collections::VecDeque, path::PathBuf};
+
+use crate::settings::get_project_dirs;
+
+const HISTORY_FILE_NAME: &str = "history.json";
+
+fn history_path() -> PathBuf {
+    get_project_dirs().data_dir().join(HISTORY_FILE_NAME)
+}
+
+/// The most recently copied OCR results, newest first, so an earlier capture can be copied again.
+pub struct ClipboardHistory {
+    entries: VecDeque<String>,
+    capacity: usize,
+    persist: bool,
+}
+
+impl ClipboardHistory {
+    /// Creates the history, loading the saved entries if it's persisted to disk.
+    pub fn new(capacity: usize, persist: bool) -> Self {
+        let entries = if persist {
+            std::fs::read_to_string(history_path()).ok()
+                .and_then(|contents| serde_json::from_str::<VecDeque<String>>(&contents).ok())
+                .unwrap_or_default()
+        } else {
+            VecDeque::new()
+        };
+
+        let mut history = ClipboardHistory { entries, capacity, persist };
+        history.entries.truncate(capacity);
+        history
+    }
+
+    /// Applies changed settings. Turning persistence off leaves the saved file alone until the next copy.
+    pub fn configure(&mut self, capacity: usize, persist: bool) {
+        self.capacity = capacity;
+        self.persist = persist;
+        self.entries.truncate(capacity);
+    }
+
+    /// Adds a copied text as the newest entry. Copying an earlier entry again moves it to the front.
+    pub fn push(&mut self, text: &str) {
+        if text.trim().is_empty() || self.capacity == 0 {
+            return;
+        }
+        self.entries.retain(|entry| entry != text);
+        self.entries.push_front(text.to_string());
+        self.entries.truncate(self.capacity);
+        self.save();
+    }
+
+    pub fn get(&self, index: usize) -> Option<&String> {
+        self.entries.get(index)
+    }
+
+    /// A single-line summary of an entry for the overlay.
+    pub fn preview(&self, index: usize, max_characters: usize) -> String {
+        let Some(entry) = self.entries.get(index) else {
+            return "-".to_string();
+        };
+        let line = entry.split_whitespace().collect::<Vec<&str>>().join(" ");
+        if line.chars().count() <= max_characters {
+            return line;
+        }
+        line.chars().take(max_characters.saturating_sub(3)).collect::<String>() + "..."
+    }
+
+    fn save(&self) {
+        if !self.persist {
+            return;
+        }
+        let result = std::fs::create_dir_all(get_project_dirs().data_dir())
+            .and_then(|_| std::fs::write(history_path(), serde_json::to_string(&self.entries).unwrap_or_default()));
+        if let Err(error) = result {
+            eprintln!("Unable to save the clipboard history: {}", error);
+        }
+    }
+}
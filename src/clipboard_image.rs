@@ -1,113 +1,78 @@
-use std::ops::Range;
+use image::DynamicImage;
+#[cfg(windows)]
+use image::ImageFormat;
 
-use clipboard_win::{formats, set_clipboard};
-use image::{DynamicImage, GenericImageView};
-// https://stackoverflow.com/a/76741325
-pub fn copy_image_to_clipboard(img: &DynamicImage) {
-    let data = gen_from_img_windows(img);
+/// Copies the image to the clipboard as both CF_DIBV5 and PNG, keeping the transparency of polygon crops.
+pub fn copy_image_to_clipboard(img: &DynamicImage) -> Result<(), String> {
     #[cfg(windows)]
-    set_clipboard(formats::Bitmap, data).unwrap();
+    {
+        crate::clipboard_text::set_windows_clipboard(image_clipboard_formats(img)?, false)
+    }
 
     #[cfg(not(windows))]
-    panic!("Not implemented for this platform");
-}
-
-fn gen_from_img_windows(img: &DynamicImage) -> Vec<u8> {
-    //Flipping image, because scan lines are stored bottom to top
-    let img = img.flipv();
-
-    //Getting the header
-    let mut byte_vec = get_header_windows(img.width(), img.height());
-
-    for (_, _, pixel) in img.pixels() {
-        // Setting the pixels, one by one
-
-        let pixel_bytes = pixel.0;
-        // One pixel is 4 bytes, BGR and unused
-        byte_vec.push(pixel_bytes[2]);
-        byte_vec.push(pixel_bytes[1]);
-        byte_vec.push(pixel_bytes[0]);
-        byte_vec.push(pixel_bytes[3]); //This is unused based on the specifications
+    {
+        let _ = img;
+        Err("Copying images is not supported on this platform".to_string())
     }
-
-    byte_vec
 }
 
-/// Generates the header for the bitmap from the width and height of the image.
-/// [Resources][http://www.ece.ualberta.ca/~elliott/ee552/studentAppNotes/2003_w/misc/bmp_file_format/bmp_file_format.htm].
-fn get_header_windows(width: u32, height: u32) -> Vec<u8> {
-    //Generating the 54 bytes long vector
-    let mut vec = vec![0; 54];
-
-    //BM, as written in specifications
-    vec[0] = 66;
-    vec[1] = 77;
-
-    //File size
-    let file_size = width * height * 4 + 54;
-    set_bytes(&mut vec, &file_size.to_le_bytes(), 2..6);
-
-    //Not used
-    set_bytes(&mut vec, &0_u32.to_le_bytes(), 6..10);
-
-    //Offset from the beginning of the file to the beginning of the image data
-    let offset = 54_u32;
-    set_bytes(&mut vec, &offset.to_le_bytes(), 10..14);
-
-    //Size of the second part of the header
-    let header_size = 40_u32;
-    set_bytes(&mut vec, &header_size.to_le_bytes(), 14..18);
-
-    //Horizontal width
-    let width_bytes = width.to_le_bytes();
-    set_bytes(&mut vec, &width_bytes, 18..22);
+/// The clipboard formats and data for an image. Most apps read CF_DIBV5 (Windows synthesizes the older
+/// bitmap formats from it), while browsers and image editors prefer the registered "PNG" format, which is
+/// also much smaller.
+#[cfg(windows)]
+pub(crate) fn image_clipboard_formats(img: &DynamicImage) -> Result<Vec<(u32, Vec<u8>)>, String> {
+    const CF_DIBV5: u32 = 17;
 
-    //Vertical height
-    let height_bytes = height.to_le_bytes();
-    set_bytes(&mut vec, &height_bytes, 22..26);
+    let mut png = std::io::Cursor::new(Vec::new());
+    img.write_to(&mut png, ImageFormat::Png).map_err(|e| format!("Unable to encode the image: {}", e))?;
 
-    //Number of planes
-    let planes = 1_u16;
-    set_bytes(&mut vec, &planes.to_le_bytes(), 26..28);
-
-    //Bits per pixel
-    let bits_per_pixel = 32_u16;
-    set_bytes(&mut vec, &bits_per_pixel.to_le_bytes(), 28..30);
-
-    //Compression type, 0=no compression
-    let compression_type = 0_u32;
-    set_bytes(&mut vec, &compression_type.to_le_bytes(), 30..34);
-
-    //Compressed size of the image, without the size of the header
-    //This size is correct
-
-    // let compressed_size = file_size - 54;
-
-    //But when there is no compression (compression type=0), 0 is an allowed size.
-    let compressed_size = 0_u32;
-
-    set_bytes(&mut vec, &compressed_size.to_le_bytes(), 34..38);
-
-    //Number of pixels / meter, but 0 is allowed
-    let horizontal_resoultion = 0_u32;
-    set_bytes(&mut vec, &horizontal_resoultion.to_le_bytes(), 38..42);
-
-    let vertical_resolution = 0_u32;
-    set_bytes(&mut vec, &vertical_resolution.to_le_bytes(), 42..46);
-
-    //I guess the last two are for other formats/compression types
-    let actually_used_colors = 0_u32;
-    set_bytes(&mut vec, &actually_used_colors.to_le_bytes(), 46..50);
-
-    let number_of_important_colors = 0_u32;
-    set_bytes(&mut vec, &number_of_important_colors.to_le_bytes(), 50..54);
-
-    vec
+    Ok(vec![(CF_DIBV5, dibv5(img)), (crate::clipboard_text::register_format("PNG")?, png.into_inner())])
 }
 
-/// Replaces the bytes of the `to` slice in the specified range with the bytes of the `from` slice.
-fn set_bytes(to: &mut [u8], from: &[u8], range: Range<usize>) {
-    for (from_zero_index, i) in range.enumerate() {
-        to[i] = from[from_zero_index];
+/// Encodes the image as a BITMAPV5HEADER followed by bottom-up 32-bit BGRA pixels. Unlike a plain
+/// BITMAPINFOHEADER, the V5 header declares an alpha mask, so apps don't have to guess whether the
+/// fourth byte is transparency.
+#[cfg(windows)]
+fn dibv5(img: &DynamicImage) -> Vec<u8> {
+    const HEADER_SIZE: u32 = 124;
+    const BI_BITFIELDS: u32 = 3;
+    const LCS_SRGB: u32 = 0x73524742; // 'sRGB'
+    const LCS_GM_IMAGES: u32 = 4;
+
+    let img = img.to_rgba8();
+    let (width, height) = img.dimensions();
+
+    let mut bytes = Vec::with_capacity(HEADER_SIZE as usize + (width * height * 4) as usize);
+    bytes.extend_from_slice(&HEADER_SIZE.to_le_bytes());
+    bytes.extend_from_slice(&(width as i32).to_le_bytes());
+    // A positive height means the rows are stored bottom to top, which is what most apps expect
+    bytes.extend_from_slice(&(height as i32).to_le_bytes());
+    bytes.extend_from_slice(&1_u16.to_le_bytes()); // Planes
+    bytes.extend_from_slice(&32_u16.to_le_bytes()); // Bits per pixel
+    bytes.extend_from_slice(&BI_BITFIELDS.to_le_bytes());
+    bytes.extend_from_slice(&(width * height * 4).to_le_bytes()); // Image size
+    bytes.extend_from_slice(&0_i32.to_le_bytes()); // Horizontal pixels per meter
+    bytes.extend_from_slice(&0_i32.to_le_bytes()); // Vertical pixels per meter
+    bytes.extend_from_slice(&0_u32.to_le_bytes()); // Colors used
+    bytes.extend_from_slice(&0_u32.to_le_bytes()); // Important colors
+    bytes.extend_from_slice(&0x00FF0000_u32.to_le_bytes()); // Red mask
+    bytes.extend_from_slice(&0x0000FF00_u32.to_le_bytes()); // Green mask
+    bytes.extend_from_slice(&0x000000FF_u32.to_le_bytes()); // Blue mask
+    bytes.extend_from_slice(&0xFF000000_u32.to_le_bytes()); // Alpha mask
+    bytes.extend_from_slice(&LCS_SRGB.to_le_bytes());
+    bytes.extend_from_slice(&[0; 36]); // Endpoints, unused for sRGB
+    bytes.extend_from_slice(&[0; 12]); // Red, green, and blue gamma, unused for sRGB
+    bytes.extend_from_slice(&LCS_GM_IMAGES.to_le_bytes());
+    bytes.extend_from_slice(&0_u32.to_le_bytes()); // Profile data offset
+    bytes.extend_from_slice(&0_u32.to_le_bytes()); // Profile size
+    bytes.extend_from_slice(&0_u32.to_le_bytes()); // Reserved
+
+    for row in img.rows().rev() {
+        for pixel in row {
+            let [r, g, b, a] = pixel.0;
+            bytes.extend_from_slice(&[b, g, r, a]);
+        }
     }
-}
\ No newline at end of file
+
+    bytes
+}
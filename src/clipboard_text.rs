@@ -0,0 +1,228 @@
+use clipboard::{ClipboardContext, ClipboardProvider};
+use image::DynamicImage;
+
+use crate::text_formatting::escape_html;
+
+/// Renders OCR text as HTML so pasting into rich text editors (Word, Gmail, OneNote, etc.) keeps the line
+/// breaks instead of collapsing everything into one paragraph. Code is kept verbatim in a `<pre>` block.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn text_to_html(text: &str, code_mode: bool) -> String {
+    if code_mode {
+        return format!("<pre>{}</pre>", escape_html(text));
+    }
+
+    text.split("\n\n")
+        .map(|paragraph| paragraph.trim_matches('\n'))
+        .filter(|paragraph| !paragraph.trim().is_empty())
+        .map(|paragraph| format!("<p>{}</p>", paragraph.lines().map(escape_html).collect::<Vec<String>>().join("<br>")))
+        .collect::<Vec<String>>()
+        .join("")
+}
+
+/// Wraps an HTML fragment in the CF_HTML format, whose header holds the byte offsets of the document and fragment.
+/// https://learn.microsoft.com/en-us/windows/win32/dataxchg/html-clipboard-format
+#[cfg_attr(not(windows), allow(dead_code))]
+fn cf_html(fragment: &str) -> String {
+    // The offsets are zero-padded, so the header is always the same length
+    let header = |start_html: usize, end_html: usize, start_fragment: usize, end_fragment: usize| format!(
+        "Version:0.9\r\nStartHTML:{:010}\r\nEndHTML:{:010}\r\nStartFragment:{:010}\r\nEndFragment:{:010}\r\n",
+        start_html, end_html, start_fragment, end_fragment
+    );
+
+    let prefix = "<html><body><!--StartFragment-->";
+    let suffix = "<!--EndFragment--></body></html>";
+    let start_html = header(0, 0, 0, 0).len();
+    let start_fragment = start_html + prefix.len();
+    let end_fragment = start_fragment + fragment.len();
+    let end_html = end_fragment + suffix.len();
+
+    format!("{}{}{}{}", header(start_html, end_html, start_fragment, end_fragment), prefix, fragment, suffix)
+}
+
+/// Renders OCR text as RTF for editors that ignore HTML. Line breaks are kept, and code uses a monospaced font.
+/// Tesseract doesn't report emphasis for LSTM models, so there's no bold or italic to carry over.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn text_to_rtf(text: &str, code_mode: bool) -> String {
+    let mut body = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' | '{' | '}' => {
+                body.push('\\');
+                body.push(c);
+            }
+            '\n' => body.push_str("\\line\n"),
+            '\r' => {}
+            '\t' => body.push_str("\\tab "),
+            c if c.is_ascii() => body.push(c),
+            // RTF is ASCII-only; other characters are written as signed UTF-16 code units with "?" as the fallback
+            c => {
+                let mut units = [0u16; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    body.push_str(&format!("\\u{}?", *unit as i16));
+                }
+            }
+        }
+    }
+
+    let font = if code_mode { 1 } else { 0 };
+    format!("{{\\rtf1\\ansi\\deff0{{\\fonttbl{{\\f0\\fswiss Calibri;}}{{\\f1\\fmodern Consolas;}}}}\\f{}\\fs22 {}}}", font, body)
+}
+
+/// Copies the text to the clipboard as plain text and, on Windows, as HTML (and optionally RTF) alongside it.
+/// On Windows, an image can be included in the same transaction so a paste target can pick text or image;
+/// elsewhere only the text is copied. Private copies are kept out of Windows' clipboard history and sync.
+pub(crate) fn copy_rich_text(text: &str, code_mode: bool, include_rtf: bool, image: Option<&DynamicImage>, private: bool) -> Result<(), String> {
+    #[cfg(windows)]
+    {
+        let mut formats = vec![
+            (CF_UNICODETEXT, unicode_text(text)),
+            (register_format("HTML Format")?, (cf_html(&text_to_html(text, code_mode)) + "\0").into_bytes())
+        ];
+        if include_rtf {
+            formats.push((register_format("Rich Text Format")?, (text_to_rtf(text, code_mode) + "\0").into_bytes()));
+        }
+        if let Some(image) = image {
+            formats.extend(crate::clipboard_image::image_clipboard_formats(image)?);
+        }
+        match set_windows_clipboard(formats, private) {
+            Ok(()) => return Ok(()),
+            Err(error) => eprintln!("Unable to copy rich text, falling back to plain text: {}", error)
+        }
+    }
+    // Rich text and images alongside text are only placed on the clipboard on Windows
+    #[cfg(not(windows))]
+    let _ = (code_mode, include_rtf, image, private);
+
+    let mut ctx: ClipboardContext = ClipboardProvider::new().map_err(|e| format!("Unable to open the clipboard: {}", e))?;
+    ctx.set_contents(text.to_string()).map_err(|e| format!("Unable to set clipboard contents: {}", e))
+}
+
+/// Copies the text to the clipboard as plain text only. Private copies are kept out of Windows' clipboard
+/// history and sync.
+pub(crate) fn copy_plain_text(text: &str, private: bool) -> Result<(), String> {
+    #[cfg(windows)]
+    {
+        if private {
+            return set_windows_clipboard(vec![(CF_UNICODETEXT, unicode_text(text))], true);
+        }
+    }
+    #[cfg(not(windows))]
+    let _ = private;
+
+    let mut ctx: ClipboardContext = ClipboardProvider::new().map_err(|e| format!("Unable to open the clipboard: {}", e))?;
+    ctx.set_contents(text.to_string()).map_err(|e| format!("Unable to set clipboard contents: {}", e))
+}
+
+/// Clears the clipboard after the given number of seconds if it still holds the copied text, so secrets
+/// don't linger. Anything copied since, by this app or another, is left alone.
+pub(crate) fn clear_clipboard_after(text: String, seconds: u64) {
+    if seconds == 0 {
+        return;
+    }
+
+    std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_secs(seconds));
+
+        let Ok(mut ctx) = <ClipboardContext as ClipboardProvider>::new() else {
+            return;
+        };
+        if ctx.get_contents().ok().as_deref() != Some(text.as_str()) {
+            return;
+        }
+
+        // Emptying the clipboard also removes the HTML, RTF, and image renditions copied alongside the text
+        #[cfg(windows)]
+        let cleared = set_windows_clipboard(Vec::new(), false);
+        #[cfg(not(windows))]
+        let cleared = ctx.set_contents(String::new()).map_err(|e| e.to_string());
+        if let Err(error) = cleared {
+            eprintln!("Unable to clear the clipboard: {}", error);
+        }
+    });
+}
+
+#[cfg(windows)]
+pub(crate) const CF_UNICODETEXT: u32 = 13;
+
+/// The clipboard's sequence number after OnScreenOCR last wrote to it, so the clipboard watcher can skip
+/// its own copies
+#[cfg(windows)]
+pub(crate) static LAST_OWN_CLIPBOARD_SEQUENCE: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+/// Text encoded for CF_UNICODETEXT: null-terminated little-endian UTF-16.
+#[cfg(windows)]
+fn unicode_text(text: &str) -> Vec<u8> {
+    text.encode_utf16().chain(std::iter::once(0)).flat_map(|unit| unit.to_le_bytes()).collect()
+}
+
+/// Formats that ask clipboard history, cloud clipboard sync, and clipboard managers to skip a copy.
+/// https://learn.microsoft.com/en-us/windows/win32/dataxchg/clipboard-formats#cloud-clipboard-and-clipboard-history-formats
+#[cfg(windows)]
+fn private_formats() -> Result<Vec<(u32, Vec<u8>)>, String> {
+    Ok(vec![
+        // The data of this one is ignored; it only has to be present
+        (register_format("ExcludeClipboardContentFromMonitorProcessing")?, 0u32.to_le_bytes().to_vec()),
+        (register_format("CanIncludeInClipboardHistory")?, 0u32.to_le_bytes().to_vec()),
+        (register_format("CanUploadToCloudClipboard")?, 0u32.to_le_bytes().to_vec())
+    ])
+}
+
+#[cfg(windows)]
+pub(crate) fn register_format(name: &str) -> Result<u32, String> {
+    let name_utf16: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+    match unsafe { windows_sys::Win32::System::DataExchange::RegisterClipboardFormatW(name_utf16.as_ptr()) } {
+        0 => Err(format!("Unable to register the '{}' clipboard format", name)),
+        format => Ok(format)
+    }
+}
+
+/// Replaces the clipboard's contents with the data for each format in a single transaction, so a paste
+/// target can pick whichever format it supports. Text formats need their null terminator included.
+/// Private copies also get the formats that keep them out of clipboard history and sync.
+#[cfg(windows)]
+pub(crate) fn set_windows_clipboard(mut formats: Vec<(u32, Vec<u8>)>, private: bool) -> Result<(), String> {
+    use windows_sys::Win32::System::DataExchange::{CloseClipboard, EmptyClipboard, GetClipboardSequenceNumber, OpenClipboard, SetClipboardData};
+    use windows_sys::Win32::System::Memory::{GlobalAlloc, GlobalFree, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
+
+    /// Copies the bytes into global memory and hands it to the clipboard, which takes ownership of it
+    unsafe fn set_data(format: u32, bytes: &[u8]) -> Result<(), String> {
+        let memory = GlobalAlloc(GMEM_MOVEABLE, bytes.len());
+        if memory as usize == 0 {
+            return Err("Unable to allocate clipboard memory".to_string());
+        }
+        let pointer = GlobalLock(memory) as *mut u8;
+        if pointer.is_null() {
+            GlobalFree(memory);
+            return Err("Unable to lock clipboard memory".to_string());
+        }
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), pointer, bytes.len());
+        GlobalUnlock(memory);
+
+        if SetClipboardData(format, memory as _) as usize == 0 {
+            GlobalFree(memory);
+            return Err("Unable to set clipboard data".to_string());
+        }
+        Ok(())
+    }
+
+    if private {
+        formats.extend(private_formats()?);
+    }
+
+    unsafe {
+        if OpenClipboard(0 as _) == 0 {
+            return Err("Unable to open the clipboard".to_string());
+        }
+
+        let mut result = if EmptyClipboard() == 0 { Err("Unable to empty the clipboard".to_string()) } else { Ok(()) };
+        for (format, bytes) in formats {
+            if result.is_err() {
+                break;
+            }
+            result = set_data(format, &bytes);
+        }
+        CloseClipboard();
+        LAST_OWN_CLIPBOARD_SEQUENCE.store(GetClipboardSequenceNumber(), std::sync::atomic::Ordering::Relaxed);
+        result
+    }
+}
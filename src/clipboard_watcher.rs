@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use winit::event_loop::EventLoopProxy;
+
+use crate::{settings::TesseractSettings, AppEvent};
+
+/// What happens with the text of images other apps copy.
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ClipboardWatcherMode {
+    /// The clipboard isn't watched
+    #[default]
+    Off,
+    /// The image on the clipboard is replaced with its text
+    Replace,
+    /// The text is added to the clipboard alongside the image, so paste targets can pick either
+    Augment,
+    /// A notification offers to copy the text, leaving the clipboard alone
+    Notify
+}
+
+/// Watches the clipboard in the background, recognizing each image copied by another app and sending
+/// the text to the main thread.
+#[cfg(windows)]
+pub(crate) fn start(tesseract_settings: TesseractSettings, loop_proxy: EventLoopProxy<AppEvent>) -> Result<(), String> {
+    use std::time::Duration;
+    use windows_sys::Win32::System::DataExchange::GetClipboardSequenceNumber;
+
+    const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    std::thread::spawn(move || {
+        let mut tess_api = crate::ocr_handler::configure_tesseract(tesseract_settings);
+        let mut last_sequence = unsafe { GetClipboardSequenceNumber() };
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+
+            // The sequence number changes whenever the clipboard does, so its contents are only read then
+            let sequence = unsafe { GetClipboardSequenceNumber() };
+            if sequence == last_sequence {
+                continue;
+            }
+            last_sequence = sequence;
+            // Images copied by OnScreenOCR itself, like screenshots of the selection, are left alone
+            if sequence == crate::clipboard_text::LAST_OWN_CLIPBOARD_SEQUENCE.load(std::sync::atomic::Ordering::Relaxed) {
+                continue;
+            }
+
+            let Some(image) = clipboard_image() else {
+                continue;
+            };
+            match crate::ocr_handler::recognize_image(&mut tess_api, &image.clone().into()) {
+                Ok(text) if !text.trim().is_empty() => {
+                    if loop_proxy.send_event(AppEvent::ClipboardImageRecognized(text.trim().to_string(), image)).is_err() {
+                        return;
+                    }
+                }
+                Ok(_) => {}
+                Err(error) => eprintln!("Unable to recognize the copied image: {}", error)
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub(crate) fn start(_tesseract_settings: TesseractSettings, _loop_proxy: EventLoopProxy<AppEvent>) -> Result<(), String> {
+    Err("Watching the clipboard is only supported on Windows".to_string())
+}
+
+/// Reads the image on the clipboard, preferring the registered "PNG" format over CF_DIB. Returns None if
+/// there's no image, or if there's text alongside it, since then there's nothing to add.
+#[cfg(windows)]
+fn clipboard_image() -> Option<image::DynamicImage> {
+    use image::ImageFormat;
+    use windows_sys::Win32::System::{
+        DataExchange::{CloseClipboard, GetClipboardData, IsClipboardFormatAvailable, OpenClipboard},
+        Memory::{GlobalLock, GlobalSize, GlobalUnlock}
+    };
+
+    const CF_DIB: u32 = 8;
+
+    let png_format = crate::clipboard_text::register_format("PNG").ok();
+    let (format, image_format) = unsafe {
+        if IsClipboardFormatAvailable(crate::clipboard_text::CF_UNICODETEXT) != 0 {
+            return None;
+        }
+        match png_format {
+            Some(png_format) if IsClipboardFormatAvailable(png_format) != 0 => (png_format, ImageFormat::Png),
+            _ if IsClipboardFormatAvailable(CF_DIB) != 0 => (CF_DIB, ImageFormat::Bmp),
+            _ => return None
+        }
+    };
+
+    let bytes = unsafe {
+        if OpenClipboard(0 as _) == 0 {
+            return None;
+        }
+        let handle = GetClipboardData(format);
+        let pointer = if handle == 0 { std::ptr::null_mut() } else { GlobalLock(handle as _) as *const u8 };
+        let bytes = if pointer.is_null() {
+            None
+        } else {
+            let bytes = std::slice::from_raw_parts(pointer, GlobalSize(handle as _)).to_vec();
+            GlobalUnlock(handle as _);
+            Some(bytes)
+        };
+        CloseClipboard();
+        bytes?
+    };
+
+    let bytes = if image_format == ImageFormat::Bmp { bmp_from_dib(&bytes)? } else { bytes };
+    image::load_from_memory_with_format(&bytes, image_format).ok()
+}
+
+/// Turns a CF_DIB into a .bmp file by adding the file header, which needs the offset of the pixels:
+/// after the info header, the color masks of BI_BITFIELDS images, and the color table.
+#[cfg(windows)]
+fn bmp_from_dib(dib: &[u8]) -> Option<Vec<u8>> {
+    const BITMAPINFOHEADER_SIZE: u32 = 40;
+    const BI_BITFIELDS: u32 = 3;
+
+    let u16_at = |offset: usize| Some(u16::from_le_bytes(dib.get(offset..offset + 2)?.try_into().ok()?));
+    let u32_at = |offset: usize| Some(u32::from_le_bytes(dib.get(offset..offset + 4)?.try_into().ok()?));
+    let header_size = u32_at(0)?;
+    let bit_count = u16_at(14)?;
+    let compression = u32_at(16)?;
+    let colors_used = u32_at(32)?;
+
+    let masks_size = if header_size == BITMAPINFOHEADER_SIZE && compression == BI_BITFIELDS { 12 } else { 0 };
+    let colors = if colors_used > 0 { colors_used } else if bit_count <= 8 { 1 << bit_count } else { 0 };
+    let pixels_offset = 14 + header_size + masks_size + colors * 4;
+
+    let mut bmp = Vec::with_capacity(14 + dib.len());
+    bmp.extend_from_slice(b"BM");
+    bmp.extend_from_slice(&(14 + dib.len() as u32).to_le_bytes());
+    bmp.extend_from_slice(&[0; 4]); // Reserved
+    bmp.extend_from_slice(&pixels_offset.to_le_bytes());
+    bmp.extend_from_slice(dib);
+    Some(bmp)
+}
@@ -0,0 +1,77 @@
+use std::{ffi::CString, io::Write, path::{Path, PathBuf}};
+
+use tesseract_sys::{TessBaseAPI, TessBaseAPIEnd, TessBaseAPIInit3, TessBaseAPIInit4, TessOcrEngineMode_OEM_DEFAULT};
+
+use crate::settings::get_project_dirs;
+
+const DICTIONARY_FILE_NAME: &str = "custom_dictionary.txt";
+
+/// The personal word list (product names, jargon, etc.), with one word per line.
+pub(crate) fn dictionary_path() -> PathBuf {
+    get_project_dirs().config_dir().join(DICTIONARY_FILE_NAME)
+}
+
+/// Reads the personal word list. A missing file just means it's empty.
+pub(crate) fn load_custom_words() -> Vec<String> {
+    std::fs::read_to_string(dictionary_path())
+        .map(|contents| contents.lines().map(|line| line.trim().to_string()).filter(|line| !line.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// Adds a word to the personal word list. Returns false if it was already there.
+pub(crate) fn add_custom_word(word: &str) -> Result<bool, String> {
+    let word = word.trim();
+    if word.is_empty() || word.contains(char::is_whitespace) {
+        return Err("Only single words can be added to the dictionary".to_string());
+    }
+    if load_custom_words().iter().any(|existing| existing == word) {
+        return Ok(false);
+    }
+
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(dictionary_path())
+        .map_err(|e| format!("Unable to open {}: {}", DICTIONARY_FILE_NAME, e))?;
+    writeln!(file, "{}", word).map_err(|e| format!("Unable to write {}: {}", DICTIONARY_FILE_NAME, e))?;
+    Ok(true)
+}
+
+/// Re-initializes Tesseract with the personal word list as its user words, so recognition
+/// favors them. `user_words_file` can only be set while initializing, so the model is reloaded.
+/// This has to happen before any other variables are set.
+pub(crate) fn load_user_words(api: &mut leptess::tesseract::TessApi, tessdata: &Path, model: &str) {
+    let path = dictionary_path();
+    if load_custom_words().is_empty() {
+        return;
+    }
+
+    let (Some(tessdata), Some(path)) = (tessdata.to_str(), path.to_str()) else {
+        return;
+    };
+    let (Ok(tessdata), Ok(model), Ok(path)) = (CString::new(tessdata), CString::new(model), CString::new(path)) else {
+        return;
+    };
+    let name = c"user_words_file";
+
+    let handle: *mut TessBaseAPI = *api.raw.as_ref();
+    let mut names = [name.as_ptr() as *mut _];
+    let mut values = [path.as_ptr() as *mut _];
+    let result = unsafe {
+        TessBaseAPIEnd(handle);
+        TessBaseAPIInit4(
+            handle,
+            tessdata.as_ptr(),
+            model.as_ptr(),
+            TessOcrEngineMode_OEM_DEFAULT,
+            std::ptr::null_mut(),
+            0,
+            names.as_mut_ptr(),
+            values.as_mut_ptr(),
+            names.len() as _,
+            0
+        )
+    };
+    if result != 0 {
+        eprintln!("Unable to load the custom dictionary into Tesseract");
+        // Fall back to the model without user words rather than leaving Tesseract uninitialized
+        unsafe { TessBaseAPIInit3(handle, tessdata.as_ptr(), model.as_ptr()) };
+    }
+}
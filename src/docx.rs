@@ -0,0 +1,166 @@
+use crate::text_formatting::escape_html;
+
+/// Builds a minimal Word document from OCR text. With a delimiter, each line is a table row split on it
+/// (quoted fields are supported, like in CSV); otherwise blank lines separate paragraphs and single line
+/// breaks are kept within them.
+pub(crate) fn text_to_docx(text: &str, table_delimiter: Option<char>) -> Vec<u8> {
+    let body = match table_delimiter {
+        Some(delimiter) => table_xml(&parse_delimited(text, delimiter)),
+        None => paragraphs_xml(text)
+    };
+    let document = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}<w:sectPr/></w:body></w:document>"#,
+        body
+    );
+
+    write_zip(&[
+        ("[Content_Types].xml", CONTENT_TYPES.as_bytes()),
+        ("_rels/.rels", RELATIONSHIPS.as_bytes()),
+        ("word/document.xml", document.as_bytes()),
+    ])
+}
+
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/></Types>"#;
+const RELATIONSHIPS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/></Relationships>"#;
+
+fn paragraphs_xml(text: &str) -> String {
+    let paragraphs = text.split("\n\n")
+        .map(|paragraph| paragraph.trim_matches('\n'))
+        .filter(|paragraph| !paragraph.trim().is_empty())
+        .map(|paragraph| format!("<w:p>{}</w:p>", paragraph.lines().map(run_xml).collect::<Vec<String>>().join("<w:r><w:br/></w:r>")))
+        .collect::<String>();
+    // Word needs at least one paragraph in the body
+    if paragraphs.is_empty() { "<w:p/>".to_string() } else { paragraphs }
+}
+
+fn table_xml(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    if columns == 0 {
+        return "<w:p/>".to_string();
+    }
+
+    let border = |side: &str| format!(r#"<w:{} w:val="single" w:sz="4" w:space="0" w:color="auto"/>"#, side);
+    let borders = ["top", "left", "bottom", "right", "insideH", "insideV"].iter().map(|side| border(side)).collect::<String>();
+    let grid = r#"<w:gridCol w:w="2000"/>"#.repeat(columns);
+    let rows = rows.iter()
+        .map(|row| {
+            // Short rows are padded so every row has the same number of cells
+            let cells = (0..columns)
+                .map(|index| format!("<w:tc><w:p>{}</w:p></w:tc>", row.get(index).map(|cell| run_xml(cell)).unwrap_or_default()))
+                .collect::<String>();
+            format!("<w:tr>{}</w:tr>", cells)
+        })
+        .collect::<String>();
+
+    // A paragraph after the table, since Word doesn't allow a table to end the body
+    format!(r#"<w:tbl><w:tblPr><w:tblW w:w="0" w:type="auto"/><w:tblBorders>{}</w:tblBorders></w:tblPr><w:tblGrid>{}</w:tblGrid>{}</w:tbl><w:p/>"#, borders, grid, rows)
+}
+
+fn run_xml(text: &str) -> String {
+    text.split('\t')
+        .map(|part| {
+            // Control characters aren't allowed in XML
+            let escaped = escape_html(&part.chars().filter(|c| !c.is_control()).collect::<String>());
+            format!(r#"<w:r><w:t xml:space="preserve">{}</w:t></w:r>"#, escaped)
+        })
+        .collect::<Vec<String>>()
+        .join("<w:r><w:tab/></w:r>")
+}
+
+/// Splits delimited text into rows of fields. Fields may be quoted, with doubled quotes for a literal quote.
+fn parse_delimited(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if field.is_empty() => in_quotes = true,
+            c if c == delimiter && !in_quotes => row.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c)
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows.retain(|row| row.iter().any(|field| !field.is_empty()));
+    rows
+}
+
+/// Writes the files into an uncompressed zip archive, which is all a .docx needs to be.
+fn write_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+    // 1980-01-01, the earliest date zip can store
+    const DOS_DATE: u16 = 0x21;
+
+    let mut archive = Vec::new();
+    let mut central_directory = Vec::new();
+    for (name, data) in files {
+        let offset = archive.len() as u32;
+        let crc = crc32(data);
+
+        archive.extend_from_slice(&0x04034b50_u32.to_le_bytes());
+        archive.extend_from_slice(&20_u16.to_le_bytes()); // Version needed to extract
+        archive.extend_from_slice(&0_u16.to_le_bytes()); // Flags
+        archive.extend_from_slice(&0_u16.to_le_bytes()); // Stored, without compression
+        archive.extend_from_slice(&0_u16.to_le_bytes()); // Modification time
+        archive.extend_from_slice(&DOS_DATE.to_le_bytes());
+        archive.extend_from_slice(&crc.to_le_bytes());
+        archive.extend_from_slice(&(data.len() as u32).to_le_bytes()); // Compressed size
+        archive.extend_from_slice(&(data.len() as u32).to_le_bytes()); // Uncompressed size
+        archive.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        archive.extend_from_slice(&0_u16.to_le_bytes()); // Extra field length
+        archive.extend_from_slice(name.as_bytes());
+        archive.extend_from_slice(data);
+
+        central_directory.extend_from_slice(&0x02014b50_u32.to_le_bytes());
+        central_directory.extend_from_slice(&20_u16.to_le_bytes()); // Version made by
+        central_directory.extend_from_slice(&20_u16.to_le_bytes()); // Version needed to extract
+        central_directory.extend_from_slice(&0_u16.to_le_bytes()); // Flags
+        central_directory.extend_from_slice(&0_u16.to_le_bytes()); // Stored, without compression
+        central_directory.extend_from_slice(&0_u16.to_le_bytes()); // Modification time
+        central_directory.extend_from_slice(&DOS_DATE.to_le_bytes());
+        central_directory.extend_from_slice(&crc.to_le_bytes());
+        central_directory.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        central_directory.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        central_directory.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        central_directory.extend_from_slice(&[0; 12]); // Extra field and comment lengths, disk number, and attributes
+        central_directory.extend_from_slice(&offset.to_le_bytes());
+        central_directory.extend_from_slice(name.as_bytes());
+    }
+
+    let central_directory_offset = archive.len() as u32;
+    archive.extend_from_slice(&central_directory);
+    archive.extend_from_slice(&0x06054b50_u32.to_le_bytes());
+    archive.extend_from_slice(&[0; 4]); // Disk numbers
+    archive.extend_from_slice(&(files.len() as u16).to_le_bytes()); // Entries on this disk
+    archive.extend_from_slice(&(files.len() as u16).to_le_bytes()); // Total entries
+    archive.extend_from_slice(&(central_directory.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&central_directory_offset.to_le_bytes());
+    archive.extend_from_slice(&0_u16.to_le_bytes()); // Comment length
+    archive
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB88320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
@@ -0,0 +1,172 @@
+use std::ops::Range;
+
+/// At most this many entities are offered, so the chips don't bury the preview for long texts
+const MAX_ENTITIES: usize = 8;
+/// Phone numbers have between this many digits, including the country code
+const PHONE_DIGITS: std::ops::RangeInclusive<usize> = 7..=15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EntityKind {
+    Email,
+    Url,
+    Phone,
+    IpAddress,
+    Date,
+}
+
+impl EntityKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            EntityKind::Email => "Email",
+            EntityKind::Url => "URL",
+            EntityKind::Phone => "Phone",
+            EntityKind::IpAddress => "IP",
+            EntityKind::Date => "Date"
+        }
+    }
+}
+
+/// Something in the recognized text that's often copied on its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Entity {
+    pub kind: EntityKind,
+    pub text: String,
+}
+
+/// Finds the emails, URLs, phone numbers, IP addresses, and ISO dates in the text, in the order they appear.
+/// Each distinct entity is only returned once.
+pub(crate) fn extract_entities(text: &str) -> Vec<Entity> {
+    let mut found: Vec<(Range<usize>, EntityKind)> = Vec::new();
+    for (range, token) in tokens(text) {
+        let kind = if is_url(token) {
+            EntityKind::Url
+        } else if is_email(token) {
+            EntityKind::Email
+        } else if is_ip_address(token) {
+            EntityKind::IpAddress
+        } else if is_iso_date(token) {
+            EntityKind::Date
+        } else {
+            continue;
+        };
+        found.push((range, kind));
+    }
+
+    // Phone numbers are usually split up by spaces, so they're found in the text itself rather than per token
+    for range in phone_numbers(text) {
+        if !found.iter().any(|(other, _)| other.start < range.end && range.start < other.end) {
+            found.push((range, EntityKind::Phone));
+        }
+    }
+    found.sort_by_key(|(range, _)| range.start);
+
+    let mut entities: Vec<Entity> = Vec::new();
+    for (range, kind) in found {
+        let entity = Entity { kind, text: text[range].to_string() };
+        if !entities.contains(&entity) {
+            entities.push(entity);
+        }
+    }
+    entities.truncate(MAX_ENTITIES);
+    entities
+}
+
+/// Splits the text on whitespace and trims the punctuation that usually surrounds entities in prose.
+fn tokens(text: &str) -> impl Iterator<Item = (Range<usize>, &str)> {
+    text.split_whitespace().filter_map(move |word| {
+        let start = word.as_ptr() as usize - text.as_ptr() as usize;
+        let trimmed = word.trim_start_matches(['(', '[', '<', '"', '\'', '“', '‘'])
+            .trim_end_matches([')', ']', '>', '"', '\'', '”', '’', ',', ';', ':', '.', '!', '?']);
+        if trimmed.is_empty() {
+            return None;
+        }
+        let offset = trimmed.as_ptr() as usize - word.as_ptr() as usize;
+        Some((start + offset..start + offset + trimmed.len(), trimmed))
+    })
+}
+
+fn is_domain(domain: &str) -> bool {
+    let labels: Vec<&str> = domain.split('.').collect();
+    labels.len() >= 2
+        && labels.iter().all(|label| !label.is_empty() && label.chars().all(|c| c.is_alphanumeric() || c == '-'))
+        && labels.last().is_some_and(|tld| tld.len() >= 2 && tld.chars().all(char::is_alphabetic))
+}
+
+fn is_url(token: &str) -> bool {
+    let rest = token.strip_prefix("https://").or_else(|| token.strip_prefix("http://"));
+    match rest {
+        Some(rest) => !rest.is_empty(),
+        None => token.starts_with("www.") && is_domain(token.split(['/', '?', '#']).next().unwrap_or(""))
+    }
+}
+
+fn is_email(token: &str) -> bool {
+    let Some((local, domain)) = token.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && local.chars().all(|c| c.is_alphanumeric() || "._%+-".contains(c))
+        && is_domain(domain)
+}
+
+fn is_ip_address(token: &str) -> bool {
+    if token.parse::<std::net::Ipv4Addr>().is_ok() {
+        return true;
+    }
+    // Plain numbers and times also parse as IPv6 fragments, so require at least a few groups
+    token.matches(':').count() >= 2 && token.parse::<std::net::Ipv6Addr>().is_ok()
+}
+
+/// Matches YYYY-MM-DD, optionally followed by a time (2024-03-02T10:15:00Z).
+fn is_iso_date(token: &str) -> bool {
+    let (date, time) = match token.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (token, None)
+    };
+    let parts: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = parts.as_slice() else {
+        return false;
+    };
+    let lengths_valid = year.len() == 4 && month.len() == 2 && day.len() == 2;
+    let month = month.parse::<u32>().unwrap_or(0);
+    let day = day.parse::<u32>().unwrap_or(0);
+    lengths_valid
+        && year.chars().all(|c| c.is_ascii_digit())
+        && (1..=12).contains(&month)
+        && (1..=31).contains(&day)
+        && time.is_none_or(|time| time.starts_with(|c: char| c.is_ascii_digit()) && time.contains(':'))
+}
+
+/// Finds runs like "+1 (555) 123-4567" or "030 1234567" that contain a plausible number of digits.
+fn phone_numbers(text: &str) -> Vec<Range<usize>> {
+    let is_phone_char = |c: char| c.is_ascii_digit() || "+()-. ".contains(c);
+
+    let mut numbers = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let preceded_by_word = text[..start].chars().next_back().is_some_and(|previous| previous.is_alphanumeric());
+        if !(c.is_ascii_digit() || c == '+' || c == '(') || preceded_by_word {
+            continue;
+        }
+
+        let mut end = start + c.len_utf8();
+        while let Some(&(index, next)) = chars.peek() {
+            if !is_phone_char(next) {
+                break;
+            }
+            end = index + next.len_utf8();
+            chars.next();
+        }
+
+        // Trailing separators (e.g. a sentence's period) aren't part of the number
+        let candidate = text[start..end].trim_end_matches(|c: char| !c.is_ascii_digit());
+        let followed_by_word = text[start + candidate.len()..].starts_with(char::is_alphanumeric);
+        let digits = candidate.chars().filter(char::is_ascii_digit).count();
+        // A single decimal point without spaces is far more likely to be a price or a measurement
+        let looks_like_decimal = candidate.matches('.').count() == 1 && !candidate.contains(' ');
+        if PHONE_DIGITS.contains(&digits) && !followed_by_word && !looks_like_decimal {
+            numbers.push(start..start + candidate.len());
+        }
+    }
+    numbers
+}
@@ -0,0 +1,164 @@
+use std::{io::Write, path::{Path, PathBuf}};
+
+use image::{DynamicImage, ImageFormat};
+
+use crate::{docx::text_to_docx, settings::TesseractSettings, text_formatting::TesseractExportMode};
+
+/// Writes an OCR result to the export directory, named by the time and with the export mode's extension.
+/// Returns the path it was written to.
+pub(crate) fn write_export(settings: &TesseractSettings, text: &str) -> Result<PathBuf, String> {
+    let directory = settings.resolved_export_directory();
+    std::fs::create_dir_all(&directory).map_err(|e| format!("Unable to create export directory: {}", e))?;
+
+    let path = unique_timestamped_path(&directory, "ocr-", &format!(".{}", settings.export_mode.extension()));
+    std::fs::write(&path, text).map_err(|e| format!("Unable to write export: {}", e))?;
+    Ok(path)
+}
+
+/// Writes an OCR result to the export directory as a Word document. Tabular export modes become a table,
+/// and plain text becomes paragraphs. Returns the path it was written to.
+pub(crate) fn write_docx(settings: &TesseractSettings, text: &str) -> Result<PathBuf, String> {
+    let table_delimiter = match settings.export_mode {
+        TesseractExportMode::UTF8 => None,
+        TesseractExportMode::TSV => Some('\t'),
+        TesseractExportMode::CSV => Some(settings.csv_delimiter),
+        TesseractExportMode::Receipt => Some(','),
+        _ => return Err("Word documents can only be saved from plain text, TSV, CSV, or receipt results".to_string())
+    };
+
+    let directory = settings.resolved_export_directory();
+    std::fs::create_dir_all(&directory).map_err(|e| format!("Unable to create export directory: {}", e))?;
+
+    let path = unique_timestamped_path(&directory, "ocr-", ".docx");
+    std::fs::write(&path, text_to_docx(text, table_delimiter)).map_err(|e| format!("Unable to write Word document: {}", e))?;
+    Ok(path)
+}
+
+/// A path in the directory named `{prefix}{milliseconds}{suffix}` by the current time, with a counter
+/// added if something with that name already exists so quick successive exports don't overwrite each other.
+pub(crate) fn unique_timestamped_path(directory: &Path, prefix: &str, suffix: &str) -> PathBuf {
+    let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_millis();
+    let mut path = directory.join(format!("{}{}{}", prefix, timestamp, suffix));
+    let mut counter = 1;
+    while path.exists() {
+        path = directory.join(format!("{}{}-{}{}", prefix, timestamp, counter, suffix));
+        counter += 1;
+    }
+    path
+}
+
+/// Appends an OCR result to the file named by the output file template, creating it if needed.
+/// Returns the path it was written to.
+pub(crate) fn write_result_file(settings: &TesseractSettings, text: &str) -> Result<PathBuf, String> {
+    let (date, time_of_day) = local_now();
+    let file_name = settings.output_file_template
+        .replace("{date}", &date)
+        .replace("{time}", &format!("{:02}-{:02}-{:02}", time_of_day / 3600, time_of_day / 60 % 60, time_of_day % 60))
+        .replace("{lang}", &settings.ocr_language_code);
+    if file_name.trim().is_empty() {
+        return Err("The output file template is empty".to_string());
+    }
+
+    let path = settings.resolved_export_directory().join(file_name);
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory).map_err(|e| format!("Unable to create output directory: {}", e))?;
+    }
+
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&path)
+        .map_err(|e| format!("Unable to open {}: {}", path.display(), e))?;
+    // Separate appended results with a blank line
+    let separator = if file.metadata().map(|metadata| metadata.len() > 0).unwrap_or(false) { "\n" } else { "" };
+    writeln!(file, "{}{}", separator, text).map_err(|e| format!("Unable to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Appends an OCR result to the configured Markdown note as a quote under a timestamp heading. With an
+/// image, it's saved to the attachments directory and embedded above the text. Returns the note's path.
+pub(crate) fn append_to_note(settings: &TesseractSettings, text: &str, image: Option<&DynamicImage>) -> Result<PathBuf, String> {
+    if settings.note.path.trim().is_empty() {
+        return Err("No note is configured for the \"Note\" output target".to_string());
+    }
+    let note_path = PathBuf::from(&settings.note.path);
+    let note_directory = note_path.parent().map(Path::to_path_buf).unwrap_or_default();
+    std::fs::create_dir_all(&note_directory).map_err(|e| format!("Unable to create the note's directory: {}", e))?;
+
+    let (date, time_of_day) = local_now();
+    let time = format!("{:02}:{:02}:{:02}", time_of_day / 3600, time_of_day / 60 % 60, time_of_day % 60);
+    let mut block = format!("### {} {}\n\n", date, time);
+
+    if let Some(image) = image {
+        let attachments_directory = note_directory.join(&settings.note.attachments_directory);
+        std::fs::create_dir_all(&attachments_directory).map_err(|e| format!("Unable to create the attachments directory: {}", e))?;
+        let image_path = attachments_directory.join(format!("ocr-{}-{}.png", date, time.replace(':', "-")));
+        image.save_with_format(&image_path, ImageFormat::Png).map_err(|e| format!("Unable to save the image: {}", e))?;
+
+        // Links are relative to the note when possible, with forward slashes and escaped spaces so Markdown accepts them
+        let link = image_path.strip_prefix(&note_directory).unwrap_or(&image_path).to_string_lossy().replace('\\', "/").replace(' ', "%20");
+        block.push_str(&format!("![]({})\n\n", link));
+    }
+
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            block.push_str(">\n");
+        } else {
+            block.push_str(&format!("> {}\n", line));
+        }
+    }
+
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&note_path)
+        .map_err(|e| format!("Unable to open {}: {}", note_path.display(), e))?;
+    // Separate the block from whatever the note already ends with
+    let separator = if file.metadata().map(|metadata| metadata.len() > 0).unwrap_or(false) { "\n" } else { "" };
+    write!(file, "{}{}", separator, block).map_err(|e| format!("Unable to write {}: {}", note_path.display(), e))?;
+    Ok(note_path)
+}
+
+/// Renders a copied result with the active copy template.
+pub(crate) fn render_copy_template(settings: &TesseractSettings, text: &str) -> String {
+    let (date, time_of_day) = local_now();
+    // The text is substituted last so placeholders in the recognized text are left alone
+    settings.active_copy_template()
+        .replace("{date}", &date)
+        .replace("{time}", &format!("{:02}:{:02}:{:02}", time_of_day / 3600, time_of_day / 60 % 60, time_of_day % 60))
+        .replace("{lang}", &settings.ocr_language_code)
+        .replace("{text}", text)
+}
+
+/// The current UTC date as YYYY-MM-DD, and the seconds since midnight.
+pub(crate) fn utc_now() -> (String, u64) {
+    let seconds = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs();
+    let (year, month, day) = civil_date(seconds / 86400);
+    (format!("{:04}-{:02}-{:02}", year, month, day), seconds % 86400)
+}
+
+/// The current local date as YYYY-MM-DD, and the seconds since local midnight. Falls back to UTC if the
+/// local time zone can't be determined.
+pub(crate) fn local_now() -> (String, u64) {
+    let seconds = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs() as libc::time_t;
+    let mut local: libc::tm = unsafe { std::mem::zeroed() };
+    #[cfg(unix)]
+    let converted = unsafe { !libc::localtime_r(&seconds, &mut local).is_null() };
+    #[cfg(windows)]
+    let converted = unsafe { libc::localtime_s(&mut local, &seconds) == 0 };
+    if !converted {
+        return utc_now();
+    }
+
+    let date = format!("{:04}-{:02}-{:02}", local.tm_year + 1900, local.tm_mon + 1, local.tm_mday);
+    (date, (local.tm_hour * 3600 + local.tm_min * 60 + local.tm_sec) as u64)
+}
+
+/// Converts days since the Unix epoch to a (year, month, day) date in the proleptic Gregorian calendar.
+fn civil_date(days: u64) -> (i64, u32, u32) {
+    // From Howard Hinnant's `civil_from_days` algorithm
+    let days = days as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
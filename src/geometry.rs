@@ -0,0 +1,608 @@
+use std::ops::{Add, Sub};
+
+use crate::renderer::SmoothFadeAnimation;
+
+/// How many vertices ellipse selections are approximated with
+pub(crate) const ELLIPSE_VERTEX_COUNT: usize = 48;
+
+/// A position in physical pixels. Coordinates are floats so selections keep sub-pixel
+/// precision on scaled displays; they're only rounded when cropping the screenshot.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct Point {
+    pub x: f32,
+    pub y: f32
+}
+
+impl Point {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn distance_to(&self, other: Point) -> f32 {
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
+    }
+}
+
+impl From<(i32, i32)> for Point {
+    fn from((x, y): (i32, i32)) -> Self {
+        Self { x: x as f32, y: y as f32 }
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+/// An axis-aligned rectangle. The width and height may be negative while a selection is being dragged
+/// up or to the left; use `to_positive_size` before doing anything that depends on the direction.
+#[derive(Debug, Clone, Default, Copy, PartialEq)]
+pub(crate) struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    pub fn from_center(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x: x - width / 2.,
+            y: y - height / 2.,
+            width,
+            height
+        }
+    }
+
+    pub fn origin(&self) -> Point {
+        Point::new(self.x, self.y)
+    }
+
+    pub fn set_center(&mut self, x: f32, y: f32) {
+        self.x = x - self.width / 2.;
+        self.y = y - self.height / 2.;
+    }
+
+    pub fn set_origin(&mut self, x: f32, y: f32) {
+        self.x = x;
+        self.y = y;
+    }
+
+    pub fn set_size(&mut self, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0. || self.height == 0.
+    }
+
+    pub fn to_positive_size(&self) -> Rect {
+        let (mut x, mut y, mut width, mut height) = (self.x, self.y, self.width, self.height);
+        if width < 0. {
+            x += width;
+            width = -width;
+        }
+        if height < 0. {
+            y += height;
+            height = -height;
+        }
+        Rect {
+            x, y, width, height
+        }
+    }
+
+    /// Rounds outwards to whole pixels, so every pixel the rectangle partially covers is included.
+    pub fn to_pixel_rect(&self) -> Rect {
+        let rect = self.to_positive_size();
+        let (left, top) = (rect.x.floor(), rect.y.floor());
+        let (right, bottom) = ((rect.x + rect.width).ceil(), (rect.y + rect.height).ceil());
+        Rect::new(left, top, right - left, bottom - top)
+    }
+
+    /// Moves every edge outwards by `amount`, or inwards if it's negative.
+    pub fn grow_by(&mut self, amount: f32) {
+        self.x -= amount;
+        self.y -= amount;
+        self.width += amount * 2.;
+        self.height += amount * 2.;
+    }
+
+    /// Multiplies every coordinate by `factor`, e.g. to go between the screen and the scaled UI.
+    pub fn scaled(&self, factor: f32) -> Rect {
+        Rect::new(self.x * factor, self.y * factor, self.width * factor, self.height * factor)
+    }
+
+    /// Whether either side of this rectangle is shorter than `minimum_size`, regardless of direction.
+    pub fn smaller_than(&self, minimum_size: u32) -> bool {
+        let rect = self.to_positive_size();
+        rect.width < minimum_size as f32 || rect.height < minimum_size as f32
+    }
+
+    pub fn contains(&self, point: impl Into<Point>) -> bool {
+        let point = point.into();
+        point.x >= self.x && point.x <= self.x + self.width && point.y >= self.y && point.y <= self.y + self.height
+    }
+
+    pub fn enclose_polygon(&mut self, polygon: &Polygon) {
+        if polygon.vertices.is_empty() {
+            *self = Rect::default();
+            return;
+        }
+
+        let min_x = polygon.vertices.iter().map(|v| v.x).fold(f32::INFINITY, f32::min);
+        let min_y = polygon.vertices.iter().map(|v| v.y).fold(f32::INFINITY, f32::min);
+        let max_x = polygon.vertices.iter().map(|v| v.x).fold(f32::NEG_INFINITY, f32::max);
+        let max_y = polygon.vertices.iter().map(|v| v.y).fold(f32::NEG_INFINITY, f32::max);
+
+        *self = Rect::new(min_x, min_y, max_x - min_x, max_y - min_y);
+    }
+}
+
+impl From<(u32, u32)> for Rect {
+    fn from(size: (u32, u32)) -> Self {
+        Rect::new(0., 0., size.0 as f32, size.1 as f32)
+    }
+}
+
+/// How far the frozen screenshot can be zoomed into
+pub(crate) const MAX_VIEW_ZOOM: f32 = 8.;
+
+/// How the frozen screenshot is zoomed and panned on screen. Selections are kept in pixels of the
+/// screenshot, so they're converted to and from the screen with this.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct View {
+    pub zoom: f32,
+    /// The point of the screenshot at the top-left of the screen
+    pub offset: Point
+}
+
+impl Default for View {
+    fn default() -> Self {
+        Self { zoom: 1., offset: Point::default() }
+    }
+}
+
+impl View {
+    pub fn to_image(&self, screen_point: Point) -> Point {
+        Point::new(self.offset.x + screen_point.x / self.zoom, self.offset.y + screen_point.y / self.zoom)
+    }
+
+    pub fn to_screen(&self, image_point: Point) -> Point {
+        Point::new((image_point.x - self.offset.x) * self.zoom, (image_point.y - self.offset.y) * self.zoom)
+    }
+
+    pub fn rect_to_screen(&self, rect: Rect) -> Rect {
+        let origin = self.to_screen(rect.origin());
+        Rect::new(origin.x, origin.y, rect.width * self.zoom, rect.height * self.zoom)
+    }
+
+    /// Multiplies the zoom by `factor`, keeping the point under `screen_point` in place.
+    pub fn zoom_at(&mut self, screen_point: Point, factor: f32, screen_size: (u32, u32)) {
+        let anchor = self.to_image(screen_point);
+        self.zoom = (self.zoom * factor).clamp(1., MAX_VIEW_ZOOM);
+        self.offset = Point::new(anchor.x - screen_point.x / self.zoom, anchor.y - screen_point.y / self.zoom);
+        self.clamp(screen_size);
+    }
+
+    /// Moves the screenshot by a distance on screen, like dragging it.
+    pub fn pan_by(&mut self, dx: f32, dy: f32, screen_size: (u32, u32)) {
+        self.offset = Point::new(self.offset.x - dx / self.zoom, self.offset.y - dy / self.zoom);
+        self.clamp(screen_size);
+    }
+
+    /// Keeps the screenshot covering the whole screen.
+    fn clamp(&mut self, screen_size: (u32, u32)) {
+        let (width, height) = (screen_size.0 as f32, screen_size.1 as f32);
+        self.offset.x = self.offset.x.clamp(0., width - width / self.zoom);
+        self.offset.y = self.offset.y.clamp(0., height - height / self.zoom);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Polygon {
+    pub vertices: Vec<Vertex>,
+    pub hovered_vertex: Option<usize>,
+    pub hovered_edge: Option<usize>
+}
+
+impl Default for Polygon {
+    fn default() -> Self {
+        // The default state includes some vertices so we don't need to immediately resize the buffer
+        Self {
+            vertices: vec![
+                Vertex::new(-50.0, -50.0),
+                Vertex::new(-50.0, -50.0),
+                Vertex::new(-50.0, -50.0),
+                Vertex::new(-50.0, -50.0)
+            ],
+            hovered_vertex: None,
+            hovered_edge: None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Vertex {
+    pub x: f32,
+    pub y: f32,
+
+    pub vertex_highlight: SmoothFadeAnimation,
+    pub edge_highlight: SmoothFadeAnimation
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct GPUVertex {
+    pub position: [f32; 2],
+    pub animation: u32,
+    pub _padding: u32
+}
+
+impl GPUVertex {
+    pub fn new(x: f32, y: f32, animation: u32) -> Self {
+        Self {
+            position: [x, y],
+            animation,
+            _padding: 0
+        }
+    }
+}
+
+impl Vertex {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self {
+            x, y,
+            vertex_highlight: SmoothFadeAnimation::new(false),
+            edge_highlight: SmoothFadeAnimation::new(false)
+        }
+    }
+
+    pub fn as_gpu_vertex(&self) -> GPUVertex {
+        GPUVertex::new(self.x, self.y, self.get_animation_int())
+    }
+
+    fn get_animation_int(&self) -> u32 {
+        // We encode vertex and edge animations into the same integer
+        // The first 16 bits are this vertex's opacity, and the next 16 bits are the opacity of the edge connecting this vertex and the next one.
+        let vertex_opacity = (self.vertex_highlight.get_opacity() * 65535.0) as u32;
+        let edge_opacity = (self.edge_highlight.get_opacity() * 65535.0) as u32;
+        vertex_opacity << 16 | edge_opacity
+    }
+
+    fn update(&mut self, delta: std::time::Duration, edge_highlight: bool, vertex_highlight: bool) {
+        self.vertex_highlight.update(delta, vertex_highlight);
+        self.edge_highlight.update(delta, edge_highlight);
+    }
+
+    pub fn position(&self) -> Point {
+        Point::new(self.x, self.y)
+    }
+}
+
+impl Polygon {
+    pub fn new() -> Self {
+        Self {
+            vertices: Vec::new(),
+            hovered_edge: None,
+            hovered_vertex: None
+        }
+    }
+
+    pub fn get_device_coords_polygon(&self, screen_size: (u32, u32)) -> Vec<GPUVertex> {
+        let mut vertices = Vec::new();
+        for vertex in &self.vertices {
+            let mut vertex = vertex.clone();
+            vertex.x /= screen_size.0 as f32;
+            vertex.y /= screen_size.1 as f32;
+            vertices.push(vertex.as_gpu_vertex());
+        }
+
+        vertices
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+    }
+
+    pub fn points(&self) -> Vec<Point> {
+        self.vertices.iter().map(|v| v.position()).collect()
+    }
+
+    /// Whether the point is inside the polygon, using the even-odd rule.
+    pub fn contains(&self, point: Point) -> bool {
+        let mut inside = false;
+        for (i, current) in self.vertices.iter().enumerate() {
+            let previous = &self.vertices[(i + self.vertices.len() - 1) % self.vertices.len()];
+            if (current.y > point.y) != (previous.y > point.y)
+                && point.x < (previous.x - current.x) * (point.y - current.y) / (previous.y - current.y) + current.x {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+
+    pub fn get_origin(&self) -> Point {
+        let min_x = self.vertices.iter().map(|v| v.x).fold(f32::INFINITY, f32::min);
+        let min_y = self.vertices.iter().map(|v| v.y).fold(f32::INFINITY, f32::min);
+        Point::new(min_x, min_y)
+    }
+
+    pub fn set_origin(&mut self, origin: Point) {
+        let offset = origin - self.get_origin();
+        self.move_by(offset.x, offset.y);
+    }
+
+    pub fn move_by(&mut self, dx: f32, dy: f32) {
+        for vertex in self.vertices.iter_mut() {
+            vertex.x += dx;
+            vertex.y += dy;
+        }
+    }
+
+    pub fn move_vertex_by(&mut self, index: usize, dx: f32, dy: f32) {
+        self.vertices[index].x += dx;
+        self.vertices[index].y += dy;
+    }
+
+    /// Inserts a vertex halfway along the edge starting at vertex `edge_index`, returning its index.
+    pub fn insert_midpoint(&mut self, edge_index: usize) -> usize {
+        let start = self.vertices[edge_index].position();
+        let end = self.vertices[(edge_index + 1) % self.vertices.len()].position();
+        self.vertices.insert(edge_index + 1, Vertex::new((start.x + end.x) / 2., (start.y + end.y) / 2.));
+        edge_index + 1
+    }
+
+    /// Moves every edge outwards by `amount` pixels, or inwards if it's negative, by scaling around the
+    /// center. Does nothing if that would leave it without a width or height.
+    pub fn grow_by(&mut self, amount: f32) {
+        if self.vertices.is_empty() {
+            return;
+        }
+
+        let min_x = self.vertices.iter().map(|v| v.x).fold(f32::INFINITY, f32::min);
+        let min_y = self.vertices.iter().map(|v| v.y).fold(f32::INFINITY, f32::min);
+        let max_x = self.vertices.iter().map(|v| v.x).fold(f32::NEG_INFINITY, f32::max);
+        let max_y = self.vertices.iter().map(|v| v.y).fold(f32::NEG_INFINITY, f32::max);
+        let (width, height) = (max_x - min_x, max_y - min_y);
+        if width + amount * 2. < 1. || height + amount * 2. < 1. {
+            return;
+        }
+
+        let center = Point::new((min_x + max_x) / 2., (min_y + max_y) / 2.);
+        let (scale_x, scale_y) = ((width + amount * 2.) / width.max(f32::EPSILON), (height + amount * 2.) / height.max(f32::EPSILON));
+        for vertex in self.vertices.iter_mut() {
+            vertex.x = center.x + (vertex.x - center.x) * scale_x;
+            vertex.y = center.y + (vertex.y - center.y) * scale_y;
+        }
+    }
+
+    pub fn clamp_vertex_to_screen(&mut self, index: usize, screen_size: (u32, u32)) {
+        let vertex = &mut self.vertices[index];
+        vertex.x = vertex.x.clamp(0., screen_size.0 as f32);
+        vertex.y = vertex.y.clamp(0., screen_size.1 as f32);
+    }
+
+    pub fn clamp_to_screen(&mut self, screen_size: (u32, u32)) -> () {
+        if self.vertices.is_empty() {
+            return;
+        }
+
+        let min_x = self.vertices.iter().map(|v| v.x).fold(f32::INFINITY, f32::min);
+        let min_y = self.vertices.iter().map(|v| v.y).fold(f32::INFINITY, f32::min);
+        let max_x = self.vertices.iter().map(|v| v.x).fold(f32::NEG_INFINITY, f32::max);
+        let max_y = self.vertices.iter().map(|v| v.y).fold(f32::NEG_INFINITY, f32::max);
+
+        let mut dx = 0.0;
+        let mut dy = 0.0;
+
+        if min_x < 0.0 {
+            dx = -min_x;
+        }
+        if min_y < 0.0 {
+            dy = -min_y;
+        }
+
+        if max_x > screen_size.0 as f32 {
+            dx = screen_size.0 as f32 - max_x;
+        }
+        if max_y > screen_size.1 as f32 {
+            dy = screen_size.1 as f32 - max_y;
+        }
+
+        self.move_by(dx, dy);
+
+        // If any vertices are still outside the screen, just clamp them
+        for vertex in self.vertices.iter_mut() {
+            if vertex.x < 0.0 {
+                vertex.x = 0.0;
+            }
+            if vertex.y < 0.0 {
+                vertex.y = 0.0;
+            }
+            if vertex.x > screen_size.0 as f32 {
+                vertex.x = screen_size.0 as f32;
+            }
+            if vertex.y > screen_size.1 as f32 {
+                vertex.y = screen_size.1 as f32;
+            }
+        }
+
+        debug_assert!(self.vertices.iter().all(|v| Rect::from(screen_size).contains(v.position())), "Clamped polygon has vertices off screen");
+    }
+
+    pub fn should_deduplicate(&self, vertex_index: usize) -> Option<Point> {
+        let margin = 5.0;
+        let vertex = &self.vertices[vertex_index];
+        for i in 0..self.vertices.len() {
+            if i == vertex_index {
+                continue;
+            }
+            let other_vertex = &self.vertices[i];
+            if (vertex.x - other_vertex.x).abs() < margin && (vertex.y - other_vertex.y).abs() < margin {
+                return Some(other_vertex.position());
+            }
+        }
+
+        None
+    }
+
+    pub fn deduplicate(&mut self) {
+        let mut i = 0;
+        while i < self.vertices.len() {
+            if self.should_deduplicate(i).is_some() {
+                self.vertices.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+
+        // Removing duplicates can collapse the polygon into a line or point, which isn't a valid selection
+        if self.vertices.len() < 3 {
+            self.vertices.clear();
+        }
+    }
+
+    pub fn set_from_rect(&mut self, rect: &Rect) {
+        self.vertices = vec![
+            Vertex::new(rect.x, rect.y),
+            Vertex::new(rect.x + rect.width, rect.y),
+            Vertex::new(rect.x + rect.width, rect.y + rect.height),
+            Vertex::new(rect.x, rect.y + rect.height)
+        ];
+    }
+
+    pub fn set_from_points(&mut self, points: &[Point]) {
+        self.vertices = points.iter().map(|point| Vertex::new(point.x, point.y)).collect();
+    }
+
+    /// Approximates the ellipse inscribed in the rectangle with `ELLIPSE_VERTEX_COUNT` vertices, so it's
+    /// cropped and drawn like any other polygon.
+    pub fn set_from_ellipse(&mut self, rect: &Rect) {
+        let (radius_x, radius_y) = (rect.width / 2., rect.height / 2.);
+        let center = Point::new(rect.x + radius_x, rect.y + radius_y);
+        self.vertices = (0..ELLIPSE_VERTEX_COUNT).map(|i| {
+            let angle = i as f32 / ELLIPSE_VERTEX_COUNT as f32 * std::f32::consts::TAU;
+            Vertex::new(center.x + radius_x * angle.cos(), center.y + radius_y * angle.sin())
+        }).collect();
+    }
+
+    pub fn get_edge_origin(&self, edge_index: usize) -> Point {
+        let vertex1 = &self.vertices[edge_index];
+        let vertex2 = &self.vertices[(edge_index + 1) % self.vertices.len()];
+        let x = (vertex1.x + vertex2.x) / 2.0;
+        let y = (vertex1.y + vertex2.y) / 2.0;
+        Point::new(x, y)
+    }
+
+    pub fn set_edge_origin(&mut self, edge_index: usize, origin: Point) {
+        let Point { x: dx, y: dy } = origin - self.get_edge_origin(edge_index);
+
+        let vertex1 = &mut self.vertices[edge_index];
+        vertex1.x += dx;
+        vertex1.y += dy;
+        
+        let vertices = self.vertices.len();
+        let vertex2 = &mut self.vertices[(edge_index + 1) % vertices];
+        vertex2.x += dx;
+        vertex2.y += dy;
+    }
+
+    pub fn as_gpu_vertices(&self) -> Vec<GPUVertex> {
+        self.vertices.iter().map(|v| v.as_gpu_vertex()).collect()
+    }
+
+    pub fn update(&mut self, delta: std::time::Duration) {
+        let vertices = self.vertices.len();
+        for (vertex, i) in self.vertices.iter_mut().zip(0..) {
+            let prev_vertex_index = (i + vertices - 1) % vertices;
+            vertex.update(delta, self.hovered_edge.is_some_and(|idx| idx == prev_vertex_index), self.hovered_vertex.is_some_and(|idx| idx == i));
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // Quarter pixels keep the arithmetic exact, so the assertions don't need tolerances
+    fn coordinate() -> impl Strategy<Value = f32> {
+        (-40_000i32..40_000).prop_map(|quarters| quarters as f32 / 4.)
+    }
+
+    fn polygon(min_vertices: usize) -> impl Strategy<Value = Polygon> {
+        prop::collection::vec((coordinate(), coordinate()), min_vertices..12).prop_map(|points| {
+            let mut polygon = Polygon::new();
+            polygon.set_from_points(&points.into_iter().map(|(x, y)| Point::new(x, y)).collect::<Vec<Point>>());
+            polygon
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn clamp_keeps_every_vertex_on_screen(mut polygon in polygon(1), width in 1u32..4000, height in 1u32..4000) {
+            polygon.clamp_to_screen((width, height));
+            let screen = Rect::from((width, height));
+            prop_assert!(polygon.vertices.iter().all(|vertex| screen.contains(vertex.position())));
+        }
+
+        #[test]
+        fn clamping_an_on_screen_polygon_does_nothing(polygon in polygon(1)) {
+            let mut moved = polygon.clone();
+            moved.set_origin(Point::new(0., 0.));
+            let before = moved.points();
+            moved.clamp_to_screen((20_000, 20_000));
+            prop_assert_eq!(moved.points(), before);
+        }
+
+        #[test]
+        fn deduplicate_leaves_three_vertices_or_none(mut polygon in polygon(0)) {
+            polygon.deduplicate();
+            prop_assert!(polygon.vertices.is_empty() || polygon.vertices.len() >= 3);
+            for i in 0..polygon.vertices.len() {
+                prop_assert!(polygon.should_deduplicate(i).is_none());
+            }
+        }
+
+        #[test]
+        fn enclose_polygon_contains_every_vertex(polygon in polygon(1)) {
+            let mut rect = Rect::default();
+            rect.enclose_polygon(&polygon);
+            prop_assert!(polygon.vertices.iter().all(|vertex| rect.contains(vertex.position())));
+        }
+
+        #[test]
+        fn split_then_merge_round_trips(polygon in polygon(3), edge in 0usize..12) {
+            // Vertices close enough to merge on their own would be removed along with the split
+            prop_assume!((0..polygon.vertices.len()).all(|i| polygon.should_deduplicate(i).is_none()));
+            let edge = edge % polygon.vertices.len();
+
+            let mut split = polygon.clone();
+            let inserted = split.insert_midpoint(edge);
+            prop_assert_eq!(split.vertices.len(), polygon.vertices.len() + 1);
+            prop_assert_eq!(split.vertices[inserted].position(), polygon.get_edge_origin(edge));
+
+            // Dragging the new vertex onto the edge's start merges it back
+            let start = split.vertices[edge].position();
+            let offset = start - split.vertices[inserted].position();
+            split.move_vertex_by(inserted, offset.x, offset.y);
+            split.deduplicate();
+            prop_assert_eq!(split.points(), polygon.points());
+        }
+    }
+}
@@ -1,9 +1,29 @@
-use std::{sync::{Arc, Mutex}, thread};
+use std::{sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex}, thread, time::Duration};
 
-use inputbot::{get_keybd_key, KeybdKey::{self, LAltKey, LControlKey, LShiftKey, LSuper}};
+use inputbot::{get_keybd_key, KeySequence, KeybdKey::{self, LAltKey, LControlKey, LShiftKey, LSuper}};
 use winit::{event::KeyEvent, event_loop::EventLoop, platform::modifier_supplement::KeyEventExtModifierSupplement};
 
-use crate::settings::Keybind;
+use crate::{settings::Keybind, AppEvent};
+
+fn keybind_pressed(keybind: &Keybind, key: char) -> bool {
+    let shift_matches = LShiftKey.is_pressed() == keybind.shift;
+    let alt_matches = LAltKey.is_pressed() == keybind.alt;
+    let control_matches = LControlKey.is_pressed() == keybind.ctrl;
+    let meta_matches = LSuper.is_pressed() == keybind.meta;
+    shift_matches && alt_matches && control_matches && meta_matches && key == keybind.key
+}
+
+/// How long to wait before typing, so focus has returned to the window the overlay was opened over
+const TYPE_OUT_DELAY: Duration = Duration::from_millis(200);
+
+/// Types the text into the focused window as simulated keystrokes, on a separate thread.
+pub fn type_text(text: String) {
+    thread::spawn(move || {
+        thread::sleep(TYPE_OUT_DELAY);
+        // Windows line endings make some targets insert blank lines, so only newlines are typed
+        KeySequence(&text.replace("\r\n", "\n")).send();
+    });
+}
 
 #[derive(Clone, Debug)]
 enum KeybindState {
@@ -37,25 +57,30 @@ impl InputHandler {
 
     pub fn handle(
         &mut self,
-        event_loop: &EventLoop<()>,
-        current_keybind: Arc<Mutex<Keybind>>
+        event_loop: &EventLoop<AppEvent>,
+        current_keybind: Arc<Mutex<Keybind>>,
+        script_keybinds: Vec<(usize, Keybind)>,
+        paused: Arc<AtomicBool>
     ) {
         let loop_proxy = event_loop.create_proxy();
 
         println!("Listening with initial keybind {}", current_keybind.lock().expect("Unable to lock keybind").to_owned().to_string());
 
         KeybdKey::bind_all(move |event| {
+            if paused.load(Ordering::Relaxed) {
+                return;
+            }
             match inputbot::from_keybd_key(event) {
                 Some(key) => {
                     let current_keybind = current_keybind.lock().expect("Unable to lock keybind").to_owned();
-                    let shift_matches = LShiftKey.is_pressed() == current_keybind.shift;
-                    let alt_matches = LAltKey.is_pressed() == current_keybind.alt;
-                    let control_matches = LControlKey.is_pressed() == current_keybind.ctrl;
-                    let meta_matches = LSuper.is_pressed() == current_keybind.meta;
-                    let key_matches = key == current_keybind.key;
-                    if shift_matches && alt_matches && control_matches && meta_matches && key_matches {
+                    if keybind_pressed(&current_keybind, key) {
                         // We need to open the window on the main thread
-                        loop_proxy.send_event(()).expect("Unable to send event");
+                        loop_proxy.send_event(AppEvent::OpenOverlay).expect("Unable to send event");
+                    }
+                    for (index, keybind) in &script_keybinds {
+                        if keybind_pressed(keybind, key) {
+                            loop_proxy.send_event(AppEvent::RunScript(*index)).expect("Unable to send event");
+                        }
                     }
                 }
                 _ => {}
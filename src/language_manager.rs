@@ -0,0 +1,203 @@
+use std::{io::{Read, Write}, path::PathBuf, sync::{Arc, Mutex}, thread};
+
+use crate::{localization::Locale, settings::{get_project_dirs, ModelQuality, OCRLanguage}};
+
+fn repository_url(quality: ModelQuality) -> &'static str {
+    match quality {
+        ModelQuality::Standard => "https://github.com/tesseract-ocr/tessdata/raw/main",
+        ModelQuality::Fast => "https://github.com/tesseract-ocr/tessdata_fast/raw/main",
+        ModelQuality::Best => "https://github.com/tesseract-ocr/tessdata_best/raw/main"
+    }
+}
+
+/// Languages that can be downloaded from the official tessdata repository.
+const AVAILABLE_LANGUAGES: &[(&str, &str)] = &[
+    ("afr", "Afrikaans"),
+    ("ara", "Arabic"),
+    ("bul", "Bulgarian"),
+    ("ces", "Czech"),
+    ("chi_sim", "Chinese (simplified)"),
+    ("chi_tra", "Chinese (traditional)"),
+    ("dan", "Danish"),
+    ("deu", "German"),
+    ("ell", "Greek"),
+    ("eng", "English"),
+    ("est", "Estonian"),
+    ("fin", "Finnish"),
+    ("fra", "French"),
+    ("heb", "Hebrew"),
+    ("hin", "Hindi"),
+    ("hrv", "Croatian"),
+    ("hun", "Hungarian"),
+    ("ind", "Indonesian"),
+    ("ita", "Italian"),
+    ("jpn", "Japanese"),
+    ("kor", "Korean"),
+    ("lat", "Latin"),
+    ("lav", "Latvian"),
+    ("lit", "Lithuanian"),
+    ("nld", "Dutch"),
+    ("nor", "Norwegian"),
+    ("pol", "Polish"),
+    ("por", "Portuguese"),
+    ("ron", "Romanian"),
+    ("rus", "Russian"),
+    ("slk", "Slovak"),
+    ("slv", "Slovenian"),
+    ("spa", "Spanish"),
+    ("srp", "Serbian"),
+    ("swe", "Swedish"),
+    ("tha", "Thai"),
+    ("tur", "Turkish"),
+    ("ukr", "Ukrainian"),
+    ("vie", "Vietnamese"),
+];
+
+fn tessdata_directory() -> PathBuf {
+    get_project_dirs().config_dir().join("tessdata")
+}
+
+/// Whether the traineddata for the language's quality tier is installed.
+pub fn model_exists(language: &OCRLanguage) -> bool {
+    tessdata_directory().join(format!("{}.traineddata", language.model())).exists()
+}
+
+#[derive(Debug, Clone)]
+enum DownloadState {
+    InProgress(u8),
+    Finished(Result<(), String>),
+}
+
+/// Lists the languages that aren't installed yet and downloads them in the background.
+pub struct LanguageManager {
+    missing_languages: Vec<OCRLanguage>,
+    selected: usize,
+    download: Option<(OCRLanguage, Arc<Mutex<DownloadState>>)>,
+}
+
+impl LanguageManager {
+    pub fn new() -> Self {
+        let mut manager = LanguageManager {
+            missing_languages: Vec::new(),
+            selected: 0,
+            download: None
+        };
+        manager.refresh();
+        manager
+    }
+
+    /// Re-reads which languages are present in the tessdata directory.
+    pub fn refresh(&mut self) {
+        let directory = tessdata_directory();
+        self.missing_languages = AVAILABLE_LANGUAGES.iter()
+            .filter(|(code, _)| !directory.join(format!("{}.traineddata", code)).exists())
+            .map(|(code, name)| OCRLanguage::new(code, name))
+            .collect();
+        self.selected = self.selected.min(self.missing_languages.len().saturating_sub(1));
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.missing_languages.is_empty() {
+            self.selected = (self.selected + 1) % self.missing_languages.len();
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        if !self.missing_languages.is_empty() {
+            self.selected = (self.selected + self.missing_languages.len() - 1) % self.missing_languages.len();
+        }
+    }
+
+    pub fn is_downloading(&self) -> bool {
+        self.download.is_some()
+    }
+
+    /// Starts downloading the selected language.
+    pub fn download_selected(&mut self) -> Result<(), String> {
+        let Some(language) = self.missing_languages.get(self.selected).cloned() else {
+            return Err("Every available language is already installed".to_string());
+        };
+        self.download(language)
+    }
+
+    /// Starts downloading the model for the language's quality tier. Only one model is downloaded at a time.
+    pub fn download(&mut self, language: OCRLanguage) -> Result<(), String> {
+        if self.is_downloading() {
+            return Err("A language is already being downloaded".to_string());
+        }
+
+        let state = Arc::new(Mutex::new(DownloadState::InProgress(0)));
+        let thread_state = state.clone();
+        let thread_language = language.clone();
+        thread::spawn(move || {
+            let result = download_traineddata(&thread_language, |progress| {
+                *thread_state.lock().unwrap() = DownloadState::InProgress(progress);
+            });
+            *thread_state.lock().unwrap() = DownloadState::Finished(result);
+        });
+
+        self.download = Some((language, state));
+        Ok(())
+    }
+
+    /// Returns the language and result once a download finishes.
+    pub fn poll(&mut self) -> Option<(OCRLanguage, Result<(), String>)> {
+        let (_, state) = self.download.as_ref()?;
+        let DownloadState::Finished(result) = state.lock().unwrap().clone() else {
+            return None;
+        };
+        let (language, _) = self.download.take().unwrap();
+        self.refresh();
+        Some((language, result))
+    }
+
+    /// The text shown in the settings panel.
+    pub fn status_text(&self, locale: &Locale) -> String {
+        if let Some((language, state)) = &self.download {
+            if let DownloadState::InProgress(progress) = *state.lock().unwrap() {
+                return format!("{} {} ({})... {}%", locale.tr("Downloading"), language.name.trim(), locale.tr(language.quality.name()), progress);
+            }
+        }
+        match self.missing_languages.get(self.selected) {
+            Some(language) => format!("{}: {}", locale.tr("Download"), language.name),
+            None => locale.tr("All languages installed").to_string()
+        }
+    }
+}
+
+/// Downloads the language's traineddata into the tessdata directory, reporting the percentage downloaded.
+/// The file is written under a temporary name first so a failed download never leaves a broken model.
+fn download_traineddata(language: &OCRLanguage, progress: impl Fn(u8)) -> Result<(), String> {
+    let code = &language.code;
+    let response = ureq::get(&format!("{}/{}.traineddata", repository_url(language.quality), code))
+        .call()
+        .map_err(|e| format!("Unable to download {}: {}", code, e))?;
+    let total_size = response.header("Content-Length").and_then(|length| length.parse::<u64>().ok());
+
+    let directory = match language.quality.directory() {
+        Some(quality_directory) => tessdata_directory().join(quality_directory),
+        None => tessdata_directory()
+    };
+    std::fs::create_dir_all(&directory).map_err(|e| format!("Unable to create tessdata directory: {}", e))?;
+    let partial_path = directory.join(format!("{}.traineddata.part", code));
+    let mut file = std::fs::File::create(&partial_path).map_err(|e| format!("Unable to create {}: {}", partial_path.display(), e))?;
+
+    let mut reader = response.into_reader();
+    let mut buffer = [0u8; 64 * 1024];
+    let mut downloaded: u64 = 0;
+    loop {
+        let read = reader.read(&mut buffer).map_err(|e| format!("Download of {} interrupted: {}", code, e))?;
+        if read == 0 {
+            break;
+        }
+        file.write_all(&buffer[..read]).map_err(|e| format!("Unable to write {}: {}", partial_path.display(), e))?;
+        downloaded += read as u64;
+        if let Some(total_size) = total_size.filter(|size| *size > 0) {
+            progress((downloaded * 100 / total_size).min(100) as u8);
+        }
+    }
+    drop(file);
+
+    std::fs::rename(&partial_path, directory.join(format!("{}.traineddata", code)))
+        .map_err(|e| format!("Unable to save {}.traineddata: {}", code, e))
+}
@@ -0,0 +1,82 @@
+use std::time::{Duration, Instant};
+
+use winit::window::Window;
+
+/// Keeps re-capturing the screen under the overlay so the text of a pinned selection stays
+/// current, e.g. for reading timers, logs, or streaming values.
+pub(crate) struct LiveMode {
+    active: bool,
+    last_capture: Instant,
+    last_copied: Option<String>,
+}
+
+impl LiveMode {
+    pub fn new() -> Self {
+        Self {
+            active: false,
+            last_capture: Instant::now(),
+            last_copied: None
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Starts live mode. The overlay is hidden from screen captures so re-capturing sees
+    /// what's underneath it rather than the frozen screenshot it shows.
+    pub fn start(&mut self, window: &Window) -> Result<(), String> {
+        set_excluded_from_capture(window, true)?;
+        self.active = true;
+        self.last_capture = Instant::now();
+        self.last_copied = None;
+        Ok(())
+    }
+
+    pub fn stop(&mut self, window: &Window) {
+        if !self.active {
+            return;
+        }
+        let _ = set_excluded_from_capture(window, false);
+        self.active = false;
+    }
+
+    /// Whether it's time to capture the screen again.
+    pub fn capture_due(&mut self, interval: Duration) -> bool {
+        if !self.active || self.last_capture.elapsed() < interval {
+            return false;
+        }
+        self.last_capture = Instant::now();
+        true
+    }
+
+    /// Whether `text` should be copied, i.e. it's different from what live mode last copied.
+    pub fn should_copy(&mut self, text: &str) -> bool {
+        if !self.active || self.last_copied.as_deref() == Some(text) {
+            return false;
+        }
+        self.last_copied = Some(text.to_string());
+        true
+    }
+}
+
+#[cfg(windows)]
+fn set_excluded_from_capture(window: &Window, excluded: bool) -> Result<(), String> {
+    use winit::raw_window_handle_05::{HasRawWindowHandle, RawWindowHandle};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{SetWindowDisplayAffinity, WDA_EXCLUDEFROMCAPTURE, WDA_NONE};
+
+    let RawWindowHandle::Win32(handle) = window.raw_window_handle() else {
+        return Err("Unable to get the overlay's window handle".to_string());
+    };
+    let affinity = if excluded { WDA_EXCLUDEFROMCAPTURE } else { WDA_NONE };
+    // Excluding windows from capture needs Windows 10 version 2004 or later
+    if unsafe { SetWindowDisplayAffinity(handle.hwnd as _, affinity) } == 0 {
+        return Err("Live mode needs Windows 10 version 2004 or later".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn set_excluded_from_capture(_window: &Window, _excluded: bool) -> Result<(), String> {
+    Err("Live mode is not supported on this platform".to_string())
+}
@@ -0,0 +1,127 @@
+use std::{sync::{Arc, Mutex}, thread, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// The OpenAI-compatible chat completions endpoint results are post-processed with.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LlmSettings {
+    #[serde(default = "default_url")]
+    pub url: String,
+    #[serde(default)]
+    pub api_key: String,
+    #[serde(default = "default_model")]
+    pub model: String,
+    #[serde(default = "default_prompt")]
+    pub prompt: String,
+    #[serde(default = "default_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+fn default_url() -> String {
+    "https://api.openai.com/v1/chat/completions".to_string()
+}
+
+fn default_model() -> String {
+    "gpt-4o-mini".to_string()
+}
+
+fn default_prompt() -> String {
+    "The following text was recognized with OCR. Fix any recognition errors and reply with only the corrected text.".to_string()
+}
+
+fn default_timeout_seconds() -> u64 {
+    30
+}
+
+impl Default for LlmSettings {
+    fn default() -> Self {
+        Self {
+            url: default_url(),
+            api_key: String::new(),
+            model: default_model(),
+            prompt: default_prompt(),
+            timeout_seconds: default_timeout_seconds()
+        }
+    }
+}
+
+/// Sends OCR results to a language model in the background, keeping the latest response.
+pub struct PostProcessor {
+    /// The text being processed and where the worker puts the response
+    request: Option<(String, Arc<Mutex<Option<Result<String, String>>>>)>,
+    /// The last text that was sent, so a result isn't sent twice even if processing it failed
+    last_requested: Option<String>,
+    /// The last processed text, along with the text it was made from
+    processed: Option<(String, String)>
+}
+
+impl PostProcessor {
+    pub fn new() -> Self {
+        PostProcessor { request: None, last_requested: None, processed: None }
+    }
+
+    /// Starts processing the text, unless it was already sent. Only one request runs at a time; a newer
+    /// text is sent once the current request finishes.
+    pub fn process(&mut self, settings: &LlmSettings, text: &str) {
+        if self.request.is_some() || self.last_requested.as_deref() == Some(text) {
+            return;
+        }
+
+        let state = Arc::new(Mutex::new(None));
+        let thread_state = state.clone();
+        let settings = settings.clone();
+        let thread_text = text.to_string();
+        thread::spawn(move || {
+            *thread_state.lock().unwrap() = Some(complete(&settings, &thread_text));
+        });
+
+        self.last_requested = Some(text.to_string());
+        self.request = Some((text.to_string(), state));
+    }
+
+    /// Stores the response once a request finishes, returning an error if it failed.
+    pub fn poll(&mut self) -> Option<Result<(), String>> {
+        let (_, state) = self.request.as_ref()?;
+        let result = state.lock().unwrap().take()?;
+        let (text, _) = self.request.take()?;
+        Some(result.map(|processed| self.processed = Some((text, processed))))
+    }
+
+    /// The processed version of the text, if it's been processed.
+    pub fn processed(&self, text: &str) -> Option<&str> {
+        match &self.processed {
+            Some((source, processed)) if source == text => Some(processed),
+            _ => None
+        }
+    }
+}
+
+fn complete(settings: &LlmSettings, text: &str) -> Result<String, String> {
+    let request = json!({
+        "model": settings.model,
+        "messages": [
+            { "role": "system", "content": settings.prompt },
+            { "role": "user", "content": text }
+        ]
+    });
+
+    let mut http_request = ureq::post(&settings.url)
+        .timeout(Duration::from_secs(settings.timeout_seconds))
+        .set("Content-Type", "application/json");
+    // Local servers like Ollama don't need a key
+    if !settings.api_key.is_empty() {
+        http_request = http_request.set("Authorization", &format!("Bearer {}", settings.api_key));
+    }
+    let response = http_request
+        .send_string(&request.to_string())
+        .map_err(|e| format!("Unable to reach the language model: {}", e))?
+        .into_string()
+        .map_err(|e| format!("Unable to read the language model's response: {}", e))?;
+
+    let response: Value = serde_json::from_str(&response).map_err(|e| format!("Unexpected language model response: {}", e))?;
+    response.pointer("/choices/0/message/content")
+        .and_then(|content| content.as_str())
+        .map(|content| content.trim().to_string())
+        .ok_or_else(|| "The language model's response has no text".to_string())
+}
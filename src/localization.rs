@@ -0,0 +1,92 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use serde::Deserialize;
+
+use crate::{settings::get_project_dirs, INITIALIZATION_ERRORS};
+
+const LOCALES_DIRECTORY: &str = "locales";
+/// The language the overlay is written in, which needs no locale file
+pub(crate) const DEFAULT_LOCALE: &str = "en";
+
+/// A locale file, e.g. `locales/de.toml`. Strings are keyed by their English text, so anything left out
+/// stays in English.
+#[derive(Debug, Default, Deserialize)]
+struct LocaleFile {
+    /// The language's name in itself, as shown in the settings panel
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    strings: HashMap<String, String>,
+}
+
+/// The translations of the overlay's labels and tooltips.
+#[derive(Debug, Clone)]
+pub struct Locale {
+    pub code: String,
+    pub name: String,
+    strings: HashMap<String, String>,
+}
+
+fn locales_path() -> PathBuf {
+    get_project_dirs().config_dir().join(LOCALES_DIRECTORY)
+}
+
+fn read_locale_file(code: &str) -> Result<LocaleFile, String> {
+    let file_name = format!("{}.toml", code);
+    let contents = std::fs::read_to_string(locales_path().join(&file_name)).map_err(|e| format!("Unable to read {}: {}", file_name, e))?;
+    toml::from_str(&contents).map_err(|e| format!("Failed to parse {}: {}", file_name, e))
+}
+
+impl Locale {
+    pub fn english() -> Self {
+        Locale { code: DEFAULT_LOCALE.to_string(), name: "English".to_string(), strings: HashMap::new() }
+    }
+
+    /// Loads a locale from the config directory, falling back to English if it's missing or invalid.
+    pub fn load(code: &str) -> Self {
+        if code.is_empty() || code == DEFAULT_LOCALE {
+            return Locale::english();
+        }
+
+        match read_locale_file(code) {
+            Ok(file) => Locale {
+                code: code.to_string(),
+                name: if file.name.is_empty() { code.to_string() } else { file.name },
+                strings: file.strings,
+            },
+            Err(error) => {
+                eprintln!("{}", error);
+                INITIALIZATION_ERRORS.lock().unwrap().push(error);
+                Locale::english()
+            }
+        }
+    }
+
+    /// Translates English text, or returns it as-is if this locale doesn't translate it.
+    pub fn tr<'a>(&'a self, text: &'a str) -> &'a str {
+        self.strings.get(text).map_or(text, |translation| translation.as_str())
+    }
+}
+
+/// The codes of every locale that can be picked, English first and then the files in the config directory.
+pub(crate) fn available_locales() -> Vec<String> {
+    let mut codes: Vec<String> = std::fs::read_dir(locales_path())
+        .map(|entries| entries.flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "toml"))
+            .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().to_string()))
+            .filter(|code| code != DEFAULT_LOCALE)
+            .collect())
+        .unwrap_or_default();
+    codes.sort();
+    codes.insert(0, DEFAULT_LOCALE.to_string());
+    codes
+}
+
+/// The locale after (or before, going backwards) `current` among the available ones.
+pub(crate) fn cycle_locale(current: &str, forwards: bool) -> String {
+    let codes = available_locales();
+    let index = codes.iter().position(|code| code == current).unwrap_or(0);
+    let next = if forwards { (index + 1) % codes.len() } else { (index + codes.len() - 1) % codes.len() };
+    codes[next].clone()
+}
@@ -5,17 +5,34 @@
 
 use clipboard::{ClipboardContext, ClipboardProvider};
 use clipboard_image::copy_image_to_clipboard;
+use image::DynamicImage;
 use input::InputHandler;
+use settings::{OutputTarget, RegionPreset};
+use meeting_notes::MeetingNotes;
+use live_mode::LiveMode;
+use subtitle_capture::SubtitleCapture;
 use inputbot::MouseCursor;
-use ocr_handler::{FormatOptions, OCRHandler, get_screenshot_path};
+use ocr_handler::{configure_tesseract, recognize_region, OCRHandler};
+use text_formatting::FormatOptions;
 use pixels::{Pixels, PixelsBuilder, SurfaceTexture};
-use screenshot::{crop_screenshot_to_bounds, crop_screenshot_to_polygon, screenshot_from_handle, Screenshot};
+use screenshot::{clear_outside_polygon, content_bounds, crop_screenshot_to_bounds, screenshot_from_handle};
+use geometry::{Point, Polygon, Rect};
+use automation::{AutomationCommand, AutomationReply};
+use protocol::ProtocolRequest;
+use scripting::{ScriptCommand, ScriptRunner};
 use selection::{Selection, SelectionInputResult};
+use transcript::{SubtitleFormat, Transcript};
 use undo_stack::UndoStack;
+use upload::Uploader;
+use anki::AnkiSender;
+use clipboard_watcher::ClipboardWatcherMode;
+use llm::PostProcessor;
+use text_regions::{ScreenLayout, SmartSelect};
+use bounds_entry::BoundsEntry;
 use windows_sys::Win32::Foundation::HWND;
 use std::sync::{mpsc, Arc, LazyLock, Mutex};
 use winit::application::ApplicationHandler;
-use winit::event::{ElementState, WindowEvent};
+use winit::event::{ElementState, MouseScrollDelta, WindowEvent};
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
 use winit::keyboard::{Key, NamedKey};
 use winit::platform::windows::WindowAttributesExtWindows;
@@ -26,35 +43,162 @@ mod ocr_handler;
 mod renderer;
 mod screenshot;
 mod selection;
+mod geometry;
 mod wgpu_text;
 mod settings;
 mod clipboard_image;
+mod clipboard_text;
 mod undo_stack;
 mod input;
+mod transcript;
+mod meeting_notes;
+mod tsv;
+mod text_formatting;
+mod capture_check;
+mod word_choices;
+mod scripting;
+mod automation;
+mod stream_deck;
+mod annotations;
+mod language_manager;
+mod live_mode;
+mod subtitle_capture;
+mod custom_dictionary;
+mod receipt;
+mod entities;
+mod export;
+mod docx;
+mod anki;
+mod upload;
+mod share;
+mod clipboard_history;
+mod protocol;
+mod llm;
+mod web_search;
+mod clipboard_watcher;
+mod notification;
+mod text_regions;
+mod bounds_entry;
+mod localization;
+#[cfg(windows)]
+mod registry;
+
+/// Events sent to the main thread from hotkeys, the tray, and scripts
+#[derive(Debug)]
+pub(crate) enum AppEvent {
+    OpenOverlay,
+    RunScript(usize),
+    Script(ScriptCommand),
+    Automation(AutomationCommand, AutomationReply),
+    CopyHistory(usize),
+    ToggleHotkeysPaused,
+    /// Text recognized in an image another app copied
+    ClipboardImageRecognized(String, DynamicImage),
+}
 
 pub static INITIALIZATION_ERRORS: LazyLock<Arc<Mutex<Vec<String>>>> = LazyLock::new(|| Arc::new(Mutex::new(Vec::new())));
 
+/// How far the middle button can move while held before it pans instead of smart selecting
+const PAN_THRESHOLD: f32 = 4.;
+/// How much one step of Ctrl + scroll zooms the screenshot
+const ZOOM_STEP: f32 = 1.25;
+/// How quickly and how close together two clicks have to be to count as a double-click
+const DOUBLE_CLICK_TIME: std::time::Duration = std::time::Duration::from_millis(400);
+const DOUBLE_CLICK_DISTANCE: f32 = 5.;
+
 fn main() {
+    if std::env::args().any(|arg| arg == capture_check::CAPTURE_CHECK_ARG) {
+        std::process::exit(if capture_check::run() { 0 } else { 1 });
+    }
+
+    // OpenMP only reads this once, so it has to be set before any threads exist, and changes need a restart
+    let thread_limit = settings::TesseractSettings::read_thread_limit();
+    if thread_limit > 0 {
+        std::env::set_var("OMP_THREAD_LIMIT", thread_limit.to_string());
+    }
+
+    // Links are handled by the instance that's already running, if there is one
+    let uri = std::env::args().find(|arg| arg.starts_with(protocol::URI_PREFIX));
+    if let Some(uri) = &uri {
+        if let Some(result) = automation::send_to_running_instance(&AutomationCommand::OpenUri { uri: uri.clone() }) {
+            match result {
+                Ok(_) => std::process::exit(0),
+                Err(error) => {
+                    eprintln!("Unable to open {}: {}", uri, error);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
     // Only run event loop on user interaction
-    let event_loop = EventLoop::new().expect("Unable to create event loop");
+    let event_loop = EventLoop::<AppEvent>::with_user_event().build().expect("Unable to create event loop");
     event_loop.set_control_flow(ControlFlow::Wait);
     
-    let loop_proxy: winit::event_loop::EventLoopProxy<()> = event_loop.create_proxy();
+    let scripts = scripting::load_scripts();
+
+    let loop_proxy: winit::event_loop::EventLoopProxy<AppEvent> = event_loop.create_proxy();
     let mut tray = tray_item::TrayItem::new(
         "OnScreenOCR",
         tray_item::IconSource::Resource("tray-default"),
     ).unwrap();
     tray.add_menu_item("Open overlay", move || {
-        loop_proxy.send_event(()).expect("Unable to send event");
+        loop_proxy.send_event(AppEvent::OpenOverlay).expect("Unable to send event");
     }).unwrap();
+    for (index, script) in scripts.iter().enumerate().filter(|(_, script)| script.tray) {
+        let loop_proxy = event_loop.create_proxy();
+        tray.add_menu_item(&format!("Run {}", script.name), move || {
+            loop_proxy.send_event(AppEvent::RunScript(index)).expect("Unable to send event");
+        }).unwrap();
+    }
+    tray.inner_mut().add_separator().unwrap();
+    // tray-item doesn't support submenus, so the most recent results get their own items
+    for (index, label) in ["Copy latest result", "Copy previous result", "Copy result from 3 captures ago"].iter().enumerate() {
+        let loop_proxy = event_loop.create_proxy();
+        tray.add_menu_item(label, move || {
+            loop_proxy.send_event(AppEvent::CopyHistory(index)).expect("Unable to send event");
+        }).unwrap();
+    }
     tray.inner_mut().add_separator().unwrap();
+    let loop_proxy = event_loop.create_proxy();
+    tray.add_menu_item("Pause or resume hotkeys", move || {
+        loop_proxy.send_event(AppEvent::ToggleHotkeysPaused).expect("Unable to send event");
+    }).unwrap();
     tray.add_menu_item("Quit", || {
         std::process::exit(0);
     }).unwrap();
 
+    let script_keybinds = scripts.iter().enumerate()
+        .filter_map(|(index, script)| script.hotkey.map(|hotkey| (index, hotkey)))
+        .collect();
+
     let mut app = App::default();
+    // Registering replaces whatever handles the links now, so it's only done when asked for
+    if app.icon_context.settings.tesseract_settings.register_url_protocol {
+        if let Err(error) = protocol::register() {
+            eprintln!("Unable to register the {} protocol: {}", protocol::URI_PREFIX, error);
+        }
+    }
+    app.script_runner = Some(ScriptRunner::new(scripts, event_loop.create_proxy()));
     let keybind = app.icon_context.settings.open_keybind.clone();
-    app.input_handler.handle(&event_loop, keybind);
+    let hotkeys_paused = app.icon_context.settings.hotkeys_paused.clone();
+    app.input_handler.handle(&event_loop, keybind, script_keybinds, hotkeys_paused);
+    automation::start(event_loop.create_proxy());
+    stream_deck::start(event_loop.create_proxy());
+    let tesseract_settings = app.icon_context.settings.tesseract_settings.clone();
+    if tesseract_settings.clipboard_watcher != ClipboardWatcherMode::Off {
+        if let Err(error) = clipboard_watcher::start(tesseract_settings, event_loop.create_proxy()) {
+            eprintln!("Clipboard watcher unavailable: {}", error);
+        }
+    }
+    if let Some(uri) = uri {
+        // Queued until the event loop starts
+        let loop_proxy = event_loop.create_proxy();
+        std::thread::spawn(move || {
+            if let Err(error) = automation::send_command(&loop_proxy, AutomationCommand::OpenUri { uri: uri.clone() }) {
+                eprintln!("Unable to open {}: {}", uri, error);
+            }
+        });
+    }
     event_loop.run_app(&mut app).expect("Unable to run event loop");
 }
 
@@ -82,6 +226,31 @@ struct App {
     undo_stack: UndoStack,
     attempt_copy_on_next_ocr: bool,
 
+    transcript: Transcript,
+    meeting_notes: MeetingNotes,
+    live_mode: LiveMode,
+    /// Lines collected while subtitle capture is running on top of live mode
+    subtitle_capture: Option<SubtitleCapture>,
+    script_runner: Option<ScriptRunner>,
+    uploader: Uploader,
+    anki_sender: AnkiSender,
+    post_processor: PostProcessor,
+    smart_select: SmartSelect,
+    screen_layout: ScreenLayout,
+    /// Where the middle button was pressed and where the cursor last was while it's held, which pans
+    /// the screenshot, or smart selects if it's released without moving
+    middle_drag: Option<(Point, Point)>,
+    /// Where the left button was pressed with Alt over a recognized word. Releasing it there finds the word in the
+    /// preview, while moving away starts the selection from that point, so Alt still drags without snapping.
+    word_click: Option<Point>,
+    /// When and where the last left click was, along with the selection and preview from before it if
+    /// it was inside the selection, so a double-click can bring them back and copy
+    last_click: Option<(std::time::Instant, Point, Option<(Rect, Polygon, Option<String>)>)>,
+    /// The most recent non-empty OCR result, kept after the overlay closes for automation clients
+    last_result: Option<String>,
+    /// When the overlay was last closed, to restore the session if it's reopened soon after
+    hidden_at: Option<std::time::Instant>,
+
     user_feedback_queue: Vec<(String, [f32; 3])>
 }
 
@@ -89,8 +258,9 @@ impl Default for App {
     fn default() -> Self {
         let (tx, rx) = mpsc::channel();
 
-        let icon_context = IconContext::new(tx);
+        let mut icon_context = IconContext::new(tx);
         let icon_event_receiver = rx;
+        INITIALIZATION_ERRORS.lock().unwrap().extend(icon_context.settings.tesseract_settings.apply_model_fallbacks());
 
         App {
             window_state: None,
@@ -107,6 +277,22 @@ impl Default for App {
             undo_stack: UndoStack::new(),
             attempt_copy_on_next_ocr: false,
 
+            transcript: Transcript::new(),
+            meeting_notes: MeetingNotes::new(),
+            live_mode: LiveMode::new(),
+            subtitle_capture: None,
+            script_runner: None,
+            uploader: Uploader::new(),
+            anki_sender: AnkiSender::new(),
+            post_processor: PostProcessor::new(),
+            smart_select: SmartSelect::new(),
+            middle_drag: None,
+            word_click: None,
+            last_click: None,
+            screen_layout: ScreenLayout::new(),
+            last_result: None,
+            hidden_at: None,
+
             user_feedback_queue: INITIALIZATION_ERRORS.lock().unwrap().iter().map(|err| (err.clone(), [0.8, 0.3, 0.4])).collect()
         }
     }
@@ -116,11 +302,14 @@ impl Default for App {
 impl App {
     fn set_mouse_cursor(&self) {
         let window = &self.window_state.as_ref().unwrap().window;
-        let cursor = match (self.selection.shift_held, self.selection.mouse_down) {
-            (true, true) => CursorIcon::Grabbing,
-            (true, false) => CursorIcon::Grab,
-            (false, true) => CursorIcon::Crosshair,
-            (false, false) => CursorIcon::Default,
+        let resize_cursor = self.selection.resize_cursor(self.icon_context.settings.use_polygon);
+        let panning = self.middle_drag.is_some() && self.icon_context.view.zoom > 1.;
+        let cursor = match (self.selection.shift_held || panning, self.selection.mouse_down || panning, resize_cursor) {
+            (true, true, _) => CursorIcon::Grabbing,
+            (true, false, _) => CursorIcon::Grab,
+            (false, _, Some(cursor)) => cursor,
+            (false, true, None) => CursorIcon::Crosshair,
+            (false, false, None) => CursorIcon::Default,
         };
         window.set_cursor(Cursor::from(cursor));
     }
@@ -130,11 +319,37 @@ impl App {
 
         self.set_mouse_cursor();
 
+        self.poll_language_download();
+        self.poll_upload();
+        self.poll_anki();
+        self.capture_live_frame();
+        self.post_process();
+        self.poll_smart_select();
+        self.poll_screen_layout();
+
         let updated = self.ocr_handler.update_ocr_preview_text();
+        if updated {
+            self.last_result = self.ocr_handler.ocr_preview_text.clone();
+        }
+        if updated && self.icon_context.settings.auto_copy {
+            self.copy_live_result();
+        }
+        if updated {
+            if let (Some(subtitle_capture), Some(text)) = (&mut self.subtitle_capture, &self.ocr_handler.ocr_preview_text) {
+                subtitle_capture.record(text);
+            }
+        }
         if updated && self.attempt_copy_on_next_ocr {
             self.attempt_copy_on_next_ocr = false;
             self.attempt_copy();
         }
+        self.transcript.record(self.ocr_handler.ocr_preview_text.as_deref());
+        if let Some(script_runner) = &self.script_runner {
+            script_runner.set_last_ocr_text(self.ocr_handler.ocr_preview_text.as_deref());
+        }
+
+        let preview_text = self.result_text();
+        let uncertain_words = if preview_text == self.ocr_handler.ocr_preview_text { &self.ocr_handler.uncertain_words[..] } else { &[] };
 
         let state = self.window_state.as_mut().unwrap();
 
@@ -146,7 +361,12 @@ impl App {
             shader_renderer.show_user_feedback(message, color);
         }
 
-        self.icon_context.has_selection = self.selection.bounds.width != 0 && self.selection.bounds.height != 0;
+        self.icon_context.has_selection = !self.selection.bounds.is_empty();
+        self.icon_context.undo_ghost = if self.selection.ctrl_held && !self.selection.mouse_down {
+            self.undo_stack.peek_undo().unwrap_or_default()
+        } else {
+            Vec::new()
+        };
 
         match self.input_handler.check_for_detected_keybind() {
             Some(keybind) => {
@@ -162,7 +382,10 @@ impl App {
                 context,
                 self.size,
                 &mut self.selection,
-                self.ocr_handler.ocr_preview_text.clone(),
+                preview_text.clone(),
+                uncertain_words,
+                &self.ocr_handler.word_boxes,
+                self.ocr_handler.ocr_progress,
                 self.relative_mouse_pos,
                 &mut self.icon_context
             );
@@ -200,6 +423,9 @@ impl App {
                 IconEvent::Screenshot => {
                     self.attempt_screenshot();
                 }
+                IconEvent::SearchWeb => {
+                    self.search_web();
+                }
                 IconEvent::ActiveOCRLeft => {
                     self.icon_context.settings.tesseract_settings.ocr_language_decrement();
                     self.ocr_handler.update_ocr_settings(self.icon_context.settings.tesseract_settings.clone());
@@ -208,6 +434,38 @@ impl App {
                     self.icon_context.settings.tesseract_settings.ocr_language_increment();
                     self.ocr_handler.update_ocr_settings(self.icon_context.settings.tesseract_settings.clone());
                 }
+                IconEvent::AddToDictionary(word_index) => {
+                    self.add_to_dictionary(word_index);
+                }
+                IconEvent::CopyEntity(text) => {
+                    self.copy_plain_text(text);
+                }
+                IconEvent::CycleOutputProfile => {
+                    self.cycle_output_profile();
+                }
+                IconEvent::CopyHistory(index) => {
+                    self.copy_history(index);
+                }
+                IconEvent::RecallRegionPreset(index) => {
+                    self.recall_region_preset(index);
+                }
+                IconEvent::CycleModelQuality => {
+                    let tesseract_settings = &mut self.icon_context.settings.tesseract_settings;
+                    let mut language = tesseract_settings.get_ocr_language_data();
+                    language.quality = tesseract_settings.next_model_quality();
+                    if language_manager::model_exists(&language) {
+                        tesseract_settings.set_model_quality(&language.code, language.quality);
+                        self.ocr_handler.update_ocr_settings(tesseract_settings.clone());
+                    } else if let Err(error) = self.icon_context.language_manager.download(language) {
+                        // The quality is switched once the download finishes
+                        self.show_negative_feedback(&error);
+                    }
+                }
+                IconEvent::DownloadLanguage => {
+                    if let Err(error) = self.icon_context.language_manager.download_selected() {
+                        self.show_negative_feedback(&error);
+                    }
+                }
                 IconEvent::UpdateOCRFormatOption => {
                     self.ocr_handler.format_option_changed(FormatOptions::from_settings(&self.icon_context.settings));
                 }
@@ -236,82 +494,826 @@ impl App {
                 IconEvent::RefreshOCRConfiguration => {
                     INITIALIZATION_ERRORS.lock().unwrap().clear();
                     self.icon_context.settings.tesseract_settings.reload();
+                    self.icon_context.reload_locale();
+                    INITIALIZATION_ERRORS.lock().unwrap().extend(self.icon_context.settings.tesseract_settings.apply_model_fallbacks());
                     for error in INITIALIZATION_ERRORS.lock().unwrap().iter() {
                         self.show_negative_feedback(&error);
                     }
 
                     self.ocr_handler.update_ocr_settings(self.icon_context.settings.tesseract_settings.clone());
+                    let tesseract_settings = &self.icon_context.settings.tesseract_settings;
+                    self.icon_context.clipboard_history.configure(tesseract_settings.history_size, tesseract_settings.persist_history);
                 }
-                IconEvent::ChangeUsePolygon => {
-                    self.selection.change_use_polygon(self.icon_context.settings.use_polygon);
+                IconEvent::ChangeUsePolygon | IconEvent::ChangeUseEllipse => {
+                    self.selection.change_shape(self.icon_context.settings.use_polygon, self.icon_context.settings.use_ellipse);
                     self.ocr_handler.selection_changed(&self.selection);
                 }
                 IconEvent::ChangeKeybind => {
                     self.icon_context.settings.open_keybind_string = "Press a key combination".to_string();
                     self.input_handler.detect_next_keybind();
                 }
+                IconEvent::ChooseAlternative(word_index, alternative_index) => {
+                    self.ocr_handler.choose_alternative(word_index, alternative_index);
+                }
+            }
+        }
+    }
+
+    fn auto_copy(&mut self) {
+        if self.icon_context.settings.auto_copy {
+            self.attempt_copy_on_next_ocr = true;
+            self.ocr_handler.selection_changed(&self.selection);
+        }
+    }
+
+    /// The text shown in the preview and copied: the OCR result, or the language model's version of it
+    /// if post-processing is on and it's finished.
+    fn result_text(&self) -> Option<String> {
+        let text = self.ocr_handler.ocr_preview_text.as_ref()?;
+        let settings = &self.icon_context.settings;
+        match self.post_processor.processed(text) {
+            Some(processed) if settings.llm_post_processing && settings.use_processed_text => Some(processed.to_string()),
+            _ => Some(text.clone())
+        }
+    }
+
+    /// Sends new OCR results to the language model when post-processing is on.
+    fn post_process(&mut self) {
+        if let Some(Err(error)) = self.post_processor.poll() {
+            self.show_negative_feedback(&error);
+        }
+        if !self.icon_context.settings.llm_post_processing || self.selection.mouse_down {
+            return;
+        }
+        if let Some(text) = &self.ocr_handler.ocr_preview_text {
+            self.post_processor.process(&self.icon_context.settings.tesseract_settings.llm, text);
+        }
+    }
+
+    fn attempt_copy(&mut self) {
+        if self.ocr_handler.ocr_preview_text.is_none() {
+            if let Err(reason) = self.check_selection_size() {
+                self.show_negative_feedback(&reason);
+                return;
+            }
+
+            if !self.icon_context.settings.copy_image_without_text {
+                self.show_negative_feedback("No text was recognized");
+                return;
+            }
+
+            match self.copy_selection_image() {
+                Ok(()) => {
+                    if self.icon_context.settings.close_on_copy {
+                        self.hide_window();
+                    }
+                    self.show_positive_feedback("No text was recognized, so the selection was copied as an image");
+                }
+                Err(reason) => self.show_negative_feedback(&reason)
+            }
+            return;
+        }
+
+        let text = export::render_copy_template(&self.icon_context.settings.tesseract_settings, &self.result_text().unwrap());
+        let output_target = self.icon_context.settings.tesseract_settings.output_target;
+
+        if output_target == OutputTarget::Note {
+            let image = if self.icon_context.settings.tesseract_settings.note.embed_image { self.selection_image().ok() } else { None };
+            match export::append_to_note(&self.icon_context.settings.tesseract_settings, &text, image.as_ref()) {
+                Ok(path) => {
+                    if self.icon_context.settings.close_on_copy {
+                        self.hide_window();
+                    }
+                    self.show_positive_feedback(&format!("Added to {}", path.to_string_lossy()));
+                }
+                Err(error) => self.show_negative_feedback(&error)
+            }
+            return;
+        }
+
+        let saved_path = if output_target.uses_file() {
+            match export::write_result_file(&self.icon_context.settings.tesseract_settings, &text) {
+                Ok(path) => Some(path),
+                Err(error) => {
+                    self.show_negative_feedback(&error);
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+
+        if output_target.uses_clipboard() {
+            // Copy the OCR text to the clipboard, along with HTML and RTF renditions for rich text editors
+            let image = if self.icon_context.settings.copy_image_with_text { self.selection_image().ok() } else { None };
+            let settings = &self.icon_context.settings;
+            let clear_clipboard_seconds = settings.tesseract_settings.clear_clipboard_seconds;
+            // Text that's cleared from the clipboard, like passwords, shouldn't be kept anywhere else either
+            let private = clear_clipboard_seconds > 0;
+            if let Err(error) = clipboard_text::copy_rich_text(&text, settings.code_mode, settings.copy_rtf, image.as_ref(), private) {
+                self.show_negative_feedback(&error);
+                return;
+            }
+            if !private {
+                self.icon_context.clipboard_history.push(&text);
+            }
+            clipboard_text::clear_clipboard_after(text.clone(), clear_clipboard_seconds);
+        }
+
+        if output_target == OutputTarget::Type {
+            // The overlay has to close so the keystrokes reach the window underneath it
+            self.hide_window();
+            input::type_text(text);
+            return;
+        }
+        
+        if self.icon_context.settings.close_on_copy {
+            self.hide_window();
+        }
+
+        match (saved_path, output_target.uses_clipboard()) {
+            (Some(path), true) => self.show_positive_feedback(&format!("Copied to clipboard and saved to {}", path.to_string_lossy())),
+            (Some(path), false) => self.show_positive_feedback(&format!("Saved to {}", path.to_string_lossy())),
+            (None, _) => self.show_positive_feedback("Copied to clipboard")
+        }
+    }
+
+    fn copy_plain_text(&mut self, text: String) {
+        let clear_clipboard_seconds = self.icon_context.settings.tesseract_settings.clear_clipboard_seconds;
+        if let Err(error) = clipboard_text::copy_plain_text(&text, clear_clipboard_seconds > 0) {
+            self.show_negative_feedback(&format!("Unable to copy: {}", error));
+            return;
+        }
+        clipboard_text::clear_clipboard_after(text.clone(), clear_clipboard_seconds);
+
+        if self.icon_context.settings.close_on_copy {
+            self.hide_window();
+        }
+        self.show_positive_feedback(&format!("Copied '{}' to clipboard", text));
+    }
+
+    fn cycle_output_profile(&mut self) {
+        let Some(name) = self.icon_context.settings.cycle_output_profile() else {
+            self.show_negative_feedback("No output profiles are defined");
+            return;
+        };
+
+        let settings = &self.icon_context.settings;
+        self.ocr_handler.output_profile_changed(settings.tesseract_settings.clone(), FormatOptions::from_settings(settings));
+        self.show_positive_feedback(&format!("Output profile: {}", name));
+    }
+
+    /// Copies an earlier result from the clipboard history again, from the overlay or the tray.
+    fn copy_history(&mut self, index: usize) {
+        let Some(text) = self.icon_context.clipboard_history.get(index).cloned() else {
+            self.show_negative_feedback("There's no result at that point in the history");
+            return;
+        };
+
+        let clear_clipboard_seconds = self.icon_context.settings.tesseract_settings.clear_clipboard_seconds;
+        let private = clear_clipboard_seconds > 0;
+        if let Err(error) = clipboard_text::copy_plain_text(&text, private) {
+            self.show_negative_feedback(&format!("Unable to copy: {}", error));
+            return;
+        }
+        if !private {
+            self.icon_context.clipboard_history.push(&text);
+        }
+        clipboard_text::clear_clipboard_after(text, clear_clipboard_seconds);
+
+        if self.icon_context.settings.close_on_copy && self.window_state.is_some() {
+            self.hide_window();
+        }
+        self.show_positive_feedback("Copied the result from the history to clipboard");
+    }
+
+    /// Returns the reason the current selection can't be used, if any.
+    fn check_selection_size(&self) -> Result<(), String> {
+        if self.selection.bounds.is_empty() {
+            return Err("Nothing is selected".to_string());
+        }
+
+        let minimum_size = self.icon_context.settings.tesseract_settings.minimum_selection_size;
+        if self.selection.bounds.smaller_than(minimum_size) {
+            return Err(format!("The selection is too small (minimum {}×{})", minimum_size, minimum_size));
+        }
+
+        Ok(())
+    }
+
+    /// Copies the selected part of the screenshot to the clipboard.
+    fn copy_selection_image(&mut self) -> Result<(), String> {
+        copy_image_to_clipboard(&self.selection_image()?)
+    }
+
+    /// The selected part of the screenshot, with everything outside a polygon selection transparent.
+    fn selection_image(&self) -> Result<DynamicImage, String> {
+        self.check_selection_size()?;
+
+        let Some(screenshot) = &self.ocr_handler.screenshot else {
+            return Err("The screenshot isn't ready yet".to_string());
+        };
+
+        let pixel_bounds = self.selection.bounds.to_pixel_rect();
+        let cropped_screenshot = crop_screenshot_to_bounds(pixel_bounds, screenshot);
+        let cropped_screenshot = clear_outside_polygon(
+            &self.selection.polygon.points().into_iter().map(|point| point - pixel_bounds.origin()).collect::<Vec<Point>>(),
+            cropped_screenshot
+        );
+
+        Ok(cropped_screenshot.into())
+    }
+
+    /// Types into the bounds entry, moving the selection whenever the typed bounds are valid.
+    fn bounds_entry_key(&mut self, event: &winit::event::KeyEvent) {
+        if event.state != ElementState::Pressed {
+            return;
+        }
+        let Some(entry) = &mut self.icon_context.bounds_entry else {
+            return;
+        };
+
+        match event.logical_key.as_ref() {
+            Key::Named(NamedKey::Tab) => {
+                entry.next_field(self.selection.shift_held);
+                return;
+            }
+            Key::Named(NamedKey::Enter) => {
+                match entry.bounds(self.size) {
+                    Ok(_) => {
+                        self.icon_context.bounds_entry = None;
+                        self.undo_stack.take_snapshot(&self.selection);
+                        self.auto_copy();
+                    }
+                    Err(error) => self.show_negative_feedback(&error)
+                }
+                return;
+            }
+            Key::Named(NamedKey::Escape) => {
+                self.selection.bounds = entry.original_bounds;
+                self.selection.polygon = entry.original_polygon.clone();
+                self.icon_context.bounds_entry = None;
+                self.ocr_handler.ocr_preview_text = None;
+                self.ocr_handler.selection_changed(&self.selection);
+                return;
             }
+            Key::Named(NamedKey::Backspace) => entry.backspace(),
+            Key::Character(text) => text.chars().for_each(|character| entry.type_character(character)),
+            _ => return
+        }
+
+        if let Ok(bounds) = entry.bounds(self.size) {
+            self.selection.bounds = bounds;
+            self.selection.polygon.set_from_rect(&bounds);
+            self.ocr_handler.ocr_preview_text = None;
+            self.ocr_handler.selection_changed(&self.selection);
+        }
+    }
+
+    /// Types the name of a region preset, saving the selection under it on Enter.
+    fn preset_name_entry_key(&mut self, event: &winit::event::KeyEvent) {
+        if event.state != ElementState::Pressed {
+            return;
+        }
+        let Some(name) = &mut self.icon_context.preset_name_entry else {
+            return;
+        };
+
+        match event.logical_key.as_ref() {
+            Key::Named(NamedKey::Enter) => {
+                let name = name.trim().to_string();
+                if name.is_empty() {
+                    self.show_negative_feedback("The preset needs a name");
+                    return;
+                }
+                self.icon_context.preset_name_entry = None;
+                self.save_region_preset(name);
+            }
+            Key::Named(NamedKey::Escape) => self.icon_context.preset_name_entry = None,
+            Key::Named(NamedKey::Backspace) => {
+                name.pop();
+            }
+            Key::Named(NamedKey::Space) => name.push(' '),
+            Key::Character(text) => name.push_str(text),
+            _ => ()
+        }
+    }
+
+    /// Saves the selection as a region preset. Polygon and ellipse selections keep their shape.
+    fn save_region_preset(&mut self, name: String) {
+        let settings = &mut self.icon_context.settings;
+        let bounds = self.selection.bounds.to_positive_size();
+        let polygon = if settings.use_polygon || settings.use_ellipse {
+            self.selection.polygon.points().into_iter().map(|point| (point.x, point.y)).collect()
+        } else {
+            Vec::new()
+        };
+
+        settings.tesseract_settings.add_region_preset(RegionPreset { name: name.clone(), x: bounds.x, y: bounds.y, width: bounds.width, height: bounds.height, polygon });
+        if let Some(index) = settings.tesseract_settings.region_presets.iter().position(|preset| preset.name == name) {
+            self.icon_context.region_preset = index;
+        }
+        self.show_positive_feedback(&format!("Saved the region as '{}'", name));
+    }
+
+    /// Selects a saved region preset. Presets with a polygon switch to polygon mode, since that's the
+    /// only mode that can edit an arbitrary shape.
+    fn recall_region_preset(&mut self, index: usize) {
+        let Some(preset) = self.icon_context.settings.tesseract_settings.region_presets.get(index).cloned() else {
+            self.show_negative_feedback(&format!("There's no region preset {}", index + 1));
+            return;
+        };
+
+        if preset.polygon.len() < 3 {
+            self.select_region(Rect::new(preset.x, preset.y, preset.width, preset.height));
+        } else {
+            let settings = &mut self.icon_context.settings;
+            settings.use_polygon = true;
+            settings.use_ellipse = false;
+            self.selection.polygon.set_from_points(&preset.polygon.iter().map(|&(x, y)| Point::new(x, y)).collect::<Vec<Point>>());
+            self.selection.polygon.clamp_to_screen(self.size);
+            self.selection.bounds.enclose_polygon(&self.selection.polygon);
+            self.ocr_handler.ocr_preview_text = None;
+            self.ocr_handler.selection_changed(&self.selection);
+            self.undo_stack.take_snapshot(&self.selection);
+            self.auto_copy();
+        }
+        self.show_positive_feedback(&format!("Selected '{}'", preset.name));
+    }
+
+    /// Grows the selection by `amount` pixels on each side around its center, or shrinks it if it's negative.
+    fn grow_selection(&mut self, amount: f32) {
+        self.selection.polygon.grow_by(amount);
+        self.selection.polygon.clamp_to_screen(self.size);
+        self.selection.bounds.enclose_polygon(&self.selection.polygon);
+        self.ocr_handler.selection_changed(&self.selection);
+        self.undo_stack.take_snapshot(&self.selection);
+    }
+
+    /// Moves the selection by the given distance, or just the hovered vertex of a polygon in steps a tenth
+    /// as large, so it can be placed precisely.
+    fn nudge_selection(&mut self, dx: f32, dy: f32) {
+        let polygon = &mut self.selection.polygon;
+        match polygon.hovered_vertex.filter(|_| self.icon_context.settings.use_polygon) {
+            Some(index) => {
+                polygon.move_vertex_by(index, dx / 10., dy / 10.);
+                polygon.clamp_vertex_to_screen(index, self.size);
+            }
+            None => {
+                polygon.move_by(dx, dy);
+                polygon.clamp_to_screen(self.size);
+            }
+        }
+        self.selection.bounds.enclose_polygon(&self.selection.polygon);
+        self.ocr_handler.selection_changed(&self.selection);
+        self.undo_stack.take_nudge_snapshot(&self.selection);
+    }
+
+    /// Adds a vertex in the middle of the hovered polygon edge, which is then hovered so arrow keys move it.
+    fn insert_polygon_vertex(&mut self) {
+        if !self.icon_context.settings.use_polygon {
+            return;
+        }
+        let Some(edge_index) = self.selection.polygon.hovered_edge else {
+            self.show_negative_feedback("Hover over an edge to add a vertex");
+            return;
+        };
+
+        let vertex_index = self.selection.polygon.insert_midpoint(edge_index);
+        self.selection.polygon.hovered_edge = None;
+        self.selection.polygon.hovered_vertex = Some(vertex_index);
+        self.selection.bounds.enclose_polygon(&self.selection.polygon);
+        self.ocr_handler.selection_changed(&self.selection);
+        self.undo_stack.take_snapshot(&self.selection);
+    }
+
+    /// Shrinks the selection to the content inside it, leaving out the background around it. The
+    /// result is always a box, since a polygon's shape can't be shrunk to fit.
+    fn trim_selection(&mut self) {
+        if let Err(reason) = self.check_selection_size() {
+            self.show_negative_feedback(&reason);
+            return;
+        }
+        let Some(screenshot) = &self.ocr_handler.screenshot else {
+            self.show_negative_feedback("The screenshot isn't ready yet");
+            return;
+        };
+
+        match content_bounds(self.selection.bounds, screenshot) {
+            Some(bounds) if bounds == self.selection.bounds.to_pixel_rect() => self.show_positive_feedback("The selection already fits its content"),
+            Some(bounds) => {
+                self.select_region(bounds);
+                self.show_positive_feedback("Trimmed the selection to its content");
+            }
+            None => self.show_negative_feedback("There's nothing but background in the selection")
+        }
+    }
+
+    fn attempt_screenshot(&mut self) {
+        if let Err(reason) = self.copy_selection_image() {
+            self.show_negative_feedback(&reason);
+            return;
+        }
+        
+        if self.icon_context.settings.close_on_copy {
+            self.hide_window();
+        }
+
+        self.show_positive_feedback("Screenshot copied to clipboard");
+    }
+
+    fn export_transcript(&mut self) {
+        if self.transcript.is_empty() {
+            self.show_negative_feedback("No text has been recognized yet");
+            return;
+        }
+
+        for format in [SubtitleFormat::SRT, SubtitleFormat::VTT] {
+            if let Err(error) = self.transcript.export(format) {
+                self.show_negative_feedback(&error);
+                return;
+            }
+        }
+
+        self.show_positive_feedback(&format!("Transcript exported to {}", transcript::get_transcript_directory().to_string_lossy()));
+    }
+
+    fn export_annotations(&mut self) {
+        if self.ocr_handler.word_boxes.is_empty() {
+            self.show_negative_feedback("No text has been recognized yet");
+            return;
+        }
+        let Some(screenshot) = self.ocr_handler.screenshot.clone() else {
+            self.show_negative_feedback("The screenshot isn't ready yet");
+            return;
+        };
+
+        match annotations::export_annotations(&screenshot, &self.selection.polygon.points(), &self.ocr_handler.word_boxes) {
+            Ok(directory) => self.show_positive_feedback(&format!("Annotations exported to {}", directory.to_string_lossy())),
+            Err(error) => self.show_negative_feedback(&error)
+        }
+    }
+
+    fn export_region(&mut self) {
+        if let Err(reason) = self.check_selection_size() {
+            self.show_negative_feedback(&reason);
+            return;
+        }
+        let Some(screenshot) = self.ocr_handler.screenshot.clone() else {
+            self.show_negative_feedback("The screenshot isn't ready yet");
+            return;
+        };
+
+        match annotations::export_region(&screenshot, &self.selection.polygon.points(), self.selection.bounds.to_positive_size()) {
+            Ok(directory) => self.show_positive_feedback(&format!("Selection region exported to {}", directory.to_string_lossy())),
+            Err(error) => self.show_negative_feedback(&error)
+        }
+    }
+
+    /// Saves the result in the current export mode to a file, e.g. for hOCR or ALTO markup that's unwieldy on the clipboard.
+    fn save_export(&mut self) {
+        let Some(text) = self.ocr_handler.ocr_preview_text.clone() else {
+            self.show_negative_feedback("No text has been recognized yet");
+            return;
+        };
+
+        match export::write_export(&self.icon_context.settings.tesseract_settings, &text) {
+            Ok(path) => self.show_positive_feedback(&format!("Export saved to {}", path.to_string_lossy())),
+            Err(error) => self.show_negative_feedback(&error)
+        }
+    }
+
+    /// Offers the text and selection image to other apps through the system share sheet.
+    fn share(&mut self) {
+        let Some(text) = self.ocr_handler.ocr_preview_text.clone() else {
+            self.show_negative_feedback("No text has been recognized yet");
+            return;
+        };
+        let image = self.selection_image().ok();
+
+        let window = &self.window_state.as_ref().unwrap().window;
+        if let Err(error) = share::share(window, text, image.as_ref()) {
+            self.show_negative_feedback(&error);
+        }
+    }
+
+    /// Opens the recognized text in the configured search engine.
+    fn search_web(&mut self) {
+        let Some(text) = self.result_text().filter(|text| !text.trim().is_empty()) else {
+            self.show_negative_feedback("No text has been recognized yet");
+            return;
+        };
+
+        let url = web_search::search_url(&self.icon_context.settings.tesseract_settings.search_url, &text);
+        match web_search::open_in_browser(&url) {
+            Ok(()) => {
+                if self.icon_context.settings.close_on_copy {
+                    self.hide_window();
+                }
+            }
+            Err(error) => self.show_negative_feedback(&error)
+        }
+    }
+
+    /// Uploads the selection image to the configured host, then copies its URL.
+    fn upload_selection_image(&mut self) {
+        let image = match self.selection_image() {
+            Ok(image) => image,
+            Err(reason) => {
+                self.show_negative_feedback(&reason);
+                return;
+            }
+        };
+
+        match self.uploader.start(self.icon_context.settings.tesseract_settings.upload.clone(), image) {
+            Ok(()) => self.show_positive_feedback("Uploading the selection..."),
+            Err(error) => self.show_negative_feedback(&error)
+        }
+    }
+
+    fn poll_upload(&mut self) {
+        match self.uploader.poll() {
+            Some(Ok(url)) => self.show_positive_feedback(&format!("Uploaded and copied {}", url)),
+            Some(Err(error)) => self.show_negative_feedback(&error),
+            None => {}
+        }
+    }
+
+    /// Tracks left clicks, copying the selection if one inside it is the second of a double-click.
+    /// Returns whether it copied, in which case the click shouldn't start a new selection.
+    fn double_click(&mut self, point: Point) -> bool {
+        let previous_click = self.last_click.take();
+        if let Some((time, previous_point, Some((bounds, polygon, preview_text)))) = previous_click {
+            if time.elapsed() < DOUBLE_CLICK_TIME && previous_point.distance_to(point) <= DOUBLE_CLICK_DISTANCE {
+                // The first click started a new selection, which is undone
+                self.selection.bounds = bounds;
+                self.selection.polygon = polygon;
+                self.undo_stack.take_snapshot(&self.selection);
+                match preview_text {
+                    Some(text) => {
+                        self.ocr_handler.ocr_preview_text = Some(text);
+                        self.attempt_copy();
+                    }
+                    None => {
+                        self.attempt_copy_on_next_ocr = true;
+                        self.ocr_handler.selection_changed(&self.selection);
+                    }
+                }
+                return true;
+            }
+        }
+
+        let image_point = self.icon_context.view.to_image(point);
+        let inside_selection = !self.selection.shift_held && !self.selection.ctrl_held
+            && !self.selection.bounds.smaller_than(self.icon_context.settings.tesseract_settings.minimum_selection_size)
+            && self.selection.polygon.contains(image_point);
+        let selection = inside_selection.then(|| (self.selection.bounds, self.selection.polygon.clone(), self.ocr_handler.ocr_preview_text.clone()));
+        self.last_click = Some((std::time::Instant::now(), point, selection));
+        false
+    }
+
+    /// The mouse position in pixels of the screenshot, which differs from the window's when it's zoomed in.
+    fn image_mouse_pos(&self) -> (i32, i32) {
+        let point = self.icon_context.view.to_image(Point::from(self.relative_mouse_pos));
+        (point.x.round() as i32, point.y.round() as i32)
+    }
+
+    /// Looks for the paragraph under the point, which becomes the selection once it's found.
+    fn start_smart_select(&mut self, point: Point) {
+        let Some(screenshot) = &self.ocr_handler.screenshot else {
+            self.show_negative_feedback("The screenshot isn't ready yet");
+            return;
+        };
+        if let Err(error) = self.smart_select.start(self.icon_context.settings.tesseract_settings.clone(), screenshot, point) {
+            self.show_negative_feedback(&error);
+        }
+    }
+
+    fn poll_smart_select(&mut self) {
+        match self.smart_select.poll() {
+            Some(Ok(Some(paragraph))) => self.select_region(paragraph),
+            Some(Ok(None)) => self.show_negative_feedback("No text found under the cursor"),
+            Some(Err(error)) => self.show_negative_feedback(&error),
+            None => {}
+        }
+    }
+
+    /// Looks for text on the new screenshot to suggest as selections and snap to, if either is turned on.
+    fn start_screen_layout(&mut self) {
+        self.icon_context.text_layout = Default::default();
+        self.screen_layout.cancel();
+        let settings = &self.icon_context.settings;
+        if !settings.text_suggestions && !settings.snap_to_text_lines {
+            return;
+        }
+        if let Some(screenshot) = &self.ocr_handler.screenshot {
+            self.screen_layout.start(settings.tesseract_settings.clone(), screenshot.clone());
+        }
+    }
+
+    fn poll_screen_layout(&mut self) {
+        match self.screen_layout.poll() {
+            Some(Ok(layout)) => self.icon_context.text_layout = layout,
+            // Suggestions and snapping are only shortcuts, so failing to find text isn't worth interrupting for
+            Some(Err(error)) => eprintln!("Unable to find the text on the screen: {}", error),
+            None => {}
+        }
+    }
+
+    /// The suggested text region that was clicked, if the click didn't draw a selection of its own.
+    fn clicked_text_suggestion(&self, point: Point) -> Option<Rect> {
+        let settings = &self.icon_context.settings;
+        if !settings.text_suggestions || !self.selection.bounds.smaller_than(settings.tesseract_settings.minimum_selection_size) {
+            return None;
+        }
+        self.icon_context.text_layout.paragraphs.iter()
+            .filter(|region| region.contains(point))
+            .min_by(|a, b| (a.width * a.height).total_cmp(&(b.width * b.height)))
+            .copied()
+    }
+
+    /// Replaces the selection with the rectangle, like drawing it would.
+    fn select_region(&mut self, region: Rect) {
+        self.selection.bounds = region;
+        self.selection.polygon.set_from_rect(&self.selection.bounds);
+        self.ocr_handler.ocr_preview_text = None;
+        self.ocr_handler.selection_changed(&self.selection);
+        self.undo_stack.take_snapshot(&self.selection);
+        self.auto_copy();
+    }
+
+    /// Adds a flashcard with the selection image and recognized text to Anki.
+    fn send_to_anki(&mut self) {
+        let Some(text) = self.ocr_handler.ocr_preview_text.clone() else {
+            self.show_negative_feedback("No text has been recognized yet");
+            return;
+        };
+        let image = match self.selection_image() {
+            Ok(image) => image,
+            Err(reason) => {
+                self.show_negative_feedback(&reason);
+                return;
+            }
+        };
+
+        match self.anki_sender.start(self.icon_context.settings.tesseract_settings.anki.clone(), text, image) {
+            Ok(()) => self.show_positive_feedback("Adding a card to Anki..."),
+            Err(error) => self.show_negative_feedback(&error)
+        }
+    }
+
+    fn poll_anki(&mut self) {
+        match self.anki_sender.poll() {
+            Some(Ok(deck)) => self.show_positive_feedback(&format!("Added a card to the '{}' deck", deck)),
+            Some(Err(error)) => self.show_negative_feedback(&error),
+            None => {}
+        }
+    }
+
+    fn save_docx(&mut self) {
+        let Some(text) = self.ocr_handler.ocr_preview_text.clone() else {
+            self.show_negative_feedback("No text has been recognized yet");
+            return;
+        };
+
+        match export::write_docx(&self.icon_context.settings.tesseract_settings, &text) {
+            Ok(path) => self.show_positive_feedback(&format!("Word document saved to {}", path.to_string_lossy())),
+            Err(error) => self.show_negative_feedback(&error)
+        }
+    }
+
+    /// Adds an uncertain word to the personal dictionary, so it's no longer flagged and Tesseract favors it.
+    fn add_to_dictionary(&mut self, word_index: usize) {
+        let Some(word) = self.ocr_handler.uncertain_word_text(word_index) else {
+            self.show_negative_feedback("There are no unknown words in the preview");
+            return;
+        };
+
+        match custom_dictionary::add_custom_word(&word) {
+            Ok(added) => {
+                self.ocr_handler.dismiss_uncertain_word(word_index);
+                if added {
+                    // Reloads the dictionary on the OCR thread
+                    self.ocr_handler.update_ocr_settings(self.icon_context.settings.tesseract_settings.clone());
+                    self.show_positive_feedback(&format!("Added '{}' to the dictionary", word));
+                } else {
+                    self.show_positive_feedback(&format!("'{}' is already in the dictionary", word));
+                }
+            }
+            Err(error) => self.show_negative_feedback(&error)
+        }
+    }
+
+    fn toggle_live_mode(&mut self) {
+        if self.live_mode.is_active() {
+            self.stop_subtitle_capture();
+            self.live_mode.stop(&self.window_state.as_ref().unwrap().window);
+            self.show_positive_feedback("Live mode disabled");
+            return;
         }
-    }
 
-    fn auto_copy(&mut self) {
-        if self.icon_context.settings.auto_copy {
-            self.attempt_copy_on_next_ocr = true;
-            self.ocr_handler.selection_changed(&self.selection);
+        let window = &self.window_state.as_ref().unwrap().window;
+
+        if let Err(reason) = self.check_selection_size() {
+            self.show_negative_feedback(&reason);
+            return;
+        }
+        match self.live_mode.start(window) {
+            Ok(()) => self.show_positive_feedback("Live mode enabled; the selection is recognized continuously"),
+            Err(error) => self.show_negative_feedback(&error)
         }
     }
 
-    fn attempt_copy(&mut self) {
-        if self.ocr_handler.ocr_preview_text.is_none() {
+    /// Subtitle capture runs on top of live mode, collecting each new line of hard-subbed video.
+    fn toggle_subtitle_capture(&mut self) {
+        if self.subtitle_capture.is_some() {
+            self.stop_subtitle_capture();
             return;
         }
 
-        // Copy the OCR text to the clipboard
-        let mut ctx: ClipboardContext = ClipboardProvider::new().unwrap();
-        ctx.set_contents(self.ocr_handler.ocr_preview_text.clone().unwrap()).expect("Unable to set clipboard contents");
-        
-        if self.icon_context.settings.close_on_copy {
-            self.hide_window();
+        if !self.live_mode.is_active() {
+            self.toggle_live_mode();
+            if !self.live_mode.is_active() {
+                return;
+            }
+        }
+        self.subtitle_capture = Some(SubtitleCapture::new());
+        self.show_positive_feedback("Capturing subtitles; press Shift + L again to save them");
+    }
+
+    /// Saves the captured subtitles and copies them to the clipboard.
+    fn stop_subtitle_capture(&mut self) {
+        let Some(subtitle_capture) = self.subtitle_capture.take() else {
+            return;
+        };
+        if subtitle_capture.line_count() == 0 {
+            self.show_negative_feedback("No subtitles were captured");
+            return;
         }
 
-        self.show_positive_feedback("Copied to clipboard");
+        let copied = ClipboardProvider::new().and_then(|mut ctx: ClipboardContext| ctx.set_contents(subtitle_capture.text()));
+        if let Err(error) = copied {
+            eprintln!("Unable to copy the subtitles: {}", error);
+        }
+        match subtitle_capture.save() {
+            Ok(path) => self.show_positive_feedback(&format!("Copied {} subtitle lines and saved them to {}", subtitle_capture.line_count(), path.to_string_lossy())),
+            Err(error) => self.show_negative_feedback(&error)
+        }
     }
 
-    fn attempt_screenshot(&mut self) {
-        if self.selection.bounds.width == 0 || self.selection.bounds.height == 0 {
+    /// Re-captures the screen under the overlay while live mode is on, so the preview follows the screen.
+    fn capture_live_frame(&mut self) {
+        let interval = std::time::Duration::from_millis(self.icon_context.settings.tesseract_settings.live_interval_ms);
+        if !self.live_mode.capture_due(interval) {
             return;
         }
-        let screenshot_path = get_screenshot_path();
-        if !std::fs::try_exists(&screenshot_path).unwrap_or(false) {
+
+        let window_state = self.window_state.as_mut().unwrap();
+        let Some(monitor) = window_state.window.current_monitor() else {
             return;
+        };
+        let screenshot = screenshot_from_handle(monitor);
+        if let Err(error) = window_state.shader_renderer.write_screenshot_to_texture(&window_state.pixels, &screenshot) {
+            println!("Error writing screenshot to texture: {:?}", error);
         }
+        self.ocr_handler.live_frame(screenshot);
+    }
 
-        let pos_bounds = self.selection.bounds.to_positive_size();
-        if pos_bounds.width < 5 || pos_bounds.height < 5 {
+    /// In live mode, auto copy keeps the clipboard up to date, but only when the text actually changes.
+    fn copy_live_result(&mut self) {
+        let Some(text) = self.ocr_handler.ocr_preview_text.clone() else {
+            return;
+        };
+        if !self.live_mode.should_copy(&text) {
             return;
         }
-        
-        // Load from screenshot_path and crop
-        let img = image::open(screenshot_path);
-        if img.is_err() {
-            eprintln!("Error loading image: {:?}", img);
+        let clear_clipboard_seconds = self.icon_context.settings.tesseract_settings.clear_clipboard_seconds;
+        if let Err(error) = clipboard_text::copy_plain_text(&text, clear_clipboard_seconds > 0) {
+            eprintln!("Unable to copy the live result: {}", error);
             return;
         }
-        let screenshot = Screenshot::from(img.unwrap());
-        
-        let cropped_screenshot = crop_screenshot_to_bounds(pos_bounds, &screenshot);
-        let cropped_screenshot = crop_screenshot_to_polygon(
-            &self.selection.polygon.vertices.iter().map(|v| (v.x as i32 - self.selection.bounds.x, v.y as i32 - self.selection.bounds.y)).collect(),
-            &cropped_screenshot
-        );
+        clipboard_text::clear_clipboard_after(text, clear_clipboard_seconds);
+    }
 
-        copy_image_to_clipboard(&cropped_screenshot.into());
-        
-        if self.icon_context.settings.close_on_copy {
-            self.hide_window();
+    fn toggle_meeting_notes(&mut self) {
+        if self.meeting_notes.is_running() {
+            self.meeting_notes.stop();
+            let path = self.meeting_notes.notes_path().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+            self.show_positive_feedback(&format!("Meeting notes saved to {}", path));
+            return;
         }
 
-        self.show_positive_feedback("Screenshot copied to clipboard");
+        let window = {
+            #[cfg(windows)] { self.window_state.as_ref().unwrap().focus_before_overlay }
+            #[cfg(not(windows))] { 0 as HWND }
+        };
+        match self.meeting_notes.start(window, self.icon_context.settings.tesseract_settings.clone()) {
+            Ok(_) => {
+                // The overlay would cover the window we're watching
+                self.hide_window();
+            }
+            Err(error) => self.show_negative_feedback(&error)
+        }
     }
 
     fn hide_window(&mut self) {
@@ -319,9 +1321,13 @@ impl App {
             return;
         }
 
+        self.stop_subtitle_capture();
+        self.live_mode.stop(&self.window_state.as_ref().unwrap().window);
+
         self.input_handler.stop_detecting_keybind();
         self.window_state.as_ref().unwrap().window.set_visible(false);
         self.icon_context.settings.save();
+        self.hidden_at = Some(std::time::Instant::now());
 
         #[cfg(windows)] {
             unsafe {
@@ -364,12 +1370,8 @@ impl App {
             state.shader_renderer.show_user_feedback(message.to_string(), [0.8, 0.3, 0.4]);
         }
     }
-}
-
-impl ApplicationHandler for App {
-    fn resumed(&mut self, _event_loop: &ActiveEventLoop) {}
 
-    fn user_event(&mut self, event_loop: &ActiveEventLoop, _event: ()) {
+    fn open_overlay(&mut self, event_loop: &ActiveEventLoop) {
         if self.window_state.is_none() {
             let global_mouse_position = MouseCursor::pos();
             let monitor = event_loop.available_monitors().find(|monitor| {
@@ -419,6 +1421,7 @@ impl ApplicationHandler for App {
                 .expect("Unable to create shader renderer");
             
             self.ocr_handler.set_screenshot(screenshot);
+            self.start_screen_layout();
             
             self.undo_stack.take_snapshot(&self.selection);
 
@@ -453,10 +1456,16 @@ impl ApplicationHandler for App {
             }
 
             // If the window is already open and on the same monitor, just hide it
-            if window.is_visible() == Some(true) && window.current_monitor() == monitor {
+            let same_monitor = window.current_monitor() == monitor;
+            if window.is_visible() == Some(true) && same_monitor {
                 self.hide_window();
                 return;
             }
+
+            // Reopening soon after closing on the same monitor brings back the previous screenshot, selection,
+            // preview, and undo history, in case the overlay was closed by accident
+            let restore_window = std::time::Duration::from_secs(self.icon_context.settings.tesseract_settings.session_restore_seconds);
+            let restore_session = same_monitor && self.hidden_at.is_some_and(|hidden_at| hidden_at.elapsed() < restore_window);
             
             window.set_fullscreen(Some(Fullscreen::Borderless(monitor)));
             window.set_visible(false);
@@ -480,24 +1489,32 @@ impl ApplicationHandler for App {
             let shader_renderer = &mut window_state.shader_renderer;
 
             shader_renderer.before_reopen_window();
-            self.ocr_handler.reset_state();
-            
-            let window = &window_state.window;
-            let screenshot = screenshot_from_handle(
-                window.current_monitor().unwrap_or(event_loop.primary_monitor().unwrap_or(event_loop.available_monitors().next().expect("No monitors found")))
-            );
+            self.icon_context.reset();
 
-            let result = shader_renderer.write_screenshot_to_texture(pixels, &screenshot);
-            if result.is_err() {
-                println!("Error writing screenshot to texture: {:?}", result);
-            }
-            self.ocr_handler.set_screenshot(screenshot);
+            if !restore_session {
+                self.ocr_handler.reset_state();
 
-            self.selection.reset();
-            self.icon_context.reset();
+                let window = &window_state.window;
+                let screenshot = screenshot_from_handle(
+                    window.current_monitor().unwrap_or(event_loop.primary_monitor().unwrap_or(event_loop.available_monitors().next().expect("No monitors found")))
+                );
 
-            self.undo_stack.reset();
-            self.undo_stack.take_snapshot(&self.selection);
+                let result = shader_renderer.write_screenshot_to_texture(pixels, &screenshot);
+                if result.is_err() {
+                    println!("Error writing screenshot to texture: {:?}", result);
+                }
+                self.ocr_handler.set_screenshot(screenshot);
+                self.start_screen_layout();
+
+                self.selection.reset();
+
+                self.undo_stack.reset(self.icon_context.settings.tesseract_settings.undo_limit as usize);
+                self.undo_stack.take_snapshot(&self.selection);
+
+                self.transcript.reset();
+            } else {
+                self.selection.reset_input_state();
+            }
 
             let window_state = self.window_state.as_mut().unwrap();
             let window = &window_state.window;
@@ -507,6 +1524,143 @@ impl ApplicationHandler for App {
         }
     }
 
+    fn handle_script_command(&mut self, command: ScriptCommand) {
+        match command {
+            ScriptCommand::SetSetting(name, value) => {
+                match self.icon_context.settings.set_toggle(&name, value) {
+                    Ok(()) => {
+                        self.ocr_handler.format_option_changed(FormatOptions::from_settings(&self.icon_context.settings));
+                        self.icon_context.settings.save();
+                    }
+                    Err(error) => self.notify(&error, false)
+                }
+            }
+            ScriptCommand::Notify(message) => self.notify(&message, true)
+        }
+    }
+
+    fn handle_automation_command(&mut self, event_loop: &ActiveEventLoop, command: AutomationCommand, reply: AutomationReply) {
+        let result = match command {
+            AutomationCommand::OpenOverlay => {
+                self.open_overlay(event_loop);
+                Ok(String::new())
+            }
+            AutomationCommand::CaptureRegion { x, y, width, height } => {
+                // Recognition can take a while, so don't block the event loop on it
+                let tesseract_settings = self.icon_context.settings.tesseract_settings.clone();
+                std::thread::spawn(move || {
+                    let mut tess_api = configure_tesseract(tesseract_settings);
+                    let _ = reply.send(recognize_region(&mut tess_api, (x, y), (width, height)));
+                });
+                return;
+            }
+            AutomationCommand::GetLastResult => self.last_result.clone().ok_or_else(|| "Nothing has been recognized yet".to_string()),
+            AutomationCommand::SetLanguage { code } => {
+                self.icon_context.settings.tesseract_settings.set_language(&code).map(|()| {
+                    self.ocr_handler.update_ocr_settings(self.icon_context.settings.tesseract_settings.clone());
+                    String::new()
+                })
+            }
+            AutomationCommand::OpenUri { uri } => match ProtocolRequest::parse(&uri) {
+                Ok(request) => {
+                    let (tx, rx) = mpsc::channel();
+                    self.handle_automation_command(event_loop, request.command.clone(), tx);
+                    std::thread::spawn(move || {
+                        let result = rx.recv().unwrap_or_else(|_| Err("The command was dropped".to_string()));
+                        let _ = reply.send(request.deliver(result));
+                    });
+                    return;
+                }
+                Err(error) => Err(error)
+            }
+        };
+        let _ = reply.send(result);
+    }
+
+    /// Adds a language to the settings once its download finishes, so it can be selected right away.
+    fn poll_language_download(&mut self) {
+        let Some((language, result)) = self.icon_context.language_manager.poll() else {
+            return;
+        };
+        match result {
+            Ok(()) => {
+                self.show_positive_feedback(&format!("Downloaded {}", language.name.trim()));
+                let tesseract_settings = &mut self.icon_context.settings.tesseract_settings;
+                if tesseract_settings.ocr_languages.iter().any(|x| x.code == language.code) {
+                    // A different quality tier of a language that's already installed
+                    tesseract_settings.set_model_quality(&language.code, language.quality);
+                } else {
+                    tesseract_settings.add_language(language);
+                }
+                self.ocr_handler.update_ocr_settings(self.icon_context.settings.tesseract_settings.clone());
+            }
+            Err(error) => self.show_negative_feedback(&error)
+        }
+    }
+
+    /// Puts the text of an image another app copied on the clipboard, or offers to, depending on the clipboard watcher mode.
+    fn handle_clipboard_image(&mut self, text: String, image: DynamicImage) {
+        let copied = match self.icon_context.settings.tesseract_settings.clipboard_watcher {
+            ClipboardWatcherMode::Off => return,
+            ClipboardWatcherMode::Replace => clipboard_text::copy_rich_text(&text, false, false, None, false),
+            ClipboardWatcherMode::Augment => clipboard_text::copy_rich_text(&text, false, false, Some(&image), false),
+            ClipboardWatcherMode::Notify => {
+                let preview: String = text.chars().take(120).collect();
+                let copy_text = text.clone();
+                let shown = notification::show_with_action("Text found in the copied image", &preview, "Copy text", move || {
+                    if let Err(error) = clipboard_text::copy_rich_text(&copy_text, false, false, None, false) {
+                        eprintln!("{}", error);
+                    }
+                });
+                if let Err(error) = shown {
+                    eprintln!("{}", error);
+                }
+                return;
+            }
+        };
+
+        match copied {
+            Ok(()) => self.icon_context.clipboard_history.push(&text),
+            Err(error) => eprintln!("Unable to copy the text of the copied image: {}", error)
+        }
+    }
+
+    /// Shows script output in the overlay if it's open, and otherwise logs it.
+    fn notify(&mut self, message: &str, positive: bool) {
+        let visible = self.window_state.as_ref().is_some_and(|state| state.window.is_visible().unwrap_or(false));
+        if !visible {
+            println!("{}", message);
+        } else if positive {
+            self.show_positive_feedback(message);
+        } else {
+            self.show_negative_feedback(message);
+        }
+    }
+}
+
+impl ApplicationHandler<AppEvent> for App {
+    fn resumed(&mut self, _event_loop: &ActiveEventLoop) {}
+
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: AppEvent) {
+        match event {
+            AppEvent::OpenOverlay => self.open_overlay(event_loop),
+            AppEvent::RunScript(index) => {
+                if let Some(script_runner) = &self.script_runner {
+                    script_runner.run(index, self.icon_context.settings.tesseract_settings.clone());
+                }
+            }
+            AppEvent::Script(command) => self.handle_script_command(command),
+            AppEvent::Automation(command, reply) => self.handle_automation_command(event_loop, command, reply),
+            AppEvent::CopyHistory(index) => self.copy_history(index),
+            AppEvent::ToggleHotkeysPaused => {
+                let paused = self.icon_context.settings.toggle_hotkeys_paused();
+                self.icon_context.settings.save();
+                self.notify(if paused { "Global hotkeys paused" } else { "Global hotkeys resumed" }, true);
+            }
+            AppEvent::ClipboardImageRecognized(text, image) => self.handle_clipboard_image(text, image),
+        }
+    }
+
     fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
         match event {
             WindowEvent::CloseRequested => {
@@ -572,6 +1726,20 @@ impl ApplicationHandler for App {
                     _ => ()
                 }
 
+                // While a text field in the settings panel is focused, the keyboard types into it instead
+                if self.window_state.as_mut().unwrap().shader_renderer.keyboard_event(&event.logical_key, event.state, &mut self.icon_context) {
+                    return;
+                }
+                // While the bounds entry is open, the keyboard types into it instead
+                if self.icon_context.bounds_entry.is_some() {
+                    self.bounds_entry_key(&event);
+                    return;
+                }
+                if self.icon_context.preset_name_entry.is_some() {
+                    self.preset_name_entry_key(&event);
+                    return;
+                }
+
                 match (event.logical_key.as_ref(), self.selection.shift_held, self.selection.ctrl_held) {
                     (Key::Named(NamedKey::Escape), _, _) => {
                         self.hide_window();
@@ -581,10 +1749,15 @@ impl ApplicationHandler for App {
                             self.icon_context.settings.use_polygon = !self.icon_context.settings.use_polygon;
                         }
                     }
-                    (Key::Character("c"), false, _) => {
+                    (Key::Character("c"), false, ctrl_held) => {
                         self.icon_context.copy_key_held = event.state == winit::event::ElementState::Pressed;
                         if event.state == winit::event::ElementState::Pressed {
-                            self.attempt_copy();
+                            // Ctrl+C copies just the part of the preview that's selected, if any
+                            let preview_selection = self.window_state.as_ref().and_then(|state| state.shader_renderer.preview_selection());
+                            match preview_selection.filter(|_| ctrl_held) {
+                                Some(text) => self.copy_plain_text(text),
+                                None => self.attempt_copy()
+                            }
                         }
                     }
                     (Key::Character("s"), false, _) => {
@@ -593,40 +1766,179 @@ impl ApplicationHandler for App {
                             self.attempt_screenshot();
                         }
                     }
-                    (Key::Named(NamedKey::ArrowDown), _, _) => {
+                    (Key::Character("t"), false, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            self.export_transcript();
+                        }
+                    }
+                    (Key::Character("e"), false, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            self.export_annotations();
+                        }
+                    }
+                    (Key::Character("E"), true, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            self.export_region();
+                        }
+                    }
+                    (Key::Character("w"), false, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            self.share();
+                        }
+                    }
+                    (Key::Character("u"), false, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            self.upload_selection_image();
+                        }
+                    }
+                    (Key::Character("k"), false, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            self.send_to_anki();
+                        }
+                    }
+                    (Key::Character("D"), true, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            self.save_docx();
+                        }
+                    }
+                    (Key::Character("S"), true, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            self.save_export();
+                        }
+                    }
+                    (Key::Character("H"), true, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            self.icon_context.history_visible = !self.icon_context.history_visible;
+                            self.icon_context.settings_panel_visible = false;
+                        }
+                    }
+                    (Key::Character("q"), false, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            self.icon_context.menubar_expanded = !self.icon_context.menubar_expanded;
+                        }
+                    }
+                    (Key::Character("f"), false, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            self.search_web();
+                        }
+                    }
+                    (Key::Character("b"), false, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            self.icon_context.bounds_entry = Some(BoundsEntry::new(self.selection.bounds, self.selection.polygon.clone()));
+                        }
+                    }
+                    (Key::Character("0"), false, true) => {
                         if event.state == winit::event::ElementState::Pressed {
-                            self.selection.polygon.move_by(0., move_dist);
-                            self.selection.polygon.clamp_to_screen(self.size);
-                            self.selection.bounds.enclose_polygon(&self.selection.polygon);
+                            self.icon_context.view = geometry::View::default();
+                        }
+                    }
+                    (Key::Character("m"), false, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            self.icon_context.preset_name_entry = Some(String::new());
+                        }
+                    }
+                    (Key::Character(digit @ ("1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9")), false, true) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            self.recall_region_preset(digit.parse::<usize>().unwrap() - 1);
+                        }
+                    }
+                    (Key::Character("r"), false, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            self.trim_selection();
+                        }
+                    }
+                    (Key::Character("o"), false, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            self.icon_context.settings.use_ellipse = !self.icon_context.settings.use_ellipse;
+                            self.selection.change_shape(self.icon_context.settings.use_polygon, self.icon_context.settings.use_ellipse);
                             self.ocr_handler.selection_changed(&self.selection);
-                            self.undo_stack.take_snapshot(&self.selection);
+                        }
+                    }
+                    (Key::Character("g"), false, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            let settings = &mut self.icon_context.settings;
+                            settings.use_processed_text = !settings.use_processed_text;
+                            if settings.use_processed_text {
+                                self.show_positive_feedback("Showing the processed text");
+                            } else {
+                                self.show_positive_feedback("Showing the raw text");
+                            }
+                        }
+                    }
+                    (Key::Character("p"), false, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            self.cycle_output_profile();
+                        }
+                    }
+                    (Key::Character("d"), false, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            // The last unknown word in the preview
+                            let word_index = self.ocr_handler.uncertain_words.len().wrapping_sub(1);
+                            self.add_to_dictionary(word_index);
+                        }
+                    }
+                    (Key::Character("l"), false, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            self.toggle_live_mode();
+                        }
+                    }
+                    (Key::Character("L"), true, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            self.toggle_subtitle_capture();
+                        }
+                    }
+                    (Key::Character("n"), false, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            self.toggle_meeting_notes();
+                        }
+                    }
+                    (Key::Character("h"), false, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            let tesseract_settings = &mut self.icon_context.settings.tesseract_settings;
+                            tesseract_settings.toggle_handwriting();
+                            // A missing handwriting model switches the engine straight back
+                            let warnings = tesseract_settings.apply_model_fallbacks();
+                            let message = match tesseract_settings.engine {
+                                settings::OCREngine::Handwriting => "Handwriting recognition enabled",
+                                settings::OCREngine::Tesseract => "Handwriting recognition disabled"
+                            };
+                            self.ocr_handler.update_ocr_settings(self.icon_context.settings.tesseract_settings.clone());
+                            if warnings.is_empty() {
+                                self.show_positive_feedback(message);
+                            }
+                            for warning in warnings {
+                                self.show_negative_feedback(&warning);
+                            }
+                        }
+                    }
+                    (Key::Character("+" | "="), _, false) => {
+                        if event.state == winit::event::ElementState::Pressed {
+                            self.grow_selection(self.icon_context.settings.tesseract_settings.resize_step as f32);
+                        }
+                    }
+                    (Key::Character("-"), _, false) => {
+                        if event.state == winit::event::ElementState::Pressed {
+                            self.grow_selection(-(self.icon_context.settings.tesseract_settings.resize_step as f32));
+                        }
+                    }
+                    (Key::Named(NamedKey::ArrowDown), _, _) => {
+                        if event.state == winit::event::ElementState::Pressed {
+                            self.nudge_selection(0., move_dist);
                         }
                     }
                     (Key::Named(NamedKey::ArrowUp), _, _) => {
                         if event.state == winit::event::ElementState::Pressed {
-                            self.selection.polygon.move_by(0., -move_dist);
-                            self.selection.polygon.clamp_to_screen(self.size);
-                            self.selection.bounds.enclose_polygon(&self.selection.polygon);
-                            self.ocr_handler.selection_changed(&self.selection);
-                            self.undo_stack.take_snapshot(&self.selection);
+                            self.nudge_selection(0., -move_dist);
                         }
                     }
                     (Key::Named(NamedKey::ArrowLeft), _, _) => {
                         if event.state == winit::event::ElementState::Pressed {
-                            self.selection.polygon.move_by(-move_dist, 0.);
-                            self.selection.polygon.clamp_to_screen(self.size);
-                            self.selection.bounds.enclose_polygon(&self.selection.polygon);
-                            self.ocr_handler.selection_changed(&self.selection);
-                            self.undo_stack.take_snapshot(&self.selection);
+                            self.nudge_selection(-move_dist, 0.);
                         }
                     }
                     (Key::Named(NamedKey::ArrowRight), _, _) => {
                         if event.state == winit::event::ElementState::Pressed {
-                            self.selection.polygon.move_by(move_dist, 0.);
-                            self.selection.polygon.clamp_to_screen(self.size);
-                            self.selection.bounds.enclose_polygon(&self.selection.polygon);
-                            self.ocr_handler.selection_changed(&self.selection);
-                            self.undo_stack.take_snapshot(&self.selection);
+                            self.nudge_selection(move_dist, 0.);
                         }
                     }
 
@@ -636,21 +1948,38 @@ impl ApplicationHandler for App {
                     (Key::Character("3"), false, false) |
                     (Key::Character("4"), false, false) |
                     (Key::Character("5"), false, false) |
-                    (Key::Character("6"), false, false) => {
+                    (Key::Character("6"), false, false) |
+                    (Key::Character("7"), false, false) |
+                    (Key::Character("8"), false, false) |
+                    (Key::Character("9"), false, false) => {
                         if event.state == winit::event::ElementState::Pressed && !event.repeat {
                             let settings = &mut self.icon_context.settings;
                             match event.logical_key.as_ref() {
                                 Key::Character("1") => settings.maintain_newline = !settings.maintain_newline,
                                 Key::Character("2") => settings.reformat_and_correct = !settings.reformat_and_correct,
-                                Key::Character("3") => settings.background_blur_enabled = !settings.background_blur_enabled,
+                                Key::Character("3") => settings.background_effect = settings.background_effect.next(),
                                 Key::Character("4") => settings.add_pilcrow_in_preview = !settings.add_pilcrow_in_preview,
                                 Key::Character("5") => settings.close_on_copy = !settings.close_on_copy,
                                 Key::Character("6") => settings.auto_copy = !settings.auto_copy,
+                                Key::Character("7") => settings.copy_image_without_text = !settings.copy_image_without_text,
+                                Key::Character("8") => {
+                                    settings.code_mode = !settings.code_mode;
+                                    self.ocr_handler.format_option_changed(FormatOptions::from_settings(settings));
+                                }
+                                Key::Character("9") => {
+                                    settings.smart_paragraphs = !settings.smart_paragraphs;
+                                    self.ocr_handler.format_option_changed(FormatOptions::from_settings(settings));
+                                }
                                 _ => (),
                             }
                         }
                     }
 
+                    (Key::Character("i"), false, false) => {
+                        if event.state == winit::event::ElementState::Pressed && !event.repeat {
+                            self.insert_polygon_vertex();
+                        }
+                    }
                     (Key::Character("z"), false, _) => {
                         if self.selection.ctrl_held && event.state == winit::event::ElementState::Pressed {
                             self.undo();
@@ -665,7 +1994,7 @@ impl ApplicationHandler for App {
                     (Key::Character("a"), false, _) => {
                         if event.state == winit::event::ElementState::Pressed && self.selection.ctrl_held {
                             self.selection.bounds = self.size.into();
-                            self.selection.polygon.set_from_bounds(&self.selection.bounds);
+                            self.selection.polygon.set_from_rect(&self.selection.bounds);
                             self.ocr_handler.selection_changed(&self.selection);
                             self.undo_stack.take_snapshot(&self.selection);
                         }
@@ -695,20 +2024,83 @@ impl ApplicationHandler for App {
                 if button == winit::event::MouseButton::Left {
                     was_handled = window_state.shader_renderer.mouse_event((x, y), state, &mut self.icon_context);
                 }
+                if button == winit::event::MouseButton::Middle {
+                    match (state, self.middle_drag.take()) {
+                        (ElementState::Pressed, _) => self.middle_drag = Some((Point::from((x, y)), Point::from((x, y)))),
+                        (ElementState::Released, Some((start, last))) if start.distance_to(last) <= PAN_THRESHOLD => {
+                            self.start_smart_select(self.icon_context.view.to_image(start));
+                        }
+                        _ => ()
+                    }
+                    return;
+                }
+
+                // Clicking away from the bounds entry keeps what was typed, like pressing Enter
+                if state == ElementState::Pressed && self.icon_context.bounds_entry.take().is_some() {
+                    self.undo_stack.take_snapshot(&self.selection);
+                }
+                if state == ElementState::Pressed {
+                    self.icon_context.preset_name_entry = None;
+                }
+
+                // Alt+clicking a recognized word finds it in the preview; the press is held back until the cursor moves
+                if !was_handled && button == winit::event::MouseButton::Left {
+                    if state == ElementState::Released && self.word_click.take().is_some() {
+                        let image_point = self.icon_context.view.to_image(Point::from((x, y)));
+                        self.window_state.as_mut().unwrap().shader_renderer.flash_word_at(image_point);
+                        return;
+                    }
+                    if state == ElementState::Pressed && self.selection.alt_held {
+                        let image_point = self.icon_context.view.to_image(Point::from((x, y)));
+                        if self.window_state.as_ref().unwrap().shader_renderer.has_word_at(image_point) {
+                            self.word_click = Some(Point::from((x, y)));
+                            return;
+                        }
+                    }
+                }
+
+                if !was_handled && button == winit::event::MouseButton::Left && state == ElementState::Pressed && self.double_click(Point::from((x, y))) {
+                    return;
+                }
 
                 if !was_handled {
-                    let result = self.selection.mouse_input(state, button, self.relative_mouse_pos, &mut self.icon_context);
+                    let result = self.selection.mouse_input(state, button, self.image_mouse_pos(), self.size, &mut self.icon_context);
                     if result == SelectionInputResult::CompletelyMoved {
                         self.ocr_handler.ocr_preview_text = None; // Clear the preview if the selection completely moved
                     }
                     self.ocr_handler.selection_changed(&self.selection);
                     if result == SelectionInputResult::SelectionFinished && state == ElementState::Released {
-                        self.auto_copy();
-                        self.undo_stack.take_snapshot(&self.selection);
+                        if let Some(region) = self.clicked_text_suggestion(self.icon_context.view.to_image(Point::from((x, y)))) {
+                            self.select_region(region);
+                        } else {
+                            self.auto_copy();
+                            self.undo_stack.take_snapshot(&self.selection);
+                        }
                     }
                 }
             },
             #[allow(unused)]
+            WindowEvent::MouseWheel {
+                device_id,
+                delta,
+                phase,
+            } => {
+                let steps = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => y,
+                    MouseScrollDelta::PixelDelta(position) => position.y as f32 / 50.
+                };
+                if let Some(window_state) = self.window_state.as_mut() {
+                    // Ctrl is for zooming, so Shift is what cycles languages from anywhere on the menubar
+                    if window_state.shader_renderer.scroll_event(self.relative_mouse_pos, steps, self.selection.shift_held, &mut self.icon_context) {
+                        return;
+                    }
+                }
+                if !self.selection.ctrl_held {
+                    return;
+                }
+                self.icon_context.view.zoom_at(Point::from(self.relative_mouse_pos), ZOOM_STEP.powf(steps), self.size);
+            }
+            #[allow(unused)]
             WindowEvent::CursorMoved {
                 device_id,
                 position,
@@ -718,15 +2110,38 @@ impl ApplicationHandler for App {
                 }
 
                 self.relative_mouse_pos = (position.x as i32, position.y as i32);
-                let result: SelectionInputResult = self.selection.cursor_moved(self.relative_mouse_pos, self.size, &mut self.icon_context);
+                if let Some((start, last)) = self.middle_drag {
+                    let current = Point::from(self.relative_mouse_pos);
+                    self.icon_context.view.pan_by(current.x - last.x, current.y - last.y, self.size);
+                    self.middle_drag = Some((start, current));
+                }
+                if let Some(start) = self.word_click {
+                    if start.distance_to(Point::from(self.relative_mouse_pos)) > DOUBLE_CLICK_DISTANCE {
+                        // Not a click on a word after all, so the held back press starts a selection
+                        self.word_click = None;
+                        let image_start = self.icon_context.view.to_image(start);
+                        let image_start = (image_start.x.round() as i32, image_start.y.round() as i32);
+                        if self.selection.mouse_input(ElementState::Pressed, winit::event::MouseButton::Left, image_start, self.size, &mut self.icon_context) == SelectionInputResult::CompletelyMoved {
+                            self.ocr_handler.ocr_preview_text = None;
+                        }
+                        self.ocr_handler.selection_changed(&self.selection);
+                    }
+                }
+                let result: SelectionInputResult = self.selection.cursor_moved(self.image_mouse_pos(), self.size, &mut self.icon_context);
 
+                // With the "on release" strategy, the selection is recognized once the mouse is released instead
+                let recognize_while_dragging = self.icon_context.settings.tesseract_settings.ocr_strategy == settings::OCRStrategy::Continuous;
                 match result {
                     SelectionInputResult::Changed => {
-                        self.ocr_handler.selection_changed(&self.selection);
+                        if recognize_while_dragging {
+                            self.ocr_handler.selection_changed(&self.selection);
+                        }
                     }
                     SelectionInputResult::CompletelyMoved => {
                         self.ocr_handler.ocr_preview_text = None;
-                        self.ocr_handler.selection_changed(&self.selection);
+                        if recognize_while_dragging {
+                            self.ocr_handler.selection_changed(&self.selection);
+                        }
                     }
                     SelectionInputResult::SelectionFinished => {
                         self.auto_copy();
@@ -0,0 +1,199 @@
+use std::{io::Write, path::PathBuf, sync::{atomic::{AtomicBool, Ordering}, Arc}, thread::{self, JoinHandle}, time::{Duration, Instant}};
+
+use windows_sys::Win32::Foundation::HWND;
+
+use crate::{ocr_handler::configure_tesseract, screenshot::{screenshot_region, Screenshot}, settings::{get_project_dirs, TesseractSettings}};
+
+/// How often the watched window is captured
+const CAPTURE_INTERVAL: Duration = Duration::from_millis(1000);
+/// The mean per-pixel difference (0-1) between two frames that counts as a new slide
+const CHANGE_THRESHOLD: f32 = 0.04;
+/// Frames are downscaled to this size before comparing so small animations and cursor movement are ignored
+const COMPARISON_SIZE: (u32, u32) = (64, 36);
+const THUMBNAIL_WIDTH: u32 = 320;
+
+/// Watches a window (e.g. a slideshow or screenshare) and appends the text of every
+/// new slide to a Markdown file, along with a timestamp and thumbnail.
+pub(crate) struct MeetingNotes {
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+    notes_path: Option<PathBuf>,
+}
+
+impl MeetingNotes {
+    pub fn new() -> Self {
+        Self {
+            running: Arc::new(AtomicBool::new(false)),
+            thread: None,
+            notes_path: None
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    pub fn notes_path(&self) -> Option<&PathBuf> {
+        self.notes_path.as_ref()
+    }
+
+    /// Starts watching `window`. Returns the path of the notes file that will be written.
+    pub fn start(&mut self, window: HWND, tesseract_settings: TesseractSettings) -> Result<PathBuf, String> {
+        if self.is_running() {
+            return Err("Meeting notes are already being recorded".to_string());
+        }
+
+        // Make sure we can capture the window before starting the thread
+        get_window_rect(window)?;
+
+        let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs();
+        let directory = get_project_dirs().data_dir().join("meeting_notes").join(timestamp.to_string());
+        std::fs::create_dir_all(&directory).map_err(|e| format!("Unable to create notes directory: {}", e))?;
+
+        let notes_path = directory.join("notes.md");
+        std::fs::write(&notes_path, "# Meeting notes\n\n").map_err(|e| format!("Unable to create notes file: {}", e))?;
+
+        self.running.store(true, Ordering::Relaxed);
+        self.thread = Some(thread::spawn({
+            let running = self.running.clone();
+            let notes_path = notes_path.clone();
+            move || {
+                let mut recorder = NotesRecorder::new(directory, notes_path, tesseract_settings);
+                while running.load(Ordering::Relaxed) {
+                    if let Err(error) = recorder.capture(window) {
+                        eprintln!("Stopping meeting notes: {}", error);
+                        running.store(false, Ordering::Relaxed);
+                        break;
+                    }
+                    thread::sleep(CAPTURE_INTERVAL);
+                }
+            }
+        }));
+        self.notes_path = Some(notes_path.clone());
+
+        Ok(notes_path)
+    }
+
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+struct NotesRecorder {
+    directory: PathBuf,
+    notes_path: PathBuf,
+    tess_api: leptess::tesseract::TessApi,
+    session_start: Instant,
+    last_frame: Option<Vec<u8>>,
+    slide_count: u32,
+}
+
+impl NotesRecorder {
+    fn new(directory: PathBuf, notes_path: PathBuf, tesseract_settings: TesseractSettings) -> Self {
+        Self {
+            directory,
+            notes_path,
+            tess_api: configure_tesseract(tesseract_settings),
+            session_start: Instant::now(),
+            last_frame: None,
+            slide_count: 0
+        }
+    }
+
+    fn capture(&mut self, window: HWND) -> Result<(), String> {
+        let (position, size) = get_window_rect(window)?;
+        if size.0 == 0 || size.1 == 0 {
+            // Probably minimized; wait until it's visible again
+            return Ok(());
+        }
+        let screenshot = screenshot_region(position, size)?;
+
+        let frame = comparison_frame(&screenshot);
+        let changed = match &self.last_frame {
+            Some(last_frame) => frame_difference(last_frame, &frame) > CHANGE_THRESHOLD,
+            None => true
+        };
+        if !changed {
+            return Ok(());
+        }
+        self.last_frame = Some(frame);
+
+        self.tess_api.raw.set_image(
+            &screenshot.bytes,
+            screenshot.width as i32,
+            screenshot.height as i32,
+            4,
+            4 * screenshot.width as i32
+        ).map_err(|e| format!("Unable to set image: {:?}", e))?;
+        self.tess_api.set_source_resolution(70); // Doesn't matter to us -- just suppress the warning
+        let text = self.tess_api.get_utf8_text().unwrap_or_default();
+        if text.trim().is_empty() {
+            return Ok(());
+        }
+
+        self.slide_count += 1;
+        let thumbnail_name = format!("slide-{}.png", self.slide_count);
+        let image: image::DynamicImage = screenshot.into();
+        let thumbnail_height = (image.height() as f32 * THUMBNAIL_WIDTH as f32 / image.width() as f32).max(1.) as u32;
+        image.thumbnail(THUMBNAIL_WIDTH, thumbnail_height)
+            .save(self.directory.join(&thumbnail_name))
+            .map_err(|e| format!("Unable to save thumbnail: {}", e))?;
+
+        let elapsed = self.session_start.elapsed().as_secs();
+        let entry = format!(
+            "## Slide {} ({:02}:{:02}:{:02})\n\n![Slide {}]({})\n\n{}\n\n",
+            self.slide_count,
+            elapsed / 3600, (elapsed / 60) % 60, elapsed % 60,
+            self.slide_count,
+            thumbnail_name,
+            text.trim()
+        );
+
+        let mut file = std::fs::OpenOptions::new().append(true).open(&self.notes_path)
+            .map_err(|e| format!("Unable to open notes file: {}", e))?;
+        file.write_all(entry.as_bytes()).map_err(|e| format!("Unable to write notes file: {}", e))?;
+
+        Ok(())
+    }
+}
+
+/// Downscales a screenshot to a small grayscale buffer for cheap frame comparisons.
+fn comparison_frame(screenshot: &Screenshot) -> Vec<u8> {
+    let mut frame = Vec::with_capacity((COMPARISON_SIZE.0 * COMPARISON_SIZE.1) as usize);
+    for y in 0..COMPARISON_SIZE.1 {
+        for x in 0..COMPARISON_SIZE.0 {
+            let source_x = (x as usize * screenshot.width) / COMPARISON_SIZE.0 as usize;
+            let source_y = (y as usize * screenshot.height) / COMPARISON_SIZE.1 as usize;
+            let idx = (source_y * screenshot.width + source_x) * 4;
+            let luminance = screenshot.bytes[idx] as u32 * 3 + screenshot.bytes[idx + 1] as u32 * 6 + screenshot.bytes[idx + 2] as u32;
+            frame.push((luminance / 10) as u8);
+        }
+    }
+    frame
+}
+
+fn frame_difference(a: &[u8], b: &[u8]) -> f32 {
+    let total: u32 = a.iter().zip(b.iter()).map(|(a, b)| (*a as i32 - *b as i32).unsigned_abs()).sum();
+    total as f32 / (a.len() as f32 * 255.)
+}
+
+fn get_window_rect(window: HWND) -> Result<((i32, i32), (u32, u32)), String> {
+    #[cfg(windows)] unsafe {
+        let mut rect = std::mem::zeroed::<windows_sys::Win32::Foundation::RECT>();
+        if windows_sys::Win32::UI::WindowsAndMessaging::GetWindowRect(window, &mut rect) == 0 {
+            return Err("The watched window no longer exists".to_string());
+        }
+        Ok((
+            (rect.left, rect.top),
+            ((rect.right - rect.left).max(0) as u32, (rect.bottom - rect.top).max(0) as u32)
+        ))
+    }
+
+    #[cfg(not(windows))] {
+        let _ = window;
+        Err("Watching windows is not supported on this platform".to_string())
+    }
+}
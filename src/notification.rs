@@ -0,0 +1,52 @@
+/// The identity notifications are shown under, registered for the current user since the app isn't packaged
+#[cfg(windows)]
+const APP_USER_MODEL_ID: &str = "Glitch752.OnScreenOCR";
+
+#[cfg(windows)]
+thread_local! {
+    /// The latest notification, kept alive so clicking its button still reaches the handler
+    static LATEST_TOAST: std::cell::RefCell<Option<windows::UI::Notifications::ToastNotification>> = const { std::cell::RefCell::new(None) };
+}
+
+/// Shows a Windows notification with a button. Clicking the button or the notification itself runs the action.
+#[cfg(windows)]
+pub(crate) fn show_with_action(title: &str, body: &str, button: &str, action: impl Fn() + Send + 'static) -> Result<(), String> {
+    use windows::{
+        core::{ComInterface, IInspectable, HSTRING},
+        Data::Xml::Dom::XmlDocument,
+        Foundation::TypedEventHandler,
+        UI::Notifications::{ToastActivatedEventArgs, ToastNotification, ToastNotificationManager}
+    };
+    use crate::text_formatting::escape_html;
+
+    let error = |e: windows::core::Error| format!("Unable to show a notification: {}", e.message());
+    crate::registry::set_user_value(&format!(r"Software\Classes\AppUserModelId\{}", APP_USER_MODEL_ID), Some("DisplayName"), "OnScreenOCR")?;
+
+    let xml = format!(
+        r#"<toast launch="action"><visual><binding template="ToastGeneric"><text>{}</text><text>{}</text></binding></visual><actions><action content="{}" arguments="action"/></actions></toast>"#,
+        escape_html(title), escape_html(body), escape_html(button)
+    );
+    let document = XmlDocument::new().map_err(error)?;
+    document.LoadXml(&HSTRING::from(xml)).map_err(error)?;
+
+    let toast = ToastNotification::CreateToastNotification(&document).map_err(error)?;
+    toast.Activated(&TypedEventHandler::<ToastNotification, IInspectable>::new(move |_, args| {
+        let arguments = args.as_ref()
+            .and_then(|args| args.cast::<ToastActivatedEventArgs>().ok())
+            .and_then(|args| args.Arguments().ok());
+        if arguments.is_some_and(|arguments| arguments == "action") {
+            action();
+        }
+        Ok(())
+    })).map_err(error)?;
+
+    ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(APP_USER_MODEL_ID)).map_err(error)?
+        .Show(&toast).map_err(error)?;
+    LATEST_TOAST.with(|latest| *latest.borrow_mut() = Some(toast));
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub(crate) fn show_with_action(_title: &str, _body: &str, _button: &str, _action: impl Fn() + Send + 'static) -> Result<(), String> {
+    Err("Notifications are only supported on Windows".to_string())
+}
@@ -1,7 +1,7 @@
-use std::{path::PathBuf, sync::{mpsc, Arc, Mutex}, time::{Duration, Instant}};
+use std::{ffi::{c_int, c_void}, hash::{DefaultHasher, Hash, Hasher}, path::PathBuf, sync::{atomic::{AtomicU64, Ordering}, mpsc, Arc, Mutex}, time::{Duration, Instant}};
 use std::thread::{self, JoinHandle};
 
-use crate::{screenshot::{crop_screenshot_to_bounds, crop_screenshot_to_polygon, Screenshot}, selection::{Bounds, Selection}, settings::{get_project_dirs, SettingsManager, TesseractExportMode, TesseractSettings}};
+use crate::{annotations::{word_boxes_from_tsv, WordBox}, custom_dictionary::{load_custom_words, load_user_words}, receipt::extract_receipt, screenshot::{crop_screenshot_to_bounds, crop_screenshot_to_polygon, normalize_contrast, screenshot_region, Screenshot}, geometry::{Point, Rect}, selection::Selection, text_regions::TextLayout, settings::{get_project_dirs, OCREngine, SettingsManager, TesseractSettings, WorkerPriority}, text_formatting::{compact_xml, fix_code_symbols, normalize_typography, normalize_whitespace, reformat_and_correct_text, FormatOptions, TesseractExportMode}, tsv::{layout_preserving_text, structured_json, text_without_noise, tsv_to_csv}, word_choices::{locate_uncertain_words, uncertain_word_choices, UncertainWord}};
 
 pub static LATEST_SCREENSHOT_FILE_NAME: &str = "latest.png";
 
@@ -12,14 +12,24 @@ pub fn get_screenshot_path() -> PathBuf {
     cache_dir.join(LATEST_SCREENSHOT_FILE_NAME)
 }
 
-const DEBOUNE_TIME: Duration = Duration::from_millis(50);
+/// Selections with at least this many pixels stream their results line by line
+const STREAMING_MIN_AREA: u64 = 600 * 400;
+/// How many recent results are kept, so switching between selections or settings is instant
+const RESULT_CACHE_SIZE: usize = 16;
+/// How many idle Tesseract instances are kept for quickly switching back to a language
+const ENGINE_POOL_SIZE: usize = 3;
 
 #[derive(Debug, Clone)]
 pub(crate) enum OCREvent {
     SelectionChanged(OCRSelectionData),
     SettingsUpdated(TesseractSettings),
-    ScreenshotChanged(Screenshot),
+    ScreenshotChanged(Arc<Screenshot>),
     FormatOptionChanged(FormatOptions),
+    /// Settings and format options changed together, so neither update replaces the other in the throttler
+    OutputProfileChanged(TesseractSettings, FormatOptions),
+    /// A new capture of the same screen in live mode; the current selection is recognized again.
+    /// Holds the event that was waiting when the frame arrived, so frames don't replace other updates.
+    LiveFrame(Arc<Screenshot>, Option<Box<OCREvent>>),
 }
 
 impl PartialEq for OCREvent {
@@ -35,14 +45,27 @@ impl PartialEq for OCREvent {
 
 pub(crate) struct OCRHandler {
     pub throttler: OCRThrottler<OCREvent>,
-    pub ocr_result_receiver: mpsc::Receiver<String>,
+    pub ocr_result_receiver: mpsc::Receiver<OCRResult>,
     pub ocr_preview_text: Option<String>,
+    /// Low-confidence words in `ocr_preview_text` that can be swapped for an alternative
+    pub uncertain_words: Vec<UncertainWord>,
+    /// How much of a large selection has been recognized, while the preview is still partial
+    pub ocr_progress: Option<u8>,
+    /// Where each recognized word is on the screenshot, for exporting annotations
+    pub word_boxes: Vec<WordBox>,
+    /// Incremented on every selection change so the OCR thread can abandon stale work
+    selection_generation: Arc<AtomicU64>,
+    /// The screenshot currently being recognized, shared with the OCR thread
+    pub screenshot: Option<Arc<Screenshot>>,
 }
 
-#[derive(Debug, Clone)]
-pub(crate) struct FormatOptions {
-    reformat_and_correct: bool,
-    maintain_newlines: bool,
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OCRResult {
+    text: String,
+    uncertain_words: Vec<UncertainWord>,
+    word_boxes: Vec<WordBox>,
+    /// The percentage of lines recognized so far, or `None` once the result is complete
+    progress: Option<u8>,
 }
 
 impl FormatOptions {
@@ -50,32 +73,152 @@ impl FormatOptions {
         Self {
             reformat_and_correct: settings.reformat_and_correct,
             maintain_newlines: settings.maintain_newline,
+            code_mode: settings.code_mode,
+            smart_paragraphs: settings.smart_paragraphs,
+            collapse_spaces: settings.collapse_spaces,
+            trim_trailing_spaces: settings.trim_trailing_spaces,
+            normalize_tabs: settings.normalize_tabs,
+            typography: settings.tesseract_settings.typography,
+            csv_delimiter: settings.tesseract_settings.csv_delimiter,
+            pretty_json: settings.tesseract_settings.pretty_json,
+            export_mode: settings.tesseract_settings.export_mode,
+            strip_noise: settings.strip_noise,
+            programmer_mode: settings.programmer_mode,
         }
     }
 }
 
 struct InitData {
-    tx: mpsc::Sender<String>,
+    tx: mpsc::Sender<OCRResult>,
     tess_api: leptess::tesseract::TessApi,
     screenshot_size: (u32, u32),
+    /// Where the cropped image sent to Tesseract starts on the screenshot
+    image_origin: Point,
+    /// A hash of the cropped image's pixels, so recognizing an unchanged region can be skipped
+    image_hash: u64,
     format_options: FormatOptions,
     latest_selection: Option<OCRSelectionData>, // Used to recalculate the same OCR when language changes
-    current_screenshot: Option<Screenshot>,
-    export_mode: TesseractExportMode,
+    current_screenshot: Option<Arc<Screenshot>>,
+    engine: OCREngine,
+    minimum_selection_size: u32,
 
     hyphenated_word_list_cache: Vec<String>,
+    /// The personal word list, which is never corrected or flagged as uncertain
+    custom_words: Vec<String>,
+    selection_generation: Arc<AtomicU64>,
+
+    /// The traineddata currently loaded, relative to /tessdata
+    model: String,
+    tesseract_parameters: toml::Table,
+    result_cache: OCRResultCache,
+    /// Recently used engines for other languages, so switching back doesn't reload traineddata
+    engine_pool: EnginePool,
+}
+
+/// Identifies the traineddata a Tesseract instance was created with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EngineKey {
+    model: String,
+    engine: OCREngine,
+}
+
+impl EngineKey {
+    fn new(model: String, engine: OCREngine) -> Self {
+        EngineKey { model, engine }
+    }
+}
+
+/// Initialized Tesseract instances that aren't currently in use, most recently used first.
+#[derive(Default)]
+struct EnginePool {
+    entries: Vec<(EngineKey, leptess::tesseract::TessApi)>,
+}
+
+impl EnginePool {
+    fn take(&mut self, key: &EngineKey) -> Option<leptess::tesseract::TessApi> {
+        let index = self.entries.iter().position(|(entry_key, _)| entry_key == key)?;
+        Some(self.entries.remove(index).1)
+    }
+
+    fn put(&mut self, key: EngineKey, tess_api: leptess::tesseract::TessApi) {
+        self.entries.retain(|(entry_key, _)| entry_key != &key);
+        self.entries.insert(0, (key, tess_api));
+        // Each instance holds a whole language model in memory
+        self.entries.truncate(ENGINE_POOL_SIZE);
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Everything that affects the result of recognizing a selection on the current screenshot.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct OCRCacheKey {
+    bounds: (i32, i32, i32, i32),
+    polygon_hash: u64,
+    image_hash: u64,
+    model: String,
+    engine: OCREngine,
+    format_options: FormatOptions,
+}
+
+impl OCRCacheKey {
+    fn new(init_data: &InitData, selection: &OCRSelectionData) -> Self {
+        let bounds = selection.bounds.to_pixel_rect();
+        let mut hasher = DefaultHasher::new();
+        for point in &selection.polygon_vertices {
+            point.x.to_bits().hash(&mut hasher);
+            point.y.to_bits().hash(&mut hasher);
+        }
+
+        OCRCacheKey {
+            bounds: (bounds.x as i32, bounds.y as i32, bounds.width as i32, bounds.height as i32),
+            polygon_hash: hasher.finish(),
+            image_hash: init_data.image_hash,
+            model: init_data.model.clone(),
+            engine: init_data.engine,
+            format_options: init_data.format_options.clone()
+        }
+    }
+}
+
+/// The most recent results, newest first. Only valid for the current Tesseract parameters.
+#[derive(Debug, Default)]
+struct OCRResultCache {
+    entries: Vec<(OCRCacheKey, OCRResult)>,
+}
+
+impl OCRResultCache {
+    fn get(&mut self, key: &OCRCacheKey) -> Option<OCRResult> {
+        let index = self.entries.iter().position(|(entry_key, _)| entry_key == key)?;
+        let entry = self.entries.remove(index);
+        let result = entry.1.clone();
+        self.entries.insert(0, entry);
+        Some(result)
+    }
+
+    fn insert(&mut self, key: OCRCacheKey, result: OCRResult) {
+        self.entries.retain(|(entry_key, _)| entry_key != &key);
+        self.entries.insert(0, (key, result));
+        self.entries.truncate(RESULT_CACHE_SIZE);
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
 }
 
 #[derive(Debug, Clone)]
 pub(crate) struct OCRSelectionData {
-    bounds: Bounds,
-    polygon_vertices: Vec<(i32, i32)>,
+    bounds: Rect,
+    polygon_vertices: Vec<Point>,
 }
 
 impl OCRSelectionData {
     pub fn from_selection(selection: &Selection) -> Self {
         let bounds = selection.bounds.clone();
-        let polygon_vertices = selection.polygon.vertices.iter().map(|x| (x.x as i32, x.y as i32)).collect();
+        let polygon_vertices = selection.polygon.points();
         OCRSelectionData {
             bounds,
             polygon_vertices
@@ -83,260 +226,681 @@ impl OCRSelectionData {
     }
 }
 
-fn configure_tesseract(tesseract_settings: TesseractSettings) -> leptess::tesseract::TessApi {
+pub(crate) fn configure_tesseract(mut tesseract_settings: TesseractSettings) -> leptess::tesseract::TessApi {
+    // The overlay applies these fallbacks itself so it can show them; here they only keep other callers working
+    for warning in tesseract_settings.apply_model_fallbacks() {
+        eprintln!("{}", warning);
+    }
+
     let directory = get_project_dirs().config_dir().join("tessdata");
-    let mut tess_api = leptess::tesseract::TessApi::new(directory.to_str(), &tesseract_settings.ocr_language_code).expect("Unable to create Tesseract instance");
+    let mut tess_api = leptess::tesseract::TessApi::new(directory.to_str(), &tesseract_settings.active_model()).expect("Unable to create Tesseract instance");
+    load_user_words(&mut tess_api, &directory, &tesseract_settings.active_model());
+    // Keep per-character alternatives for the word picker. This can still be overridden in the settings file.
+    let _ = tess_api.raw.set_variable(c"lstm_choice_mode", c"2");
     tesseract_settings.configure_tesseract(&mut tess_api);
     tess_api
 }
 
+/// Changes the priority of the calling thread, which should be the OCR worker.
+fn set_worker_priority(priority: WorkerPriority) {
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::System::Threading::{GetCurrentThread, SetThreadPriority, THREAD_PRIORITY_BELOW_NORMAL, THREAD_PRIORITY_LOWEST, THREAD_PRIORITY_NORMAL};
+        let priority = match priority {
+            WorkerPriority::Normal => THREAD_PRIORITY_NORMAL,
+            WorkerPriority::BelowNormal => THREAD_PRIORITY_BELOW_NORMAL,
+            WorkerPriority::Lowest => THREAD_PRIORITY_LOWEST
+        };
+        unsafe { SetThreadPriority(GetCurrentThread(), priority) };
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let niceness = match priority {
+            WorkerPriority::Normal => 0,
+            WorkerPriority::BelowNormal => 5,
+            WorkerPriority::Lowest => 19
+        };
+        // On Linux, niceness is per-thread and 0 refers to the calling thread. Going back to a higher
+        // priority needs extra privileges, so that only takes effect after restarting.
+        unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, niceness) };
+    }
+
+    #[cfg(not(any(windows, target_os = "linux")))]
+    let _ = priority;
+}
+
+/// Captures a region of the screen and recognizes its text, for callers outside the overlay.
+pub(crate) fn recognize_region(tess_api: &mut leptess::tesseract::TessApi, position: (i32, i32), size: (u32, u32)) -> Result<String, String> {
+    recognize_image(tess_api, &screenshot_region(position, size)?)
+}
+
+/// Recognizes the text of an image that didn't come from the overlay, like one copied by another app.
+pub(crate) fn recognize_image(tess_api: &mut leptess::tesseract::TessApi, screenshot: &Screenshot) -> Result<String, String> {
+    tess_api.raw.set_image(
+        &screenshot.bytes,
+        screenshot.width as i32,
+        screenshot.height as i32,
+        4,
+        4 * screenshot.width as i32
+    ).map_err(|e| format!("Unable to set image: {:?}", e))?;
+    tess_api.set_source_resolution(70); // Doesn't matter to us -- just suppress the warning
+    Ok(tess_api.get_utf8_text().unwrap_or_default())
+}
+
 impl OCRHandler {
     pub fn new(initial_format_options: FormatOptions) -> Self {
-        let (tx, rx) = mpsc::channel::<String>();
+        let (tx, rx) = mpsc::channel::<OCRResult>();
         let tesseract_settings = TesseractSettings::default();
+        let debounce_time = Duration::from_millis(tesseract_settings.debounce_ms);
+        let selection_generation = Arc::new(AtomicU64::new(0));
+        let thread_selection_generation = selection_generation.clone();
         OCRHandler {
             throttler: OCRThrottler::new::<_, _, InitData>(
-                DEBOUNE_TIME,
-                move |event, init_data| match event {
-                    OCREvent::SelectionChanged(selection) => {
-                        if init_data.current_screenshot.is_none() {
-                            return;
-                        }
-
-                        if selection.bounds.width == 0 || selection.bounds.height == 0 {
-                            return;
-                        }
-
-                        let cropped_screenshot = crop_screenshot_to_bounds(selection.bounds, init_data.current_screenshot.as_ref().unwrap());
-                        let cropped_screenshot = crop_screenshot_to_polygon(
-                            &selection.polygon_vertices.iter().map(|v| (v.0 - selection.bounds.x, v.1 - selection.bounds.y)).collect(),
-                            &cropped_screenshot
-                        );
-                        init_data.tess_api.raw.set_image(
-                            &cropped_screenshot.bytes,
-                            cropped_screenshot.width as i32,
-                            cropped_screenshot.height as i32,
-                            4,
-                            4 * cropped_screenshot.width as i32
-                        ).expect("Unable to set image");
-                        init_data.screenshot_size = (cropped_screenshot.width as u32, cropped_screenshot.height as u32);
-                        init_data.tess_api.set_source_resolution(70); // Doesn't matter to us -- just suppress the warning
-                        
-                        init_data.latest_selection = Some(selection);
-                        perform_ocr(init_data);
-                    }
-                    OCREvent::FormatOptionChanged(format_options) => {
-                        init_data.format_options = format_options;
-                        if init_data.latest_selection.is_some() {
-                            perform_ocr(init_data);
-                        }
-                    }
-                    OCREvent::SettingsUpdated(tesseract_settings) => {
-                        init_data.hyphenated_word_list_cache = get_hyphenated_word_list_cache(&tesseract_settings.ocr_language_code);
-                        init_data.export_mode = tesseract_settings.export_mode;
-
-                        init_data.tess_api = configure_tesseract(tesseract_settings);
-
-                        let selection = init_data.latest_selection.as_ref();
-                        if selection.is_none() {
-                            return;
-                        }
-                        let selection = selection.unwrap();
-
-                        let cropped_screenshot = crop_screenshot_to_bounds(selection.bounds, init_data.current_screenshot.as_ref().unwrap());
-                        let cropped_screenshot = crop_screenshot_to_polygon(
-                            &selection.polygon_vertices.iter().map(|v| (v.0 - selection.bounds.x, v.1 - selection.bounds.y)).collect(),
-                            &cropped_screenshot
-                        );
-                        init_data.tess_api.raw.set_image(
-                            &cropped_screenshot.bytes,
-                            cropped_screenshot.width as i32,
-                            cropped_screenshot.height as i32,
-                            4,
-                            4 * cropped_screenshot.width as i32
-                        ).expect("Unable to set image");
-                        init_data.screenshot_size = (cropped_screenshot.width as u32, cropped_screenshot.height as u32);
-                        init_data.tess_api.set_source_resolution(70); // Doesn't matter to us -- just suppress the warning
-
-                        if init_data.latest_selection.is_some() {
-                            perform_ocr(init_data);
-                        }
-                    }
-                    OCREvent::ScreenshotChanged(screenshot) => {
-                        // Also save screenshot as latest.png for screenshot functionality and debugging
-                        let screenshot_image = image::ImageBuffer::<image::Rgba<u8>, Vec<u8>>::from_vec(screenshot.width as u32, screenshot.height as u32, screenshot.bytes.clone()).expect("Unable to create image buffer");
-                        screenshot_image.save(get_screenshot_path()).expect("Unable to save latest.png");
-                        
-                        init_data.current_screenshot = Some(screenshot);
-                    }
-                },
+                debounce_time,
+                handle_ocr_event,
                 move || {
+                    set_worker_priority(tesseract_settings.worker_priority);
+                    let custom_words = load_custom_words();
                     InitData {
-                        hyphenated_word_list_cache: get_hyphenated_word_list_cache(&tesseract_settings.ocr_language_code),
-                        export_mode: tesseract_settings.export_mode,
+                        hyphenated_word_list_cache: get_hyphenated_word_list_cache(&tesseract_settings.ocr_language_code, &custom_words),
+                        custom_words,
+                        engine: tesseract_settings.engine,
+                        minimum_selection_size: tesseract_settings.minimum_selection_size,
+                        model: tesseract_settings.active_model(),
+                        tesseract_parameters: tesseract_settings.tesseract_parameters.clone(),
+                        result_cache: OCRResultCache::default(),
+                        engine_pool: EnginePool::default(),
                         tess_api: configure_tesseract(tesseract_settings),
                         tx,
                         screenshot_size: (0, 0),
+                        image_origin: Point::default(),
+                        image_hash: 0,
                         format_options: initial_format_options,
                         current_screenshot: None,
                         latest_selection: None,
+                        selection_generation: thread_selection_generation,
                     }
                 },
             ),
             ocr_result_receiver: rx,
-            ocr_preview_text: None
+            ocr_preview_text: None,
+            uncertain_words: Vec::new(),
+            ocr_progress: None,
+            word_boxes: Vec::new(),
+            selection_generation,
+            screenshot: None
         }
     }
 
     pub fn set_screenshot(&mut self, screenshot: Screenshot) {
+        let screenshot = Arc::new(screenshot);
+        self.screenshot = Some(screenshot.clone());
         self.throttler.put(OCREvent::ScreenshotChanged(screenshot));
     }
 
+    pub fn live_frame(&mut self, screenshot: Screenshot) {
+        let screenshot = Arc::new(screenshot);
+        self.screenshot = Some(screenshot.clone());
+        self.throttler.put_with(|pending| match pending {
+            None => OCREvent::LiveFrame(screenshot, None),
+            Some(OCREvent::LiveFrame(_, pending)) => OCREvent::LiveFrame(screenshot, pending),
+            Some(pending) => OCREvent::LiveFrame(screenshot, Some(Box::new(pending))),
+        });
+    }
+
     pub fn reset_state(&mut self) {
         self.ocr_preview_text = None;
+        self.uncertain_words.clear();
+        self.ocr_progress = None;
+        self.word_boxes.clear();
         while self.ocr_result_receiver.try_recv().is_ok() {}
     }
 
     pub fn selection_changed(&mut self, latest_selection: &Selection) {
         let ocr_selection_data = OCRSelectionData::from_selection(latest_selection);
+        // Whatever is being recognized now is for an older selection, so stop it
+        self.selection_generation.fetch_add(1, Ordering::Relaxed);
         self.throttler.put(OCREvent::SelectionChanged(ocr_selection_data));
     }
 
     pub fn update_ocr_preview_text(&mut self) -> bool {
-        if let Some(text) = self.get_ocr_result() {
-            if text.is_empty() {
+        if let Some(result) = self.get_ocr_result() {
+            self.uncertain_words = result.uncertain_words;
+            self.ocr_progress = result.progress;
+            self.word_boxes = result.word_boxes;
+            if result.text.is_empty() {
                 self.ocr_preview_text = None;
                 return false
             } else {
-                self.ocr_preview_text = Some(text.clone());
-                return true
+                self.ocr_preview_text = Some(result.text);
+                // Partial results are only for the preview; they shouldn't be copied
+                return result.progress.is_none()
             }
         }
         false
     }
 
-    fn get_ocr_result(&mut self) -> Option<String> {
+    fn get_ocr_result(&mut self) -> Option<OCRResult> {
         self.ocr_result_receiver.try_recv().ok()
     }
 
+    /// Swaps an uncertain word for one of its alternatives. The replaced word becomes an
+    /// alternative itself, so the choice can be undone the same way.
+    pub fn choose_alternative(&mut self, word_index: usize, alternative_index: usize) {
+        let Some(text) = &mut self.ocr_preview_text else {
+            return;
+        };
+        let Some(word) = self.uncertain_words.get_mut(word_index) else {
+            return;
+        };
+        if alternative_index >= word.alternatives.len() {
+            return;
+        }
+
+        let original = text[word.range.clone()].to_string();
+        let alternative = std::mem::replace(&mut word.alternatives[alternative_index], original.clone());
+        text.replace_range(word.range.clone(), &alternative);
+
+        let old_end = word.range.end;
+        word.range.end = word.range.start + alternative.len();
+        let new_end = word.range.end;
+        for other in self.uncertain_words.iter_mut().skip(word_index + 1) {
+            other.range.start = other.range.start + new_end - old_end;
+            other.range.end = other.range.end + new_end - old_end;
+        }
+    }
+
+    /// The text of an uncertain word as it currently appears in the preview.
+    pub fn uncertain_word_text(&self, word_index: usize) -> Option<String> {
+        let text = self.ocr_preview_text.as_ref()?;
+        let word = self.uncertain_words.get(word_index)?;
+        text.get(word.range.clone()).map(|word| word.to_string())
+    }
+
+    /// Stops offering alternatives for a word, e.g. once it's been added to the dictionary.
+    pub fn dismiss_uncertain_word(&mut self, word_index: usize) {
+        if word_index < self.uncertain_words.len() {
+            self.uncertain_words.remove(word_index);
+        }
+    }
+
     pub fn update_ocr_settings(&mut self, settings: TesseractSettings) {
+        self.throttler.set_delay(Duration::from_millis(settings.debounce_ms));
         self.throttler.put(OCREvent::SettingsUpdated(settings));
     }
 
     pub fn format_option_changed(&mut self, format_options: FormatOptions) {
         self.throttler.put(OCREvent::FormatOptionChanged(format_options));
     }
+
+    pub fn output_profile_changed(&mut self, settings: TesseractSettings, format_options: FormatOptions) {
+        self.throttler.set_delay(Duration::from_millis(settings.debounce_ms));
+        self.throttler.put(OCREvent::OutputProfileChanged(settings, format_options));
+    }
+}
+
+/// Handles an event on the OCR thread once the throttler's delay has passed.
+fn handle_ocr_event(event: OCREvent, init_data: &mut InitData) {
+    match event {
+        OCREvent::SelectionChanged(selection) => {
+            if init_data.current_screenshot.is_none() {
+                return;
+            }
+
+            if selection.bounds.is_empty() {
+                return;
+            }
+
+            if selection.bounds.smaller_than(init_data.minimum_selection_size) {
+                // Tiny regions only produce garbage, so clear the preview instead
+                init_data.latest_selection = None;
+                init_data.tx.send(OCRResult::default()).expect("Unable to send text");
+                return;
+            }
+
+            set_tesseract_image(init_data, &selection);
+            
+            init_data.latest_selection = Some(selection);
+            perform_ocr(init_data);
+        }
+        OCREvent::FormatOptionChanged(format_options) => {
+            init_data.format_options = format_options;
+            if init_data.latest_selection.is_some() {
+                perform_ocr(init_data);
+            }
+        }
+        OCREvent::SettingsUpdated(tesseract_settings) => {
+            apply_tesseract_settings(init_data, tesseract_settings);
+        }
+        OCREvent::OutputProfileChanged(tesseract_settings, format_options) => {
+            init_data.format_options = format_options;
+            apply_tesseract_settings(init_data, tesseract_settings);
+        }
+        OCREvent::LiveFrame(screenshot, pending) => {
+            // Events that arrived between frames were merged into this one rather than replaced by it
+            if let Some(pending) = pending {
+                handle_ocr_event(*pending, init_data);
+            }
+
+            // Unlike ScreenshotChanged, this isn't saved to disk, since it happens many times a second
+            init_data.current_screenshot = Some(screenshot);
+            let Some(selection) = init_data.latest_selection.clone() else {
+                return;
+            };
+            set_tesseract_image(init_data, &selection);
+            perform_ocr(init_data);
+        }
+        OCREvent::ScreenshotChanged(screenshot) => {
+            // Also save screenshot as latest.png for screenshot functionality and debugging.
+            // Encoding a whole monitor takes a while, so it's done in the background.
+            thread::spawn({
+                let screenshot = screenshot.clone();
+                move || {
+                    let result = image::save_buffer(get_screenshot_path(), &screenshot.bytes, screenshot.width as u32, screenshot.height as u32, image::ExtendedColorType::Rgba8);
+                    if let Err(error) = result {
+                        eprintln!("Unable to save latest.png: {}", error);
+                    }
+                }
+            });
+
+            // The cache isn't cleared, since keys include the cropped pixels; re-taking a screenshot
+            // where the selected region didn't change reuses the previous result
+            init_data.current_screenshot = Some(screenshot);
+        }
+    }
+}
+
+/// Applies changed Tesseract settings on the worker thread and recognizes the latest selection again.
+fn apply_tesseract_settings(init_data: &mut InitData, tesseract_settings: TesseractSettings) {
+    set_worker_priority(tesseract_settings.worker_priority);
+    let previous_engine_key = EngineKey::new(init_data.model.clone(), init_data.engine);
+
+    let custom_words = load_custom_words();
+    init_data.hyphenated_word_list_cache = get_hyphenated_word_list_cache(&tesseract_settings.ocr_language_code, &custom_words);
+    init_data.engine = tesseract_settings.engine;
+    init_data.minimum_selection_size = tesseract_settings.minimum_selection_size;
+    init_data.model = tesseract_settings.active_model();
+    // Typography and the export mode are configured in the Tesseract settings file, but they're applied as format options
+    init_data.format_options.typography = tesseract_settings.typography;
+    init_data.format_options.csv_delimiter = tesseract_settings.csv_delimiter;
+    init_data.format_options.pretty_json = tesseract_settings.pretty_json;
+    init_data.format_options.export_mode = tesseract_settings.export_mode;
+
+    let engine_key = EngineKey::new(init_data.model.clone(), init_data.engine);
+    if init_data.tesseract_parameters != tesseract_settings.tesseract_parameters || init_data.custom_words != custom_words {
+        // Cached results and idle engines were set up with the old parameters or user words
+        init_data.tesseract_parameters = tesseract_settings.tesseract_parameters.clone();
+        init_data.custom_words = custom_words;
+        init_data.result_cache.clear();
+        init_data.engine_pool.clear();
+        init_data.tess_api = configure_tesseract(tesseract_settings);
+    } else if engine_key != previous_engine_key {
+        let tess_api = init_data.engine_pool.take(&engine_key).unwrap_or_else(|| configure_tesseract(tesseract_settings));
+        let previous_tess_api = std::mem::replace(&mut init_data.tess_api, tess_api);
+        init_data.engine_pool.put(previous_engine_key, previous_tess_api);
+    }
+
+    let selection = init_data.latest_selection.clone();
+    if selection.is_none() {
+        return;
+    }
+    let selection = selection.unwrap();
+
+    set_tesseract_image(init_data, &selection);
+
+    if init_data.latest_selection.is_some() {
+        perform_ocr(init_data);
+    }
+}
+
+fn set_tesseract_image(init_data: &mut InitData, selection: &OCRSelectionData) {
+    let pixel_bounds = selection.bounds.to_pixel_rect();
+    let cropped_screenshot = crop_screenshot_to_bounds(pixel_bounds, init_data.current_screenshot.as_ref().unwrap());
+    let mut cropped_screenshot = crop_screenshot_to_polygon(
+        &selection.polygon_vertices.iter().map(|point| *point - pixel_bounds.origin()).collect::<Vec<Point>>(),
+        cropped_screenshot
+    );
+    if init_data.engine == OCREngine::Handwriting {
+        cropped_screenshot = normalize_contrast(&cropped_screenshot);
+    }
+
+    init_data.tess_api.raw.set_image(
+        &cropped_screenshot.bytes,
+        cropped_screenshot.width as i32,
+        cropped_screenshot.height as i32,
+        4,
+        4 * cropped_screenshot.width as i32
+    ).expect("Unable to set image");
+    init_data.screenshot_size = (cropped_screenshot.width as u32, cropped_screenshot.height as u32);
+    init_data.image_origin = pixel_bounds.origin();
+
+    let mut hasher = DefaultHasher::new();
+    init_data.screenshot_size.hash(&mut hasher);
+    cropped_screenshot.bytes.hash(&mut hasher);
+    init_data.image_hash = hasher.finish();
+    init_data.tess_api.set_source_resolution(70); // Doesn't matter to us -- just suppress the warning
 }
 
 fn perform_ocr(init_data: &mut InitData) {
+    let Some(selection) = &init_data.latest_selection else {
+        return;
+    };
+    let cache_key = OCRCacheKey::new(init_data, selection);
+    if let Some(result) = init_data.result_cache.get(&cache_key) {
+        init_data.tx.send(result).expect("Unable to send text");
+        return;
+    }
+
+    let result = if should_stream(init_data) {
+        perform_streaming_ocr(init_data)
+    } else {
+        recognize_selection(init_data)
+    };
+    // Cancelled recognitions are incomplete, so they're neither sent nor cached
+    if let Some(result) = result {
+        init_data.result_cache.insert(cache_key, result.clone());
+        init_data.tx.send(result).expect("Unable to send text");
+    }
+}
+
+/// Recognizes the whole selection at once. Returns `None` if it was cancelled.
+fn recognize_selection(init_data: &mut InitData) -> Option<OCRResult> {
+    let cancel = CancelToken::new(&init_data.selection_generation);
+    if !recognize(&mut init_data.tess_api, &cancel) {
+        return None;
+    }
     let tesseract_api = &mut init_data.tess_api;
-    tesseract_api.recognize();
+    let word_boxes = word_boxes_from_tsv(&tesseract_api.get_tsv_text(0).unwrap_or_default(), init_data.image_origin);
 
-    match init_data.export_mode {
+    match init_data.format_options.export_mode {
         TesseractExportMode::UTF8 => {
-            let mut text = tesseract_api.get_utf8_text().unwrap_or("".to_string());
-
-            if init_data.format_options.reformat_and_correct {
-                let corrected_text = reformat_and_correct_text(text, init_data);
-                init_data.tx.send(corrected_text).expect("Unable to send text");
+            let word_choices = without_custom_words(uncertain_word_choices(tesseract_api), &init_data.custom_words);
+            let text = if init_data.format_options.code_mode {
+                // Code needs its indentation and newlines, so skip every other kind of reformatting
+                layout_preserving_text(&tesseract_api.get_tsv_text(0).unwrap_or("".to_string()))
+            } else if init_data.format_options.strip_noise {
+                // Noise is detected from per-word confidences, which are only available in the TSV output
+                let text = text_without_noise(&tesseract_api.get_tsv_text(0).unwrap_or("".to_string()));
+                reformat_text(init_data, text)
             } else {
-                if !init_data.format_options.maintain_newlines {
-                    text = text.replace("\n", " ");
-                }
-                init_data.tx.send(text).expect("Unable to send text");
-            }
+                let text = tesseract_api.get_utf8_text().unwrap_or("".to_string());
+                reformat_text(init_data, text)
+            };
+
+            let text = normalize_whitespace(text, &init_data.format_options);
+            let text = normalize_symbols(init_data, text);
+            let uncertain_words = locate_uncertain_words(&text, word_choices);
+            Some(OCRResult { text, uncertain_words, word_boxes, progress: None })
         }
         TesseractExportMode::Alto => {
             let mut text = tesseract_api.get_alto_text(0).unwrap_or("".to_string());
             if !init_data.format_options.maintain_newlines {
                 text = compact_xml(&text);
             }
-            init_data.tx.send(text).expect("Unable to send text");
+            Some(OCRResult { text, word_boxes, ..Default::default() })
         }
         TesseractExportMode::HOCR => {
             let mut text = tesseract_api.get_hocr_text(0).unwrap_or("".to_string());
             if !init_data.format_options.maintain_newlines {
                 text = compact_xml(&text);
             }
-            init_data.tx.send(text).expect("Unable to send text");
+            Some(OCRResult { text, word_boxes, ..Default::default() })
         }
         TesseractExportMode::TSV => {
             let text = tesseract_api.get_tsv_text(0).unwrap_or("".to_string());
-            init_data.tx.send(text).expect("Unable to send text");
+            Some(OCRResult { text, word_boxes, ..Default::default() })
+        }
+        TesseractExportMode::CSV => {
+            let text = tsv_to_csv(&tesseract_api.get_tsv_text(0).unwrap_or("".to_string()), init_data.format_options.csv_delimiter);
+            Some(OCRResult { text, word_boxes, ..Default::default() })
+        }
+        TesseractExportMode::JSON => {
+            let tsv = tesseract_api.get_tsv_text(0).unwrap_or("".to_string());
+            let text = structured_json(&tsv, (init_data.image_origin.x, init_data.image_origin.y), init_data.format_options.pretty_json);
+            Some(OCRResult { text, word_boxes, ..Default::default() })
+        }
+        TesseractExportMode::Receipt => {
+            // Line items are paired with their prices by position, which only the TSV output has
+            let text = extract_receipt(&tesseract_api.get_tsv_text(0).unwrap_or("".to_string()));
+            Some(OCRResult { text, word_boxes, ..Default::default() })
         }
     }
 }
 
-fn compact_xml(xml_string: &str) -> String {
-    let mut reader = quick_xml::Reader::from_str(xml_string);
-    let mut writer = quick_xml::Writer::new(std::io::Cursor::new(Vec::new()));
-
-    loop {
-        match reader.read_event() {
-            Ok(quick_xml::events::Event::Text(_)) => {}, // Skip text events
-            Ok(quick_xml::events::Event::Eof) => break,
-            Ok(e) => assert!(writer.write_event(e).is_ok()),
-            Err(e) => panic!("Error at position {}: {:?}", reader.buffer_position(), e),
+fn reformat_text(init_data: &InitData, mut text: String) -> String {
+    // Programmer mode never hyphen-joins or reflows lines, since neither makes sense for code
+    if init_data.format_options.reformat_and_correct && !init_data.format_options.programmer_mode {
+        reformat_and_correct_text(text, &init_data.format_options, &init_data.hyphenated_word_list_cache)
+    } else {
+        if !init_data.format_options.maintain_newlines {
+            text = text.replace("\n", " ");
         }
+        text
     }
+}
 
-    return String::from_utf8(writer.into_inner().into_inner()).expect("Unable to convert XML to string");
+fn normalize_symbols(init_data: &InitData, text: String) -> String {
+    if init_data.format_options.programmer_mode {
+        // Code is copied as literal text, so symbols are only ever made plainer, never typographic
+        fix_code_symbols(text)
+    } else if init_data.format_options.code_mode {
+        // Curly quotes, changed dashes, and expanded ligatures would corrupt source code
+        text
+    } else {
+        normalize_typography(text, &init_data.format_options.typography)
+    }
 }
 
-fn get_hyphenated_word_list_cache(language_code: &str) -> Vec<String> {
-    let path = format!("./correction_data/hyphenated/{}.txt", language_code);
-    // If the file doesn't exist, return an empty list
-    if !std::fs::try_exists(&path).unwrap_or(false) {
-        return Vec::new();
+/// Large selections are recognized line by line so the preview fills in as lines complete.
+/// Code mode and noise stripping need the whole page's layout, so they never stream.
+fn should_stream(init_data: &InitData) -> bool {
+    matches!(init_data.format_options.export_mode, TesseractExportMode::UTF8)
+        && !init_data.format_options.code_mode
+        && !init_data.format_options.strip_noise
+        && init_data.screenshot_size.0 as u64 * init_data.screenshot_size.1 as u64 >= STREAMING_MIN_AREA
+}
+
+fn perform_streaming_ocr(init_data: &mut InitData) -> Option<OCRResult> {
+    let result = recognize_line_by_line(init_data);
+    // The last line's rectangle would stay in effect until the next image is set, so anything that recognizes
+    // the same image again, like changing a format option, would only see that line
+    let (width, height) = init_data.screenshot_size;
+    set_rectangle(&mut init_data.tess_api, (0, 0, width as i32, height as i32));
+    result
+}
+
+fn recognize_line_by_line(init_data: &mut InitData) -> Option<OCRResult> {
+    let cancel = CancelToken::new(&init_data.selection_generation);
+    let lines = text_line_bounds(&mut init_data.tess_api);
+
+    let mut raw_text = String::new();
+    let mut word_choices = Vec::new();
+    let mut word_boxes = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        if line.starts_paragraph && !raw_text.is_empty() {
+            raw_text.push('\n');
+        }
+        let line_text = recognize_rectangle(&mut init_data.tess_api, line.bounds, &cancel)?;
+        raw_text.push_str(&line_text);
+        word_choices.extend(without_custom_words(uncertain_word_choices(&mut init_data.tess_api), &init_data.custom_words));
+        word_boxes.extend(word_boxes_from_tsv(&init_data.tess_api.get_tsv_text(0).unwrap_or_default(), init_data.image_origin));
+
+        if index + 1 < lines.len() {
+            let text = normalize_whitespace(reformat_text(init_data, raw_text.clone()), &init_data.format_options);
+            let progress = ((index + 1) * 100 / lines.len()) as u8;
+            init_data.tx.send(OCRResult { text, progress: Some(progress), ..Default::default() }).expect("Unable to send text");
+        }
     }
 
-    // The files are split by newlines
-    let file = std::fs::read_to_string(path).expect("Unable to read hyphenated word list");
-    file.lines().map(|x| x.to_string()).collect()
+    let text = normalize_whitespace(reformat_text(init_data, raw_text), &init_data.format_options);
+    let text = normalize_symbols(init_data, text);
+    let uncertain_words = locate_uncertain_words(&text, word_choices);
+    Some(OCRResult { text, uncertain_words, word_boxes, progress: None })
 }
 
-fn reformat_and_correct_text(text: String, init_data: &mut InitData) -> String {
-    // 1. If a line ends with a hyphen and the word isn't detected to be a hyphenated word, remove the hyphen
-    let hyphenated_words = &init_data.hyphenated_word_list_cache;
-    let mut lines = text.lines().map(|x| format!("{}\n", x.to_string())).collect::<Vec<String>>();
+struct TextLine {
+    /// Left, top, width, and height in pixels of the cropped selection
+    bounds: (i32, i32, i32, i32),
+    starts_paragraph: bool,
+}
 
-    // Remove empty lines. This may not be an ideal solution, but it works for now.
-    lines.retain(|x| !x.trim().is_empty());
+/// Runs layout analysis only, which is fast, and returns every line of text it found in reading order.
+fn text_line_bounds(tess_api: &mut leptess::tesseract::TessApi) -> Vec<TextLine> {
+    use tesseract_sys::{TessBaseAPI, TessBaseAPIAnalyseLayout, TessPageIteratorBoundingBox, TessPageIteratorDelete, TessPageIteratorIsAtBeginningOf, TessPageIteratorLevel_RIL_PARA, TessPageIteratorLevel_RIL_TEXTLINE, TessPageIteratorNext};
 
-    let lines_loop = lines.clone();
-    for (line, i) in lines_loop.iter().zip(0..) {
-        if line.ends_with("-\n") {
-            // The last word of this line plus the first word of the next is our query
-            let last_word = line.split_whitespace().last().unwrap_or("");
-            let next_first_word = lines_loop.get(i + 1).map(|x| x.split_whitespace().next()).flatten().unwrap_or("");
-            let query = format!("{}{}", last_word, next_first_word);
-            if hyphenated_words.contains(&query) {
-                continue;
+    let handle: *mut TessBaseAPI = *tess_api.raw.as_ref();
+    let mut lines = Vec::new();
+    unsafe {
+        let iterator = TessBaseAPIAnalyseLayout(handle);
+        if iterator.is_null() {
+            return lines;
+        }
+        loop {
+            let (mut left, mut top, mut right, mut bottom) = (0, 0, 0, 0);
+            if TessPageIteratorBoundingBox(iterator, TessPageIteratorLevel_RIL_TEXTLINE, &mut left, &mut top, &mut right, &mut bottom) != 0 {
+                lines.push(TextLine {
+                    bounds: (left, top, right - left, bottom - top),
+                    starts_paragraph: TessPageIteratorIsAtBeginningOf(iterator, TessPageIteratorLevel_RIL_PARA) != 0
+                });
             }
+            if TessPageIteratorNext(iterator, TessPageIteratorLevel_RIL_TEXTLINE) == 0 {
+                break;
+            }
+        }
+        TessPageIteratorDelete(iterator);
+    }
+    lines
+}
 
-            // Remove the hyphen and the newline
-            lines.get_mut(i).map(|x| {
-                let last = x.pop();
-                if last == Some('\n') {
-                    x.pop();
-                }
-            });
-            // Add a newline to the end of the word on the next line to roughly maintain the same formatting
-            lines.get_mut(i + 1).map(|x| {
-                let words = x.split_whitespace().collect::<Vec<&str>>();
-                if let Some(first) = words.first() {
-                    *x = format!("{}\n{}", first, &x[first.len()..].trim_start());
-                }
-            });
+/// Finds the lines and paragraphs of an image with layout analysis only, without recognizing them.
+/// Their bounds are in pixels of the image.
+pub(crate) fn text_layout(tess_api: &mut leptess::tesseract::TessApi, screenshot: &Screenshot) -> Result<TextLayout, String> {
+    use tesseract_sys::{TessBaseAPI, TessBaseAPIAnalyseLayout, TessPageIteratorBoundingBox, TessPageIteratorDelete, TessPageIteratorIsAtBeginningOf, TessPageIteratorLevel_RIL_PARA, TessPageIteratorLevel_RIL_TEXTLINE, TessPageIteratorNext};
+
+    tess_api.raw.set_image(
+        &screenshot.bytes,
+        screenshot.width as i32,
+        screenshot.height as i32,
+        4,
+        4 * screenshot.width as i32
+    ).map_err(|e| format!("Unable to set image: {:?}", e))?;
+    tess_api.set_source_resolution(70);
+
+    let handle: *mut TessBaseAPI = *tess_api.raw.as_ref();
+    let mut layout = TextLayout::default();
+    unsafe {
+        let iterator = TessBaseAPIAnalyseLayout(handle);
+        if iterator.is_null() {
+            return Ok(layout);
+        }
+        let bounds = |level| {
+            let (mut left, mut top, mut right, mut bottom) = (0, 0, 0, 0);
+            if TessPageIteratorBoundingBox(iterator, level, &mut left, &mut top, &mut right, &mut bottom) != 0 {
+                Some(Rect::new(left as f32, top as f32, (right - left) as f32, (bottom - top) as f32))
+            } else {
+                None
+            }
+        };
+        loop {
+            layout.lines.extend(bounds(TessPageIteratorLevel_RIL_TEXTLINE));
+            if TessPageIteratorIsAtBeginningOf(iterator, TessPageIteratorLevel_RIL_PARA) != 0 {
+                layout.paragraphs.extend(bounds(TessPageIteratorLevel_RIL_PARA));
+            }
+            if TessPageIteratorNext(iterator, TessPageIteratorLevel_RIL_TEXTLINE) == 0 {
+                break;
+            }
+        }
+        TessPageIteratorDelete(iterator);
+    }
+    Ok(layout)
+}
+
+/// Recognizes part of the image, or returns `None` if it was cancelled.
+fn recognize_rectangle(tess_api: &mut leptess::tesseract::TessApi, bounds: (i32, i32, i32, i32), cancel: &CancelToken) -> Option<String> {
+    set_rectangle(tess_api, bounds);
+    if !recognize(tess_api, cancel) {
+        return None;
+    }
+    Some(tess_api.get_utf8_text().unwrap_or_default())
+}
+
+/// Limits recognition to part of the image, in pixels. Clears any earlier results.
+fn set_rectangle(tess_api: &mut leptess::tesseract::TessApi, (left, top, width, height): (i32, i32, i32, i32)) {
+    use tesseract_sys::{TessBaseAPI, TessBaseAPISetRectangle};
+
+    let handle: *mut TessBaseAPI = *tess_api.raw.as_ref();
+    unsafe { TessBaseAPISetRectangle(handle, left, top, width, height) };
+}
+
+/// Tells a running recognition whether a newer selection has arrived since it started.
+struct CancelToken {
+    selection_generation: Arc<AtomicU64>,
+    started_at: u64,
+}
+
+impl CancelToken {
+    fn new(selection_generation: &Arc<AtomicU64>) -> Self {
+        Self {
+            selection_generation: selection_generation.clone(),
+            started_at: selection_generation.load(Ordering::Relaxed)
         }
     }
-    let mut text = lines.join("");
 
-    if !init_data.format_options.maintain_newlines {
-        text = text.replace("\n", " ");
+    fn is_cancelled(&self) -> bool {
+        self.selection_generation.load(Ordering::Relaxed) != self.started_at
     }
+}
 
-    return text;
+/// Tesseract polls this while recognizing; returning true stops it.
+unsafe extern "C" fn should_cancel(cancel_this: *mut c_void, _words: c_int) -> c_int {
+    let cancel = &*(cancel_this as *const CancelToken);
+    cancel.is_cancelled() as c_int
+}
+
+/// Runs recognition with a monitor so it stops as soon as the selection changes.
+/// Returns whether recognition finished; a cancelled result shouldn't be used.
+fn recognize(tess_api: &mut leptess::tesseract::TessApi, cancel: &CancelToken) -> bool {
+    use tesseract_sys::{TessBaseAPI, TessBaseAPIRecognize, TessMonitorCreate, TessMonitorDelete, TessMonitorSetCancelFunc, TessMonitorSetCancelThis};
+
+    if cancel.is_cancelled() {
+        return false;
+    }
+
+    let handle: *mut TessBaseAPI = *tess_api.raw.as_ref();
+    unsafe {
+        let monitor = TessMonitorCreate();
+        TessMonitorSetCancelFunc(monitor, Some(should_cancel));
+        TessMonitorSetCancelThis(monitor, cancel as *const CancelToken as *mut c_void);
+        let result = TessBaseAPIRecognize(handle, monitor);
+        TessMonitorDelete(monitor);
+        result == 0 && !cancel.is_cancelled()
+    }
+}
+
+/// Words in the personal dictionary are known to be right, so they're never offered alternatives.
+fn without_custom_words(mut word_choices: Vec<(String, Vec<String>)>, custom_words: &[String]) -> Vec<(String, Vec<String>)> {
+    word_choices.retain(|(word, _)| !custom_words.contains(word));
+    word_choices
+}
+
+/// Custom words are included too, so hyphenated words from the personal dictionary keep their hyphens.
+fn get_hyphenated_word_list_cache(language_code: &str, custom_words: &[String]) -> Vec<String> {
+    let mut words = get_hyphenated_word_list(language_code);
+    words.extend(custom_words.iter().cloned());
+    words
+}
+
+fn get_hyphenated_word_list(language_code: &str) -> Vec<String> {
+    let path = format!("./correction_data/hyphenated/{}.txt", language_code);
+    // If the file doesn't exist, return an empty list
+    if !std::fs::try_exists(&path).unwrap_or(false) {
+        return Vec::new();
+    }
+
+    // The files are split by newlines
+    let file = std::fs::read_to_string(path).expect("Unable to read hyphenated word list");
+    file.lines().map(|x| x.to_string()).collect()
 }
 
 enum State<Type> {
@@ -377,6 +941,11 @@ impl<Type> OCRThrottlerState<Type> {
         self.last_value = Some(value);
         self.last_time = Instant::now();
     }
+
+    fn put_with(&mut self, merge: impl FnOnce(Option<Type>) -> Type) {
+        let value = merge(self.last_value.take());
+        self.put(value);
+    }
 }
 
 struct OCRThrottlerThread<Type> {
@@ -433,4 +1002,14 @@ impl<T: PartialEq> OCRThrottler<T> {
         self.0.mutex.lock().unwrap().put(data);
         self.0.thread.thread().unpark();
     }
+
+    /// Replaces the waiting value with one built from it, so it can be merged instead of dropped.
+    pub fn put_with(&self, merge: impl FnOnce(Option<T>) -> T) {
+        self.0.mutex.lock().unwrap().put_with(merge);
+        self.0.thread.thread().unpark();
+    }
+
+    pub fn set_delay(&self, delay: Duration) {
+        self.0.mutex.lock().unwrap().delay = delay;
+    }
 }
\ No newline at end of file
@@ -0,0 +1,159 @@
+use clipboard::{ClipboardContext, ClipboardProvider};
+
+use crate::automation::AutomationCommand;
+
+pub(crate) const URI_PREFIX: &str = "onscreenocr://";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResultFormat {
+    Text,
+    Json
+}
+
+/// An `onscreenocr://` link, e.g. `onscreenocr://capture?x=0&y=0&width=200&height=50&format=json`
+#[derive(Debug, Clone)]
+pub(crate) struct ProtocolRequest {
+    pub command: AutomationCommand,
+    format: ResultFormat
+}
+
+impl ProtocolRequest {
+    /// Parses a link: `open`, `capture` (which opens the overlay unless a region is given with `x`, `y`,
+    /// `width`, and `height`), `last-result`, or `language?code=<code>`. `format=json` copies results as
+    /// `{"text": ...}` instead of plain text.
+    pub(crate) fn parse(uri: &str) -> Result<Self, String> {
+        let rest = uri.strip_prefix(URI_PREFIX).ok_or_else(|| format!("'{}' isn't an {} link", uri, URI_PREFIX))?;
+        let (action, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let parameters: Vec<(String, String)> = query.split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (percent_decode(key), percent_decode(value))
+            })
+            .collect();
+        let parameter = |key: &str| parameters.iter().find(|(name, _)| name == key).map(|(_, value)| value.as_str());
+        let number = |key: &str| parameter(key)
+            .ok_or_else(|| format!("A capture region needs x, y, width, and height; {} is missing", key))?
+            .parse::<i64>()
+            .map_err(|_| format!("{} isn't a number", key));
+
+        let format = match parameter("format") {
+            None | Some("text") => ResultFormat::Text,
+            Some("json") => ResultFormat::Json,
+            Some(format) => return Err(format!("Unknown format '{}'", format))
+        };
+
+        // Some launchers add a trailing slash after the host
+        let command = match action.trim_end_matches('/') {
+            "open" => AutomationCommand::OpenOverlay,
+            "capture" if ["x", "y", "width", "height"].iter().all(|key| parameter(key).is_none()) => AutomationCommand::OpenOverlay,
+            "capture" => AutomationCommand::CaptureRegion {
+                x: number("x")? as i32,
+                y: number("y")? as i32,
+                width: number("width")?.max(0) as u32,
+                height: number("height")?.max(0) as u32
+            },
+            "last-result" => AutomationCommand::GetLastResult,
+            "language" => match parameter("code") {
+                Some(code) if !code.is_empty() => AutomationCommand::SetLanguage { code: code.to_string() },
+                _ => return Err("language needs a code, e.g. onscreenocr://language?code=eng".to_string())
+            },
+            action => return Err(format!("Unknown action '{}'", action))
+        };
+
+        Ok(ProtocolRequest { command, format })
+    }
+
+    /// Copies the result of the command to the clipboard in the requested format, since the program that
+    /// opened the link has no other way to get it.
+    pub(crate) fn deliver(&self, result: Result<String, String>) -> Result<String, String> {
+        let text = result?;
+        if text.is_empty() {
+            return Ok(text);
+        }
+
+        let text = match self.format {
+            ResultFormat::Text => text,
+            ResultFormat::Json => serde_json::json!({ "text": text }).to_string()
+        };
+        let mut ctx: ClipboardContext = ClipboardProvider::new().map_err(|e| format!("Unable to open the clipboard: {}", e))?;
+        ctx.set_contents(text.clone()).map_err(|e| format!("Unable to copy the result: {}", e))?;
+        Ok(text)
+    }
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = bytes.get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (b'+', _) => {
+                decoded.push(b' ');
+                index += 1;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Registers this executable as the handler for `onscreenocr://` links for the current user.
+#[cfg(windows)]
+pub(crate) fn register() -> Result<(), String> {
+    use crate::registry::{get_user_value, set_user_value};
+
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let command = format!("\"{}\" \"%1\"", exe.display());
+    // Only touch the registry when something changed, e.g. the executable moved
+    if get_user_value(r"Software\Classes\onscreenocr\shell\open\command", None).is_some_and(|existing| existing == command) {
+        return Ok(());
+    }
+    set_user_value(r"Software\Classes\onscreenocr", None, "URL:OnScreenOCR")?;
+    set_user_value(r"Software\Classes\onscreenocr", Some("URL Protocol"), "")?;
+    set_user_value(r"Software\Classes\onscreenocr\shell\open\command", None, &command)
+}
+
+/// Registers this executable as the handler for `onscreenocr://` links for the current user, through a
+/// desktop entry that claims the URL scheme.
+#[cfg(target_os = "linux")]
+pub(crate) fn register() -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let Some(base_dirs) = directories::BaseDirs::new() else {
+        return Err("Unable to find the home directory".to_string());
+    };
+    let applications = base_dirs.data_dir().join("applications");
+    let path = applications.join("onscreenocr-url-handler.desktop");
+
+    let entry = format!(
+        "[Desktop Entry]\nType=Application\nName=OnScreenOCR\nExec=\"{}\" %u\nNoDisplay=true\nTerminal=false\nMimeType=x-scheme-handler/onscreenocr;\n",
+        exe.display()
+    );
+    // Only touch the desktop database when something changed, e.g. the executable moved
+    if std::fs::read_to_string(&path).is_ok_and(|existing| existing == entry) {
+        return Ok(());
+    }
+    std::fs::create_dir_all(&applications).map_err(|e| format!("Unable to create {}: {}", applications.display(), e))?;
+    std::fs::write(&path, entry).map_err(|e| format!("Unable to write {}: {}", path.display(), e))?;
+
+    std::process::Command::new("xdg-mime")
+        .args(["default", "onscreenocr-url-handler.desktop", "x-scheme-handler/onscreenocr"])
+        .status()
+        .map_err(|e| format!("Unable to run xdg-mime: {}", e))
+        .and_then(|status| if status.success() { Ok(()) } else { Err(format!("xdg-mime exited with {}", status)) })
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+pub(crate) fn register() -> Result<(), String> {
+    Err("Registering the onscreenocr:// protocol isn't supported on this platform".to_string())
+}
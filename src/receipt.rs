@@ -0,0 +1,200 @@
+use crate::tsv::{parse_tsv, TSVRow, TSV_WORD_LEVEL};
+
+/// Labels that mark a line as one of the receipt's totals rather than a line item, most specific first.
+/// Matched against whole words of the lowercased description, so e.g. "Multipack" isn't mistaken for a tip.
+const TOTAL_LABELS: &[(&str, &str)] = &[
+    ("subtotal", "subtotal"),
+    ("sub total", "subtotal"),
+    ("amount due", "total"),
+    ("balance due", "total"),
+    ("grand total", "total"),
+    ("total", "total"),
+    ("tax", "tax"),
+    ("vat", "tax"),
+    ("gst", "tax"),
+    ("tip", "tip"),
+    ("gratuity", "tip"),
+    ("discount", "discount"),
+    ("change", "change"),
+    ("cash", "payment"),
+    ("card", "payment"),
+    ("visa", "payment"),
+    ("mastercard", "payment"),
+];
+
+/// Prices further than this many average character widths left of the price column are treated as part of
+/// the description (e.g. a unit price) rather than the line's amount.
+const PRICE_COLUMN_TOLERANCE: f32 = 4.;
+
+const CURRENCY_SYMBOLS: [char; 4] = ['$', '€', '£', '¥'];
+
+/// A row of words that sit at the same height, in left-to-right order.
+struct Row {
+    words: Vec<TSVRow>,
+}
+
+impl Row {
+    fn center(&self) -> f32 {
+        let top = self.words.iter().map(|word| word.top).min().unwrap_or(0);
+        let bottom = self.words.iter().map(|word| word.top + word.height).max().unwrap_or(0);
+        (top + bottom) as f32 / 2.
+    }
+}
+
+/// Groups words into rows by their vertical position. Tesseract often splits a receipt's description and
+/// price columns into separate blocks, so its own line numbers can't be used to pair them up.
+fn group_rows(tsv: &str) -> Vec<Row> {
+    let mut words: Vec<TSVRow> = parse_tsv(tsv).into_iter()
+        .filter(|row| row.level == TSV_WORD_LEVEL && !row.text.trim().is_empty())
+        .collect();
+    words.sort_by_key(|word| word.top + word.height / 2);
+
+    let mut rows: Vec<Row> = Vec::new();
+    for word in words {
+        let center = (word.top + word.height / 2) as f32;
+        match rows.last_mut() {
+            Some(row) if (row.center() - center).abs() < word.height.max(1) as f32 / 2. => row.words.push(word),
+            _ => rows.push(Row { words: vec![word] })
+        }
+    }
+    for row in &mut rows {
+        row.words.sort_by_key(|word| word.left);
+    }
+    rows
+}
+
+/// Parses an amount like "$12.50", "12,50", or "-3.00A" (with a trailing tax code). Amounts always have exactly two
+/// decimal places on receipts, which keeps quantities and item numbers from being mistaken for prices.
+fn parse_price(word: &str) -> Option<String> {
+    let word = word.trim().trim_start_matches(CURRENCY_SYMBOLS).trim_end_matches(|c: char| c.is_ascii_alphabetic() || CURRENCY_SYMBOLS.contains(&c));
+    let (negative, word) = match word.strip_prefix('-') {
+        Some(rest) => (true, rest.trim_start_matches(CURRENCY_SYMBOLS)),
+        None => (false, word)
+    };
+    if !word.is_ascii() {
+        return None;
+    }
+    let separator = word.len().checked_sub(3)?;
+    let (whole, fraction) = (&word[..separator], &word[separator + 1..]);
+    if !word[separator..].starts_with(['.', ',']) || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    // Thousands separators are whichever of . and , isn't the decimal separator
+    let whole = whole.replace([',', '.', '\''], "");
+    if whole.is_empty() || !whole.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(format!("{}{}.{}", if negative { "-" } else { "" }, whole, fraction))
+}
+
+/// Finds a date like 2024-03-02, 02/03/2024, or 2.3.24 in a word. It's returned as written, since whether
+/// the day or the month comes first can't be known from the receipt alone.
+fn parse_date(word: &str) -> Option<String> {
+    let word = word.trim_matches(|c: char| !c.is_ascii_digit());
+    let separator = word.chars().find(|c| ['-', '/', '.'].contains(c))?;
+    let parts: Vec<&str> = word.split(separator).collect();
+    if parts.len() != 3 || parts.iter().any(|part| part.is_empty() || !part.chars().all(|c| c.is_ascii_digit())) {
+        return None;
+    }
+
+    let lengths: Vec<usize> = parts.iter().map(|part| part.len()).collect();
+    let valid = match lengths.as_slice() {
+        [4, 1..=2, 1..=2] => true,
+        [1..=2, 1..=2, 2] | [1..=2, 1..=2, 4] => true,
+        _ => false
+    };
+    valid.then(|| word.to_string())
+}
+
+/// Splits a leading quantity like "2x", "2 x", or "2 @" off a line item's description.
+fn split_quantity(description: &str) -> (Option<u32>, String) {
+    let mut words = description.split_whitespace().collect::<Vec<&str>>();
+    let Some(first) = words.first() else {
+        return (None, String::new());
+    };
+
+    let quantity = if let Some(number) = first.strip_suffix(['x', 'X']) {
+        number.parse::<u32>().ok().map(|quantity| (quantity, 1))
+    } else if words.get(1).is_some_and(|word| ["x", "X", "@"].contains(word)) {
+        first.parse::<u32>().ok().map(|quantity| (quantity, 2))
+    } else {
+        None
+    };
+
+    match quantity {
+        Some((quantity, consumed)) => {
+            words.drain(..consumed);
+            (Some(quantity), words.join(" "))
+        }
+        None => (None, description.to_string())
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Pulls the date, totals, and line items out of a receipt or invoice and returns them as CSV with
+/// `type,description,quantity,amount` columns. Lines without an amount (the store's address, etc.) are skipped.
+pub(crate) fn extract_receipt(tsv: &str) -> String {
+    let rows = group_rows(tsv);
+
+    let character_widths = rows.iter().flat_map(|row| row.words.iter())
+        .filter(|word| word.width > 0)
+        .map(|word| word.width as f32 / word.text.chars().count().max(1) as f32)
+        .collect::<Vec<f32>>();
+    let character_width = character_widths.iter().sum::<f32>() / character_widths.len().max(1) as f32;
+
+    // The price column is where the rightmost amounts end, so amounts well to its left are unit prices and the like
+    let price_column = rows.iter()
+        .filter_map(|row| row.words.last().filter(|word| parse_price(&word.text).is_some()))
+        .map(|word| word.left + word.width)
+        .max();
+
+    let mut output = vec!["type,description,quantity,amount".to_string()];
+    let mut found_date = false;
+    for row in &rows {
+        if !found_date {
+            if let Some(date) = row.words.iter().find_map(|word| parse_date(&word.text)) {
+                output.push(format!("date,,,{}", csv_field(&date)));
+                found_date = true;
+                continue;
+            }
+        }
+
+        let Some((last, description_words)) = row.words.split_last() else {
+            continue;
+        };
+        let Some(amount) = parse_price(&last.text) else {
+            continue;
+        };
+        let in_price_column = price_column.is_none_or(|column| {
+            (column - (last.left + last.width)) as f32 <= character_width * PRICE_COLUMN_TOLERANCE
+        });
+        if !in_price_column {
+            continue;
+        }
+
+        let description = description_words.iter().map(|word| word.text.trim()).collect::<Vec<&str>>().join(" ");
+        let words = description.to_lowercase().split_whitespace()
+            .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+            .collect::<Vec<&str>>()
+            .join(" ");
+        let words = format!(" {} ", words);
+        match TOTAL_LABELS.iter().find(|(label, _)| words.contains(&format!(" {} ", label))) {
+            Some((_, kind)) => output.push(format!("{},{},,{}", kind, csv_field(&description), amount)),
+            None => {
+                let (quantity, description) = split_quantity(&description);
+                let quantity = quantity.map(|quantity| quantity.to_string()).unwrap_or_default();
+                output.push(format!("item,{},{},{}", csv_field(&description), quantity, amount));
+            }
+        }
+    }
+
+    output.join("\n")
+}
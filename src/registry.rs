@@ -0,0 +1,58 @@
+use windows_sys::Win32::{
+    Foundation::ERROR_SUCCESS,
+    System::Registry::{RegCloseKey, RegCreateKeyExW, RegGetValueW, RegSetValueExW, HKEY, HKEY_CURRENT_USER, KEY_WRITE, REG_OPTION_NON_VOLATILE, REG_SZ, RRF_RT_REG_SZ}
+};
+
+/// Writes a string value under HKEY_CURRENT_USER, creating the key if needed. A name of None sets the
+/// key's default value.
+pub(crate) fn set_user_value(key_path: &str, name: Option<&str>, value: &str) -> Result<(), String> {
+    let wide = |text: &str| text.encode_utf16().chain(std::iter::once(0)).collect::<Vec<u16>>();
+
+    let mut key: HKEY = 0;
+    let key_path_wide = wide(key_path);
+    let status = unsafe {
+        RegCreateKeyExW(HKEY_CURRENT_USER, key_path_wide.as_ptr(), 0, std::ptr::null(), REG_OPTION_NON_VOLATILE, KEY_WRITE, std::ptr::null(), &mut key, std::ptr::null_mut())
+    };
+    if status != ERROR_SUCCESS {
+        return Err(format!("Unable to open HKEY_CURRENT_USER\\{} (error {})", key_path, status));
+    }
+
+    let name = name.map(wide);
+    let value = wide(value);
+    let status = unsafe {
+        RegSetValueExW(key, name.as_ref().map_or(std::ptr::null(), |name| name.as_ptr()), 0, REG_SZ, value.as_ptr() as *const u8, (value.len() * 2) as u32)
+    };
+    unsafe { RegCloseKey(key) };
+    if status != ERROR_SUCCESS {
+        return Err(format!("Unable to write to HKEY_CURRENT_USER\\{} (error {})", key_path, status));
+    }
+    Ok(())
+}
+
+/// Reads a string value under HKEY_CURRENT_USER, or None if it doesn't exist. A name of None reads the
+/// key's default value.
+pub(crate) fn get_user_value(key_path: &str, name: Option<&str>) -> Option<String> {
+    let wide = |text: &str| text.encode_utf16().chain(std::iter::once(0)).collect::<Vec<u16>>();
+
+    let key_path_wide = wide(key_path);
+    let name = name.map(wide);
+    let name_pointer = name.as_ref().map_or(std::ptr::null(), |name| name.as_ptr());
+    let mut size = 0u32;
+    let status = unsafe {
+        RegGetValueW(HKEY_CURRENT_USER, key_path_wide.as_ptr(), name_pointer, RRF_RT_REG_SZ, std::ptr::null_mut(), std::ptr::null_mut(), &mut size)
+    };
+    if status != ERROR_SUCCESS {
+        return None;
+    }
+
+    let mut value = vec![0u16; (size as usize).div_ceil(2)];
+    let status = unsafe {
+        RegGetValueW(HKEY_CURRENT_USER, key_path_wide.as_ptr(), name_pointer, RRF_RT_REG_SZ, std::ptr::null_mut(), value.as_mut_ptr() as _, &mut size)
+    };
+    if status != ERROR_SUCCESS {
+        return None;
+    }
+    // The size includes the null terminator
+    value.truncate((size as usize / 2).saturating_sub(1));
+    Some(String::from_utf16_lossy(&value))
+}
@@ -4,17 +4,17 @@ pub use icon_renderer::{IconContext, IconEvent};
 use icon_renderer::IconRenderer;
 use ocr_preview_renderer::OCRPreviewRenderer;
 use pixels::{wgpu, PixelsContext, TextureError};
-use winit::event::ElementState;
-use crate::selection::Bounds;
+use winit::{event::ElementState, keyboard::Key};
 
 pub(crate) use animation::SmoothFadeAnimation;
 
-use crate::{screenshot::Screenshot, selection::Selection};
+use crate::{annotations::WordBox, geometry::Point, screenshot::Screenshot, selection::Selection, word_choices::UncertainWord};
 
 mod icon_renderer;
 mod ocr_preview_renderer;
 mod animation;
 mod background_renderer;
+mod fallback_fonts;
 
 #[allow(dead_code)] // Many of these fields are actually used
 pub(crate) struct Renderer {
@@ -79,10 +79,35 @@ impl Renderer {
 
     pub(crate) fn mouse_event(&mut self, mouse_pos: (i32, i32), state: ElementState, icon_context: &mut IconContext) -> bool {
         self.icon_renderer.mouse_event(mouse_pos, state, icon_context)
+            || self.ocr_preview_renderer.mouse_event(mouse_pos, state, icon_context)
+    }
+
+    /// The part of the preview text selected by dragging over it, if any.
+    pub(crate) fn preview_selection(&self) -> Option<String> {
+        self.ocr_preview_renderer.selected_text()
+    }
+
+    pub(crate) fn has_word_at(&self, image_point: Point) -> bool {
+        self.ocr_preview_renderer.has_word_at(image_point)
+    }
+
+    /// Highlights the word whose box contains a point on the screenshot, both there and in the preview.
+    pub(crate) fn flash_word_at(&mut self, image_point: Point) -> bool {
+        self.ocr_preview_renderer.flash_word_at(image_point)
+    }
+
+    pub(crate) fn scroll_event(&mut self, mouse_pos: (i32, i32), steps: f32, cycle_language: bool, icon_context: &mut IconContext) -> bool {
+        self.icon_renderer.scroll_event(mouse_pos, steps, cycle_language, icon_context)
+    }
+
+    pub(crate) fn keyboard_event(&mut self, key: &Key, state: ElementState, icon_context: &mut IconContext) -> bool {
+        self.icon_renderer.keyboard_event(key, state, icon_context)
     }
 
     pub(crate) fn before_reopen_window(&mut self) {
         self.last_update = std::time::Instant::now();
+        self.icon_renderer.unfocus_text_inputs();
+        self.icon_renderer.cancel_menubar_drag();
     }
 
     pub(crate) fn update(
@@ -91,15 +116,19 @@ impl Renderer {
         window_size: (u32, u32),
         selection: &mut Selection,
         ocr_preview_text: Option<String>,
+        uncertain_words: &[UncertainWord],
+        word_boxes: &[WordBox],
+        ocr_progress: Option<u8>,
         relative_mouse_pos: (i32, i32),
         icon_context: &IconContext
     ) {
-        let delta = self.last_update.elapsed();
+        let delta = if icon_context.settings.reduced_motion { animation::INSTANT_ANIMATION_DELTA } else { self.last_update.elapsed() };
         selection.polygon.update(delta); // This probably isn't the best place to put this, but it works best for now.
         self.last_update = std::time::Instant::now();
 
-        self.ocr_preview_renderer.update(context, window_size, selection.bounds, ocr_preview_text, icon_context, delta, &mut self.icon_renderer);
-        self.background_renderer.update(context, window_size, selection, icon_context);
+        self.ocr_preview_renderer.update(context, window_size, selection.bounds, selection.mouse_down, relative_mouse_pos, ocr_preview_text, uncertain_words, word_boxes, ocr_progress, icon_context, delta, &mut self.icon_renderer);
+        let word_flash = self.ocr_preview_renderer.word_flash(icon_context.settings.reduced_motion);
+        self.background_renderer.update(context, window_size, selection, relative_mouse_pos, self.ocr_preview_renderer.text_backing(), word_flash, icon_context);
         self.icon_renderer.update(context, delta, relative_mouse_pos, icon_context);
     }
 
@@ -1,31 +1,140 @@
+use std::time::Instant;
+
 use pixels::{check_texture_size, wgpu::{self, util::DeviceExt}, PixelsContext, TextureError};
 
-use crate::{screenshot::Screenshot, selection::{GPUVertex, Polygon, Selection}};
+use crate::{geometry::{GPUVertex, Point, Polygon, Rect, View}, screenshot::Screenshot, selection::Selection, settings::{BackgroundEffect, MAX_BLUR_RADIUS, MAX_MAGNIFIER_ZOOM, MIN_MAGNIFIER_ZOOM}};
 
 use super::IconContext;
 
+/// How thick the selection border is in high-contrast mode, as a multiple of the default width
+const HIGH_CONTRAST_BORDER_WIDTH: f32 = 6.;
+
+/// How many text suggestions the shader can draw, which is fixed since only the vertices can vary in length
+pub(crate) const MAX_TEXT_SUGGESTIONS: usize = 16;
+const DEFAULT_BORDER_COLOR: [f32; 3] = [0.482, 0.412, 0.745];
+
+/// How the selection's border is drawn
+#[derive(Clone, Debug)]
+pub(crate) struct BorderStyle {
+    pub color: [f32; 3],
+    /// A multiple of the default width
+    pub width: f32,
+    pub corner_markers: bool,
+    pub marching_ants: bool,
+    /// Seconds since the renderer was created, which moves the marching ants
+    pub time: f32
+}
+
 #[repr(C)]
 #[derive(Clone, Debug)]
 pub(crate) struct Locals {
-    blur_enabled: u32,
-    vertices: Vec<GPUVertex>
+    /// In pixels, or 0 to not blur
+    blur_radius: f32,
+    /// How dark the area outside the selection is, from 0 to 1
+    dim_opacity: f32,
+    /// What the area outside the selection is dimmed towards, which is the theme's
+    dim_color: [f32; 3],
+    /// Left, top, right, and bottom of the solid backing behind the preview text, relative to the screen
+    /// size, which is only drawn in high-contrast mode
+    text_backing: Option<[f32; 4]>,
+    /// Left, top, right, and bottom of the word box clicked in the preview, relative to the screen size,
+    /// and the color it's highlighted with, whose alpha fades it out
+    word_flash: Option<([f32; 4], [f32; 4])>,
+    /// The cursor relative to the screen size
+    cursor: (f32, f32),
+    /// How much the loupe magnifies, or 0 to hide it
+    loupe_zoom: f32,
+    /// Whether guide lines are drawn through the cursor
+    crosshair: u32,
+    /// The screenshot's offset relative to the screen size, and its zoom
+    view: (f32, f32, f32),
+    /// Whether handles are drawn at the middle of each edge, which only box mode has
+    edge_handles: u32,
+    border: BorderStyle,
+    /// Left, top, right, and bottom of each suggested text region, relative to the screen size
+    suggestions: Vec<[f32; 4]>,
+    vertices: Vec<GPUVertex>,
+    /// The outline of the selection Ctrl+Z would go back to, drawn after the selection's vertices
+    ghost_vertices: Vec<GPUVertex>
 }
 
 impl Locals {
-    pub(crate) fn new(selection: &Selection, window_size: (u32, u32), blur_enabled: bool) -> Self {
+    pub(crate) fn new(
+        selection: &Selection,
+        window_size: (u32, u32),
+        blur_radius: f32,
+        dim_opacity: f32,
+        dim_color: [f32; 3],
+        edge_handles: bool,
+        border: BorderStyle,
+        suggestions: &[Rect],
+        text_backing: Option<Rect>,
+        word_flash: Option<(Rect, [f32; 4])>,
+        mouse_position: (i32, i32),
+        loupe_zoom: Option<f32>,
+        crosshair: bool,
+        view: View,
+        undo_ghost: &[Point]
+    ) -> Self {
+        let (width, height) = (window_size.0 as f32, window_size.1 as f32);
         Self {
-            blur_enabled: if blur_enabled { 1 } else { 0 },
-            // Temporary, until we get polygon logic working for the actual selection
-            vertices: selection.polygon.get_device_coords_polygon(window_size)
+            blur_radius,
+            dim_opacity,
+            dim_color,
+            text_backing: text_backing.map(|rect| [rect.x / width, rect.y / height, (rect.x + rect.width) / width, (rect.y + rect.height) / height]),
+            // Word boxes are in pixels of the screenshot, like the selection
+            word_flash: word_flash.map(|(rect, color)| {
+                let rect = view.rect_to_screen(rect);
+                ([rect.x / width, rect.y / height, (rect.x + rect.width) / width, (rect.y + rect.height) / height], color)
+            }),
+            // The middle of the pixel under the cursor, so it's centered in the loupe and crosshair
+            cursor: ((mouse_position.0 as f32 + 0.5) / width, (mouse_position.1 as f32 + 0.5) / height),
+            loupe_zoom: loupe_zoom.unwrap_or(0.),
+            crosshair: if crosshair { 1 } else { 0 },
+            view: (view.offset.x / width, view.offset.y / height, view.zoom),
+            edge_handles: if edge_handles { 1 } else { 0 },
+            border,
+            suggestions: suggestions.iter()
+                .take(MAX_TEXT_SUGGESTIONS)
+                .map(|rect| view.rect_to_screen(*rect))
+                .map(|rect| [rect.x / width, rect.y / height, (rect.x + rect.width) / width, (rect.y + rect.height) / height])
+                .collect(),
+            // The selection is in pixels of the screenshot, so it's drawn where the zoomed screenshot puts it
+            vertices: selection.polygon.get_device_coords_polygon(window_size).into_iter()
+                .map(|mut vertex| {
+                    let position = view.to_screen(Point::new(vertex.position[0] * width, vertex.position[1] * height));
+                    vertex.position = [position.x / width, position.y / height];
+                    vertex
+                })
+                .collect(),
+            ghost_vertices: undo_ghost.iter()
+                .map(|point| view.to_screen(*point))
+                .map(|position| GPUVertex::new(position.x / width, position.y / height, 0))
+                .collect()
         }
     }
 
     pub(crate) fn as_bytes(&self) -> Vec<u8> {
-        let blur_enabled_bytes = bytemuck::bytes_of(&self.blur_enabled);
+        let blur_radius_bytes = bytemuck::bytes_of(&self.blur_radius);
+        let edge_handles_bytes = bytemuck::bytes_of(&self.edge_handles);
 
         let vertex_count = self.vertices.len() as u32;
         let vertex_count_bytes = bytemuck::bytes_of(&vertex_count);
 
+        let suggestion_count = self.suggestions.len() as u32;
+        let suggestion_count_bytes = bytemuck::bytes_of(&suggestion_count);
+
+        // The color is a vec4 in the shader, so it's padded with an unused alpha
+        let [red, green, blue] = self.border.color;
+        let border_color = [red, green, blue, 1.0f32];
+        let border_color_bytes: &[u8] = bytemuck::cast_slice(&border_color);
+        let corner_markers: u32 = if self.border.corner_markers { 1 } else { 0 };
+        let marching_ants: u32 = if self.border.marching_ants { 1 } else { 0 };
+
+        let mut suggestions = [[0f32; 4]; MAX_TEXT_SUGGESTIONS];
+        suggestions[..self.suggestions.len()].copy_from_slice(&self.suggestions);
+        let suggestions_bytes: &[u8] = bytemuck::cast_slice(&suggestions);
+
         let polygon_bytes = bytemuck::try_cast_slice(&self.vertices);
         if polygon_bytes.is_err() {
             eprintln!("Failed to cast polygon vertices to bytes");
@@ -33,20 +142,75 @@ impl Locals {
         }
 
         let polygon_bytes = polygon_bytes.unwrap();
-        let mut bytes = Vec::with_capacity(blur_enabled_bytes.len() + vertex_count_bytes.len() + polygon_bytes.len());
-        bytes.extend_from_slice(blur_enabled_bytes);
+        let mut bytes = Vec::with_capacity(Self::header_size() + polygon_bytes.len() + self.ghost_vertices.len() * std::mem::size_of::<GPUVertex>());
+        bytes.extend_from_slice(blur_radius_bytes);
+        bytes.extend_from_slice(edge_handles_bytes);
         bytes.extend_from_slice(vertex_count_bytes);
+        bytes.extend_from_slice(suggestion_count_bytes);
+        bytes.extend_from_slice(border_color_bytes);
+        bytes.extend_from_slice(bytemuck::bytes_of(&self.border.width));
+        bytes.extend_from_slice(bytemuck::bytes_of(&corner_markers));
+        bytes.extend_from_slice(bytemuck::bytes_of(&marching_ants));
+        bytes.extend_from_slice(bytemuck::bytes_of(&self.border.time));
+        bytes.extend_from_slice(bytemuck::bytes_of(&self.dim_opacity));
+        bytes.extend_from_slice(bytemuck::bytes_of(&self.loupe_zoom));
+        bytes.extend_from_slice(bytemuck::cast_slice(&[self.cursor.0, self.cursor.1]));
+        bytes.extend_from_slice(bytemuck::cast_slice(&[self.view.0, self.view.1, self.view.2, 0.]));
+        bytes.extend_from_slice(bytemuck::bytes_of(&self.crosshair));
+        bytes.extend_from_slice(bytemuck::bytes_of(&(self.ghost_vertices.len() as u32)));
+        // The dim color is a vec4, so it's aligned to 16 bytes
+        bytes.extend_from_slice(&[0u8; 2 * std::mem::size_of::<u32>()]);
+        let [red, green, blue] = self.dim_color;
+        bytes.extend_from_slice(bytemuck::cast_slice(&[red, green, blue, 0.]));
+        // An empty rectangle draws nothing
+        bytes.extend_from_slice(bytemuck::cast_slice(&self.text_backing.unwrap_or([0.; 4])));
+        let (word_flash, word_flash_color) = self.word_flash.unwrap_or(([0.; 4], [0.; 4]));
+        bytes.extend_from_slice(bytemuck::cast_slice(&word_flash));
+        bytes.extend_from_slice(bytemuck::cast_slice(&word_flash_color));
+        bytes.extend_from_slice(suggestions_bytes);
         bytes.extend_from_slice(&polygon_bytes);
+        bytes.extend_from_slice(bytemuck::cast_slice(&self.ghost_vertices));
 
         bytes
     }
+
+    /// The size of everything before the vertices: the counts and flags, the border style, the dim
+    /// opacity, the loupe, the view, the crosshair, the ghost's vertex count and padding, the dim color, the text backing, the word flash and its color, and the suggestions
+    fn header_size() -> usize {
+        4 * std::mem::size_of::<u32>() + 8 * std::mem::size_of::<f32>() + 4 * std::mem::size_of::<f32>() + 4 * std::mem::size_of::<f32>()
+            + 4 * std::mem::size_of::<u32>() + 16 * std::mem::size_of::<f32>() + MAX_TEXT_SUGGESTIONS * std::mem::size_of::<[f32; 4]>()
+    }
+}
+
+impl Default for BorderStyle {
+    fn default() -> Self {
+        Self {
+            color: DEFAULT_BORDER_COLOR,
+            width: 1.,
+            corner_markers: false,
+            marching_ants: false,
+            time: 0.
+        }
+    }
 }
 
 impl Default for Locals {
     fn default() -> Self {
         Self {
             vertices: Polygon::default().as_gpu_vertices(),
-            blur_enabled: 0
+            blur_radius: 0.,
+            dim_opacity: 0.,
+            dim_color: [0., 0., 0.02],
+            text_backing: None,
+            word_flash: None,
+            cursor: (0., 0.),
+            loupe_zoom: 0.,
+            crosshair: 0,
+            view: (0., 0., 1.),
+            edge_handles: 0,
+            border: BorderStyle::default(),
+            suggestions: Vec::new(),
+            ghost_vertices: Vec::new()
         }
     }
 }
@@ -60,6 +224,8 @@ pub(crate) struct BackgroundRenderer {
     background_pipeline: wgpu::RenderPipeline,
     locals_buffer: wgpu::Buffer,
     vertex_buffer: wgpu::Buffer,
+    /// When the renderer was created, which the marching ants are animated relative to
+    created: Instant,
 }
 
 impl BackgroundRenderer {
@@ -149,7 +315,8 @@ impl BackgroundRenderer {
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Storage { read_only: true },
                         has_dynamic_offset: false,
-                        min_binding_size: wgpu::BufferSize::new(std::mem::size_of::<Locals>() as u64),
+                        // The shader needs room for at least one vertex
+                        min_binding_size: wgpu::BufferSize::new((Locals::header_size() + std::mem::size_of::<GPUVertex>()) as u64),
                     },
                     count: None,
                 },
@@ -204,7 +371,8 @@ impl BackgroundRenderer {
             background_pipeline: render_pipeline,
 
             locals_buffer,
-            vertex_buffer
+            vertex_buffer,
+            created: Instant::now()
         })
     }
     
@@ -269,9 +437,52 @@ impl BackgroundRenderer {
         context: &PixelsContext,
         window_size: (u32, u32),
         selection: &Selection,
+        mouse_position: (i32, i32),
+        text_backing: Option<Rect>,
+        word_flash: Option<(Rect, f32)>,
         icon_context: &IconContext,
     ) {
-        let locals = Locals::new(selection, window_size, icon_context.settings.background_blur_enabled);
+        // Suggestions are only shown until something is selected, so they don't clutter the selection
+        let show_suggestions = icon_context.settings.text_suggestions
+            && selection.bounds.smaller_than(icon_context.settings.tesseract_settings.minimum_selection_size);
+        let suggestions: &[Rect] = if show_suggestions {
+            icon_context.text_layout.paragraphs.as_slice()
+        } else {
+            &[]
+        };
+        let settings = &icon_context.settings;
+        let theme_colors = icon_context.theme_colors();
+        let high_contrast = icon_context.high_contrast();
+        let border = BorderStyle {
+            color: if high_contrast { theme_colors.accent } else { settings.tesseract_settings.selection_border_color() },
+            width: if high_contrast { HIGH_CONTRAST_BORDER_WIDTH } else { settings.tesseract_settings.selection_border_width.clamp(1, 4) as f32 },
+            // Every vertex of an ellipse is a corner, so markers would cover its whole border
+            corner_markers: settings.border_corner_markers && !settings.use_ellipse,
+            marching_ants: settings.border_marching_ants,
+            // Still ants with reduced motion
+            time: if settings.reduced_motion { 0. } else { self.created.elapsed().as_secs_f32() }
+        };
+        let locals = Locals::new(
+            selection,
+            window_size,
+            if settings.background_effect == BackgroundEffect::Blur { settings.blur_radius.clamp(1, MAX_BLUR_RADIUS) as f32 } else { 0. },
+            if settings.background_effect == BackgroundEffect::None { 0. } else { settings.dim_opacity.clamp(0., 1.) },
+            theme_colors.dim,
+            !settings.use_polygon && !settings.use_ellipse,
+            border,
+            suggestions,
+            text_backing.filter(|_| high_contrast),
+            word_flash.map(|(rect, opacity)| {
+                let [red, green, blue] = theme_colors.accent;
+                (rect, [red, green, blue, opacity])
+            }),
+            mouse_position,
+            (settings.magnifier && selection.is_adjusting())
+                .then(|| settings.tesseract_settings.magnifier_zoom.clamp(MIN_MAGNIFIER_ZOOM, MAX_MAGNIFIER_ZOOM) as f32),
+            settings.crosshair_guides,
+            icon_context.view,
+            &icon_context.undo_ghost
+        );
 
         let device = &context.device;
         let queue = &context.queue;
@@ -282,7 +493,8 @@ impl BackgroundRenderer {
             // Resize the locals buffer
             self.locals_buffer.destroy();
             self.locals_buffer = {
-                let unpadded_size = current_locals_size * 2;
+                // Ellipses add many vertices at once, so doubling isn't always enough
+                let unpadded_size = (current_locals_size * 2).max(local_data.len() as u64);
                 let buffer = device.create_buffer(&wgpu::BufferDescriptor {
                     label: Some("Background renderer locals buffer"),
                     // Properly align the buffer
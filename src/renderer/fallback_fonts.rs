@@ -0,0 +1,134 @@
+use std::{collections::HashMap, path::{Path, PathBuf}};
+
+use glyph_brush::{ab_glyph::{Font, FontArc}, FontId};
+
+use crate::wgpu_text::TextBrush;
+
+/// How deep font directories are searched, since Linux distributions nest fonts by format and family
+const MAX_SEARCH_DEPTH: usize = 4;
+
+/// Font files covering the scripts of Tesseract languages, by the names Windows, macOS, and the Noto
+/// packages on Linux use. The first one found for each script is used.
+fn script_font_names(language_code: &str) -> &'static [&'static str] {
+    match language_code.trim_end_matches("_vert") {
+        "jpn" => &["YuGothM.ttc", "meiryo.ttc", "msgothic.ttc", "Hiragino Sans GB.ttc", "NotoSansCJK-Regular.ttc", "NotoSansCJKjp-Regular.otf"],
+        "chi_sim" => &["msyh.ttc", "simsun.ttc", "PingFang.ttc", "NotoSansCJK-Regular.ttc", "NotoSansCJKsc-Regular.otf"],
+        "chi_tra" => &["msjh.ttc", "mingliub.ttc", "PingFang.ttc", "NotoSansCJK-Regular.ttc", "NotoSansCJKtc-Regular.otf"],
+        "kor" => &["malgun.ttf", "gulim.ttc", "AppleSDGothicNeo.ttc", "NotoSansCJK-Regular.ttc", "NotoSansCJKkr-Regular.otf"],
+        "ara" | "fas" | "urd" | "pus" | "snd" | "uig" => &["segoeui.ttf", "tahoma.ttf", "GeezaPro.ttc", "NotoSansArabic-Regular.ttf", "NotoNaskhArabic-Regular.ttf"],
+        "heb" | "yid" => &["segoeui.ttf", "arial.ttf", "ArialHB.ttc", "NotoSansHebrew-Regular.ttf"],
+        "tha" => &["LeelawUI.ttf", "tahoma.ttf", "Thonburi.ttc", "NotoSansThai-Regular.ttf"],
+        "hin" | "mar" | "nep" | "san" => &["Nirmala.ttf", "Kohinoor.ttc", "NotoSansDevanagari-Regular.ttf"],
+        _ => &[]
+    }
+}
+
+/// Fonts for emoji and symbols, which can turn up in any language. Color-only emoji fonts can't be drawn,
+/// so these are ones with outlines.
+const SYMBOL_FONT_NAMES: &[&str] = &["seguiemj.ttf", "seguisym.ttf", "Apple Symbols.ttf", "Symbola.ttf", "NotoSansSymbols2-Regular.ttf"];
+
+fn font_directories() -> Vec<PathBuf> {
+    let mut directories = Vec::new();
+    if let Ok(windows_directory) = std::env::var("WINDIR") {
+        directories.push(Path::new(&windows_directory).join("Fonts"));
+    }
+    if let Ok(local_app_data) = std::env::var("LOCALAPPDATA") {
+        directories.push(Path::new(&local_app_data).join("Microsoft").join("Windows").join("Fonts"));
+    }
+    directories.extend(["/System/Library/Fonts", "/Library/Fonts", "/usr/share/fonts", "/usr/local/share/fonts"].map(PathBuf::from));
+    if let Some(home) = directories::BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf()) {
+        directories.push(home.join(".local").join("share").join("fonts"));
+        directories.push(home.join("Library").join("Fonts"));
+    }
+    directories
+}
+
+/// Finds the first of the font files under any of the directories, ignoring case.
+fn find_font(directories: &[PathBuf], names: &[&str]) -> Option<PathBuf> {
+    fn search(directory: &Path, name: &str, depth: usize) -> Option<PathBuf> {
+        let entries: Vec<_> = std::fs::read_dir(directory).ok()?.flatten().map(|entry| entry.path()).collect();
+        if let Some(path) = entries.iter().find(|path| path.file_name().is_some_and(|file_name| file_name.to_string_lossy().eq_ignore_ascii_case(name))) {
+            return Some(path.clone());
+        }
+        if depth == 0 {
+            return None;
+        }
+        entries.iter().filter(|path| path.is_dir()).find_map(|path| search(path, name, depth - 1))
+    }
+
+    names.iter().find_map(|name| directories.iter().find_map(|directory| search(directory, name, MAX_SEARCH_DEPTH)))
+}
+
+/// The fonts text falls back to, in order, for characters the preview's own font doesn't have.
+/// Fonts are added to the brush as they're first needed and kept, since a brush can't drop fonts.
+pub(crate) struct FallbackFonts {
+    /// The language and font files the chain was last built for
+    configuration: Option<(String, Vec<String>, bool)>,
+    /// The brush's font for each file loaded so far, or none if it couldn't be read
+    loaded: HashMap<PathBuf, Option<FontId>>,
+    chain: Vec<FontId>,
+}
+
+impl FallbackFonts {
+    pub fn new() -> Self {
+        FallbackFonts { configuration: None, loaded: HashMap::new(), chain: Vec::new() }
+    }
+
+    /// Rebuilds the chain if the OCR language or font settings changed: the configured font files first, then,
+    /// if `use_system_fonts`, system fonts for the scripts of each language in `language_code` (e.g. "jpn+eng"),
+    /// then symbol and emoji fonts.
+    pub fn update(&mut self, text_brush: &mut TextBrush<FontArc>, language_code: &str, font_files: &[String], use_system_fonts: bool) {
+        let configuration = (language_code.to_string(), font_files.to_vec(), use_system_fonts);
+        if self.configuration.as_ref() == Some(&configuration) {
+            return;
+        }
+        self.configuration = Some(configuration);
+
+        let mut paths: Vec<PathBuf> = font_files.iter().filter(|file| !file.trim().is_empty()).map(PathBuf::from).collect();
+        if use_system_fonts {
+            let directories = font_directories();
+            paths.extend(language_code.split('+').filter_map(|code| find_font(&directories, script_font_names(code))));
+            paths.extend(SYMBOL_FONT_NAMES.iter().filter_map(|name| find_font(&directories, &[name])));
+        }
+
+        self.chain.clear();
+        for path in paths {
+            let font_id = *self.loaded.entry(path.clone()).or_insert_with(|| {
+                let font = std::fs::read(&path).map_err(|e| e.to_string()).and_then(|data| FontArc::try_from_vec(data).map_err(|e| e.to_string()));
+                match font {
+                    Ok(font) => Some(text_brush.add_font(font)),
+                    Err(error) => {
+                        eprintln!("Unable to load fallback font {}: {}", path.to_string_lossy(), error);
+                        None
+                    }
+                }
+            });
+            if let Some(font_id) = font_id.filter(|font_id| !self.chain.contains(font_id)) {
+                self.chain.push(font_id);
+            }
+        }
+    }
+
+    /// Splits text into runs that can each be drawn in one font: `primary` wherever it has the character,
+    /// and otherwise the first fallback that does. Characters no font has stay in `primary`.
+    pub fn split_runs(&self, text: &str, fonts: &[FontArc], primary: FontId) -> Vec<(String, FontId)> {
+        if self.chain.is_empty() {
+            return vec![(text.to_string(), primary)];
+        }
+        let mut runs: Vec<(String, FontId)> = Vec::new();
+        for character in text.chars() {
+            let has_glyph = |font_id: &FontId| fonts[font_id.0].glyph_id(character).0 != 0;
+            let font_id = if character.is_whitespace() || has_glyph(&primary) {
+                // Whitespace joins whichever run it's in, so runs only break at characters that need it
+                runs.last().map_or(primary, |(_, font_id)| if character.is_whitespace() { *font_id } else { primary })
+            } else {
+                self.chain.iter().copied().find(has_glyph).unwrap_or(primary)
+            };
+            match runs.last_mut() {
+                Some((run, run_font_id)) if *run_font_id == font_id => run.push(character),
+                _ => runs.push((character.to_string(), font_id))
+            }
+        }
+        runs
+    }
+}
@@ -1,13 +1,13 @@
-use std::sync::mpsc;
+use std::{collections::HashSet, sync::mpsc};
 
 use glyph_brush::{ab_glyph::FontRef, OwnedSection, OwnedText};
-use icon_layout::get_icon_layouts;
+use icon_layout::{cycle_active_ocr, get_icon_layouts, menubar_position, panel_position};
 use pixels::{wgpu::{self, util::DeviceExt, Device, Queue}, Pixels, PixelsContext};
-use winit::event::ElementState;
+use winit::{event::ElementState, keyboard::Key};
 
-use crate::{selection::Bounds, settings::SettingsManager, wgpu_text::{BrushBuilder, Matrix, TextBrush}};
+use crate::{bounds_entry::BoundsEntry, clipboard_history::ClipboardHistory, geometry::{Point, Rect, View}, language_manager::LanguageManager, localization::Locale, settings::{system_high_contrast, MenubarPosition, SettingsManager, Theme, ThemeColors, MAX_UI_SCALE, MIN_UI_SCALE}, text_regions::TextLayout, wgpu_text::{BrushBuilder, Matrix, TextBrush}};
 use super::animation::SmoothMoveFadeAnimation;
-use icon_layout_engine::{create_icon, IconLayouts};
+use icon_layout_engine::{create_icon, IconLayouts, ScreenLocation, ScreenRelativePosition};
 
 pub use icon_layout_engine::TEXT_HEIGHT;
 pub use icon_layout::IconEvent;
@@ -15,30 +15,112 @@ pub use icon_layout::IconEvent;
 mod icon_layout_engine;
 mod icon_layout;
 
+/// How wide tooltips get before wrapping onto another line
+const TOOLTIP_MAX_WIDTH: f32 = 320.;
+/// How far outside the compact menubar the cursor can go before it collapses again
+const MENUBAR_HOVER_MARGIN: f32 = 20.;
+/// How much larger the icons and text are in high-contrast mode, on top of the UI scale
+const HIGH_CONTRAST_SCALE: f32 = 1.4;
+
 pub struct IconContext {
     pub settings: SettingsManager,
     pub settings_panel_visible: bool,
     pub copy_key_held: bool,
     pub screenshot_key_held: bool,
     pub has_selection: bool,
+    pub language_manager: LanguageManager,
+    pub history_visible: bool,
+    pub clipboard_history: ClipboardHistory,
+    /// The text found on the screen when the overlay opened
+    pub text_layout: TextLayout,
+    /// The bounds being typed, while the bounds entry is open
+    pub bounds_entry: Option<BoundsEntry>,
+    /// The name being typed for a new region preset, while it's being saved
+    pub preset_name_entry: Option<String>,
+    /// The region preset shown in the settings panel
+    pub region_preset: usize,
+    /// How the screenshot is zoomed and panned
+    pub view: View,
+    /// The selection Ctrl+Z would go back to, outlined while Ctrl is held
+    pub undo_ghost: Vec<Point>,
+    /// The settings panel sections that are collapsed, by name
+    pub collapsed_sections: HashSet<String>,
+    /// What the settings panel is filtered by
+    pub settings_search: String,
+    /// Whether Windows' high contrast mode was on when the overlay opened
+    pub system_high_contrast: bool,
+    /// Whether the compact menubar was expanded with Q or its pill, so it stays open without hovering
+    pub menubar_expanded: bool,
+    /// The translations of the labels and tooltips, for the interface language in the settings
+    pub locale: Locale,
 
     pub(crate) channel: mpsc::Sender<IconEvent>
 }
 
 impl IconContext {
     pub fn new(channel: mpsc::Sender<IconEvent>) -> Self {
+        let settings = SettingsManager::new();
+        let clipboard_history = ClipboardHistory::new(settings.tesseract_settings.history_size, settings.tesseract_settings.persist_history);
+        let locale = Locale::load(&settings.tesseract_settings.interface_language);
         Self {
-            settings: SettingsManager::new(),
+            settings,
             settings_panel_visible: false,
             copy_key_held: false,
             has_selection: false,
             screenshot_key_held: false,
+            language_manager: LanguageManager::new(),
+            history_visible: false,
+            clipboard_history,
+            text_layout: TextLayout::default(),
+            bounds_entry: None,
+            preset_name_entry: None,
+            region_preset: 0,
+            view: View::default(),
+            undo_ghost: Vec::new(),
+            collapsed_sections: HashSet::new(),
+            settings_search: String::new(),
+            system_high_contrast: system_high_contrast(),
+            menubar_expanded: false,
+            locale,
             channel
         }
     }
 
     pub fn reset(&mut self) {
         self.settings_panel_visible = false;
+        self.history_visible = false;
+        self.bounds_entry = None;
+        self.preset_name_entry = None;
+        self.view = View::default();
+        self.undo_ghost.clear();
+        self.settings_search.clear();
+        self.system_high_contrast = system_high_contrast();
+        self.menubar_expanded = false;
+    }
+
+    /// Loads the locale for the interface language in the settings, e.g. after it's changed or the settings are reloaded.
+    pub fn reload_locale(&mut self) {
+        self.locale = Locale::load(&self.settings.tesseract_settings.interface_language);
+    }
+
+    /// Whether high-contrast mode is on, either in the settings or because Windows' is.
+    pub fn high_contrast(&self) -> bool {
+        self.settings.high_contrast || self.system_high_contrast
+    }
+
+    /// How many times larger than normal the icons and text are drawn.
+    pub fn ui_scale(&self) -> f32 {
+        let scale = self.settings.tesseract_settings.ui_scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE) as f32 / 100.;
+        if self.high_contrast() { scale * HIGH_CONTRAST_SCALE } else { scale }
+    }
+
+    /// The colors everything is drawn in. High-contrast mode always uses the high-visibility theme.
+    pub fn theme_colors(&self) -> ThemeColors {
+        if self.high_contrast() {
+            Theme::HighVisibility.colors()
+        } else {
+            self.settings.tesseract_settings.theme_colors()
+        }
     }
 }
 
@@ -55,7 +137,7 @@ pub(crate) struct Icon {
     pub active: bool,
     pub disabled: bool,
 
-    pub bounds: Bounds,
+    pub bounds: Rect,
 
     pub visible: bool,
     pub anim: SmoothMoveFadeAnimation,
@@ -66,6 +148,8 @@ pub(crate) struct Icon {
     pub get_disabled: Option<Box<dyn Fn(&IconContext) -> bool>>,
 
     pub tooltip_text: Option<String>,
+    /// A tooltip that changes, e.g. to show a toggle's current state, which takes priority over `tooltip_text`
+    pub get_tooltip_text: Option<Box<dyn Fn(&IconContext) -> String>>,
 
     pub(crate) icon_normal_pos: (u32, u32),
     pub(crate) icon_hovered_pos: (u32, u32),
@@ -82,10 +166,10 @@ pub(crate) struct TooltipState {
 }
 
 impl TooltipState {
-    pub fn new(bounds: Bounds, text: String, disabled_icon: bool) -> Self {
+    pub fn new(bounds: Rect, text: String, disabled_icon: bool) -> Self {
         Self {
             start_time: std::time::Instant::now(),
-            position: (bounds.x as f32 + bounds.width as f32 / 2., bounds.y as f32 + bounds.height as f32 + 10.),
+            position: (bounds.x + bounds.width / 2., bounds.y + bounds.height + 10.),
             text,
             hidden: false,
             disabled_icon
@@ -132,7 +216,17 @@ pub(crate) struct IconRenderer {
 
     pub matrix_buffer: wgpu::Buffer,
 
+    /// The size the icons are laid out in, which is the window's size shrunk by `scale`
     pub current_screen_size: (f32, f32),
+    pub window_size: (f32, f32),
+    /// How much larger than normal the icons and their text are drawn
+    pub scale: f32,
+    /// While the menubar is being dragged, where it's held from relative to its center
+    pub menubar_drag: Option<(f32, f32)>,
+    /// Whether the compact menubar is showing as just its pill
+    pub menubar_collapsed: bool,
+    /// Wheel movement that hasn't added up to a whole step yet, e.g. from touchpads
+    pub scroll_remainder: f32,
 
     pub text_brush: TextBrush<FontRef<'static>>,
     pub should_render_text: bool,
@@ -243,8 +337,8 @@ impl IconRenderer {
             mapped_at_creation: false
         });
         let instance_icon_state_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Icon Atlas Instance State Buffer"), // vec3<f32>
-            size: (3 * icon_count * std::mem::size_of::<f32>()) as wgpu::BufferAddress,
+            label: Some("Icon Atlas Instance State Buffer"), // vec3<f32>, vec4<f32>
+            size: (7 * icon_count * std::mem::size_of::<f32>()) as wgpu::BufferAddress,
             usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false
         });
@@ -359,14 +453,19 @@ impl IconRenderer {
                         }
                     ]
                 }, wgpu::VertexBufferLayout {
-                    // Icon atlas position, opacity
-                    array_stride: 3 * std::mem::size_of::<f32>() as wgpu::BufferAddress,
+                    // Icon atlas position, opacity, theme tint
+                    array_stride: 7 * std::mem::size_of::<f32>() as wgpu::BufferAddress,
                     step_mode: wgpu::VertexStepMode::Instance,
                     attributes: &[
                         wgpu::VertexAttribute {
                             format: wgpu::VertexFormat::Float32x3,
                             offset: 0,
                             shader_location: 2
+                        },
+                        wgpu::VertexAttribute {
+                            format: wgpu::VertexFormat::Float32x4,
+                            offset: 3 * std::mem::size_of::<f32>() as wgpu::BufferAddress,
+                            shader_location: 3
                         }
                     ]
                 }],
@@ -418,6 +517,11 @@ impl IconRenderer {
             matrix_buffer,
 
             current_screen_size: (width, height),
+            window_size: (width, height),
+            scale: 1.,
+            menubar_drag: None,
+            menubar_collapsed: false,
+            scroll_remainder: 0.,
             
             text_brush: BrushBuilder::using_font_bytes(include_bytes!("../../fonts/DejaVuSans.ttf")).expect("Unable to load font")
                 .build(
@@ -432,7 +536,8 @@ impl IconRenderer {
             icon_tooltip_anim: SmoothMoveFadeAnimation::new(false, super::animation::MoveDirection::Up, 10.),
             icon_tooltip_section: OwnedSection::default()
                 .add_text(OwnedText::new("").with_scale(18.0)) // Color and position are set when updating
-                .with_layout(glyph_brush::Layout::default_single_line().h_align(glyph_brush::HorizontalAlign::Center))
+                .with_bounds((TOOLTIP_MAX_WIDTH, f32::INFINITY))
+                .with_layout(glyph_brush::Layout::default_wrap().h_align(glyph_brush::HorizontalAlign::Center))
         }
     }
 
@@ -470,11 +575,92 @@ impl IconRenderer {
     }
 
     pub fn mouse_event(&mut self, mouse_pos: (i32, i32), state: ElementState, icon_context: &mut IconContext) -> bool {
+        let mouse_pos = self.to_layout_position(mouse_pos);
+        if self.menubar_drag_event(mouse_pos, state, icon_context) {
+            return true;
+        }
+
         let mut found = false;
         self.icons_mut().iter_mut().for_each(|icon| found = icon.mouse_event(mouse_pos, state, icon_context) || found);
+        self.icons.text_mut().into_iter().for_each(|text| found = text.mouse_event(mouse_pos, state, icon_context) || found);
         found
     }
 
+    /// Starts dragging the menubar when its background is pressed, and drops it against the nearest edge or
+    /// corner when released. Returns whether the event was used.
+    fn menubar_drag_event(&mut self, mouse_pos: (i32, i32), state: ElementState, icon_context: &mut IconContext) -> bool {
+        let (x, y) = (mouse_pos.0 as f32, mouse_pos.1 as f32);
+        match (state, self.menubar_drag.take()) {
+            (ElementState::Pressed, _) => {
+                if self.menubar_collapsed {
+                    return false;
+                }
+                let menubar_bounds = self.icons.bounds("menubar");
+                let on_button = self.icons().iter().any(|icon| icon.visible && icon.behavior != IconBehavior::Visual && icon.bounds.contains(mouse_pos));
+                if !menubar_bounds.contains(mouse_pos) || on_button {
+                    return false;
+                }
+                let (center_x, center_y) = (menubar_bounds.x + menubar_bounds.width / 2., menubar_bounds.y + menubar_bounds.height / 2.);
+                self.menubar_drag = Some((center_x - x, center_y - y));
+                true
+            }
+            (ElementState::Released, Some(_)) => {
+                icon_context.settings.tesseract_settings.menubar_position = MenubarPosition::nearest((x, y), self.current_screen_size);
+                true
+            }
+            (ElementState::Released, None) => false
+        }
+    }
+
+    /// Collapses the compact menubar to its pill unless it's hovered, expanded, or needed for an open panel.
+    fn update_menubar_collapsed(&mut self, mouse_pos: (i32, i32), icon_context: &IconContext) {
+        let can_collapse = icon_context.settings.compact_menubar
+            && !icon_context.menubar_expanded
+            && !icon_context.settings_panel_visible
+            && !icon_context.history_visible
+            && self.menubar_drag.is_none();
+        // Whichever is showing is what's hovered, so the menubar stays open while the cursor is over it
+        let mut hover_bounds = self.icons.bounds(if self.menubar_collapsed { "menubar-pill" } else { "menubar" });
+        hover_bounds.grow_by(MENUBAR_HOVER_MARGIN);
+        self.menubar_collapsed = can_collapse && !hover_bounds.contains(mouse_pos);
+
+        self.icons.set_visible("menubar", !self.menubar_collapsed);
+        self.icons.set_visible("menubar-pill", self.menubar_collapsed);
+    }
+
+    /// Handles the mouse wheel over scrollable text, or anywhere on the menubar while `cycle_language` is held,
+    /// where it cycles the OCR language. Returns whether the scroll was used.
+    pub fn scroll_event(&mut self, mouse_pos: (i32, i32), steps: f32, cycle_language: bool, icon_context: &mut IconContext) -> bool {
+        let mouse_pos = self.to_layout_position(mouse_pos);
+        self.scroll_remainder += steps;
+        let whole_steps = self.scroll_remainder.trunc();
+        self.scroll_remainder -= whole_steps;
+        let whole_steps = whole_steps as i32;
+
+        if self.icons.text_mut().into_iter().any(|text| text.scroll_event(mouse_pos, whole_steps, icon_context)) {
+            return true;
+        }
+        if !cycle_language || self.menubar_collapsed || !self.icons.bounds("menubar").contains(mouse_pos) {
+            self.scroll_remainder = 0.;
+            return false;
+        }
+        cycle_active_ocr(icon_context, whole_steps);
+        true
+    }
+
+    /// Passes a key to the focused text input, if there is one, returning whether it was used.
+    pub fn keyboard_event(&mut self, key: &Key, state: ElementState, icon_context: &mut IconContext) -> bool {
+        self.icons.text_mut().into_iter().any(|text| text.keyboard_event(key, state, icon_context))
+    }
+
+    pub fn unfocus_text_inputs(&mut self) {
+        self.icons.text_mut().into_iter().for_each(|text| text.unfocus());
+    }
+
+    pub fn cancel_menubar_drag(&mut self) {
+        self.menubar_drag = None;
+    }
+
     pub fn update(
         &mut self,
         context: &PixelsContext,
@@ -482,11 +668,28 @@ impl IconRenderer {
         mouse_pos: (i32, i32),
         icon_context: &IconContext
     ) {
-        self.icons.recalculate_positions(self.current_screen_size);
-
         let device = &context.device;
         let queue = &context.queue;
 
+        let scale = icon_context.ui_scale();
+        if scale != self.scale {
+            self.scale = scale;
+            self.apply_view(queue);
+        }
+        let mouse_pos = self.to_layout_position(mouse_pos);
+        let menubar = match self.menubar_drag {
+            Some((grab_x, grab_y)) => ScreenRelativePosition::new(ScreenLocation::TopLeft, (mouse_pos.0 as f32 + grab_x, mouse_pos.1 as f32 + grab_y)),
+            None => menubar_position(icon_context.settings.tesseract_settings.menubar_position)
+        };
+        self.icons.set_position("menubar", menubar);
+        self.icons.set_position("menubar-pill", menubar_position(icon_context.settings.tesseract_settings.menubar_position));
+        let menubar_bounds = self.icons.bounds("menubar");
+        for panel in ["settings", "history"] {
+            self.icons.set_position(panel, panel_position(icon_context.settings.tesseract_settings.menubar_position, (menubar_bounds.width, menubar_bounds.height)));
+        }
+        self.update_menubar_collapsed(mouse_pos, icon_context);
+        self.icons.recalculate_positions(self.current_screen_size);
+
         let hover_state = self.icons.update_all(mouse_pos, delta, icon_context);
         if let Some(mut state) = hover_state {
             if self.icon_tooltip_state.hidden {
@@ -502,17 +705,19 @@ impl IconRenderer {
         }
         self.icon_tooltip_anim.update(delta, self.icon_tooltip_state.should_show());
 
-        self.update_icon_state_buffer(queue);
+        self.update_icon_state_buffer(queue, icon_context);
         self.update_icon_position_buffer(queue);
 
         self.icons.set_visible("settings", icon_context.settings_panel_visible);
+        self.icons.set_visible("history", icon_context.history_visible);
 
         // Update text
         let mut sections: Vec<&glyph_brush::OwnedSection> = self.icons.text_sections();
         if self.icon_tooltip_anim.visible_at_all() {
             self.icon_tooltip_section.screen_position = self.icon_tooltip_anim.move_point(self.icon_tooltip_state.position);
             self.icon_tooltip_section.text[0].text = self.icon_tooltip_state.text.clone();
-            self.icon_tooltip_section.text[0].extra.color = [1.0, 1.0, 1.0, self.icon_tooltip_anim.get_opacity() * if self.icon_tooltip_state.disabled_icon { 0.5 } else { 1.0 }];
+            let [red, green, blue] = icon_context.theme_colors().text;
+            self.icon_tooltip_section.text[0].extra.color = [red, green, blue, self.icon_tooltip_anim.get_opacity() * if self.icon_tooltip_state.disabled_icon { 0.5 } else { 1.0 }];
             sections.push(&self.icon_tooltip_section);
         }
         self.should_render_text = sections.len() > 0;
@@ -543,19 +748,20 @@ impl IconRenderer {
             return;
         }
         self.icons.set_visible("copy", true);
-        self.icons.set_center("copy", pos.unwrap().0, pos.unwrap().1);
+        self.icons.set_center("copy", pos.unwrap().0 / self.scale, pos.unwrap().1 / self.scale);
     }
 
     fn update_icon_position_buffer(&mut self, queue: &Queue) {
         let instance_data: Vec<f32> = self.icons().iter().flat_map(|icon| {
-            let pos = icon.anim.move_point((icon.bounds.x as f32, icon.bounds.y as f32));
-            vec![pos.0, pos.1, icon.bounds.width as f32, icon.bounds.height as f32]
+            let pos = icon.anim.move_point((icon.bounds.x, icon.bounds.y));
+            vec![pos.0, pos.1, icon.bounds.width, icon.bounds.height]
         }).collect();
 
         queue.write_buffer(&self.instance_icon_position_buffer, 0, bytemuck::cast_slice(&instance_data));
     }
 
-    fn update_icon_state_buffer(&mut self, queue: &Queue) {
+    fn update_icon_state_buffer(&mut self, queue: &Queue, icon_context: &IconContext) {
+        let theme_colors = icon_context.theme_colors();
         let instance_data: Vec<f32> = self.icons().iter().flat_map(|icon| {
             let active_icon_pos = match (icon.active, icon.hovered) {
                 (true, true) => icon.icon_selected_hovered_pos,
@@ -563,10 +769,18 @@ impl IconRenderer {
                 (false, true) => icon.icon_hovered_pos,
                 (false, false) => icon.icon_normal_pos
             };
+            // Backgrounds are the only visual icons, so they take the panel color
+            let tint = match (&icon.behavior, icon.active, icon.hovered) {
+                (IconBehavior::Visual, _, _) => theme_colors.panel,
+                (_, true, _) => theme_colors.active_icon,
+                (_, false, true) => theme_colors.hovered_icon,
+                (_, false, false) => theme_colors.icon
+            };
             vec![
                 active_icon_pos.0 as f32 / self.icon_atlas_width as f32,
                 active_icon_pos.1 as f32 / self.icon_atlas_height as f32,
-                icon.anim.get_opacity() * if icon.disabled { 0.5 } else { 1.0 }
+                icon.anim.get_opacity() * if icon.disabled { 0.5 } else { 1.0 },
+                tint[0], tint[1], tint[2], tint[3]
             ]
         }).collect();
 
@@ -574,12 +788,23 @@ impl IconRenderer {
     }
 
     pub fn resize_view(&mut self, width: f32, height: f32, queue: &wgpu::Queue) {
+        self.window_size = (width, height);
+        self.apply_view(queue);
+    }
+
+    /// Lays the icons out in the window shrunk by the scale, then stretches them back over the whole window.
+    fn apply_view(&mut self, queue: &wgpu::Queue) {
+        let (width, height) = (self.window_size.0 / self.scale, self.window_size.1 / self.scale);
         self.update_matrix(crate::wgpu_text::ortho(width, height), queue);
-        self.text_brush.resize_view(width as f32, height as f32, queue);
-        
+        self.text_brush.resize_view(width, height, queue);
+
         self.current_screen_size = (width, height);
     }
 
+    fn to_layout_position(&self, position: (i32, i32)) -> (i32, i32) {
+        ((position.0 as f32 / self.scale) as i32, (position.1 as f32 / self.scale) as i32)
+    }
+
     fn update_matrix(&self, matrix: crate::wgpu_text::Matrix, queue: &wgpu::Queue) {
         queue.write_buffer(&self.matrix_buffer, 0, bytemuck::cast_slice(&matrix));
     }
@@ -631,10 +856,13 @@ impl Icon {
 
         self.anim.update(delta, self.visible);
 
-        if mouse_over && self.tooltip_text.is_some() && self.anim.fully_visible() {
-            Some(TooltipState::new(self.bounds, self.tooltip_text.clone().unwrap(), self.disabled))
-        } else {
-            None
+        if !mouse_over || !self.anim.fully_visible() {
+            return None;
         }
+        let tooltip_text = match &self.get_tooltip_text {
+            Some(get_tooltip_text) => Some(get_tooltip_text(icon_context)),
+            None => self.tooltip_text.as_deref().map(|text| icon_context.locale.tr(text).to_string())
+        };
+        tooltip_text.map(|text| TooltipState::new(self.bounds, text, self.disabled))
     }
 }
\ No newline at end of file
@@ -1,9 +1,14 @@
 use crate::renderer::icon_renderer::IconBehavior;
-use crate::selection::Bounds;
+use crate::geometry::Rect;
+use crate::localization::cycle_locale;
+use crate::settings::{BackgroundEffect, MenubarPosition, MAX_BLUR_RADIUS, MAX_UI_SCALE, MIN_UI_SCALE};
 
-use super::icon_layout_engine::{create_icon, CrossJustify, Direction, IconLayouts, IconText, Layout, LayoutChild, ScreenLocation, ScreenRelativePosition, ICON_MARGIN, ICON_SIZE };
+use super::icon_layout_engine::{create_icon, CrossJustify, Direction, IconLayouts, IconText, Layout, LayoutChild, ScreenLocation, ScreenRelativePosition, ICON_MARGIN };
 use super::IconContext;
 
+/// How much the settings panel's arrows change the UI scale by, in percent
+const UI_SCALE_STEP: u32 = 25;
+
 pub enum IconEvent {
     Copy,
     Screenshot,
@@ -16,8 +21,61 @@ pub enum IconEvent {
     OpenOCRConfiguration,
 
     ChangeUsePolygon,
+    ChangeUseEllipse,
+
+    ChangeKeybind,
+
+    DownloadLanguage,
+    CycleModelQuality,
+
+    /// Swap an uncertain word in the preview for one of its alternatives (word index, alternative index)
+    ChooseAlternative(usize, usize),
+    /// Add an uncertain word in the preview to the personal dictionary as it was recognized (word index)
+    AddToDictionary(usize),
+    /// Apply the next output profile
+    CycleOutputProfile,
+    /// Copy an entry of the clipboard history again (index, newest first)
+    CopyHistory(usize),
+    /// Select a saved region preset (index)
+    RecallRegionPreset(usize),
+    /// Search the web for the recognized text
+    SearchWeb,
+    /// Copy a single entity (an email, URL, etc.) found in the preview
+    CopyEntity(String)
+}
+
+/// How many history entries the overlay shows, and how much of each
+const HISTORY_ROWS: usize = 5;
+const HISTORY_PREVIEW_CHARACTERS: usize = 48;
+
+/// A setting's tooltip with its current value, and its shortcut if its name ends with one like "(1)",
+/// e.g. "Maintain newlines in text: On — press 1". The name and value are translated.
+fn setting_tooltip(ctx: &IconContext, name: &str, value: &str) -> String {
+    let (name, value) = (ctx.locale.tr(name), ctx.locale.tr(value));
+    let shortcut = name.strip_suffix(')')
+        .and_then(|name| name.rsplit_once(" ("))
+        .filter(|(_, shortcut)| !shortcut.is_empty() && !shortcut.contains(' '));
+    match shortcut {
+        Some((name, shortcut)) => format!("{}: {} — {} {}", name, value, ctx.locale.tr("press"), shortcut),
+        None => format!("{}: {}", name, value)
+    }
+}
 
-    ChangeKeybind
+/// A translated label followed by a value, e.g. "Theme: Dark".
+fn labeled(ctx: &IconContext, label: &str, value: impl std::fmt::Display) -> String {
+    format!("{}: {}", ctx.locale.tr(label), value)
+}
+
+fn on_off(active: bool) -> &'static str {
+    if active { "On" } else { "Off" }
+}
+
+/// Steps through the OCR languages by wheel steps; scrolling down moves right, like the arrow icons.
+pub fn cycle_active_ocr(ctx: &mut IconContext, steps: i32) {
+    for _ in 0..steps.abs() {
+        let event = if steps < 0 { IconEvent::ActiveOCRRight } else { IconEvent::ActiveOCRLeft };
+        ctx.channel.send(event).expect("Unable to send active OCR event");
+    }
 }
 
 pub fn get_icon_layouts() -> IconLayouts {
@@ -29,7 +87,7 @@ pub fn get_icon_layouts() -> IconLayouts {
             ctx.settings.use_polygon = !ctx.settings.use_polygon;
             ctx.channel.send(IconEvent::ChangeUsePolygon).expect("Unable to send change use polygon event");
         }));
-        icon.tooltip_text = Some("Use polygons (Tab)".to_string());
+        icon.get_tooltip_text = Some(Box::new(|ctx: &IconContext| { setting_tooltip(ctx, "Use polygons (Tab)", on_off(ctx.settings.use_polygon)) }));
         icon
     });
     menubar_layout.add_icon({
@@ -39,7 +97,7 @@ pub fn get_icon_layouts() -> IconLayouts {
             ctx.settings.maintain_newline = !ctx.settings.maintain_newline;
             ctx.channel.send(IconEvent::UpdateOCRFormatOption).expect("Unable to send update OCR format option event");
         }));
-        icon.tooltip_text = Some("Maintain newlines in text (1)".to_string());
+        icon.get_tooltip_text = Some(Box::new(|ctx: &IconContext| { setting_tooltip(ctx, "Maintain newlines in text (1)", on_off(ctx.settings.maintain_newline)) }));
         icon
     });
     menubar_layout.add_icon({
@@ -49,16 +107,39 @@ pub fn get_icon_layouts() -> IconLayouts {
             ctx.settings.reformat_and_correct = !ctx.settings.reformat_and_correct;
             ctx.channel.send(IconEvent::UpdateOCRFormatOption).expect("Unable to send update OCR format option event");
         }));
-        icon.tooltip_text = Some("Reformat and correct text (2)".to_string());
+        icon.get_tooltip_text = Some(Box::new(|ctx: &IconContext| { setting_tooltip(ctx, "Reformat and correct text (2)", on_off(ctx.settings.reformat_and_correct)) }));
+        icon
+    });
+    menubar_layout.add_icon({
+        let mut icon = create_icon!("edit", IconBehavior::SettingToggle);
+        icon.get_active = Some(Box::new(|ctx: &IconContext| { ctx.settings.programmer_mode }));
+        icon.click_callback = Some(Box::new(|ctx: &mut IconContext| {
+            ctx.settings.programmer_mode = !ctx.settings.programmer_mode;
+            ctx.channel.send(IconEvent::UpdateOCRFormatOption).expect("Unable to send update OCR format option event");
+        }));
+        icon.get_tooltip_text = Some(Box::new(|ctx: &IconContext| { setting_tooltip(ctx, "Programmer mode", on_off(ctx.settings.programmer_mode)) }));
         icon
     });
     menubar_layout.add_icon({
         let mut icon = create_icon!("settings", IconBehavior::SettingToggle);
         icon.get_active = Some(Box::new(|ctx: &IconContext| { ctx.settings_panel_visible }));
-        icon.click_callback = Some(Box::new(|ctx: &mut IconContext| { ctx.settings_panel_visible = !ctx.settings_panel_visible; }));
+        icon.click_callback = Some(Box::new(|ctx: &mut IconContext| {
+            ctx.settings_panel_visible = !ctx.settings_panel_visible;
+            ctx.history_visible = false;
+        }));
         icon.tooltip_text = Some("Settings".to_string());
         icon
     });
+    menubar_layout.add_icon({
+        let mut icon = create_icon!("refresh", IconBehavior::SettingToggle);
+        icon.get_active = Some(Box::new(|ctx: &IconContext| { ctx.history_visible }));
+        icon.click_callback = Some(Box::new(|ctx: &mut IconContext| {
+            ctx.history_visible = !ctx.history_visible;
+            ctx.settings_panel_visible = false;
+        }));
+        icon.tooltip_text = Some("History (Shift + H)".to_string());
+        icon
+    });
     menubar_layout.add_icon({
         let mut icon = create_icon!("copy", IconBehavior::Click);
         icon.click_callback = Some(Box::new(|ctx| { ctx.channel.send(IconEvent::Copy).expect("Unable to send copy event"); }));
@@ -75,6 +156,13 @@ pub fn get_icon_layouts() -> IconLayouts {
         icon.get_disabled = Some(Box::new(|ctx| { !ctx.has_selection }));
         icon
     });
+    menubar_layout.add_icon({
+        let mut icon = create_icon!("open", IconBehavior::Click);
+        icon.click_callback = Some(Box::new(|ctx| { ctx.channel.send(IconEvent::SearchWeb).expect("Unable to send search web event"); }));
+        icon.tooltip_text = Some("Search the web (F)".to_string());
+        icon.get_disabled = Some(Box::new(|ctx| { !ctx.has_selection }));
+        icon
+    });
     menubar_layout.add_icon({
         let mut icon = create_icon!("close", IconBehavior::Click);
         icon.click_callback = Some(Box::new(|ctx| { ctx.channel.send(IconEvent::Close).expect("Unable to send close event"); }));
@@ -93,6 +181,7 @@ pub fn get_icon_layouts() -> IconLayouts {
                 layout.add_icon({
                     let mut icon = create_icon!($icon, IconBehavior::SettingToggle);
                     icon.get_active = Some(Box::new(|ctx: &IconContext| { ctx.settings.$setting }));
+                    icon.get_tooltip_text = Some(Box::new(|ctx: &IconContext| { setting_tooltip(ctx, $name, on_off(ctx.settings.$setting)) }));
                     icon.click_callback = Some(Box::new(|ctx: &mut IconContext| {
                         ctx.settings.$setting = !ctx.settings.$setting;
                         if $send_format_update {
@@ -106,15 +195,262 @@ pub fn get_icon_layouts() -> IconLayouts {
         };
     }
 
+    // A collapsible header for the settings after it, up to the next one
+    macro_rules! settings_section {
+        ($name:literal) => {
+            settings_layout.add_layout({
+                let mut layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
+                layout.add_text(IconText::new($name.to_string()));
+                layout.add_icon({
+                    let mut icon = create_icon!("right", IconBehavior::SettingToggle);
+                    icon.get_active = Some(Box::new(|ctx: &IconContext| { !ctx.collapsed_sections.contains($name) }));
+                    icon.click_callback = Some(Box::new(|ctx: &mut IconContext| {
+                        if !ctx.collapsed_sections.remove($name) {
+                            ctx.collapsed_sections.insert($name.to_string());
+                        }
+                    }));
+                    icon.tooltip_text = Some("Show or hide these settings".to_string());
+                    icon
+                });
+                layout.get_expanded = Some(Box::new(|ctx: &IconContext| { !ctx.collapsed_sections.contains($name) }));
+                layout
+            });
+        };
+    }
+
     settings_layout.add_text(IconText::new("Settings".to_string()));
+    settings_layout.get_filter = Some(Box::new(|ctx: &IconContext| { ctx.settings_search.clone() }));
+    settings_layout.add_layout({
+        let mut layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
+        let mut text = IconText::new_input(
+            "Search".to_string(),
+            20,
+            Box::new(|ctx: &IconContext| { ctx.settings_search.clone() }),
+            Box::new(|ctx: &mut IconContext, value: String| { ctx.settings_search = value; })
+        );
+        text.submit_on_change();
+        layout.add_text(text);
+        layout.add_icon({
+            let mut icon = create_icon!("close", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| { ctx.settings_search.clear(); }));
+            icon.get_disabled = Some(Box::new(|ctx: &IconContext| { ctx.settings_search.is_empty() }));
+            icon.tooltip_text = Some("Clear the search".to_string());
+            icon
+        });
+        layout
+    });
+
+    settings_section!("Shape and background");
     horizontal_setting_layout!("Draw polygon paths (Tab)", "hexagon", use_polygon, true);
+    settings_layout.add_layout({
+        let mut layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
+        layout.add_text(IconText::new("Draw ellipses (O)".to_string()));
+        layout.add_icon({
+            let mut icon = create_icon!("hexagon", IconBehavior::SettingToggle);
+            icon.get_active = Some(Box::new(|ctx: &IconContext| { ctx.settings.use_ellipse }));
+            icon.get_tooltip_text = Some(Box::new(|ctx: &IconContext| { setting_tooltip(ctx, "Draw ellipses (O)", on_off(ctx.settings.use_ellipse)) }));
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| {
+                ctx.settings.use_ellipse = !ctx.settings.use_ellipse;
+                ctx.channel.send(IconEvent::ChangeUseEllipse).expect("Unable to send change use ellipse event");
+            }));
+            icon
+        });
+        layout
+    });
+    settings_layout.add_layout({
+        let mut layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
+        let mut text = IconText::new("Outside the selection (3): ____".to_string()); // Plenty of characters to make the text allocate enough background tiles
+        text.get_text = Some(Box::new(|ctx: &IconContext| { labeled(ctx, "Outside the selection (3)", ctx.locale.tr(ctx.settings.background_effect.name())) }));
+        layout.add_text(text);
+        layout.add_icon({
+            let mut icon = create_icon!("blur", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| { ctx.settings.background_effect = ctx.settings.background_effect.next(); }));
+            icon.get_tooltip_text = Some(Box::new(|ctx: &IconContext| {
+                format!("{}. {}", setting_tooltip(ctx, "Outside the selection (3)", ctx.settings.background_effect.name()), ctx.locale.tr("Switches between blur, dim, and none"))
+            }));
+            icon
+        });
+        layout
+    });
+    settings_layout.add_layout({
+        let mut layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
+        layout.add_icon({
+            let mut icon = create_icon!("left", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| { ctx.settings.blur_radius = ctx.settings.blur_radius.saturating_sub(1).clamp(1, MAX_BLUR_RADIUS); }));
+            icon.get_disabled = Some(Box::new(|ctx: &IconContext| { ctx.settings.background_effect != BackgroundEffect::Blur }));
+            icon
+        });
+        layout.add_text({
+            let mut text = IconText::new("Blur radius: _".to_string()); // Plenty of characters to make the text allocate enough background tiles
+            text.get_text = Some(Box::new(|ctx: &IconContext| { labeled(ctx, "Blur radius", ctx.settings.blur_radius) }));
+            text
+        });
+        layout.add_icon({
+            let mut icon = create_icon!("right", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| { ctx.settings.blur_radius = (ctx.settings.blur_radius + 1).clamp(1, MAX_BLUR_RADIUS); }));
+            icon.get_disabled = Some(Box::new(|ctx: &IconContext| { ctx.settings.background_effect != BackgroundEffect::Blur }));
+            icon
+        });
+        layout.add_icon({
+            let mut icon = create_icon!("left", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| { ctx.settings.dim_opacity = ((ctx.settings.dim_opacity * 20.).round() - 1.).clamp(0., 20.) / 20.; }));
+            icon.get_disabled = Some(Box::new(|ctx: &IconContext| { ctx.settings.background_effect == BackgroundEffect::None }));
+            icon
+        });
+        // Steps of 5% with the arrows, or any percentage typed in
+        layout.add_text(IconText::new_input(
+            "Dim opacity".to_string(),
+            4,
+            Box::new(|ctx: &IconContext| { format!("{:.0}%", ctx.settings.dim_opacity * 100.) }),
+            Box::new(|ctx: &mut IconContext, value: String| {
+                if let Some(percent) = value.trim().trim_end_matches('%').trim().parse::<f32>().ok().filter(|percent| percent.is_finite()) {
+                    ctx.settings.dim_opacity = (percent / 100.).clamp(0., 1.);
+                }
+            })
+        ));
+        layout.add_icon({
+            let mut icon = create_icon!("right", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| { ctx.settings.dim_opacity = ((ctx.settings.dim_opacity * 20.).round() + 1.).clamp(0., 20.) / 20.; }));
+            icon.get_disabled = Some(Box::new(|ctx: &IconContext| { ctx.settings.background_effect == BackgroundEffect::None }));
+            icon
+        });
+        layout
+    });
+    settings_section!("Text and copying");
     horizontal_setting_layout!("Maintain newlines in text (1)", "new-line", maintain_newline, true);
     horizontal_setting_layout!("Reformat and correct text (2)", "fix-text", reformat_and_correct, true);
-    horizontal_setting_layout!("Background blur enabled (3)", "blur", background_blur_enabled);
     horizontal_setting_layout!("Add pilcrows to preview (4)", "return", add_pilcrow_in_preview);
     horizontal_setting_layout!("Close on copy (5)", "auto-close", close_on_copy);
     horizontal_setting_layout!("Auto copy when selecting (6)", "auto-copy", auto_copy);
+    horizontal_setting_layout!("Copy image if no text is found (7)", "screenshot", copy_image_without_text);
+    horizontal_setting_layout!("Code mode (8)", "edit", code_mode, true);
+    horizontal_setting_layout!("Join lines into paragraphs (9)", "return", smart_paragraphs, true);
+    horizontal_setting_layout!("Collapse repeated spaces", "fix-text", collapse_spaces, true);
+    horizontal_setting_layout!("Trim trailing spaces", "fix-text", trim_trailing_spaces, true);
+    horizontal_setting_layout!("Convert tabs to spaces", "fix-text", normalize_tabs, true);
+    horizontal_setting_layout!("Strip noise and watermarks", "blur", strip_noise, true);
+    horizontal_setting_layout!("Programmer mode", "edit", programmer_mode, true);
+    horizontal_setting_layout!("Also copy as RTF", "copy", copy_rtf);
+    horizontal_setting_layout!("Copy image along with text", "screenshot", copy_image_with_text);
+    horizontal_setting_layout!("Post-process with a language model", "fix-text", llm_post_processing);
+    horizontal_setting_layout!("Show and copy processed text (G)", "return", use_processed_text);
+    settings_section!("Selection guides");
+    horizontal_setting_layout!("Suggest text regions when opening", "screenshot", text_suggestions);
+    horizontal_setting_layout!("Snap to text lines (hold Alt to disable)", "new-line", snap_to_text_lines);
+    horizontal_setting_layout!("Snap to grid and screen (hold Alt to disable)", "hexagon", snap_to_grid);
+    horizontal_setting_layout!("Magnifier while drawing and resizing", "screenshot", magnifier);
+    horizontal_setting_layout!("Crosshair guides with edge distances", "hexagon", crosshair_guides);
+    horizontal_setting_layout!("Corner markers on the selection", "hexagon", border_corner_markers);
+    horizontal_setting_layout!("Marching ants selection border", "refresh", border_marching_ants);
+
+    settings_layout.add_layout({
+        let mut layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
+        let mut text = IconText::new("Selection border: _______, width _".to_string()); // Plenty of characters to make the text allocate enough background tiles
+        text.get_text = Some(Box::new(|ctx: &IconContext| {
+            let tesseract_settings = &ctx.settings.tesseract_settings;
+            let color = if tesseract_settings.selection_border_color.trim().is_empty() { ctx.locale.tr("theme") } else { tesseract_settings.selection_border_color.as_str() };
+            format!("{}: {}, {} {}", ctx.locale.tr("Selection border"), color, ctx.locale.tr("width"), tesseract_settings.selection_border_width)
+        }));
+        layout.add_text(text);
+        layout.add_icon({
+            let mut icon = create_icon!("edit", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| { ctx.settings.tesseract_settings.cycle_selection_border_color(); }));
+            icon.tooltip_text = Some("Switch to the next border color".to_string());
+            icon
+        });
+        layout.add_icon({
+            let mut icon = create_icon!("refresh", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| { ctx.settings.tesseract_settings.cycle_selection_border_width(); }));
+            icon.tooltip_text = Some("Switch to the next border width".to_string());
+            icon
+        });
+        layout
+    });
+    settings_layout.add_layout({
+        let mut layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
+        let mut text = IconText::new("Theme: _______________".to_string()); // Plenty of characters to make the text allocate enough background tiles
+        text.get_text = Some(Box::new(|ctx: &IconContext| { labeled(ctx, "Theme", ctx.locale.tr(ctx.settings.tesseract_settings.theme.name())) }));
+        layout.add_text(text);
+        layout.add_icon({
+            let mut icon = create_icon!("refresh", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| {
+                let tesseract_settings = &mut ctx.settings.tesseract_settings;
+                tesseract_settings.theme = tesseract_settings.theme.next();
+            }));
+            icon.tooltip_text = Some("Switch to the next theme".to_string());
+            icon
+        });
+        layout
+    });
+    settings_layout.add_layout({
+        let mut layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
+        layout.add_icon({
+            let mut icon = create_icon!("left", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| {
+                let tesseract_settings = &mut ctx.settings.tesseract_settings;
+                tesseract_settings.interface_language = cycle_locale(&tesseract_settings.interface_language, false);
+                ctx.reload_locale();
+            }));
+            icon
+        });
+        layout.add_text({
+            let mut text = IconText::new("Interface language: ______________".to_string()); // Plenty of characters to make the text allocate enough background tiles
+            text.get_text = Some(Box::new(|ctx: &IconContext| { labeled(ctx, "Interface language", &ctx.locale.name) }));
+            text
+        });
+        layout.add_icon({
+            let mut icon = create_icon!("right", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| {
+                let tesseract_settings = &mut ctx.settings.tesseract_settings;
+                tesseract_settings.interface_language = cycle_locale(&tesseract_settings.interface_language, true);
+                ctx.reload_locale();
+            }));
+            icon
+        });
+        layout
+    });
+    settings_layout.add_layout({
+        let mut layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
+        layout.add_text(IconText::new_input(
+            "Accent color".to_string(),
+            7,
+            Box::new(|ctx: &IconContext| { ctx.settings.tesseract_settings.accent_color.clone() }),
+            Box::new(|ctx: &mut IconContext, value: String| { ctx.settings.tesseract_settings.accent_color = value.trim().to_string(); })
+        ));
+        layout
+    });
+    settings_layout.add_layout({
+        let mut layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
+        layout.add_icon({
+            let mut icon = create_icon!("left", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| {
+                let tesseract_settings = &mut ctx.settings.tesseract_settings;
+                tesseract_settings.ui_scale = tesseract_settings.ui_scale.saturating_sub(UI_SCALE_STEP).clamp(MIN_UI_SCALE, MAX_UI_SCALE);
+            }));
+            icon.get_disabled = Some(Box::new(|ctx: &IconContext| { ctx.settings.tesseract_settings.ui_scale <= MIN_UI_SCALE }));
+            icon
+        });
+        layout.add_text({
+            let mut text = IconText::new("UI scale: ____".to_string()); // Plenty of characters to make the text allocate enough background tiles
+            text.get_text = Some(Box::new(|ctx: &IconContext| { labeled(ctx, "UI scale", format!("{}%", ctx.settings.tesseract_settings.ui_scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE))) }));
+            text
+        });
+        layout.add_icon({
+            let mut icon = create_icon!("right", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| {
+                let tesseract_settings = &mut ctx.settings.tesseract_settings;
+                tesseract_settings.ui_scale = (tesseract_settings.ui_scale + UI_SCALE_STEP).clamp(MIN_UI_SCALE, MAX_UI_SCALE);
+            }));
+            icon.get_disabled = Some(Box::new(|ctx: &IconContext| { ctx.settings.tesseract_settings.ui_scale >= MAX_UI_SCALE }));
+            icon
+        });
+        layout
+    });
+    horizontal_setting_layout!("High contrast (also on with Windows' high contrast)", "background", high_contrast);
+    horizontal_setting_layout!("Reduced motion (no fading or sliding)", "hexagon", reduced_motion);
+    horizontal_setting_layout!("Compact menubar (hover or Q to expand)", "settings", compact_menubar);
 
+    settings_section!("Recognition and presets");
     settings_layout.add_layout({
         let mut layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
         layout.add_text(IconText::new("Advanced OCR configuration".to_string()));
@@ -142,7 +478,8 @@ pub fn get_icon_layouts() -> IconLayouts {
         });
         layout.add_text({
             let mut text = IconText::new("_______________________________________________________".to_string()); // Plenty of characters to make the text allocate enough background tiles
-            text.get_text = Some(Box::new(|ctx: &IconContext| { format!("Current OCR: {}", ctx.settings.tesseract_settings.get_ocr_language_data().name) }));
+            text.get_text = Some(Box::new(|ctx: &IconContext| { labeled(ctx, "Current OCR", &ctx.settings.tesseract_settings.get_ocr_language_data().name) }));
+            text.scroll_callback = Some(Box::new(cycle_active_ocr));
             text
         });
         layout.add_icon({
@@ -153,10 +490,169 @@ pub fn get_icon_layouts() -> IconLayouts {
         layout
     });
 
+    settings_layout.add_layout({
+        let mut layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
+        let mut text = IconText::new("Model quality: ________".to_string()); // Plenty of characters to make the text allocate enough background tiles
+        text.get_text = Some(Box::new(|ctx: &IconContext| { labeled(ctx, "Model quality", ctx.locale.tr(ctx.settings.tesseract_settings.get_ocr_language_data().quality.name())) }));
+        layout.add_text(text);
+        layout.add_icon({
+            let mut icon = create_icon!("refresh", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| { ctx.channel.send(IconEvent::CycleModelQuality).expect("Unable to send cycle model quality event"); }));
+            icon.get_disabled = Some(Box::new(|ctx: &IconContext| { ctx.language_manager.is_downloading() }));
+            icon.tooltip_text = Some("Switch between standard, fast, and best models".to_string());
+            icon
+        });
+        layout
+    });
+
+    settings_layout.add_layout({
+        let mut layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
+        layout.add_icon({
+            let mut icon = create_icon!("left", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| { ctx.language_manager.select_previous(); }));
+            icon
+        });
+        layout.add_text({
+            let mut text = IconText::new("_______________________________________________________".to_string()); // Plenty of characters to make the text allocate enough background tiles
+            text.get_text = Some(Box::new(|ctx: &IconContext| { ctx.language_manager.status_text(&ctx.locale) }));
+            text
+        });
+        layout.add_icon({
+            let mut icon = create_icon!("right", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| { ctx.language_manager.select_next(); }));
+            icon
+        });
+        layout.add_icon({
+            let mut icon = create_icon!("open", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| { ctx.channel.send(IconEvent::DownloadLanguage).expect("Unable to send download language event"); }));
+            icon.get_disabled = Some(Box::new(|ctx: &IconContext| { ctx.language_manager.is_downloading() }));
+            icon.tooltip_text = Some("Download language".to_string());
+            icon
+        });
+        layout
+    });
+
+    settings_layout.add_layout({
+        let mut layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
+        layout.add_icon({
+            let mut icon = create_icon!("left", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| { ctx.settings.tesseract_settings.copy_template_decrement(); }));
+            icon
+        });
+        layout.add_text({
+            let mut text = IconText::new("_______________________________________________________".to_string()); // Plenty of characters to make the text allocate enough background tiles
+            text.get_text = Some(Box::new(|ctx: &IconContext| { labeled(ctx, "Copy template", ctx.settings.tesseract_settings.active_copy_template().replace('\n', " ").chars().take(40).collect::<String>()) }));
+            text
+        });
+        layout.add_icon({
+            let mut icon = create_icon!("right", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| { ctx.settings.tesseract_settings.copy_template_increment(); }));
+            icon
+        });
+        layout
+    });
+
+    settings_layout.add_layout({
+        let mut layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
+        layout.add_icon({
+            let mut icon = create_icon!("left", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| {
+                let count = ctx.settings.tesseract_settings.region_presets.len();
+                if count > 0 {
+                    ctx.region_preset = (ctx.region_preset.min(count - 1) + count - 1) % count;
+                }
+            }));
+            icon
+        });
+        layout.add_text({
+            let mut text = IconText::new("_______________________________________________".to_string()); // Plenty of characters to make the text allocate enough background tiles
+            text.get_text = Some(Box::new(|ctx: &IconContext| {
+                match ctx.settings.tesseract_settings.region_presets.get(ctx.region_preset) {
+                    Some(preset) if ctx.region_preset < 9 => format!("{} (Ctrl + {}): {}", ctx.locale.tr("Region preset"), ctx.region_preset + 1, preset.name.chars().take(24).collect::<String>()),
+                    Some(preset) => labeled(ctx, "Region preset", preset.name.chars().take(24).collect::<String>()),
+                    None => ctx.locale.tr("Region presets: None saved (M)").to_string()
+                }
+            }));
+            text
+        });
+        layout.add_icon({
+            let mut icon = create_icon!("right", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| {
+                let count = ctx.settings.tesseract_settings.region_presets.len();
+                if count > 0 {
+                    ctx.region_preset = (ctx.region_preset + 1) % count;
+                }
+            }));
+            icon
+        });
+        layout.add_icon({
+            let mut icon = create_icon!("return", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| { ctx.channel.send(IconEvent::RecallRegionPreset(ctx.region_preset)).expect("Unable to send recall region preset event"); }));
+            icon.get_disabled = Some(Box::new(|ctx: &IconContext| { ctx.settings.tesseract_settings.region_presets.get(ctx.region_preset).is_none() }));
+            icon.tooltip_text = Some("Select this region".to_string());
+            icon
+        });
+        layout.add_icon({
+            let mut icon = create_icon!("close", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| {
+                ctx.settings.tesseract_settings.remove_region_preset(ctx.region_preset);
+                ctx.region_preset = ctx.region_preset.min(ctx.settings.tesseract_settings.region_presets.len().saturating_sub(1));
+            }));
+            icon.get_disabled = Some(Box::new(|ctx: &IconContext| { ctx.settings.tesseract_settings.region_presets.get(ctx.region_preset).is_none() }));
+            icon.tooltip_text = Some("Delete this preset".to_string());
+            icon
+        });
+        layout
+    });
+
+    settings_section!("Output");
+    settings_layout.add_layout({
+        let mut layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
+        let mut text = IconText::new("Export mode: _______".to_string()); // Plenty of characters to make the text allocate enough background tiles
+        text.get_text = Some(Box::new(|ctx: &IconContext| { labeled(ctx, "Export mode", ctx.locale.tr(ctx.settings.tesseract_settings.export_mode.name())) }));
+        layout.add_text(text);
+        layout.add_icon({
+            let mut icon = create_icon!("refresh", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| {
+                let tesseract_settings = &mut ctx.settings.tesseract_settings;
+                tesseract_settings.export_mode = tesseract_settings.export_mode.next();
+                ctx.channel.send(IconEvent::UpdateOCRFormatOption).expect("Unable to send update OCR format option event");
+            }));
+            icon.tooltip_text = Some("Switch between text, hOCR, ALTO, TSV, CSV, JSON, and receipt output".to_string());
+            icon
+        });
+        layout
+    });
+    settings_layout.add_layout({
+        let mut layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
+        layout.add_text(IconText::new_input(
+            "Export folder".to_string(),
+            24,
+            Box::new(|ctx: &IconContext| { ctx.settings.tesseract_settings.export_directory.clone() }),
+            Box::new(|ctx: &mut IconContext, value: String| { ctx.settings.tesseract_settings.export_directory = value.trim().to_string(); })
+        ));
+        layout
+    });
+    settings_layout.add_layout({
+        let mut layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
+        let mut text = IconText::new("Output profile (P): ______________________".to_string()); // Plenty of characters to make the text allocate enough background tiles
+        text.get_text = Some(Box::new(|ctx: &IconContext| { labeled(ctx, "Output profile (P)", ctx.settings.output_profile_name().unwrap_or(ctx.locale.tr("None"))) }));
+        layout.add_text(text);
+        layout.add_icon({
+            let mut icon = create_icon!("refresh", IconBehavior::Click);
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| { ctx.channel.send(IconEvent::CycleOutputProfile).expect("Unable to send cycle output profile event"); }));
+            icon.get_disabled = Some(Box::new(|ctx: &IconContext| { ctx.settings.output_profile_name().is_none() }));
+            icon.tooltip_text = Some("Switch to the next output profile".to_string());
+            icon
+        });
+        layout
+    });
+
+    settings_section!("Hotkeys");
     settings_layout.add_layout({
         let mut layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
         let mut text = IconText::new("Keybind: ____________________________".to_string());
-        text.get_text = Some(Box::new(|ctx: &IconContext| { format!("Keybind: {}", ctx.settings.open_keybind_string) }));
+        text.get_text = Some(Box::new(|ctx: &IconContext| { labeled(ctx, "Keybind", ctx.locale.tr(&ctx.settings.open_keybind_string)) }));
         layout.add_text(text); // Plenty of characters to make the text allocate enough background tiles
         layout.add_icon({
             let mut icon = create_icon!("edit", IconBehavior::Click);
@@ -169,22 +665,84 @@ pub fn get_icon_layouts() -> IconLayouts {
         layout
     });
 
+    settings_layout.add_layout({
+        let mut layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
+        layout.add_text(IconText::new("Pause global hotkeys".to_string()));
+        layout.add_icon({
+            let mut icon = create_icon!("close", IconBehavior::SettingToggle);
+            icon.get_active = Some(Box::new(|ctx: &IconContext| { ctx.settings.hotkeys_paused.load(std::sync::atomic::Ordering::Relaxed) }));
+            icon.click_callback = Some(Box::new(|ctx: &mut IconContext| { ctx.settings.toggle_hotkeys_paused(); }));
+            icon.tooltip_text = Some("Ignore the global hotkeys until resumed here or from the tray".to_string());
+            icon
+        });
+        layout
+    });
+
+    // What the compact menubar collapses to
+    let mut menubar_pill_layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
+    menubar_pill_layout.add_icon({
+        let mut icon = create_icon!("right", IconBehavior::Click);
+        icon.click_callback = Some(Box::new(|ctx: &mut IconContext| { ctx.menubar_expanded = true; }));
+        icon.tooltip_text = Some("Show the menubar (Q)".to_string());
+        icon
+    });
+
+    let mut history_layout = Layout::new(Direction::Vertical, CrossJustify::Center, ICON_MARGIN * 1.5, false);
+    history_layout.add_text(IconText::new("History".to_string()));
+    for index in 0..HISTORY_ROWS {
+        history_layout.add_layout({
+            let mut layout = Layout::new(Direction::Horizontal, CrossJustify::Center, ICON_MARGIN, true);
+            layout.add_text({
+                let mut text = IconText::new("_".repeat(HISTORY_PREVIEW_CHARACTERS)); // Plenty of characters to make the text allocate enough background tiles
+                text.get_text = Some(Box::new(move |ctx: &IconContext| { ctx.clipboard_history.preview(index, HISTORY_PREVIEW_CHARACTERS) }));
+                text
+            });
+            layout.add_icon({
+                let mut icon = create_icon!("copy", IconBehavior::Click);
+                icon.click_callback = Some(Box::new(move |ctx: &mut IconContext| { ctx.channel.send(IconEvent::CopyHistory(index)).expect("Unable to send copy history event"); }));
+                icon.get_disabled = Some(Box::new(move |ctx: &IconContext| { ctx.clipboard_history.get(index).is_none() }));
+                icon.tooltip_text = Some("Copy again".to_string());
+                icon
+            });
+            layout
+        });
+    }
+
     let mut icon_layouts = IconLayouts::new();
     icon_layouts.add_layout(
         String::from("copy"),
         ScreenRelativePosition::new(ScreenLocation::TopLeft, (0., 0.)), // Updated live
         {
             let mut icon = create_icon!("copy", IconBehavior::Click);
-            icon.bounds = Bounds::new(0, 0, 25, 25);
+            icon.bounds = Rect::new(0., 0., 25., 25.);
             icon.click_callback = Some(Box::new(|ctx| { ctx.channel.send(IconEvent::Copy).expect("Unable to send copy event"); }));
             icon.get_active = Some(Box::new(|ctx| { ctx.copy_key_held }));
             LayoutChild::Icon(icon)
         }
     );
-    icon_layouts.add_layout(String::from("menubar"), ScreenRelativePosition::new(ScreenLocation::TopCenter, (0., ICON_SIZE / 2. + ICON_MARGIN)), LayoutChild::Layout(menubar_layout));
-    icon_layouts.add_layout(String::from("settings"), ScreenRelativePosition::new(ScreenLocation::TopCenter, (0., ICON_SIZE * 9. + ICON_MARGIN * 2.)), LayoutChild::Layout(settings_layout));
+    icon_layouts.add_layout(String::from("menubar"), menubar_position(MenubarPosition::default()), LayoutChild::Layout(menubar_layout)); // Updated live
+    icon_layouts.add_layout(String::from("menubar-pill"), menubar_position(MenubarPosition::default()), LayoutChild::Layout(menubar_pill_layout)); // Updated live
+    icon_layouts.add_layout(String::from("settings"), panel_position(MenubarPosition::default(), (0., 0.)), LayoutChild::Layout(settings_layout)); // Updated live
+    icon_layouts.add_layout(String::from("history"), panel_position(MenubarPosition::default(), (0., 0.)), LayoutChild::Layout(history_layout)); // Updated live
 
     icon_layouts.initialize();
 
     return icon_layouts;
+}
+
+pub fn menubar_position(position: MenubarPosition) -> ScreenRelativePosition {
+    ScreenRelativePosition::inside(position.into(), ICON_MARGIN)
+}
+
+/// Where the panels opened from the menubar go: against the same edge or corner, just past the menubar.
+pub fn panel_position(position: MenubarPosition, menubar_size: (f32, f32)) -> ScreenRelativePosition {
+    let location: ScreenLocation = position.into();
+    let (direction_x, direction_y) = location.inward_direction();
+    // Panels open below or above menubars at the top or bottom, and beside ones in the middle of a side
+    let offset = if direction_y != 0. {
+        (0., direction_y * (menubar_size.1 + ICON_MARGIN))
+    } else {
+        (direction_x * (menubar_size.0 + ICON_MARGIN), 0.)
+    };
+    ScreenRelativePosition::inside(location, ICON_MARGIN).offset_by(offset)
 }
\ No newline at end of file
@@ -1,9 +1,13 @@
 use std::collections::HashMap;
 
-use super::super::{animation::MoveDirection, animation::SmoothMoveFadeAnimation, icon_renderer::*, Bounds};
+use super::super::{animation::MoveDirection, animation::SmoothMoveFadeAnimation, icon_renderer::*};
+use crate::geometry::Rect;
+use crate::settings::MenubarPosition;
 
 use glyph_brush::OwnedSection;
 use glyph_brush::OwnedText;
+use winit::event::ElementState;
+use winit::keyboard::{Key, NamedKey};
 
 pub const ICON_SIZE: f32 = 40.0;
 pub const ICON_MARGIN: f32 = 10.0;
@@ -30,13 +34,14 @@ macro_rules! create_icon {
                 active: false,
                 disabled: false,
 
-                bounds: Bounds::new(0, 0, ICON_SIZE, ICON_SIZE),
+                bounds: crate::geometry::Rect::new(0., 0., ICON_SIZE, ICON_SIZE),
                 behavior: $behavior,
                 click_callback: None,
                 get_active: None,
                 get_disabled: None,
 
                 tooltip_text: None,
+                get_tooltip_text: None,
 
                 visible: true,
                 anim: SmoothMoveFadeAnimation::new(true, MoveDirection::Up, 10.0),
@@ -62,13 +67,14 @@ macro_rules! create_background {
                 active: false,
                 disabled: false,
 
-                bounds: Bounds::from_center($bounds.0, $bounds.1, ICON_SIZE + ICON_MARGIN, ICON_SIZE + ICON_MARGIN),
+                bounds: crate::geometry::Rect::from_center($bounds.0, $bounds.1, ICON_SIZE + ICON_MARGIN, ICON_SIZE + ICON_MARGIN),
                 behavior: IconBehavior::Visual,
                 click_callback: None,
                 get_active: None,
                 get_disabled: None,
 
                 tooltip_text: None,
+                get_tooltip_text: None,
 
                 visible: true,
                 anim: SmoothMoveFadeAnimation::new(true, MoveDirection::Up, 10.0),
@@ -101,6 +107,15 @@ impl IconLayouts {
         self.layouts.get_mut(label).unwrap().set_offset(x, y);
     }
 
+    pub fn set_position(&mut self, label: &str, center_position: ScreenRelativePosition) {
+        self.layouts.get_mut(label).unwrap().center_position = center_position;
+    }
+
+    /// Where a layout is on screen, as of the last time positions were calculated.
+    pub fn bounds(&self, label: &str) -> Rect {
+        self.layouts[label].bounds()
+    }
+
     pub fn set_visible(&mut self, label: &str, visible: bool) {
         self.layouts.get_mut(label).unwrap().set_visible(visible);
     }
@@ -117,6 +132,10 @@ impl IconLayouts {
         self.layouts.iter().flat_map(|(_, sub_layout)| sub_layout.text_sections()).collect()
     }
 
+    pub fn text_mut(&mut self) -> Vec<&mut IconText> {
+        self.layouts.iter_mut().flat_map(|(_, sub_layout)| sub_layout.text_mut()).collect()
+    }
+
     pub fn recalculate_positions(&mut self, screen_size: (f32, f32)) -> () {
         for (_, sub_layout) in self.layouts.iter_mut() {
             sub_layout.recalculate_positions(screen_size, false);
@@ -195,7 +214,12 @@ impl PositionedLayout {
     pub fn recalculate_positions(&mut self, screen_size: (f32, f32), force: bool) -> () {
         self.last_screen_size = screen_size;
 
-        self.calculated_center_position = self.center_position.get_position(screen_size);
+        let size = match &mut self.layout {
+            LayoutChild::Icon(icon) => (icon.bounds.width, icon.bounds.height),
+            LayoutChild::Text(text) => (text.bounds.width, text.bounds.height),
+            LayoutChild::Layout(layout) => layout.calculate_size()
+        };
+        self.calculated_center_position = self.center_position.get_position(screen_size, size);
         if !force {
             if Some(self.calculated_center_position) == self.last_center_position {
                 return;
@@ -230,6 +254,15 @@ impl PositionedLayout {
         self.center_position.offset = (x, y);
     }
 
+    pub fn bounds(&self) -> Rect {
+        let (width, height) = match &self.layout {
+            LayoutChild::Icon(icon) => (icon.bounds.width, icon.bounds.height),
+            LayoutChild::Text(text) => (text.bounds.width, text.bounds.height),
+            LayoutChild::Layout(layout) => layout.calculated_size
+        };
+        Rect::from_center(self.calculated_center_position.0, self.calculated_center_position.1, width, height)
+    }
+
     pub fn set_visible(&mut self, visible: bool) {
         match &mut self.layout {
             LayoutChild::Icon(icon) => icon.visible = visible,
@@ -249,6 +282,9 @@ impl PositionedLayout {
         
         let mut any_text_changed = false;
         self.text_mut().into_iter().for_each(|text| { any_text_changed = text.update(delta, icon_context) || any_text_changed; });
+        if let LayoutChild::Layout(layout) = &mut self.layout {
+            any_text_changed = layout.update_hidden(icon_context) || any_text_changed;
+        }
         if any_text_changed {
             self.recalculate_positions(self.last_screen_size, true);
         }
@@ -280,16 +316,64 @@ pub(crate) struct Layout {
     background_children: Vec<Icon>,
 
     calculated_position: (f32, f32),
-    calculated_size: (f32, f32)
+    calculated_size: (f32, f32),
+
+    /// Whether the layout is left out of its parent, because its section is collapsed or it's filtered out
+    hidden: bool,
+    /// Makes this layout a section header; the layouts after it in its parent, up to the next header,
+    /// are only shown while this returns true
+    pub get_expanded: Option<Box<dyn Fn(&IconContext) -> bool>>,
+    /// While this returns text, only child layouts containing it (ignoring case) are shown, along with
+    /// every layout in a section whose header contains it. Layouts before the first header are never hidden.
+    pub get_filter: Option<Box<dyn Fn(&IconContext) -> String>>
 }
 
 pub(crate) struct IconText {
-    bounds: Bounds,
+    bounds: Rect,
     text_section: OwnedSection,
+    /// The text it was created with, which is shown translated unless `get_text` or an input replaces it
+    source: String,
     visible: bool,
     anim: SmoothMoveFadeAnimation,
 
-    pub get_text: Option<Box<dyn Fn(&IconContext) -> String>>
+    pub get_text: Option<Box<dyn Fn(&IconContext) -> String>>,
+    /// Called with the number of whole wheel steps scrolled over the text; positive is away from the user
+    pub scroll_callback: Option<Box<dyn Fn(&mut IconContext, i32)>>,
+    input: Option<TextInput>
+}
+
+/// The editing state of text that can be clicked to type into
+struct TextInput {
+    label: String,
+    value: String,
+    /// Where the caret is in the value, in characters
+    caret: usize,
+    focused: bool,
+    /// Whether every edit is submitted right away, rather than only on Enter
+    submit_on_change: bool,
+    get_value: Box<dyn Fn(&IconContext) -> String>,
+    submit_callback: Box<dyn Fn(&mut IconContext, String)>
+}
+
+impl TextInput {
+    fn display_text(&self, context: &IconContext) -> String {
+        let label = context.locale.tr(&self.label);
+        if !self.focused {
+            return format!("{}: {}", label, (self.get_value)(context));
+        }
+        let mut value = self.value.clone();
+        value.insert(self.caret_byte_index(), '|');
+        format!("{}: {}", label, value)
+    }
+
+    fn caret_byte_index(&self) -> usize {
+        self.value.char_indices().nth(self.caret).map_or(self.value.len(), |(index, _)| index)
+    }
+
+    fn insert(&mut self, text: &str) {
+        self.value.insert_str(self.caret_byte_index(), text);
+        self.caret += text.chars().count();
+    }
 }
 
 fn approximate_text_size(string: &String) -> (f32, f32) {
@@ -300,33 +384,152 @@ fn approximate_text_size(string: &String) -> (f32, f32) {
 impl IconText {
     pub fn new(string: String) -> Self {
         let size = approximate_text_size(&string);
-        let bounds = Bounds::new(0, 0, size.0, size.1);
+        let bounds = Rect::new(0., 0., size.0, size.1);
         IconText {
             bounds,
             text_section: OwnedSection {
                 screen_position: (0.0, 0.0),
                 bounds: (f32::INFINITY, f32::INFINITY),
                 layout: glyph_brush::Layout::default(),
-                text: vec![OwnedText::new(string).with_scale(20.0).with_color([1.0, 1.0, 1.0, 1.0])],
+                text: vec![OwnedText::new(string.clone()).with_scale(20.0).with_color([1.0, 1.0, 1.0, 1.0])],
             },
+            source: string,
             visible: true,
             anim: SmoothMoveFadeAnimation::new(true, MoveDirection::Up, 10.0),
-            get_text: None
+            get_text: None,
+            scroll_callback: None,
+            input: None
+        }
+    }
+
+    /// Creates text showing `label: ` followed by a value that can be clicked to edit it inline. Enter passes
+    /// the edited value to `submit_callback`, while Escape or clicking elsewhere discards it. The placeholder
+    /// length is how many characters of value the background is sized for. The label is translated.
+    pub fn new_input(
+        label: String,
+        placeholder_length: usize,
+        get_value: Box<dyn Fn(&IconContext) -> String>,
+        submit_callback: Box<dyn Fn(&mut IconContext, String)>
+    ) -> Self {
+        let mut text = IconText::new(format!("{}: {}", label, "_".repeat(placeholder_length)));
+        text.input = Some(TextInput {
+            label,
+            value: String::new(),
+            caret: 0,
+            focused: false,
+            submit_on_change: false,
+            get_value,
+            submit_callback
+        });
+        text
+    }
+
+    /// Submits the value after every edit, e.g. to filter as it's typed.
+    pub fn submit_on_change(&mut self) {
+        if let Some(input) = &mut self.input {
+            input.submit_on_change = true;
+        }
+    }
+
+    /// Focuses the text input when it's clicked, and unfocuses it when anything else is. Returns whether
+    /// the click was on the input.
+    pub fn mouse_event(&mut self, mouse_pos: (i32, i32), state: ElementState, context: &mut IconContext) -> bool {
+        let hit = self.visible && self.bounds.contains(mouse_pos);
+        let Some(input) = &mut self.input else {
+            return false;
+        };
+
+        if state == ElementState::Pressed {
+            if hit && !input.focused {
+                input.value = (input.get_value)(context);
+                input.caret = input.value.chars().count();
+            }
+            input.focused = hit;
+        }
+        hit
+    }
+
+    /// Passes wheel steps to the scroll callback if the text is under the cursor, returning whether it used them.
+    pub fn scroll_event(&mut self, mouse_pos: (i32, i32), steps: i32, context: &mut IconContext) -> bool {
+        let Some(scroll_callback) = &self.scroll_callback else {
+            return false;
+        };
+        if !self.visible || !self.bounds.contains(mouse_pos) {
+            return false;
+        }
+        if steps != 0 {
+            scroll_callback(context, steps);
+        }
+        true
+    }
+
+    /// Types into the text input if it's focused, returning whether the key was used.
+    pub fn keyboard_event(&mut self, key: &Key, state: ElementState, context: &mut IconContext) -> bool {
+        let Some(input) = self.input.as_mut().filter(|input| input.focused) else {
+            return false;
+        };
+        if state != ElementState::Pressed {
+            return true;
+        }
+
+        let length = input.value.chars().count();
+        match key.as_ref() {
+            Key::Named(NamedKey::Enter) => {
+                input.focused = false;
+                (input.submit_callback)(context, input.value.clone());
+            }
+            Key::Named(NamedKey::Escape) => input.focused = false,
+            Key::Named(NamedKey::Backspace) => {
+                if input.caret > 0 {
+                    input.caret -= 1;
+                    let index = input.caret_byte_index();
+                    input.value.remove(index);
+                }
+            }
+            Key::Named(NamedKey::Delete) => {
+                if input.caret < length {
+                    let index = input.caret_byte_index();
+                    input.value.remove(index);
+                }
+            }
+            Key::Named(NamedKey::ArrowLeft) => input.caret = input.caret.saturating_sub(1),
+            Key::Named(NamedKey::ArrowRight) => input.caret = (input.caret + 1).min(length),
+            Key::Named(NamedKey::Home) => input.caret = 0,
+            Key::Named(NamedKey::End) => input.caret = length,
+            Key::Named(NamedKey::Space) => input.insert(" "),
+            Key::Character(text) => input.insert(text),
+            _ => return true
+        }
+        if input.submit_on_change && input.focused {
+            (input.submit_callback)(context, input.value.clone());
+        }
+        true
+    }
+
+    pub fn unfocus(&mut self) {
+        if let Some(input) = &mut self.input {
+            input.focused = false;
         }
     }
 
     pub fn update_section_position(&mut self) {
-        self.text_section.screen_position = self.anim.move_point((self.bounds.x as f32 + ICON_MARGIN, self.bounds.y as f32));
+        self.text_section.screen_position = self.anim.move_point((self.bounds.x + ICON_MARGIN, self.bounds.y));
     }
 
     pub fn update(&mut self, delta: std::time::Duration, context: &IconContext) -> bool {
         self.anim.update(delta, self.visible);
         self.update_section_position();
+        let [red, green, blue] = context.theme_colors().text;
+        self.text_section.text[0].extra.color = [red, green, blue, 1.0];
 
-        if let Some(get_text) = &self.get_text {
-            let current_string = self.text_section.text[0].text.clone();
-            let new_string = get_text(context);
-            if current_string != new_string {
+        let new_string = match (&self.input, &self.get_text) {
+            (Some(input), _) => Some(input.display_text(context)),
+            (None, Some(get_text)) => Some(get_text(context)),
+            (None, None) => Some(context.locale.tr(&self.source).to_string())
+        };
+        if let Some(new_string) = new_string {
+            let current_string = &self.text_section.text[0].text;
+            if *current_string != new_string {
                 let size = approximate_text_size(&new_string);
                 self.text_section.text[0].text = new_string;
                 self.bounds.set_size(size.0, size.1);
@@ -353,10 +556,80 @@ impl Layout {
             has_background,
             background_children: Vec::new(),
             calculated_position: (0.0, 0.0),
-            calculated_size: (0.0, 0.0)
+            calculated_size: (0.0, 0.0),
+            hidden: false,
+            get_expanded: None,
+            get_filter: None
         }
     }
 
+    /// All of the text in the layout, as it's currently shown.
+    fn text(&self) -> String {
+        self.children.iter().map(|child| match child {
+            LayoutChild::Text(text) => text.text_section.text[0].text.clone(),
+            LayoutChild::Layout(layout) => layout.text(),
+            _ => String::new()
+        }).collect::<Vec<_>>().join(" ")
+    }
+
+    /// Decides which child layouts are hidden by collapsed sections and the filter, returning whether any changed.
+    pub fn update_hidden(&mut self, context: &IconContext) -> bool {
+        let filter = self.get_filter.as_ref().map(|get_filter| get_filter(context).trim().to_lowercase()).unwrap_or_default();
+        let matches = |layout: &Layout| filter.is_empty() || layout.text().to_lowercase().contains(&filter);
+
+        // Each layout's section header, if it has one, and whether that header matches the filter
+        let mut sections: Vec<Option<(bool, bool)>> = Vec::new();
+        let mut current_section: Option<(bool, bool)> = None;
+        for child in self.children.iter() {
+            if let LayoutChild::Layout(layout) = child {
+                if let Some(get_expanded) = &layout.get_expanded {
+                    current_section = Some((get_expanded(context), matches(layout)));
+                    sections.push(None);
+                    continue;
+                }
+            }
+            sections.push(current_section);
+        }
+
+        let row_visible = |layout: &Layout, section: Option<(bool, bool)>| match section {
+            None => true,
+            Some((expanded, _)) if filter.is_empty() => expanded,
+            Some((_, header_matches)) => header_matches || matches(layout)
+        };
+        let mut changed = false;
+        let mut visible_rows = vec![false; self.children.len()];
+        for (index, child) in self.children.iter_mut().enumerate() {
+            if let LayoutChild::Layout(layout) = child {
+                changed = layout.update_hidden(context) || changed;
+                visible_rows[index] = layout.get_expanded.is_none() && row_visible(layout, sections[index]);
+            }
+        }
+
+        // Headers stay visible while any of their section is, or always without a filter
+        let mut header_index = None;
+        let mut header_visible = vec![filter.is_empty(); self.children.len()];
+        for (index, child) in self.children.iter().enumerate() {
+            match child {
+                LayoutChild::Layout(layout) if layout.get_expanded.is_some() => {
+                    header_index = Some(index);
+                    header_visible[index] = header_visible[index] || matches(layout);
+                }
+                _ => if let Some(header_index) = header_index {
+                    header_visible[header_index] = header_visible[header_index] || visible_rows[index];
+                }
+            }
+        }
+
+        for (index, child) in self.children.iter_mut().enumerate() {
+            if let LayoutChild::Layout(layout) = child {
+                let visible = if layout.get_expanded.is_some() { header_visible[index] } else { visible_rows[index] };
+                changed = changed || layout.hidden == visible;
+                layout.hidden = !visible;
+            }
+        }
+        changed
+    }
+
     pub fn initialize(&mut self) {
         self.calculate_size();
         let primary_dimension = match self.direction {
@@ -366,7 +639,7 @@ impl Layout {
         // There is 1 background child for every (ICON_SIZE * 0.9) length
         let background_icons_required = if self.has_background { (primary_dimension / (ICON_SIZE * 0.9) + 0.2).floor() as u32 } else { 0 };
         for _ in 0..background_icons_required {
-            self.background_children.push(create_background!((0, 0)));
+            self.background_children.push(create_background!((0., 0.)));
         }
 
         for child in self.children.iter_mut() {
@@ -430,15 +703,16 @@ impl Layout {
                 LayoutChild::Icon(Icon { bounds, .. }) | LayoutChild::Text(IconText { bounds, .. }) => {
                     match self.direction {
                         Direction::Horizontal => {
-                            width += bounds.width as f32 + self.spacing;
-                            height = height.max(bounds.height as f32);
+                            width += bounds.width + self.spacing;
+                            height = height.max(bounds.height);
                         }
                         Direction::Vertical => {
-                            width = width.max(bounds.width as f32);
-                            height += bounds.height as f32 + self.spacing;
+                            width = width.max(bounds.width);
+                            height += bounds.height + self.spacing;
                         }
                     }
                 }
+                LayoutChild::Layout(layout) if layout.hidden => {}
                 LayoutChild::Layout(layout) => {
                     let (child_width, child_height) = layout.calculate_size();
                     match self.direction {
@@ -481,13 +755,13 @@ impl Layout {
                                     icon.bounds.set_origin(top_left_position.0, top_left_position.1);
                                 }
                                 CrossJustify::Center => {
-                                    icon.bounds.set_center(top_left_position.0 + icon.bounds.width as f32 / 2., top_left_position.1 + icon.bounds.height as f32 / 2.);
+                                    icon.bounds.set_center(top_left_position.0 + icon.bounds.width / 2., top_left_position.1 + icon.bounds.height / 2.);
                                 }
                                 CrossJustify::End => {
-                                    icon.bounds.set_origin(top_left_position.0, top_left_position.1 + self.calculated_size.1 - icon.bounds.height as f32);
+                                    icon.bounds.set_origin(top_left_position.0, top_left_position.1 + self.calculated_size.1 - icon.bounds.height);
                                 }
                             }
-                            top_left_position.0 += icon.bounds.width as f32 + self.spacing;
+                            top_left_position.0 += icon.bounds.width + self.spacing;
                         }
                         Direction::Vertical => {
                             match self.cross_justify {
@@ -495,16 +769,17 @@ impl Layout {
                                     icon.bounds.set_origin(top_left_position.0, top_left_position.1);
                                 }
                                 CrossJustify::Center => {
-                                    icon.bounds.set_center(top_left_position.0 + icon.bounds.width as f32 / 2., top_left_position.1 + icon.bounds.height as f32 / 2.);
+                                    icon.bounds.set_center(top_left_position.0 + icon.bounds.width / 2., top_left_position.1 + icon.bounds.height / 2.);
                                 }
                                 CrossJustify::End => {
-                                    icon.bounds.set_origin(top_left_position.0 + self.calculated_size.0 - icon.bounds.width as f32, top_left_position.1);
+                                    icon.bounds.set_origin(top_left_position.0 + self.calculated_size.0 - icon.bounds.width, top_left_position.1);
                                 }
                             }
-                            top_left_position.1 += icon.bounds.height as f32 + self.spacing;
+                            top_left_position.1 += icon.bounds.height + self.spacing;
                         }
                     }
                 }
+                LayoutChild::Layout(layout) if layout.hidden => {}
                 LayoutChild::Layout(layout) => {
                     match self.cross_justify {
                         CrossJustify::Start => {
@@ -530,12 +805,12 @@ impl Layout {
                 LayoutChild::Text(text) => {
                     match self.direction {
                         Direction::Horizontal => {
-                            text.bounds.set_origin(top_left_position.0, top_left_position.1 + (self.calculated_size.1 - text.bounds.height as f32) / 2.);
-                            top_left_position.0 += text.bounds.width as f32 + self.spacing;
+                            text.bounds.set_origin(top_left_position.0, top_left_position.1 + (self.calculated_size.1 - text.bounds.height) / 2.);
+                            top_left_position.0 += text.bounds.width + self.spacing;
                         }
                         Direction::Vertical => {
-                            text.bounds.set_origin(top_left_position.0 + (self.calculated_size.0 - text.bounds.width as f32) / 2., top_left_position.1);
-                            top_left_position.1 += text.bounds.height as f32 + self.spacing;
+                            text.bounds.set_origin(top_left_position.0 + (self.calculated_size.0 - text.bounds.width) / 2., top_left_position.1);
+                            top_left_position.1 += text.bounds.height + self.spacing;
                         }
                     }
                     text.update_section_position();
@@ -567,7 +842,7 @@ impl Layout {
         for child in self.children.iter_mut() {
             match child {
                 LayoutChild::Icon(icon) => icon.visible = visible,
-                LayoutChild::Layout(layout) => layout.set_visible(visible),
+                LayoutChild::Layout(layout) => layout.set_visible(visible && !layout.hidden),
                 LayoutChild::Text(text) => text.visible = visible
             }
         }
@@ -604,23 +879,78 @@ impl ScreenLocation {
             ScreenLocation::BottomRight => (screen_size.0, screen_size.1)
         }
     }
+
+    /// Which way is into the screen from this location, e.g. (1, 1) for the top left.
+    pub fn inward_direction(self: &ScreenLocation) -> (f32, f32) {
+        match self {
+            ScreenLocation::TopLeft => (1., 1.),
+            ScreenLocation::TopCenter => (0., 1.),
+            ScreenLocation::TopRight => (-1., 1.),
+            ScreenLocation::CenterLeft => (1., 0.),
+            ScreenLocation::Center => (0., 0.),
+            ScreenLocation::CenterRight => (-1., 0.),
+            ScreenLocation::BottomLeft => (1., -1.),
+            ScreenLocation::BottomCenter => (0., -1.),
+            ScreenLocation::BottomRight => (-1., -1.)
+        }
+    }
+}
+
+impl From<MenubarPosition> for ScreenLocation {
+    fn from(position: MenubarPosition) -> Self {
+        match position {
+            MenubarPosition::TopLeft => ScreenLocation::TopLeft,
+            MenubarPosition::TopCenter => ScreenLocation::TopCenter,
+            MenubarPosition::TopRight => ScreenLocation::TopRight,
+            MenubarPosition::CenterLeft => ScreenLocation::CenterLeft,
+            MenubarPosition::CenterRight => ScreenLocation::CenterRight,
+            MenubarPosition::BottomLeft => ScreenLocation::BottomLeft,
+            MenubarPosition::BottomCenter => ScreenLocation::BottomCenter,
+            MenubarPosition::BottomRight => ScreenLocation::BottomRight
+        }
+    }
 }
 
 pub(crate) struct ScreenRelativePosition {
     relative_to: ScreenLocation,
-    offset: (f32, f32)
+    offset: (f32, f32),
+    /// If set, the layout is kept this far inside the screen's edges at its location, whatever its size
+    margin_inside: Option<f32>
 }
 
 impl ScreenRelativePosition {
     pub fn new(relative_to: ScreenLocation, offset: (f32, f32)) -> Self {
         ScreenRelativePosition {
             relative_to,
-            offset
+            offset,
+            margin_inside: None
         }
     }
 
-    pub fn get_position(self: &ScreenRelativePosition, screen_size: (f32, f32)) -> (f32, f32) {
+    /// A position against the screen's edges at `relative_to`, `margin` away from them.
+    pub fn inside(relative_to: ScreenLocation, margin: f32) -> Self {
+        ScreenRelativePosition {
+            relative_to,
+            offset: (0., 0.),
+            margin_inside: Some(margin)
+        }
+    }
+
+    /// Moves the position by `offset` on top of where it would otherwise be.
+    pub fn offset_by(mut self, offset: (f32, f32)) -> Self {
+        self.offset = (self.offset.0 + offset.0, self.offset.1 + offset.1);
+        self
+    }
+
+    pub fn get_position(self: &ScreenRelativePosition, screen_size: (f32, f32), size: (f32, f32)) -> (f32, f32) {
         let (x, y) = self.relative_to.get_on_screen_size(screen_size);
-        (x + self.offset.0, y + self.offset.1)
+        let (inset_x, inset_y) = match self.margin_inside {
+            Some(margin) => {
+                let (direction_x, direction_y) = self.relative_to.inward_direction();
+                (direction_x * (size.0 / 2. + margin), direction_y * (size.1 / 2. + margin))
+            }
+            None => (0., 0.)
+        };
+        (x + self.offset.0 + inset_x, y + self.offset.1 + inset_y)
     }
 }
\ No newline at end of file
@@ -1,26 +1,81 @@
-use std::time::Instant;
+use std::{ops::Range, time::{Duration, Instant}};
 
-use glyph_brush::{ab_glyph::FontRef, BuiltInLineBreaker, HorizontalAlign, OwnedSection, OwnedText};
+use glyph_brush::{ab_glyph::{Font, FontArc, ScaleFont}, BuiltInLineBreaker, FontId, HorizontalAlign, OwnedSection, OwnedText, SectionGlyph};
 use pixels::{wgpu, PixelsContext};
+use winit::event::ElementState;
 
-use crate::{selection::Bounds, wgpu_text::{BrushBuilder, TextBrush}};
+use crate::{annotations::{locate_word_boxes, WordBox}, entities::{extract_entities, Entity}, geometry::{Point, Rect}, localization::Locale, word_choices::UncertainWord, wgpu_text::{BrushBuilder, TextBrush}};
 
-use super::{animation::{MoveDirection, SmoothMoveFadeAnimation}, icon_renderer::{TEXT_HEIGHT, IconRenderer}, IconContext};
+use super::{animation::{MoveDirection, SmoothMoveFadeAnimation}, fallback_fonts::FallbackFonts, icon_renderer::{TEXT_HEIGHT, IconRenderer}, IconContext, IconEvent};
+
+/// The font used for the preview in code mode; the default font is `FontId(0)`
+const MONOSPACE_FONT_ID: FontId = FontId(1);
+const UNCERTAIN_WORD_COLOR: [f32; 3] = [0.95, 0.75, 0.4];
+const ENTITY_CHIP_COLOR: [f32; 3] = [0.55, 0.75, 0.95];
+/// How far the solid backing extends past the preview text in high-contrast mode
+const TEXT_BACKING_PADDING: f32 = 8.;
+/// How long a word and its box on the screenshot stay highlighted after either is clicked
+const WORD_FLASH_DURATION: Duration = Duration::from_millis(1500);
 
 pub(crate) struct OCRPreviewRenderer {
     anim: SmoothMoveFadeAnimation,
     last_text: Option<String>,
+    /// The recognized text before tabs were widened and pilcrows added for the preview, along with the
+    /// character of it each character of the preview text came from, if any
+    source_text: Option<(Vec<char>, Vec<Option<usize>>)>,
     last_placement: Option<PreviewTextPlacement>,
 
-    text_brush: TextBrush<FontRef<'static>>,
+    text_brush: TextBrush<FontArc>,
     should_render_text: bool,
+    /// The fonts the preview text falls back to for scripts and emoji the default fonts don't cover
+    fallback_fonts: FallbackFonts,
 
     active_feedback_text: Option<String>,
     active_feedback_color: [f32; 3],
     feedback_text_anim: SmoothMoveFadeAnimation,
     current_feedback_start_time: Instant,
 
-    feedback_text_queue: Vec<(String, [f32; 3])>
+    feedback_text_queue: Vec<(String, [f32; 3])>,
+
+    size_warning_anim: SmoothMoveFadeAnimation,
+    last_size_warning: String,
+
+    progress_anim: SmoothMoveFadeAnimation,
+    last_progress: u8,
+
+    /// The uncertain word each text of the preview section shows, if any
+    word_sections: Vec<Option<usize>>,
+    /// Where in the preview text, in characters, each text of the preview section starts; none for the heading
+    text_offsets: Vec<Option<usize>>,
+    /// The character offset in the preview text and bounds of each glyph of the preview, for selecting part of it
+    preview_glyphs: Vec<(usize, Rect)>,
+    /// Where the selected part of the preview text starts and ends, in characters, in the order it was dragged
+    text_selection: Option<(usize, usize)>,
+    /// Whether the text selection is being dragged out
+    selecting_text: bool,
+    /// Where each of Tesseract's word boxes is in the preview text, in characters, and on the screenshot
+    word_boxes: Vec<(Range<usize>, Rect)>,
+    /// The word highlighted in the preview and on the screenshot, and when it was clicked
+    word_flash: Option<(Range<usize>, Rect, Instant)>,
+    /// The index, start, and bounds of each uncertain word on screen
+    word_hitboxes: Vec<(usize, usize, Rect)>,
+    /// The word whose alternatives are shown, along with where the word starts so the picker closes if the text changes
+    open_picker: Option<(usize, usize)>,
+    alternative_hitboxes: Vec<Rect>,
+    /// Where the preview text is, padded, so it can be drawn on a solid backing in high-contrast mode
+    text_backing: Option<Rect>,
+
+    window_size: (u32, u32),
+    /// How much larger than normal the text is drawn. Everything is laid out in the window shrunk by this
+    /// much, then stretched back over it.
+    scale: f32,
+
+    /// The text entities were last extracted from, so they're only extracted again when it changes
+    entities_text: Option<String>,
+    entities: Vec<Entity>,
+    /// The entity each text of the chip section shows, if any
+    entity_sections: Vec<Option<usize>>,
+    entity_hitboxes: Vec<(usize, Rect)>
 }
 
 #[derive(Debug, Clone)]
@@ -41,8 +96,12 @@ impl OCRPreviewRenderer {
         Self {
             anim: SmoothMoveFadeAnimation::new(false, MoveDirection::Right, 6.),
             last_text: None,
+            source_text: None,
             last_placement: None,
-            text_brush: BrushBuilder::using_font_bytes(include_bytes!("../../fonts/DejaVuSans.ttf")).expect("Unable to load font")
+            text_brush: BrushBuilder::using_fonts(vec![
+                    FontArc::try_from_slice(include_bytes!("../../fonts/DejaVuSans.ttf")).expect("Unable to load font"),
+                    FontArc::try_from_slice(include_bytes!("../../fonts/DejaVuSansMono.ttf")).expect("Unable to load monospace font")
+                ])
                 .build(
                     device,
                     width,
@@ -50,12 +109,39 @@ impl OCRPreviewRenderer {
                     pixels.render_texture_format()
                 ),
             should_render_text: false,
+            fallback_fonts: FallbackFonts::new(),
 
             active_feedback_text: None,
             active_feedback_color: [1.0, 1.0, 1.0],
             feedback_text_anim: SmoothMoveFadeAnimation::new(false, MoveDirection::Down, 10.),
             feedback_text_queue: vec![],
-            current_feedback_start_time: Instant::now()
+            current_feedback_start_time: Instant::now(),
+
+            size_warning_anim: SmoothMoveFadeAnimation::new(false, MoveDirection::Down, 4.),
+            last_size_warning: String::new(),
+
+            progress_anim: SmoothMoveFadeAnimation::new(false, MoveDirection::Down, 10.),
+            last_progress: 0,
+
+            word_sections: Vec::new(),
+            text_offsets: Vec::new(),
+            preview_glyphs: Vec::new(),
+            text_selection: None,
+            selecting_text: false,
+            word_boxes: Vec::new(),
+            word_flash: None,
+            word_hitboxes: Vec::new(),
+            open_picker: None,
+            alternative_hitboxes: Vec::new(),
+            text_backing: None,
+
+            window_size: (width, height),
+            scale: 1.,
+
+            entities_text: None,
+            entities: Vec::new(),
+            entity_sections: Vec::new(),
+            entity_hitboxes: Vec::new()
         }
     }
     
@@ -63,7 +149,7 @@ impl OCRPreviewRenderer {
         &self,
         is_fading_out: bool,
         window_size: (u32, u32),
-        bounds: Bounds,
+        bounds: Rect,
         text_lines: i32,
         max_line_characters: i32
     ) -> Option<PreviewTextPlacement> {
@@ -72,61 +158,61 @@ impl OCRPreviewRenderer {
         }
 
         let bounds = bounds.to_positive_size();
-        if bounds.width == 0 || bounds.height == 0 {
+        if bounds.is_empty() {
             return None;
         }
 
         let left_side_space = bounds.x;
-        let right_side_space = window_size.0 as i32 - (bounds.x + bounds.width);
+        let right_side_space = window_size.0 as f32 - (bounds.x + bounds.width);
 
-        let margin = 10;
+        let margin = 10.;
 
-        let y = std::cmp::max(margin + 16, std::cmp::min(bounds.y, window_size.1 as i32 - ((text_lines - 1) * 18 + margin)));
+        let y = bounds.y.min(window_size.1 as f32 - ((text_lines - 1) as f32 * 18. + margin)).max(margin + 16.);
 
-        let minimum_side_space = 100;
+        let minimum_side_space = 100.;
 
         if left_side_space >= right_side_space {
             if left_side_space < minimum_side_space {
                 return Some(PreviewTextPlacement {
-                    x: margin as f32,
-                    y: y as f32,
+                    x: margin,
+                    y,
                     horizontal_align: glyph_brush::HorizontalAlign::Left,
-                    max_line_length: window_size.0 as f32 - margin as f32 * 2.
+                    max_line_length: window_size.0 as f32 - margin * 2.
                 });
             }
 
-            let max_line_length = bounds.x as f32 - margin as f32 * 2.;
+            let max_line_length = bounds.x - margin * 2.;
             // If we have more than 3 lines and any line is very long, we should align to the left at the edge of the screen instead since it just looks better
             // Very long is subjective here -- we could come up with a real heuristic but that would require feedback from the layout engine which I do not want to do.
             if text_lines > 3 && max_line_characters as f32 * TEXT_HEIGHT as f32 / 2. > max_line_length {
                 return Some(PreviewTextPlacement {
-                    x: margin as f32,
-                    y: y as f32,
+                    x: margin,
+                    y,
                     horizontal_align: glyph_brush::HorizontalAlign::Left,
                     max_line_length
                 });
             }
 
             Some(PreviewTextPlacement {
-                x: (bounds.x - margin) as f32,
-                y: y as f32,
+                x: bounds.x - margin,
+                y,
                 horizontal_align: glyph_brush::HorizontalAlign::Right,
                 max_line_length
             })
         } else {
             if right_side_space < minimum_side_space {
                 return Some(PreviewTextPlacement {
-                    x: window_size.0 as f32 - margin as f32,
-                    y: y as f32,
+                    x: window_size.0 as f32 - margin,
+                    y,
                     horizontal_align: glyph_brush::HorizontalAlign::Right,
-                    max_line_length: window_size.0 as f32 - margin as f32 * 2.
+                    max_line_length: window_size.0 as f32 - margin * 2.
                 });
             }
 
-            let max_line_length = window_size.0 as f32 - (bounds.x as f32 + bounds.width as f32 + margin as f32);
+            let max_line_length = window_size.0 as f32 - (bounds.x + bounds.width + margin);
             Some(PreviewTextPlacement {
-                x: (bounds.x + bounds.width + margin) as f32,
-                y: y as f32,
+                x: bounds.x + bounds.width + margin,
+                y,
                 horizontal_align: glyph_brush::HorizontalAlign::Left,
                 max_line_length
             })
@@ -136,10 +222,11 @@ impl OCRPreviewRenderer {
     fn get_ocr_section(
         &mut self,
         ocr_preview_text: Option<String>,
+        uncertain_words: &[UncertainWord],
         window_size: (u32, u32),
         icon_renderer: &mut IconRenderer,
         delta: std::time::Duration,
-        bounds: Bounds,
+        bounds: Rect,
         #[allow(unused_variables)]
         icon_context: &super::IconContext
     ) -> Option<OwnedSection> {
@@ -149,9 +236,41 @@ impl OCRPreviewRenderer {
             return None;
         }
 
-        let mut text = ocr_preview_text.clone().unwrap_or_else(|| self.last_text.clone().unwrap());
-
-        text = text.replace("\t", "  ");
+        // Uncertain words get their own text so they can be colored and hit-tested
+        let chunks = match &ocr_preview_text {
+            Some(text) => split_uncertain_words(text, uncertain_words),
+            None => vec![(self.last_text.clone().unwrap(), None)]
+        };
+        let add_pilcrows = icon_context.settings.add_pilcrow_in_preview && ocr_preview_text.is_some();
+        let mut source_offsets = Vec::new();
+        let mut source_characters = 0;
+        let chunks: Vec<(String, Option<usize>)> = chunks.into_iter().map(|(chunk, word_index)| {
+            let mut preview_chunk = String::with_capacity(chunk.len());
+            for character in chunk.chars() {
+                let source_offset = Some(source_characters);
+                source_characters += 1;
+                match character {
+                    '\t' => {
+                        preview_chunk.push_str("  ");
+                        source_offsets.extend([source_offset; 2]);
+                    }
+                    '\n' if add_pilcrows => {
+                        preview_chunk.push_str(" ¶\n");
+                        source_offsets.extend([None, None, source_offset]);
+                    }
+                    _ => {
+                        preview_chunk.push(character);
+                        source_offsets.push(source_offset);
+                    }
+                }
+            }
+            (preview_chunk, word_index)
+        }).collect();
+        // Once the result is gone, the preview fades out showing its last text, so keep mapping into that
+        if let Some(ocr_preview_text) = &ocr_preview_text {
+            self.source_text = Some((ocr_preview_text.chars().collect(), source_offsets));
+        }
+        let text = chunks.iter().map(|(chunk, _)| chunk.as_str()).collect::<String>();
 
         if text.trim().is_empty() {
             self.last_text = None;
@@ -160,12 +279,11 @@ impl OCRPreviewRenderer {
             return None;
         }
 
-        if icon_context.settings.add_pilcrow_in_preview {
-            text = text.lines().map(|x| x.to_string() + " ¶").collect::<Vec<String>>().join("\n");
-            // Remove the last pilcrow
-            text.pop();
+        if self.last_text.as_ref() != Some(&text) {
+            self.text_selection = None;
+            self.selecting_text = false;
+            self.word_flash = None;
         }
-
         self.last_text = Some(text.clone());
 
         let visible = ocr_preview_text.is_some(); // && !icon_context.settings_panel_visible;
@@ -182,10 +300,42 @@ impl OCRPreviewRenderer {
         self.anim.update(delta, visible);
         self.anim.fade_move_direction = if placement.horizontal_align == HorizontalAlign::Left { MoveDirection::Right } else { MoveDirection::Left };
 
-        icon_renderer.update_text_icon_positions(ocr_preview_text.map(|_| (placement.x + (if placement.horizontal_align == HorizontalAlign::Left { -24. } else { 24. }), placement.y - 18.0)));
-        let section = Some(OwnedSection::default()
-            .add_text(OwnedText::new("Preview:\n").with_color([1.0, 1.0, 1.0, 0.9 * self.anim.get_opacity()]).with_scale(16.0))
-            .add_text(OwnedText::new(text).with_color([0.8, 0.8, 0.8, 0.8 * self.anim.get_opacity()]).with_scale(18.0))
+        let copy_icon_position = (placement.x + (if placement.horizontal_align == HorizontalAlign::Left { -24. } else { 24. }), placement.y - 18.0);
+        icon_renderer.update_text_icon_positions(ocr_preview_text.map(|_| (copy_icon_position.0 * self.scale, copy_icon_position.1 * self.scale)));
+        let tesseract_settings = &icon_context.settings.tesseract_settings;
+        self.fallback_fonts.update(
+            &mut self.text_brush,
+            &tesseract_settings.ocr_language_code,
+            &tesseract_settings.fallback_fonts,
+            tesseract_settings.system_fallback_fonts
+        );
+        let font_id = if icon_context.settings.code_mode { MONOSPACE_FONT_ID } else { FontId::default() };
+        let [red, green, blue] = icon_context.theme_colors().text;
+        let mut section = OwnedSection::default()
+            .add_text(OwnedText::new(format!("{}:\n", icon_context.locale.tr("Preview"))).with_color([red, green, blue, 0.9 * self.anim.get_opacity()]).with_scale(16.0));
+        let [accent_red, accent_green, accent_blue] = icon_context.theme_colors().accent;
+        // A selection being dragged out takes over from a flashed word
+        let selected = self.selected_range().or_else(|| self.word_flash.as_ref().map(|(range, _, _)| range.clone()));
+        self.word_sections = vec![None];
+        self.text_offsets = vec![None];
+        let mut offset = 0;
+        for (chunk, word_index) in chunks {
+            let color = match word_index {
+                Some(_) => [UNCERTAIN_WORD_COLOR[0], UNCERTAIN_WORD_COLOR[1], UNCERTAIN_WORD_COLOR[2], 0.9 * self.anim.get_opacity()],
+                None => [red * 0.8, green * 0.8, blue * 0.8, 0.8 * self.anim.get_opacity()]
+            };
+            for (part, part_selected) in split_selected(&chunk, offset, selected.as_ref()) {
+                let color = if part_selected { [accent_red, accent_green, accent_blue, self.anim.get_opacity()] } else { color };
+                for (run, run_font_id) in self.fallback_fonts.split_runs(&part, self.text_brush.fonts(), font_id) {
+                    let length = run.chars().count();
+                    section = section.add_text(OwnedText::new(run).with_color(color).with_scale(18.0).with_font_id(run_font_id));
+                    self.word_sections.push(word_index);
+                    self.text_offsets.push(Some(offset));
+                    offset += length;
+                }
+            }
+        }
+        let section = Some(section
             .with_screen_position(self.anim.move_point((placement.x, placement.y - 18.0)))
             .with_layout(glyph_brush::Layout::default()
                 .h_align(placement.horizontal_align)
@@ -208,7 +358,16 @@ impl OCRPreviewRenderer {
         width: u32,
         height: u32
     ) -> () {
-        self.text_brush.resize_view(width as f32, height as f32, pixels.queue());
+        self.window_size = (width, height);
+        self.apply_view(pixels.queue());
+    }
+
+    fn apply_view(&mut self, queue: &wgpu::Queue) {
+        self.text_brush.resize_view(self.window_size.0 as f32 / self.scale, self.window_size.1 as f32 / self.scale, queue);
+    }
+
+    fn to_layout_position(&self, position: (i32, i32)) -> (i32, i32) {
+        ((position.0 as f32 / self.scale) as i32, (position.1 as f32 / self.scale) as i32)
     }
 
     fn get_feedback_text(
@@ -251,6 +410,150 @@ impl OCRPreviewRenderer {
         section
     }
 
+    /// A subtle hint next to the cursor while dragging a selection that's too small to be recognized
+    fn get_size_warning_section(
+        &mut self,
+        delta: std::time::Duration,
+        bounds: Rect,
+        selecting: bool,
+        mouse_pos: (i32, i32),
+        icon_context: &IconContext
+    ) -> Option<OwnedSection> {
+        let minimum_size = icon_context.settings.tesseract_settings.minimum_selection_size;
+        let too_small = selecting && (bounds.width != 0. || bounds.height != 0.) && bounds.smaller_than(minimum_size);
+        self.size_warning_anim.update(delta, too_small);
+
+        if too_small {
+            let bounds = bounds.to_positive_size();
+            let locale = &icon_context.locale;
+            self.last_size_warning = format!(
+                "{} ({:.0}×{:.0}, {} {}×{})",
+                locale.tr("Too small to recognize"), bounds.width, bounds.height, locale.tr("minimum"), minimum_size, minimum_size
+            );
+        }
+        if !self.size_warning_anim.visible_at_all() {
+            return None;
+        }
+
+        Some(OwnedSection::default()
+            .add_text(OwnedText::new(self.last_size_warning.clone()).with_color([0.9, 0.6, 0.4, 0.8 * self.size_warning_anim.get_opacity()]).with_scale(14.0))
+            .with_screen_position(self.size_warning_anim.move_point((mouse_pos.0 as f32 + 16., mouse_pos.1 as f32 + 16.)))
+        )
+    }
+
+    /// The selection's size in pixels, below its bottom-right corner or just inside it if there's no room
+    fn get_dimensions_section(&self, window_size: (u32, u32), bounds: Rect, screen_bounds: Rect, icon_context: &IconContext) -> Option<OwnedSection> {
+        if bounds.to_positive_size().is_empty() || icon_context.bounds_entry.is_some() {
+            return None;
+        }
+
+        let pixel_bounds = bounds.to_pixel_rect();
+        let bounds = screen_bounds.to_positive_size();
+        let bottom = bounds.y + bounds.height;
+        let y = if bottom + 24. <= window_size.1 as f32 { bottom + 6. } else { bottom - 20. };
+        Some(OwnedSection::default()
+            .add_text(OwnedText::new(format!("{:.0} × {:.0} px", pixel_bounds.width, pixel_bounds.height)).with_color([0.8, 0.8, 0.8, 0.7]).with_scale(14.0))
+            .with_screen_position((bounds.x + bounds.width, y))
+            .with_layout(glyph_brush::Layout::default_single_line()
+                .h_align(glyph_brush::HorizontalAlign::Right)
+            )
+        )
+    }
+
+    /// The distances from the cursor to the screen's edges and the selection's, next to the cursor while the crosshair is shown
+    fn get_crosshair_section(&self, window_size: (u32, u32), bounds: Rect, mouse_pos: (i32, i32), icon_context: &IconContext) -> Option<OwnedSection> {
+        if !icon_context.settings.crosshair_guides {
+            return None;
+        }
+
+        // Distances are in pixels of the screenshot, like the selection
+        let cursor = icon_context.view.to_image(Point::from(mouse_pos));
+        let (width, height) = (window_size.0 as f32, window_size.1 as f32);
+        let mut section = OwnedSection::default()
+            .add_text(OwnedText::new(format!("← {:.0}  → {:.0}  ↑ {:.0}  ↓ {:.0}", cursor.x, width - cursor.x, cursor.y, height - cursor.y)).with_color([0.9, 0.9, 0.9, 0.8]).with_scale(14.0));
+
+        let bounds = bounds.to_positive_size();
+        if !bounds.is_empty() {
+            section = section.add_text(OwnedText::new(format!(
+                "\nSelection: ← {:.0}  → {:.0}  ↑ {:.0}  ↓ {:.0}",
+                (cursor.x - bounds.x).abs(),
+                (bounds.x + bounds.width - cursor.x).abs(),
+                (cursor.y - bounds.y).abs(),
+                (bounds.y + bounds.height - cursor.y).abs()
+            )).with_color([0.7, 0.7, 0.7, 0.8]).with_scale(14.0));
+        }
+
+        // Flipped to the other side of the cursor near the right and bottom edges of the screen
+        let mouse_pos = self.to_layout_position(mouse_pos);
+        let (width, height) = (width / self.scale, height / self.scale);
+        let (x, align) = if (mouse_pos.0 as f32) < width - 260. {
+            (mouse_pos.0 as f32 + 12., glyph_brush::HorizontalAlign::Left)
+        } else {
+            (mouse_pos.0 as f32 - 12., glyph_brush::HorizontalAlign::Right)
+        };
+        let y = if (mouse_pos.1 as f32) < height - 50. { mouse_pos.1 as f32 + 12. } else { mouse_pos.1 as f32 - 44. };
+        Some(section
+            .with_screen_position((x, y))
+            .with_layout(glyph_brush::Layout::default().h_align(align))
+        )
+    }
+
+    /// The bounds entry, above the selection or below it if there's no room
+    fn get_bounds_entry_section(&self, bounds: Rect, icon_context: &IconContext) -> Option<OwnedSection> {
+        let entry = icon_context.bounds_entry.as_ref()?;
+        let bounds = bounds.to_positive_size();
+        let y = if bounds.y >= 30. { bounds.y - 26. } else { bounds.y + bounds.height + 8. };
+
+        let mut section = OwnedSection::default().with_screen_position((bounds.x, y));
+        for (text, active) in entry.fields() {
+            section = if active {
+                section.add_text(OwnedText::new(format!("{}|   ", text)).with_color([1.0, 1.0, 1.0, 1.0]).with_scale(18.0))
+            } else {
+                section.add_text(OwnedText::new(format!("{}   ", text)).with_color([0.7, 0.7, 0.7, 0.8]).with_scale(18.0))
+            };
+        }
+        Some(section.add_text(OwnedText::new(icon_context.locale.tr("Tab to switch, Enter to apply")).with_color([0.7, 0.7, 0.7, 0.6]).with_scale(14.0)))
+    }
+
+    /// The name of a region preset being saved, in the same place as the bounds entry
+    fn get_preset_name_section(&self, bounds: Rect, icon_context: &IconContext) -> Option<OwnedSection> {
+        let name = icon_context.preset_name_entry.as_ref()?;
+        let bounds = bounds.to_positive_size();
+        let y = if bounds.y >= 30. { bounds.y - 26. } else { bounds.y + bounds.height + 8. };
+
+        Some(OwnedSection::default()
+            .with_screen_position((bounds.x, y))
+            .add_text(OwnedText::new(format!("{}: {}|   ", icon_context.locale.tr("Preset name"), name)).with_color([1.0, 1.0, 1.0, 1.0]).with_scale(18.0))
+            .add_text(OwnedText::new(icon_context.locale.tr("Enter to save, Esc to cancel")).with_color([0.7, 0.7, 0.7, 0.6]).with_scale(14.0))
+        )
+    }
+
+    /// Shows how much of a large selection has been recognized, in the feedback area when it's free
+    fn get_progress_section(
+        &mut self,
+        delta: std::time::Duration,
+        window_size: (u32, u32),
+        progress: Option<u8>,
+        locale: &Locale
+    ) -> Option<OwnedSection> {
+        let visible = progress.is_some() && self.active_feedback_text.is_none();
+        self.progress_anim.update(delta, visible);
+        if let Some(progress) = progress {
+            self.last_progress = progress;
+        }
+        if !self.progress_anim.visible_at_all() {
+            return None;
+        }
+
+        Some(OwnedSection::default()
+            .add_text(OwnedText::new(format!("{} {}%", locale.tr("Recognizing..."), self.last_progress)).with_color([0.8, 0.8, 0.8, 0.8 * self.progress_anim.get_opacity()]).with_scale(20.0))
+            .with_screen_position(self.progress_anim.move_point((window_size.0 as f32 / 2., 65.)))
+            .with_layout(glyph_brush::Layout::default()
+                .h_align(glyph_brush::HorizontalAlign::Center)
+            )
+        )
+    }
+
     pub(crate) fn show_user_feedback(
         &mut self,
         text: String,
@@ -263,8 +566,13 @@ impl OCRPreviewRenderer {
         &mut self,
         context: &PixelsContext,
         window_size: (u32, u32),
-        bounds: Bounds,
+        bounds: Rect,
+        selecting: bool,
+        mouse_pos: (i32, i32),
         ocr_preview_text: Option<String>,
+        uncertain_words: &[UncertainWord],
+        word_boxes: &[WordBox],
+        ocr_progress: Option<u8>,
         icon_context: &IconContext,
         delta: std::time::Duration,
         icon_renderer: &mut IconRenderer
@@ -272,24 +580,366 @@ impl OCRPreviewRenderer {
         let device = &context.device;
         let queue = &context.queue;
 
+        let scale = icon_context.ui_scale();
+        if scale != self.scale {
+            self.scale = scale;
+            self.apply_view(queue);
+        }
+        // Only the crosshair's distances need the real cursor position and window size
+        let real_mouse_pos = mouse_pos;
+        let real_window_size = window_size;
+        let mouse_pos = self.to_layout_position(mouse_pos);
+        let window_size = ((window_size.0 as f32 / scale) as u32, (window_size.1 as f32 / scale) as u32);
+
         let mut sections = Vec::new();
         
-        let ocr_section = self.get_ocr_section(ocr_preview_text, window_size, icon_renderer, delta, bounds, icon_context);
+        let preview_visible = ocr_preview_text.is_some();
+        if self.word_flash.as_ref().is_some_and(|(_, _, start)| start.elapsed() >= WORD_FLASH_DURATION) {
+            self.word_flash = None;
+        }
+        if self.selecting_text {
+            if let (Some((anchor, _)), Some(focus)) = (self.text_selection, preview_offset_at(&self.preview_glyphs, mouse_pos)) {
+                self.text_selection = Some((anchor, focus));
+            }
+        }
+        // The selection is in pixels of the screenshot, but everything around it is placed on screen
+        let screen_bounds = icon_context.view.rect_to_screen(bounds).scaled(1. / scale);
+        let ocr_section = self.get_ocr_section(ocr_preview_text.clone(), uncertain_words, window_size, icon_renderer, delta, screen_bounds, icon_context);
         if ocr_section.is_some() {
             sections.push(ocr_section.as_ref().unwrap());
         }
 
+        self.word_hitboxes = match (&ocr_section, preview_visible) {
+            (Some(section), true) => self.section_hitboxes(section).into_iter()
+                .filter_map(|(section_index, hitbox)| {
+                    let word_index = self.word_sections.get(section_index).copied().flatten()?;
+                    Some((word_index, uncertain_words.get(word_index)?.range.start, hitbox))
+                })
+                .collect(),
+            _ => Vec::new()
+        };
+
+        self.preview_glyphs = match (&ocr_section, preview_visible) {
+            (Some(section), true) => self.preview_glyph_positions(section),
+            _ => Vec::new()
+        };
+        if self.preview_glyphs.is_empty() {
+            self.text_selection = None;
+            self.selecting_text = false;
+        }
+        self.word_boxes = match (&self.last_text, preview_visible) {
+            (Some(text), true) => locate_word_boxes(text, word_boxes),
+            _ => Vec::new()
+        };
+
+        self.text_backing = match (&ocr_section, preview_visible && icon_context.high_contrast()) {
+            (Some(section), true) => self.section_hitboxes(section).into_iter()
+                .map(|(_, hitbox)| hitbox)
+                .reduce(|union, hitbox| {
+                    let right = (union.x + union.width).max(hitbox.x + hitbox.width);
+                    let bottom = (union.y + union.height).max(hitbox.y + hitbox.height);
+                    let (x, y) = (union.x.min(hitbox.x), union.y.min(hitbox.y));
+                    Rect::new(x, y, right - x, bottom - y)
+                })
+                .map(|union| Rect::new(
+                    union.x - TEXT_BACKING_PADDING,
+                    union.y - TEXT_BACKING_PADDING,
+                    union.width + TEXT_BACKING_PADDING * 2.,
+                    union.height + TEXT_BACKING_PADDING * 2.
+                ).scaled(scale)),
+            _ => None
+        };
+
+        let entity_chips = match (&ocr_section, &ocr_preview_text) {
+            (Some(section), Some(text)) => self.get_entity_chip_section(section, text, mouse_pos),
+            _ => {
+                self.entity_hitboxes.clear();
+                None
+            }
+        };
+        if entity_chips.is_some() {
+            sections.push(entity_chips.as_ref().unwrap());
+        }
+
+        let picker = self.get_picker_section(uncertain_words, mouse_pos, &icon_context.locale);
+        if picker.is_some() {
+            sections.push(picker.as_ref().unwrap());
+        }
+
+        let size_warning = self.get_size_warning_section(delta, bounds, selecting, mouse_pos, icon_context);
+        if size_warning.is_some() {
+            sections.push(size_warning.as_ref().unwrap());
+        }
+
+        let dimensions = self.get_dimensions_section(window_size, bounds, screen_bounds, icon_context);
+        if dimensions.is_some() {
+            sections.push(dimensions.as_ref().unwrap());
+        }
+
+        let crosshair = self.get_crosshair_section(real_window_size, bounds, real_mouse_pos, icon_context);
+        if crosshair.is_some() {
+            sections.push(crosshair.as_ref().unwrap());
+        }
+
+        let bounds_entry = self.get_bounds_entry_section(screen_bounds, icon_context);
+        if bounds_entry.is_some() {
+            sections.push(bounds_entry.as_ref().unwrap());
+        }
+
+        let preset_name = self.get_preset_name_section(screen_bounds, icon_context);
+        if preset_name.is_some() {
+            sections.push(preset_name.as_ref().unwrap());
+        }
+
         let feedback_text = self.get_feedback_text(delta, window_size);
         if feedback_text.is_some() {
             sections.push(feedback_text.as_ref().unwrap());
         }
 
+        let progress = self.get_progress_section(delta, window_size, ocr_progress, &icon_context.locale);
+        if progress.is_some() {
+            sections.push(progress.as_ref().unwrap());
+        }
+
         self.should_render_text = sections.len() > 0;
         if self.should_render_text {
             self.text_brush.queue(device, queue, sections).unwrap();
         }
     }
 
+    /// Where the preview text's solid backing goes, if it has one.
+    pub(crate) fn text_backing(&self) -> Option<Rect> {
+        self.text_backing
+    }
+
+    /// The part of the preview text selected by dragging over it, if any.
+    pub(crate) fn selected_text(&self) -> Option<String> {
+        let range = self.selected_range()?;
+        let (source, source_offsets) = self.source_text.as_ref()?;
+        // Both spaces a tab is widened to map back to the tab, so only take each source character once
+        let mut selected = String::new();
+        let mut last_offset = None;
+        for &offset in source_offsets.get(range)?.iter().flatten() {
+            if last_offset != Some(offset) {
+                selected.push(source[offset]);
+                last_offset = Some(offset);
+            }
+        }
+        Some(selected)
+    }
+
+    /// The screenshot bounds of the flashed word and how strongly to highlight them, fading out unless
+    /// motion is reduced.
+    pub(crate) fn word_flash(&self, reduced_motion: bool) -> Option<(Rect, f32)> {
+        let (_, bounds, start) = self.word_flash.as_ref()?;
+        let progress = start.elapsed().as_secs_f32() / WORD_FLASH_DURATION.as_secs_f32();
+        Some((*bounds, if reduced_motion { 1. } else { (1. - progress).clamp(0., 1.) }))
+    }
+
+    /// Whether a recognized word's box on the screenshot contains a point.
+    pub(crate) fn has_word_at(&self, image_point: Point) -> bool {
+        self.word_boxes.iter().any(|(_, bounds)| bounds.contains(image_point))
+    }
+
+    /// Highlights the word whose box on the screenshot contains a point, along with the box. Returns whether
+    /// there was one.
+    pub(crate) fn flash_word_at(&mut self, image_point: Point) -> bool {
+        let Some((range, bounds)) = self.word_boxes.iter().find(|(_, bounds)| bounds.contains(image_point)) else {
+            return false;
+        };
+        self.word_flash = Some((range.clone(), *bounds, Instant::now()));
+        true
+    }
+
+    fn selected_range(&self) -> Option<Range<usize>> {
+        let (anchor, focus) = self.text_selection?;
+        Some(anchor.min(focus)..anchor.max(focus)).filter(|range| !range.is_empty())
+    }
+
+    /// The character offset in the preview text and bounds of each glyph of the preview section
+    fn preview_glyph_positions(&mut self, section: &OwnedSection) -> Vec<(usize, Rect)> {
+        let glyphs: Vec<_> = self.text_brush.glyphs_iter(section).cloned().collect();
+        let fonts = self.text_brush.fonts();
+
+        glyphs.iter().filter_map(|glyph| {
+            let text_offset = self.text_offsets.get(glyph.section_index).copied().flatten()?;
+            let text = &section.text.get(glyph.section_index)?.text;
+            let offset = text_offset + text.get(..glyph.byte_index)?.chars().count();
+            Some((offset, glyph_rect(fonts, glyph)))
+        }).collect()
+    }
+
+    /// The bounds of each text in a section, by text index
+    fn section_hitboxes(&mut self, section: &OwnedSection) -> Vec<(usize, Rect)> {
+        let glyphs: Vec<_> = self.text_brush.glyphs_iter(section).cloned().collect();
+        let fonts = self.text_brush.fonts();
+
+        let mut hitboxes: Vec<(usize, Rect)> = Vec::new();
+        for glyph in glyphs {
+            let glyph_rect = glyph_rect(fonts, &glyph);
+            match hitboxes.iter_mut().find(|(section_index, _)| *section_index == glyph.section_index) {
+                Some((_, hitbox)) => {
+                    // Words can wrap onto a second line; the union of both is close enough
+                    let right = (hitbox.x + hitbox.width).max(glyph_rect.x + glyph_rect.width);
+                    let bottom = (hitbox.y + hitbox.height).max(glyph_rect.y + glyph_rect.height);
+                    hitbox.x = hitbox.x.min(glyph_rect.x);
+                    hitbox.y = hitbox.y.min(glyph_rect.y);
+                    hitbox.width = right - hitbox.x;
+                    hitbox.height = bottom - hitbox.y;
+                }
+                None => hitboxes.push((glyph.section_index, glyph_rect))
+            }
+        }
+        hitboxes
+    }
+
+    /// The list of alternatives for the word that was clicked, shown just below it
+    fn get_picker_section(&mut self, uncertain_words: &[UncertainWord], mouse_pos: (i32, i32), locale: &Locale) -> Option<OwnedSection> {
+        let (word_index, word_start) = self.open_picker?;
+        let word = uncertain_words.get(word_index).filter(|word| word.range.start == word_start);
+        let hitbox = self.word_hitboxes.iter().find(|(index, _, _)| *index == word_index).map(|(_, _, hitbox)| *hitbox);
+        let (Some(word), Some(hitbox)) = (word, hitbox) else {
+            self.open_picker = None;
+            self.alternative_hitboxes.clear();
+            return None;
+        };
+
+        // Lay out once to find the hovered alternative, then again with it highlighted
+        let build_section = |hovered: Option<usize>| {
+            let mut section = OwnedSection::default()
+                .with_screen_position((hitbox.x, hitbox.y + hitbox.height + 6.));
+            for (index, alternative) in word.alternatives.iter().enumerate() {
+                let color = if hovered == Some(index) { [1.0, 1.0, 1.0, 1.0] } else { [0.8, 0.8, 0.8, 0.9] };
+                section = section.add_text(OwnedText::new(format!("{}\n", alternative)).with_color(color).with_scale(18.0));
+            }
+            // The last entry keeps the word as-is and adds it to the personal dictionary
+            let color = if hovered == Some(word.alternatives.len()) { [0.6, 1.0, 0.6, 1.0] } else { [0.5, 0.8, 0.5, 0.9] };
+            section = section.add_text(OwnedText::new(format!("{}\n", locale.tr("Add to dictionary"))).with_color(color).with_scale(18.0));
+            section
+        };
+
+        let section = build_section(None);
+        self.alternative_hitboxes = self.section_hitboxes(&section).into_iter().map(|(_, hitbox)| hitbox).collect();
+        let hovered = self.alternative_hitboxes.iter().position(|hitbox| hitbox.contains(mouse_pos));
+        Some(build_section(hovered))
+    }
+
+    /// Small clickable chips under the preview for the emails, URLs, etc. in the text, which copy just that entity
+    fn get_entity_chip_section(&mut self, ocr_section: &OwnedSection, text: &str, mouse_pos: (i32, i32)) -> Option<OwnedSection> {
+        if self.entities_text.as_deref() != Some(text) {
+            self.entities = extract_entities(text);
+            self.entities_text = Some(text.to_string());
+        }
+        if self.entities.is_empty() {
+            self.entity_hitboxes.clear();
+            return None;
+        }
+
+        let preview_bottom = self.section_hitboxes(ocr_section).into_iter()
+            .map(|(_, hitbox)| hitbox.y + hitbox.height)
+            .fold(ocr_section.screen_position.1, f32::max);
+        let opacity = self.anim.get_opacity();
+
+        // Lay out once to find the hovered chip, then again with it highlighted
+        let build_section = |hovered: Option<usize>, entities: &[Entity]| {
+            let mut section = OwnedSection::default()
+                .with_screen_position((ocr_section.screen_position.0, preview_bottom + 8.))
+                .with_layout(ocr_section.layout)
+                .with_bounds(ocr_section.bounds);
+            let mut sections = Vec::new();
+            for (index, entity) in entities.iter().enumerate() {
+                if index > 0 {
+                    section = section.add_text(OwnedText::new("   ").with_scale(14.0));
+                    sections.push(None);
+                }
+                let color = if hovered == Some(index) { [1.0, 1.0, 1.0] } else { ENTITY_CHIP_COLOR };
+                section = section.add_text(OwnedText::new(format!("[{}: {}]", entity.kind.label(), entity.text)).with_color([color[0], color[1], color[2], 0.9 * opacity]).with_scale(14.0));
+                sections.push(Some(index));
+            }
+            (section, sections)
+        };
+
+        let (section, entity_sections) = build_section(None, &self.entities);
+        self.entity_sections = entity_sections;
+        self.entity_hitboxes = self.section_hitboxes(&section).into_iter()
+            .filter_map(|(section_index, hitbox)| Some((self.entity_sections.get(section_index).copied().flatten()?, hitbox)))
+            .collect();
+        let hovered = self.entity_hitboxes.iter().find(|(_, hitbox)| hitbox.contains(mouse_pos)).map(|(index, _)| *index);
+        Some(build_section(hovered, &self.entities).0)
+    }
+
+    /// Handles clicks on uncertain words, their alternatives, and entity chips, and dragging to select part of the
+    /// preview text. Returns whether the click was used.
+    pub(crate) fn mouse_event(&mut self, mouse_pos: (i32, i32), state: ElementState, icon_context: &mut IconContext) -> bool {
+        let mouse_pos = self.to_layout_position(mouse_pos);
+        if let Some((word_index, _)) = self.open_picker {
+            if let Some(alternative_index) = self.alternative_hitboxes.iter().position(|hitbox| hitbox.contains(mouse_pos)) {
+                if state == ElementState::Released {
+                    let event = if alternative_index == self.alternative_hitboxes.len() - 1 {
+                        IconEvent::AddToDictionary(word_index)
+                    } else {
+                        IconEvent::ChooseAlternative(word_index, alternative_index)
+                    };
+                    icon_context.channel.send(event).expect("Unable to send picker event");
+                    self.open_picker = None;
+                }
+                return true;
+            }
+        }
+
+        if let Some(&(entity_index, _)) = self.entity_hitboxes.iter().find(|(_, hitbox)| hitbox.contains(mouse_pos)) {
+            if state == ElementState::Released {
+                if let Some(entity) = self.entities.get(entity_index) {
+                    icon_context.channel.send(IconEvent::CopyEntity(entity.text.clone())).expect("Unable to send copy entity event");
+                }
+            }
+            return true;
+        }
+
+        let on_text = self.preview_glyphs.iter().any(|(_, glyph)| glyph.contains(mouse_pos));
+        match state {
+            ElementState::Pressed if on_text => {
+                self.text_selection = preview_offset_at(&self.preview_glyphs, mouse_pos).map(|offset| (offset, offset));
+                self.selecting_text = true;
+                return true;
+            }
+            ElementState::Released if self.selecting_text => {
+                self.selecting_text = false;
+                if self.selected_range().is_some() {
+                    return true;
+                }
+                // Just a click, which flashes the word's box on the screenshot and opens or closes an uncertain word's picker
+                self.text_selection = None;
+                let clicked_offset = self.preview_glyphs.iter().find(|(_, glyph)| glyph.contains(mouse_pos)).map(|(offset, _)| *offset);
+                if let Some((range, bounds)) = clicked_offset.and_then(|offset| self.word_boxes.iter().find(|(range, _)| range.contains(&offset))) {
+                    self.word_flash = Some((range.clone(), *bounds, Instant::now()));
+                }
+                if !self.word_hitboxes.iter().any(|(_, _, hitbox)| hitbox.contains(mouse_pos)) {
+                    self.open_picker = None;
+                    return true;
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(&(word_index, word_start, _)) = self.word_hitboxes.iter().find(|(_, _, hitbox)| hitbox.contains(mouse_pos)) {
+            if state == ElementState::Released {
+                self.open_picker = match self.open_picker {
+                    Some((open_index, _)) if open_index == word_index => None,
+                    _ => Some((word_index, word_start))
+                };
+            }
+            return true;
+        }
+
+        // Clicking anywhere else closes the picker and clears the text selection, but still goes through to the selection
+        if state == ElementState::Pressed {
+            self.open_picker = None;
+            self.text_selection = None;
+        }
+        false
+    }
+
     pub(crate) fn render<'pass>(
         &'pass mut self,
         rpass: &mut wgpu::RenderPass<'pass>
@@ -298,4 +948,57 @@ impl OCRPreviewRenderer {
             self.text_brush.draw(rpass);
         }
     }
-}
\ No newline at end of file
+}
+
+fn glyph_rect(fonts: &[FontArc], glyph: &SectionGlyph) -> Rect {
+    let glyph_bounds = fonts[glyph.font_id.0].as_scaled(glyph.glyph.scale).glyph_bounds(&glyph.glyph);
+    Rect::new(glyph_bounds.min.x, glyph_bounds.min.y, glyph_bounds.width(), glyph_bounds.height())
+}
+
+/// The character offset in the preview text nearest a point, on whichever side of the glyph it's closer to.
+/// Lines are matched first, so points past the end of a line land at its end.
+fn preview_offset_at(glyphs: &[(usize, Rect)], position: (i32, i32)) -> Option<usize> {
+    let (x, y) = (position.0 as f32, position.1 as f32);
+    let distance = |rect: &Rect| (
+        (rect.y - y).max(y - (rect.y + rect.height)).max(0.),
+        (rect.x - x).max(x - (rect.x + rect.width)).max(0.)
+    );
+    let (offset, rect) = glyphs.iter().min_by(|(_, a), (_, b)| distance(a).partial_cmp(&distance(b)).unwrap_or(std::cmp::Ordering::Equal))?;
+    Some(if x > rect.x + rect.width / 2. { offset + 1 } else { *offset })
+}
+
+/// Splits part of the preview text, starting `offset` characters in, into what's inside and outside the selected range.
+fn split_selected(chunk: &str, offset: usize, selected: Option<&Range<usize>>) -> Vec<(String, bool)> {
+    let Some(selected) = selected else {
+        return vec![(chunk.to_string(), false)];
+    };
+    let mut parts: Vec<(String, bool)> = Vec::new();
+    for (index, character) in chunk.chars().enumerate() {
+        let is_selected = selected.contains(&(offset + index));
+        match parts.last_mut() {
+            Some((part, part_selected)) if *part_selected == is_selected => part.push(character),
+            _ => parts.push((character.to_string(), is_selected))
+        }
+    }
+    parts
+}
+
+/// Splits the preview text into plain runs and uncertain words, tagging each word with its index.
+fn split_uncertain_words(text: &str, uncertain_words: &[UncertainWord]) -> Vec<(String, Option<usize>)> {
+    let mut chunks = Vec::new();
+    let mut position = 0;
+    for (index, word) in uncertain_words.iter().enumerate() {
+        if word.range.start < position || text.get(word.range.clone()).is_none() {
+            continue;
+        }
+        if word.range.start > position {
+            chunks.push((text[position..word.range.start].to_string(), None));
+        }
+        chunks.push((text[word.range.clone()].to_string(), Some(index)));
+        position = word.range.end;
+    }
+    if position < text.len() {
+        chunks.push((text[position..].to_string(), None));
+    }
+    chunks
+}
@@ -1,7 +1,7 @@
 use image::{DynamicImage, GenericImageView};
 use winit::monitor::MonitorHandle;
 
-use crate::selection::Bounds;
+use crate::geometry::{Point, Rect};
 
 extern crate libc;
 
@@ -41,67 +41,160 @@ pub(crate) fn screenshot_from_handle(monitor: MonitorHandle) -> Screenshot {
     }
 }
 
-pub(crate) fn crop_screenshot_to_polygon(vertices: &Vec<(i32, i32)>, screenshot: &Screenshot) -> Screenshot {
-	let mut new_bytes: Vec<u8> = Vec::with_capacity(screenshot.width * screenshot.height * 4);
+/// Screenshots an arbitrary region of the virtual screen, e.g. a single window.
+pub(crate) fn screenshot_region(position: (i32, i32), size: (u32, u32)) -> Result<Screenshot, &'static str> {
+	#[cfg(target_os = "windows")]
+	{
+		let ss = crate::screenshot::ffi::screenshot_global_position(position, size)?;
+		let ss_bytes = ss.as_ref().to_vec();
+
+		Ok(Screenshot {
+			width: ss.width(),
+			height: ss.height(),
+			bytes: ss_bytes
+		})
+	}
+
+	#[cfg(not(target_os = "windows"))]
+	{
+		let _ = (position, size);
+		Err("Region screenshots are not supported on this platform")
+	}
+}
+
+/// Whitens every pixel outside the polygon. Takes the screenshot by value so the pixels are changed in place.
+pub(crate) fn crop_screenshot_to_polygon(vertices: &[Point], screenshot: Screenshot) -> Screenshot {
+	// Tesseract seems to work better with wite pixels instead of black
+	fill_outside_polygon(vertices, screenshot, [255, 255, 255, 255])
+}
+
+/// Makes every pixel outside the polygon transparent, for copying polygon selections as images.
+/// They're transparent white, so apps that ignore alpha show white like the OCR input.
+pub(crate) fn clear_outside_polygon(vertices: &[Point], screenshot: Screenshot) -> Screenshot {
+	fill_outside_polygon(vertices, screenshot, [255, 255, 255, 0])
+}
+
+fn fill_outside_polygon(vertices: &[Point], mut screenshot: Screenshot, color: [u8; 4]) -> Screenshot {
 	for y in 0..screenshot.height {
 		for x in 0..screenshot.width {
-			if contains_point(&vertices, (x as i32, y as i32)) {
+			// Test the center of each pixel so edges that fall between pixels are handled consistently
+			if !contains_point(vertices, Point::new(x as f32 + 0.5, y as f32 + 0.5)) {
 				let idx = (y * screenshot.width + x) * 4;
-				new_bytes.push(screenshot.bytes[idx]);
-				new_bytes.push(screenshot.bytes[idx + 1]);
-				new_bytes.push(screenshot.bytes[idx + 2]);
-				new_bytes.push(screenshot.bytes[idx + 3]);
-			} else {
-				// Tesseract seems to work better with wite pixels instead of black
-				new_bytes.push(255);
-				new_bytes.push(255);
-				new_bytes.push(255);
-				new_bytes.push(255);
+				screenshot.bytes[idx..idx + 4].copy_from_slice(&color);
 			}
 		}
 	}
 
+	screenshot
+}
+
+pub(crate) fn crop_screenshot_to_bounds(bounds: Rect, screenshot: &Screenshot) -> Screenshot {
+	let bounds = bounds.to_pixel_rect();
+
+	assert!(bounds.x + bounds.width <= screenshot.width as f32);
+	assert!(bounds.y + bounds.height <= screenshot.height as f32);
+	assert!(bounds.x >= 0.);
+	assert!(bounds.y >= 0.);
+
+	let (left, top) = (bounds.x as usize, bounds.y as usize);
+	let (width, height) = (bounds.width as usize, bounds.height as usize);
+
+	let mut new_bytes: Vec<u8> = Vec::with_capacity(width * height * 4);
+	for y in top..top+height {
+		let row_start = (y * screenshot.width + left) * 4;
+		new_bytes.extend_from_slice(&screenshot.bytes[row_start..row_start + width * 4]);
+	}
+
 	Screenshot {
-		width: screenshot.width,
-		height: screenshot.height,
+		width,
+		height,
 		bytes: new_bytes
 	}
 }
 
-pub(crate) fn crop_screenshot_to_bounds(bounds: Bounds, screenshot: &Screenshot) -> Screenshot {
-	let bounds = bounds.to_positive_size();
-
-	assert!(bounds.x + bounds.width <= screenshot.width as i32);
-	assert!(bounds.y + bounds.height <= screenshot.height as i32);
-	assert!(bounds.x >= 0);
-	assert!(bounds.y >= 0);
-
-	let mut new_bytes: Vec<u8> = Vec::with_capacity(bounds.width as usize * bounds.height as usize  * 4);
-	for y in bounds.y..bounds.y+bounds.height {
-		for x in bounds.x..bounds.x+bounds.width {
-			let idx = (y as usize * screenshot.width + x as usize) * 4;
-			new_bytes.push(screenshot.bytes[idx]);
-			new_bytes.push(screenshot.bytes[idx + 1]);
-			new_bytes.push(screenshot.bytes[idx + 2]);
-			new_bytes.push(screenshot.bytes[idx + 3]);
-		}
+/// Converts to grayscale and stretches the contrast to the full range.
+/// Photos of whiteboards and paper are usually low-contrast, which hurts handwriting recognition.
+pub(crate) fn normalize_contrast(screenshot: &Screenshot) -> Screenshot {
+	let luminances: Vec<u8> = screenshot.bytes.chunks(4).map(|pixel| {
+		((pixel[0] as u32 * 3 + pixel[1] as u32 * 6 + pixel[2] as u32) / 10) as u8
+	}).collect();
+
+	let min = *luminances.iter().min().unwrap_or(&0) as f32;
+	let max = *luminances.iter().max().unwrap_or(&255) as f32;
+	let range = (max - min).max(1.);
+
+	let mut new_bytes: Vec<u8> = Vec::with_capacity(screenshot.bytes.len());
+	for luminance in luminances {
+		let value = ((luminance as f32 - min) / range * 255.) as u8;
+		new_bytes.extend_from_slice(&[value, value, value, 255]);
 	}
 
 	Screenshot {
-		width: bounds.width as usize,
-		height: bounds.height as usize,
+		width: screenshot.width,
+		height: screenshot.height,
 		bytes: new_bytes
 	}
 }
 
-pub fn contains_point(vertices: &Vec<(i32, i32)>, point: (i32, i32)) -> bool {
-	let (x, y) = point;
+/// How much a channel may differ from the background color before a pixel counts as content
+const TRIM_TOLERANCE: u8 = 24;
+/// How much space is left around the content when trimming, so antialiased edges aren't cut off
+const TRIM_PADDING: f32 = 2.;
+
+/// The tightest rectangle within the bounds holding everything that isn't background, where the
+/// background is the most common color along the edges of the bounds. Returns None if it's all background.
+pub(crate) fn content_bounds(bounds: Rect, screenshot: &Screenshot) -> Option<Rect> {
+	let bounds = bounds.to_pixel_rect();
+	let cropped = crop_screenshot_to_bounds(bounds, screenshot);
+	let (width, height) = (cropped.width, cropped.height);
+	if width == 0 || height == 0 {
+		return None;
+	}
+	let pixel = |x: usize, y: usize| {
+		let index = (y * width + x) * 4;
+		[cropped.bytes[index], cropped.bytes[index + 1], cropped.bytes[index + 2]]
+	};
+
+	let mut edge_colors: std::collections::HashMap<[u8; 3], usize> = std::collections::HashMap::new();
+	for x in 0..width {
+		*edge_colors.entry(pixel(x, 0)).or_default() += 1;
+		*edge_colors.entry(pixel(x, height - 1)).or_default() += 1;
+	}
+	for y in 0..height {
+		*edge_colors.entry(pixel(0, y)).or_default() += 1;
+		*edge_colors.entry(pixel(width - 1, y)).or_default() += 1;
+	}
+	let background = edge_colors.into_iter().max_by_key(|(_, count)| *count)?.0;
+
+	let (mut left, mut top, mut right, mut bottom) = (usize::MAX, usize::MAX, 0, 0);
+	for y in 0..height {
+		for x in 0..width {
+			let is_content = pixel(x, y).iter().zip(background).any(|(channel, background)| channel.abs_diff(background) > TRIM_TOLERANCE);
+			if is_content {
+				(left, top) = (left.min(x), top.min(y));
+				(right, bottom) = (right.max(x + 1), bottom.max(y + 1));
+			}
+		}
+	}
+	if left == usize::MAX {
+		return None;
+	}
+
+	let left = (bounds.x + left as f32 - TRIM_PADDING).max(bounds.x);
+	let top = (bounds.y + top as f32 - TRIM_PADDING).max(bounds.y);
+	let right = (bounds.x + right as f32 + TRIM_PADDING).min(bounds.x + bounds.width);
+	let bottom = (bounds.y + bottom as f32 + TRIM_PADDING).min(bounds.y + bounds.height);
+	Some(Rect::new(left, top, right - left, bottom - top))
+}
+
+pub fn contains_point(vertices: &[Point], point: Point) -> bool {
+	let Point { x, y } = point;
 	let mut inside = false;
 	let vertex_count = vertices.len();
 	for i in 0..vertex_count {
 		let vertex1 = &vertices[i];
 		let vertex2 = &vertices[(i + 1) % vertex_count];
-		if (vertex1.1 > y) != (vertex2.1 > y) && x < (vertex2.0 - vertex1.0) * (y - vertex1.1) / (vertex2.1 - vertex1.1) + vertex1.0 {
+		if (vertex1.y > y) != (vertex2.y > y) && x < (vertex2.x - vertex1.x) * (y - vertex1.y) / (vertex2.y - vertex1.y) + vertex1.x {
 			inside = !inside;
 		}
 	}
@@ -0,0 +1,137 @@
+use std::{cell::RefCell, path::PathBuf, rc::Rc, sync::{Arc, Mutex}, thread};
+
+use clipboard::{ClipboardContext, ClipboardProvider};
+use rhai::{Engine, EvalAltResult};
+use serde::Deserialize;
+use winit::event_loop::EventLoopProxy;
+
+use crate::{ocr_handler::{configure_tesseract, recognize_region}, settings::{get_project_dirs, Keybind, TesseractSettings}, AppEvent, INITIALIZATION_ERRORS};
+
+const SCRIPTS_DIRECTORY: &str = "scripts";
+const SCRIPTS_FILE_NAME: &str = "scripts.toml";
+
+/// A script listed in `scripts/scripts.toml`.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct ScriptEntry {
+    pub name: String,
+    /// The script file, relative to the scripts directory
+    pub file: String,
+    #[serde(default)]
+    pub hotkey: Option<Keybind>,
+    /// Whether the script gets an entry in the tray menu
+    #[serde(default)]
+    pub tray: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ScriptsFile {
+    #[serde(default)]
+    scripts: Vec<ScriptEntry>,
+}
+
+/// Requests from a running script that have to be handled on the main thread.
+#[derive(Debug, Clone)]
+pub(crate) enum ScriptCommand {
+    SetSetting(String, bool),
+    Notify(String),
+}
+
+fn scripts_directory() -> PathBuf {
+    get_project_dirs().config_dir().join(SCRIPTS_DIRECTORY)
+}
+
+/// Reads the script list. A missing file just means there are no scripts.
+pub(crate) fn load_scripts() -> Vec<ScriptEntry> {
+    let Ok(contents) = std::fs::read_to_string(scripts_directory().join(SCRIPTS_FILE_NAME)) else {
+        return Vec::new();
+    };
+
+    match toml::from_str::<ScriptsFile>(&contents) {
+        Ok(file) => file.scripts,
+        Err(error) => {
+            eprintln!("Failed to parse {}: {}", SCRIPTS_FILE_NAME, error);
+            INITIALIZATION_ERRORS.lock().unwrap().push(format!("Failed to parse {}: {}", SCRIPTS_FILE_NAME, error));
+            Vec::new()
+        }
+    }
+}
+
+/// Runs user scripts on their own thread so a slow script never blocks the overlay.
+pub(crate) struct ScriptRunner {
+    scripts: Vec<ScriptEntry>,
+    last_ocr_text: Arc<Mutex<Option<String>>>,
+    loop_proxy: EventLoopProxy<AppEvent>,
+}
+
+impl ScriptRunner {
+    pub fn new(scripts: Vec<ScriptEntry>, loop_proxy: EventLoopProxy<AppEvent>) -> Self {
+        Self {
+            scripts,
+            last_ocr_text: Arc::new(Mutex::new(None)),
+            loop_proxy
+        }
+    }
+
+    /// Remembers the most recent OCR result so scripts can read it with `get_ocr_text()`.
+    pub fn set_last_ocr_text(&self, text: Option<&str>) {
+        if let Some(text) = text {
+            *self.last_ocr_text.lock().unwrap() = Some(text.to_string());
+        }
+    }
+
+    pub fn run(&self, index: usize, tesseract_settings: TesseractSettings) {
+        let Some(script) = self.scripts.get(index).cloned() else {
+            return;
+        };
+
+        let last_ocr_text = self.last_ocr_text.clone();
+        let loop_proxy = self.loop_proxy.clone();
+        thread::spawn(move || {
+            let engine = create_engine(last_ocr_text, loop_proxy.clone(), tesseract_settings);
+            if let Err(error) = engine.run_file(scripts_directory().join(&script.file)) {
+                eprintln!("Script '{}' failed: {}", script.name, error);
+                let _ = loop_proxy.send_event(AppEvent::Script(ScriptCommand::Notify(format!("Script '{}' failed: {}", script.name, error))));
+            }
+        });
+    }
+}
+
+fn create_engine(
+    last_ocr_text: Arc<Mutex<Option<String>>>,
+    loop_proxy: EventLoopProxy<AppEvent>,
+    tesseract_settings: TesseractSettings
+) -> Engine {
+    let mut engine = Engine::new();
+
+    // Creating a Tesseract instance is slow, so only do it if the script captures something
+    let tess_api: Rc<RefCell<Option<leptess::tesseract::TessApi>>> = Rc::new(RefCell::new(None));
+    engine.register_fn("capture_region", move |x: i64, y: i64, width: i64, height: i64| -> Result<String, Box<EvalAltResult>> {
+        if width <= 0 || height <= 0 {
+            return Err("capture_region needs a positive width and height".into());
+        }
+        let mut tess_api = tess_api.borrow_mut();
+        let tess_api = tess_api.get_or_insert_with(|| configure_tesseract(tesseract_settings.clone()));
+        Ok(recognize_region(tess_api, (x as i32, y as i32), (width as u32, height as u32))?)
+    });
+
+    engine.register_fn("get_ocr_text", move || -> String {
+        last_ocr_text.lock().unwrap().clone().unwrap_or_default()
+    });
+
+    let proxy = loop_proxy.clone();
+    engine.register_fn("set_setting", move |name: &str, value: bool| {
+        let _ = proxy.send_event(AppEvent::Script(ScriptCommand::SetSetting(name.to_string(), value)));
+    });
+
+    engine.register_fn("copy", |text: &str| -> Result<(), Box<EvalAltResult>> {
+        let mut ctx: ClipboardContext = ClipboardProvider::new().map_err(|e| format!("Unable to open the clipboard: {}", e))?;
+        ctx.set_contents(text.to_string()).map_err(|e| format!("Unable to copy: {}", e))?;
+        Ok(())
+    });
+
+    engine.register_fn("notify", move |message: &str| {
+        let _ = loop_proxy.send_event(AppEvent::Script(ScriptCommand::Notify(message.to_string())));
+    });
+
+    engine
+}
@@ -1,127 +1,6 @@
-use winit::{event::{ElementState, KeyEvent, MouseButton}, keyboard::{Key, NamedKey}};
+use winit::{event::{ElementState, KeyEvent, MouseButton}, keyboard::{Key, NamedKey}, window::CursorIcon};
 
-use crate::renderer::{IconContext, SmoothFadeAnimation};
-
-#[derive(Debug, Clone, Default, Copy, PartialEq)]
-pub(crate) struct Bounds {
-    pub x: i32,
-    pub y: i32,
-    pub width: i32,
-    pub height: i32
-}
-
-// Implement f32 -> i32 lossy conversion for Bounds
-pub trait BoundsNumber: Copy {
-    fn lossy_into(self) -> i32;
-}
-
-impl BoundsNumber for f32 {
-    fn lossy_into(self) -> i32 {
-        self as i32
-    }
-}
-
-impl BoundsNumber for i32 {
-    fn lossy_into(self) -> i32 {
-        self
-    }
-}
-
-impl Bounds {
-    // Not sure if there's a better way of doing this, but this works for now
-    pub fn new<X: BoundsNumber, Y: BoundsNumber, W: BoundsNumber, H: BoundsNumber>(x: X, y: Y, width: W, height: H) -> Self {
-        Self {
-            x: x.lossy_into(),
-            y: y.lossy_into(),
-            width: width.lossy_into(),
-            height: height.lossy_into()
-        }
-    }
-    
-    pub fn from_center<X: BoundsNumber, Y: BoundsNumber, W: BoundsNumber, H: BoundsNumber>(x: X, y: Y, width: W, height: H) -> Self {
-        let x = x.lossy_into();
-        let y = y.lossy_into();
-        let width = width.lossy_into();
-        let height = height.lossy_into();
-        Self {
-            x: x - width / 2,
-            y: y - height / 2,
-            width,
-            height
-        }
-    }
-
-    pub fn set_center<X: BoundsNumber, Y: BoundsNumber>(&mut self, x: X, y: Y) {
-        let x = x.lossy_into();
-        let y = y.lossy_into();
-        self.x = x - self.width / 2;
-        self.y = y - self.height / 2;
-    }
-
-    pub fn set_origin<X: BoundsNumber, Y: BoundsNumber>(&mut self, x: X, y: Y) {
-        self.x = x.lossy_into();
-        self.y = y.lossy_into();
-    }
-
-    pub fn set_size<W: BoundsNumber, H: BoundsNumber>(&mut self, width: W, height: H) {
-        self.width = width.lossy_into();
-        self.height = height.lossy_into();
-    }
-
-    pub fn to_positive_size(&self) -> Bounds {
-        let (mut x, mut y, mut width, mut height) = (self.x, self.y, self.width, self.height);
-        if width < 0 {
-            x += width;
-            width = -width;
-        }
-        if height < 0 {
-            y += height;
-            height = -height;
-        }
-        Bounds {
-            x, y, width, height
-        }
-    }
-
-    pub fn contains(&self, point: (i32, i32)) -> bool {
-        let (x, y) = point;
-        x >= self.x && x <= self.x + self.width && y >= self.y && y <= self.y + self.height
-    }
-
-    pub fn enclose_polygon(&mut self, polygon: &Polygon) {
-        if polygon.vertices.is_empty() {
-            *self = Bounds::default();
-            return;
-        }
-
-        let mut min_x = i32::MAX;
-        let mut min_y = i32::MAX;
-        let mut max_x = i32::MIN;
-        let mut max_y = i32::MIN;
-
-        for vertex in &polygon.vertices {
-            let x = vertex.x;
-            let y = vertex.y;
-            if x < min_x as f32 {
-                min_x = x as i32;
-            }
-            if y < min_y as f32 {
-                min_y = y as i32;
-            }
-            if x > max_x as f32 {
-                max_x = x as i32;
-            }
-            if y > max_y as f32 {
-                max_y = y as i32;
-            }
-        }
-
-        self.x = min_x;
-        self.y = min_y;
-        self.width = max_x - min_x;
-        self.height = max_y - min_y;
-    }
-}
+use crate::{geometry::{Point, Polygon, Rect, Vertex}, renderer::IconContext};
 
 enum PolygonHitResult {
     None,
@@ -143,39 +22,40 @@ enum DraggingEditState {
 
 #[derive(Debug, Clone, PartialEq)]
 struct NewBoxEditState {
-    start_location: (i32, i32),
-    start_origin: (f32, f32),
+    start_location: Point,
+    start_origin: Point,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 struct PolygonVertexEditState {
-    start_location: (i32, i32),
-    start_origin: (f32, f32),
+    start_location: Point,
+    start_origin: Point,
     vertex_index: usize
 }
 
 #[derive(Debug, Clone, PartialEq)]
 struct PolygonEdgeEditState {
-    start_location: (i32, i32),
-    start_origin: (f32, f32),
+    start_location: Point,
+    start_origin: Point,
     edge_index: usize
 }
 
 #[derive(Debug, Clone, PartialEq)]
 struct ShiftSelectionEditState {
-    start_location: (i32, i32),
-    start_origin: (f32, f32),
+    start_location: Point,
+    start_origin: Point,
     from_edge: bool
 }
 
 #[derive(Debug, Clone, Default)]
 pub(crate) struct Selection {
-    pub bounds: Bounds,
+    pub bounds: Rect,
     pub polygon: Polygon,
 
     pub mouse_down: bool,
     pub shift_held: bool,
     pub ctrl_held: bool,
+    pub alt_held: bool,
 
     drag_state: DraggingEditState,
 }
@@ -191,14 +71,20 @@ pub(crate) enum SelectionInputResult {
 impl Selection {
     pub fn reset(&mut self) {
         self.polygon.clear();
-        self.bounds = Bounds::default();
+        self.bounds = Rect::default();
+
+        self.reset_input_state();
+    }
 
+    /// Forgets any drag or held modifier, keeping the selection itself.
+    pub fn reset_input_state(&mut self) {
         self.mouse_down = false;
 
         self.shift_held = false;
         self.drag_state = DraggingEditState::None;
         
         self.ctrl_held = false;
+        self.alt_held = false;
     }
 
     pub fn cursor_moved(
@@ -208,9 +94,10 @@ impl Selection {
         icon_context: &mut IconContext
     ) -> SelectionInputResult {
         // If shift is held, move the selection instead of resizing
-        let (x, y) = (mouse_position.0, mouse_position.1);
+        let mouse = Point::from(mouse_position);
+        let (x, y) = (mouse.x, mouse.y);
 
-        let hit = self.detect_polygon_hit(mouse_position);
+        let hit = self.detect_hit(mouse_position, icon_context.settings.use_polygon, icon_context.settings.use_ellipse);
         match hit {
             PolygonHitResult::Vertex(index) => {
                 self.polygon.hovered_edge = None;
@@ -233,15 +120,37 @@ impl Selection {
                     return SelectionInputResult::Nothing;
                 }
 
-                self.bounds.x = state.start_origin.0 as i32;
-                self.bounds.y = state.start_origin.1 as i32;
-                self.bounds.width = x - self.bounds.x;
-                self.bounds.height = y - self.bounds.y;
-                self.polygon.set_from_bounds(&self.bounds);
+                let (mut width, mut height) = (x - state.start_origin.x, y - state.start_origin.y);
+                // Ctrl only starts a polygon in polygon mode, so in box mode it's free to lock the box to a square
+                let aspect_ratio = if self.ctrl_held && !icon_context.settings.use_polygon {
+                    Some(1.0)
+                } else {
+                    parse_aspect_ratio(&icon_context.settings.tesseract_settings.selection_aspect_ratio)
+                };
+                if let Some(aspect_ratio) = aspect_ratio {
+                    // The box follows whichever side the mouse pulls furthest, keeping the corner it was dragged toward
+                    if width.abs() / aspect_ratio > height.abs() {
+                        height = height.signum() * width.abs() / aspect_ratio;
+                    } else {
+                        width = width.signum() * height.abs() * aspect_ratio;
+                    }
+                } else {
+                    // Snapping would break the aspect ratio, so it's only done for free boxes
+                    let corner = self.snap(mouse, state.start_origin, screen_size, icon_context);
+                    (width, height) = (corner.x - state.start_origin.x, corner.y - state.start_origin.y);
+                }
+
+                self.bounds.set_origin(state.start_origin.x, state.start_origin.y);
+                self.bounds.set_size(width, height);
+                if icon_context.settings.use_ellipse {
+                    self.polygon.set_from_ellipse(&self.bounds);
+                } else {
+                    self.polygon.set_from_rect(&self.bounds);
+                }
                 
                 if self.shift_held {
                     self.drag_state = DraggingEditState::ShiftSelection(ShiftSelectionEditState {
-                        start_location: (x, y),
+                        start_location: mouse,
                         start_origin: self.polygon.get_origin(),
                         from_edge: false
                     });
@@ -253,10 +162,7 @@ impl Selection {
                     return SelectionInputResult::Nothing;
                 }
                 
-                let (start_x, start_y) = state.start_location;
-                let (start_bounds_x, start_bounds_y) = state.start_origin;
-                let (dx, dy) = (x - start_x, y - start_y);
-                self.polygon.set_origin(start_bounds_x + dx as f32, start_bounds_y + dy as f32);
+                self.polygon.set_origin(state.start_origin + (mouse - state.start_location));
                 self.polygon.clamp_to_screen(screen_size);
                 
                 if !self.shift_held {
@@ -265,13 +171,13 @@ impl Selection {
                         self.drag_state = DraggingEditState::ShiftPolygonEdge(PolygonEdgeEditState {
                             edge_index: self.get_nearest_edge_index(x, y),
                             start_origin: origin,
-                            start_location: (origin.0 as i32, origin.1 as i32)
+                            start_location: origin
                         });
                     } else {
                         self.drag_state = DraggingEditState::PolygonVertex(PolygonVertexEditState {
                             vertex_index: self.get_nearest_vertex_index(x, y),
                             start_origin: origin,
-                            start_location: (origin.0 as i32, origin.1 as i32)
+                            start_location: origin
                         });
                     }
                 }
@@ -285,10 +191,7 @@ impl Selection {
                 }
 
                 if icon_context.settings.use_polygon {
-                    let (start_x, start_y) = edge.start_location;
-                    let (start_bounds_x, start_bounds_y) = edge.start_origin;
-                    let (dx, dy) = (x - start_x, y - start_y);
-                    self.polygon.set_edge_origin(edge.edge_index, (start_bounds_x + dx as f32, start_bounds_y + dy as f32));
+                    self.polygon.set_edge_origin(edge.edge_index, edge.start_origin + (mouse - edge.start_location));
                     self.polygon.clamp_to_screen(screen_size);
                     
                     if !self.shift_held {
@@ -300,12 +203,15 @@ impl Selection {
                     let first_vertex = edge.edge_index;
                     let second_vertex = (edge.edge_index + 1) % self.polygon.vertices.len();
 
+                    // The edge moves like a corner of it, with the opposite corner staying put
+                    let opposite = self.polygon.vertices[(edge.edge_index + 2) % self.polygon.vertices.len()].position();
+                    let snap = |point: Point| self.snap(point, opposite, screen_size, icon_context);
                     if first_vertex % 2 == 0 {
-                        let y = edge.start_origin.1 + y as f32 - edge.start_location.1 as f32;
+                        let y = snap(Point::new(self.polygon.vertices[first_vertex].x, edge.start_origin.y + y - edge.start_location.y)).y;
                         self.polygon.vertices[first_vertex].y = y;
                         self.polygon.vertices[second_vertex].y = y;
                     } else {
-                        let x = edge.start_origin.0 + x as f32 - edge.start_location.0 as f32;
+                        let x = snap(Point::new(edge.start_origin.x + x - edge.start_location.x, self.polygon.vertices[first_vertex].y)).x;
                         self.polygon.vertices[first_vertex].x = x;
                         self.polygon.vertices[second_vertex].x = x;
                     }
@@ -313,7 +219,7 @@ impl Selection {
 
                 if self.shift_held && !icon_context.settings.use_polygon {
                     self.drag_state = DraggingEditState::ShiftSelection(ShiftSelectionEditState {
-                        start_location: (x, y),
+                        start_location: mouse,
                         start_origin: self.polygon.get_origin(),
                         from_edge: true
                     });
@@ -328,43 +234,43 @@ impl Selection {
                 }
 
                 if icon_context.settings.use_polygon {
-                    self.polygon.vertices[vertex.vertex_index].x = x as f32;
-                    self.polygon.vertices[vertex.vertex_index].y = y as f32;
+                    self.polygon.vertices[vertex.vertex_index].x = x;
+                    self.polygon.vertices[vertex.vertex_index].y = y;
                     
                     let pos = self.should_merge_surrounding_edges(vertex.vertex_index);
-                    if pos.is_some() {
-                        let (x, y) = pos.unwrap();
-                        self.polygon.vertices[vertex.vertex_index].x = x;
-                        self.polygon.vertices[vertex.vertex_index].y = y;
+                    if let Some(pos) = pos {
+                        self.polygon.vertices[vertex.vertex_index].x = pos.x;
+                        self.polygon.vertices[vertex.vertex_index].y = pos.y;
                     }
 
                     let deduplicated_pos = self.polygon.should_deduplicate(vertex.vertex_index);
-                    if deduplicated_pos.is_some() {
-                        let (x, y) = deduplicated_pos.unwrap();
-                        self.polygon.vertices[vertex.vertex_index].x = x;
-                        self.polygon.vertices[vertex.vertex_index].y = y;
+                    if let Some(pos) = deduplicated_pos {
+                        self.polygon.vertices[vertex.vertex_index].x = pos.x;
+                        self.polygon.vertices[vertex.vertex_index].y = pos.y;
                     }
                 } else {
                     let curr_vertex = vertex.vertex_index;
                     let next_vertex = (vertex.vertex_index + 1) % self.polygon.vertices.len();
                     let prev_vertex = (vertex.vertex_index + self.polygon.vertices.len() - 1) % self.polygon.vertices.len();
                     
-                    let (dx, dy) = (x - vertex.start_location.0, y - vertex.start_location.1);
-                    self.polygon.vertices[curr_vertex].x = vertex.start_origin.0 + dx as f32;
-                    self.polygon.vertices[curr_vertex].y = vertex.start_origin.1 + dy as f32;
+                    let position = vertex.start_origin + (mouse - vertex.start_location);
+                    let opposite = self.polygon.vertices[(curr_vertex + 2) % self.polygon.vertices.len()].position();
+                    let position = self.snap(position, opposite, screen_size, icon_context);
+                    self.polygon.vertices[curr_vertex].x = position.x;
+                    self.polygon.vertices[curr_vertex].y = position.y;
                     
                     if vertex.vertex_index % 2 == 0 {
-                        self.polygon.vertices[prev_vertex].x = vertex.start_origin.0 + dx as f32;
-                        self.polygon.vertices[next_vertex].y = vertex.start_origin.1 + dy as f32;
+                        self.polygon.vertices[prev_vertex].x = position.x;
+                        self.polygon.vertices[next_vertex].y = position.y;
                     } else {
-                        self.polygon.vertices[next_vertex].x = vertex.start_origin.0 + dx as f32;
-                        self.polygon.vertices[prev_vertex].y = vertex.start_origin.1 + dy as f32;
+                        self.polygon.vertices[next_vertex].x = position.x;
+                        self.polygon.vertices[prev_vertex].y = position.y;
                     }
                 }
 
                 if self.shift_held {
                     self.drag_state = DraggingEditState::ShiftSelection(ShiftSelectionEditState {
-                        start_location: (x, y),
+                        start_location: mouse,
                         start_origin: self.polygon.get_origin(),
                         from_edge: false
                     });
@@ -376,12 +282,12 @@ impl Selection {
 
             DraggingEditState::CreatePolygon => {
                 if self.polygon.vertices.len() <= 1 {
-                    self.polygon.vertices.push(Vertex::new(x as f32, y as f32));
+                    self.polygon.vertices.push(Vertex::new(x, y));
                 }
 
                 let last_vertex = self.polygon.vertices.iter_mut().last().unwrap();
-                last_vertex.x = x as f32;
-                last_vertex.y = y as f32;
+                last_vertex.x = x;
+                last_vertex.y = y;
 
                 self.bounds.enclose_polygon(&self.polygon);
 
@@ -396,11 +302,11 @@ impl Selection {
         SelectionInputResult::Nothing
     }
 
-    pub fn get_nearest_vertex_index(&self, x: i32, y: i32) -> usize {
+    pub fn get_nearest_vertex_index(&self, x: f32, y: f32) -> usize {
         let mut min_distance = f32::INFINITY;
         let mut min_index = 0;
         for (i, vertex) in self.polygon.vertices.iter().enumerate() {
-            let distance = ((vertex.x - x as f32).powi(2) + (vertex.y - y as f32).powi(2)).sqrt();
+            let distance = vertex.position().distance_to(Point::new(x, y));
             if distance < min_distance {
                 min_distance = distance;
                 min_index = i;
@@ -410,7 +316,7 @@ impl Selection {
         min_index
     }
 
-    pub fn get_nearest_edge_index(&self, x: i32, y: i32) -> usize {
+    pub fn get_nearest_edge_index(&self, x: f32, y: f32) -> usize {
         let mut min_distance = f32::INFINITY;
         let mut min_index = 0;
         for i in 0..self.polygon.vertices.len() {
@@ -421,7 +327,7 @@ impl Selection {
 
             let dx = x2 - x1;
             let dy = y2 - y1;
-            let d = ((x1 - x as f32) * dy - (y1 - y as f32) * dx).abs() / (dx * dx + dy * dy).sqrt();
+            let d = ((x1 - x) * dy - (y1 - y) * dx).abs() / (dx * dx + dy * dy).sqrt();
             if d < min_distance {
                 min_distance = d;
                 min_index = i;
@@ -431,26 +337,33 @@ impl Selection {
         min_index
     }
 
-    pub fn change_use_polygon(&mut self, new_use_polygon: bool) {
-        if !new_use_polygon {
-            self.polygon.set_from_bounds(&self.bounds);
+    /// Reshapes the selection after switching between boxes, ellipses, and polygons. Polygons keep their
+    /// shape, while boxes and ellipses are redrawn in the selection's bounds.
+    pub fn change_shape(&mut self, use_polygon: bool, use_ellipse: bool) {
+        if use_polygon || self.polygon.vertices.is_empty() {
+            return;
+        }
+        if use_ellipse {
+            self.polygon.set_from_ellipse(&self.bounds);
+        } else {
+            self.polygon.set_from_rect(&self.bounds);
         }
     }
 
-    fn check_edge_split_input(&mut self, x: i32, y: i32, index: usize, use_polygon: bool) {
+    fn check_edge_split_input(&mut self, x: f32, y: f32, index: usize, use_polygon: bool) {
         if !self.shift_held && use_polygon {
             // Split the edge
-            let new_vertex = Vertex::new(x as f32, y as f32);
+            let new_vertex = Vertex::new(x, y);
 
             self.polygon.vertices.insert(index + 1, new_vertex);
             self.drag_state = DraggingEditState::PolygonVertex(PolygonVertexEditState {
-                start_location: (x, y),
-                start_origin: self.polygon.vertices[index].pos_tuple(),
+                start_location: Point::new(x, y),
+                start_origin: self.polygon.vertices[index].position(),
                 vertex_index: index + 1
             });
         } else {
             self.drag_state = DraggingEditState::ShiftPolygonEdge(PolygonEdgeEditState {
-                start_location: (x, y),
+                start_location: Point::new(x, y),
                 start_origin: self.polygon.get_edge_origin(index),
                 edge_index: index
             });
@@ -462,17 +375,19 @@ impl Selection {
         state: ElementState,
         button: MouseButton,
         mouse_position: (i32, i32),
+        screen_size: (u32, u32),
         icon_context: &mut IconContext
     ) -> SelectionInputResult {
-        let (x, y) = (mouse_position.0, mouse_position.1);
+        let mouse = Point::from(mouse_position);
+        let (x, y) = (mouse.x, mouse.y);
 
         let mut result = SelectionInputResult::Nothing;
 
         if state == winit::event::ElementState::Pressed {
             if self.drag_state == DraggingEditState::CreatePolygon {
-                self.polygon.vertices.push(Vertex::new(x as f32, y as f32));
+                self.polygon.vertices.push(Vertex::new(x, y));
             } else {
-                let hit = self.detect_polygon_hit(mouse_position);
+                let hit = self.detect_hit(mouse_position, icon_context.settings.use_polygon, icon_context.settings.use_ellipse);
                 match hit {
                     PolygonHitResult::Vertex(index) => {
                         if button == MouseButton::Right && icon_context.settings.use_polygon {
@@ -482,8 +397,8 @@ impl Selection {
                             self.polygon.vertices.remove(index);
                         } else {
                             self.drag_state = DraggingEditState::PolygonVertex(PolygonVertexEditState {
-                                start_location: (x, y),
-                                start_origin: self.polygon.vertices[index].pos_tuple(),
+                                start_location: mouse,
+                                start_origin: self.polygon.vertices[index].position(),
                                 vertex_index: index
                             });
                         }
@@ -504,25 +419,23 @@ impl Selection {
                         if !self.shift_held {
                             if self.ctrl_held && icon_context.settings.use_polygon {
                                 self.polygon.clear();
-                                self.polygon.vertices.push(Vertex::new(x as f32, y as f32));
-                                self.polygon.vertices.push(Vertex::new(x as f32, y as f32));
+                                self.polygon.vertices.push(Vertex::new(x, y));
+                                self.polygon.vertices.push(Vertex::new(x, y));
                                 self.drag_state = DraggingEditState::CreatePolygon;
                             } else {
-                                self.bounds.x = x;
-                                self.bounds.y = y;
-                                self.bounds.width = 0;
-                                self.bounds.height = 0;
-                                self.polygon.set_from_bounds(&self.bounds);
+                                let start = self.snap(mouse, mouse, screen_size, icon_context);
+                                self.bounds = Rect::new(start.x, start.y, 0., 0.);
+                                self.polygon.set_from_rect(&self.bounds);
 
                                 result = SelectionInputResult::CompletelyMoved;
                                 self.drag_state = DraggingEditState::NewBox(NewBoxEditState {
-                                    start_location: (x, y),
+                                    start_location: mouse,
                                     start_origin: self.polygon.get_origin()
                                 });
                             }
                         } else {
                             self.drag_state = DraggingEditState::ShiftSelection(ShiftSelectionEditState {
-                                start_location: (x, y),
+                                start_location: mouse,
                                 start_origin: self.polygon.get_origin(),
                                 from_edge: false
                             });
@@ -598,6 +511,9 @@ impl Selection {
             Key::Named(NamedKey::Shift) => {
                 self.shift_held = event.state == winit::event::ElementState::Pressed;
             }
+            Key::Named(NamedKey::Alt) => {
+                self.alt_held = event.state == winit::event::ElementState::Pressed;
+            }
             Key::Named(NamedKey::Control) => {
                 self.ctrl_held = event.state == winit::event::ElementState::Pressed;
                 if self.drag_state == DraggingEditState::CreatePolygon && !self.ctrl_held {
@@ -621,7 +537,7 @@ impl Selection {
         self.drag_state = DraggingEditState::None;
     }
 
-    fn should_merge_surrounding_edges(&self, vertex_index: usize) -> Option<(f32, f32)> {
+    fn should_merge_surrounding_edges(&self, vertex_index: usize) -> Option<Point> {
         // If the two surrounding edges are within a small angle of each other, merge them
         let vertex = &self.polygon.vertices[vertex_index];
         let prev_vertex = &self.polygon.vertices[(vertex_index + self.polygon.vertices.len() - 1) % self.polygon.vertices.len()];
@@ -645,7 +561,7 @@ impl Selection {
             // Additionally, check if the vertex is close enough, so it doesn't depend as much on the line length.
             let distance = ((vertex.x - x).powi(2) + (vertex.y - y).powi(2)).sqrt();
             if distance < if self.shift_held || self.ctrl_held { 4.0 } else { 15.0 } {
-                return Some((x, y));
+                return Some(Point::new(x, y));
             }
 
             None
@@ -654,6 +570,99 @@ impl Selection {
         }
     }
 
+    /// Where a dragged corner ends up after snapping to text lines and the grid, unless Alt is held.
+    fn snap(&self, corner: Point, opposite: Point, screen_size: (u32, u32), icon_context: &IconContext) -> Point {
+        let settings = &icon_context.settings;
+        if self.alt_held {
+            return corner;
+        }
+
+        let (mut x, mut y) = (None, None);
+        if settings.snap_to_text_lines {
+            (x, y) = snap_to_text_lines(corner, opposite, &icon_context.text_layout.lines);
+        }
+        // Text lines are the more specific target, so the grid only applies to what didn't snap to one
+        if settings.snap_to_grid {
+            let grid_size = settings.tesseract_settings.snap_grid_size;
+            x = x.or_else(|| snap_to_grid(corner.x, screen_size.0, grid_size));
+            y = y.or_else(|| snap_to_grid(corner.y, screen_size.1, grid_size));
+        }
+
+        Point::new(x.unwrap_or(corner.x), y.unwrap_or(corner.y))
+    }
+
+    /// Whether an edge or vertex is being placed under the cursor, i.e. while drawing, resizing, or
+    /// adding polygon vertices, but not while moving the whole selection.
+    pub fn is_adjusting(&self) -> bool {
+        match self.drag_state {
+            DraggingEditState::NewBox(_) | DraggingEditState::PolygonVertex(_) | DraggingEditState::ShiftPolygonEdge(_) => self.mouse_down,
+            DraggingEditState::CreatePolygon => true,
+            _ => false
+        }
+    }
+
+    /// The cursor to show over a resize handle in box mode, or while one is being dragged.
+    pub fn resize_cursor(&self, use_polygon: bool) -> Option<CursorIcon> {
+        if use_polygon || self.shift_held {
+            return None;
+        }
+
+        let (vertex, edge) = match &self.drag_state {
+            DraggingEditState::None => (self.polygon.hovered_vertex, self.polygon.hovered_edge),
+            DraggingEditState::PolygonVertex(state) => (Some(state.vertex_index), None),
+            DraggingEditState::ShiftPolygonEdge(state) => (None, Some(state.edge_index)),
+            _ => return None
+        };
+
+        if let Some(vertex) = vertex.and_then(|index| self.polygon.vertices.get(index)) {
+            // Dragging can flip the rectangle, so the corner is found from its position rather than its index
+            let count = self.polygon.vertices.len() as f32;
+            let center = Point::new(
+                self.polygon.vertices.iter().map(|v| v.x).sum::<f32>() / count,
+                self.polygon.vertices.iter().map(|v| v.y).sum::<f32>() / count
+            );
+            let same_sign = (vertex.x - center.x) * (vertex.y - center.y) >= 0.0;
+            return Some(if same_sign { CursorIcon::NwseResize } else { CursorIcon::NeswResize });
+        }
+        // Even edges run along the top and bottom since the rectangle's vertices alternate which axis they share
+        edge.map(|index| if index % 2 == 0 { CursorIcon::NsResize } else { CursorIcon::EwResize })
+    }
+
+    fn detect_hit(&self, mouse_position: (i32, i32), use_polygon: bool, use_ellipse: bool) -> PolygonHitResult {
+        if use_polygon {
+            self.detect_polygon_hit(mouse_position)
+        } else if use_ellipse {
+            // Box mode's edits keep the rectangle's corners square, which would break an ellipse apart,
+            // so ellipses are redrawn or moved instead
+            PolygonHitResult::None
+        } else {
+            self.detect_handle_hit(mouse_position)
+        }
+    }
+
+    /// In box mode, the rectangle has handles at its corners and the middle of its edges, which can be
+    /// grabbed from further away than the edges themselves.
+    fn detect_handle_hit(&self, mouse_position: (i32, i32)) -> PolygonHitResult {
+        let margin = 8.0;
+        let mouse = Point::from(mouse_position);
+        let count = self.polygon.vertices.len();
+
+        if let Some(index) = self.polygon.vertices.iter().position(|vertex| vertex.position().distance_to(mouse) < margin) {
+            return PolygonHitResult::Vertex(index);
+        }
+
+        for i in 0..count {
+            let vertex1 = self.polygon.vertices[i].position();
+            let vertex2 = self.polygon.vertices[(i + 1) % count].position();
+            let midpoint = Point::new((vertex1.x + vertex2.x) / 2.0, (vertex1.y + vertex2.y) / 2.0);
+            if midpoint.distance_to(mouse) < margin {
+                return PolygonHitResult::Edge(i);
+            }
+        }
+
+        self.detect_polygon_hit(mouse_position)
+    }
+
     fn detect_polygon_hit(&self, mouse_position: (i32, i32)) -> PolygonHitResult {
         let margin = 5.0;
 
@@ -686,256 +695,54 @@ impl Selection {
     }
 }
 
-impl Into<Bounds> for (u32, u32) {
-    fn into(self) -> Bounds {
-        Bounds::new(0, 0, self.0 as i32, self.1 as i32)
-    }
-}
-
-#[derive(Debug, Clone)]
-pub(crate) struct Polygon {
-    pub vertices: Vec<Vertex>,
-    pub hovered_vertex: Option<usize>,
-    pub hovered_edge: Option<usize>
-}
-
-impl Default for Polygon {
-    fn default() -> Self {
-        // The default state includes some vertices so we don't need to immediately resize the buffer
-        Self {
-            vertices: vec![
-                Vertex::new(-50.0, -50.0),
-                Vertex::new(-50.0, -50.0),
-                Vertex::new(-50.0, -50.0),
-                Vertex::new(-50.0, -50.0)
-            ],
-            hovered_vertex: None,
-            hovered_edge: None
-        }
+/// Parses an aspect ratio like "16:9" into width divided by height.
+fn parse_aspect_ratio(text: &str) -> Option<f32> {
+    let (width, height) = text.split_once(':')?;
+    let (width, height) = (width.trim().parse::<f32>().ok()?, height.trim().parse::<f32>().ok()?);
+    if width > 0.0 && height > 0.0 {
+        Some(width / height)
+    } else {
+        None
     }
 }
 
-#[derive(Debug, Clone, Default)]
-pub(crate) struct Vertex {
-    pub x: f32,
-    pub y: f32,
-
-    pub vertex_highlight: SmoothFadeAnimation,
-    pub edge_highlight: SmoothFadeAnimation
+/// How close an edge has to be to a line of text to snap to it, in pixels
+const SNAP_DISTANCE: f32 = 6.;
+/// How far outside the line snapped edges go, so they don't touch the text
+const SNAP_PADDING: f32 = 2.;
+
+/// Finds the nearest edge of a text line the box covers for each coordinate of a corner, if one is close.
+/// Edges above or left of the opposite corner snap to where lines start, and the others to where they end.
+fn snap_to_text_lines(corner: Point, opposite: Point, lines: &[Rect]) -> (Option<f32>, Option<f32>) {
+    let (left, right) = (corner.x.min(opposite.x), corner.x.max(opposite.x));
+    let (top, bottom) = (corner.y.min(opposite.y), corner.y.max(opposite.y));
+    let nearest = |candidates: Vec<f32>, value: f32| candidates.into_iter()
+        .filter(|candidate| (candidate - value).abs() < SNAP_DISTANCE)
+        .min_by(|a, b| (a - value).abs().total_cmp(&(b - value).abs()));
+
+    let snapped_y = nearest(lines.iter()
+        .filter(|line| line.x <= right && line.x + line.width >= left)
+        .map(|line| if corner.y <= opposite.y { line.y - SNAP_PADDING } else { line.y + line.height + SNAP_PADDING })
+        .collect(), corner.y);
+    let snapped_x = nearest(lines.iter()
+        .filter(|line| line.y <= bottom && line.y + line.height >= top)
+        .map(|line| if corner.x <= opposite.x { line.x - SNAP_PADDING } else { line.x + line.width + SNAP_PADDING })
+        .collect(), corner.x);
+
+    (snapped_x, snapped_y)
 }
 
-#[repr(C)]
-#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct GPUVertex {
-    pub position: [f32; 2],
-    pub animation: u32,
-    pub _padding: u32
-}
+/// How close an edge has to be to the screen's edges, halves, or thirds to snap to them, in pixels
+const SCREEN_SNAP_DISTANCE: f32 = 12.;
 
-impl GPUVertex {
-    pub fn new(x: f32, y: f32, animation: u32) -> Self {
-        Self {
-            position: [x, y],
-            animation,
-            _padding: 0
-        }
-    }
-}
-
-impl Vertex {
-    pub fn new(x: f32, y: f32) -> Self {
-        Self {
-            x, y,
-            vertex_highlight: SmoothFadeAnimation::new(false),
-            edge_highlight: SmoothFadeAnimation::new(false)
-        }
-    }
+/// Snaps a coordinate to the screen's edges, halves, or thirds along an axis of the given length if
+/// it's close to one, and otherwise to the nearest multiple of the grid size (unless that's 0).
+fn snap_to_grid(value: f32, screen_length: u32, grid_size: u32) -> Option<f32> {
+    let screen_length = screen_length as f32;
+    let screen_snap = [0., 1. / 3., 0.5, 2. / 3., 1.].into_iter()
+        .map(|fraction| (fraction * screen_length).round())
+        .filter(|candidate| (candidate - value).abs() < SCREEN_SNAP_DISTANCE)
+        .min_by(|a, b| (a - value).abs().total_cmp(&(b - value).abs()));
 
-    pub fn as_gpu_vertex(&self) -> GPUVertex {
-        GPUVertex::new(self.x, self.y, self.get_animation_int())
-    }
-
-    fn get_animation_int(&self) -> u32 {
-        // We encode vertex and edge animations into the same integer
-        // The first 16 bits are this vertex's opacity, and the next 16 bits are the opacity of the edge connecting this vertex and the next one.
-        let vertex_opacity = (self.vertex_highlight.get_opacity() * 65535.0) as u32;
-        let edge_opacity = (self.edge_highlight.get_opacity() * 65535.0) as u32;
-        vertex_opacity << 16 | edge_opacity
-    }
-
-    fn update(&mut self, delta: std::time::Duration, edge_highlight: bool, vertex_highlight: bool) {
-        self.vertex_highlight.update(delta, vertex_highlight);
-        self.edge_highlight.update(delta, edge_highlight);
-    }
-
-    fn pos_tuple(&self) -> (f32, f32) {
-        (self.x, self.y)
-    }
+    screen_snap.or_else(|| (grid_size > 0).then(|| (value / grid_size as f32).round() * grid_size as f32))
 }
-
-impl Polygon {
-    pub fn new() -> Self {
-        Self {
-            vertices: Vec::new(),
-            hovered_edge: None,
-            hovered_vertex: None
-        }
-    }
-
-    pub fn get_device_coords_polygon(&self, screen_size: (u32, u32)) -> Vec<GPUVertex> {
-        let mut vertices = Vec::new();
-        for vertex in &self.vertices {
-            let mut vertex = vertex.clone();
-            vertex.x /= screen_size.0 as f32;
-            vertex.y /= screen_size.1 as f32;
-            vertices.push(vertex.as_gpu_vertex());
-        }
-
-        vertices
-    }
-
-    pub fn clear(&mut self) {
-        self.vertices.clear();
-    }
-
-    pub fn get_origin(&self) -> (f32, f32) {
-        let min_x = self.vertices.iter().map(|v| v.x).fold(f32::INFINITY, f32::min);
-        let min_y = self.vertices.iter().map(|v| v.y).fold(f32::INFINITY, f32::min);
-        (min_x, min_y)
-    }
-
-    pub fn set_origin(&mut self, x: f32, y: f32) {
-        let current_origin = self.get_origin();
-
-        for vertex in self.vertices.iter_mut() {
-            vertex.x -= current_origin.0;
-            vertex.y -= current_origin.1;
-            vertex.x += x;
-            vertex.y += y;
-        }
-    }
-
-    pub fn move_by(&mut self, dx: f32, dy: f32) {
-        for vertex in self.vertices.iter_mut() {
-            vertex.x += dx;
-            vertex.y += dy;
-        }
-    }
-
-    pub fn clamp_to_screen(&mut self, screen_size: (u32, u32)) -> () {
-        if self.vertices.is_empty() {
-            return;
-        }
-
-        let min_x = self.vertices.iter().map(|v| v.x).fold(f32::INFINITY, f32::min);
-        let min_y = self.vertices.iter().map(|v| v.y).fold(f32::INFINITY, f32::min);
-        let max_x = self.vertices.iter().map(|v| v.x).fold(f32::NEG_INFINITY, f32::max);
-        let max_y = self.vertices.iter().map(|v| v.y).fold(f32::NEG_INFINITY, f32::max);
-
-        let mut dx = 0.0;
-        let mut dy = 0.0;
-
-        if min_x < 0.0 {
-            dx = -min_x;
-        }
-        if min_y < 0.0 {
-            dy = -min_y;
-        }
-
-        if max_x > screen_size.0 as f32 {
-            dx = screen_size.0 as f32 - max_x;
-        }
-        if max_y > screen_size.1 as f32 {
-            dy = screen_size.1 as f32 - max_y;
-        }
-
-        self.move_by(dx, dy);
-
-        // If any vertices are still outside the screen, just clamp them
-        for vertex in self.vertices.iter_mut() {
-            if vertex.x < 0.0 {
-                vertex.x = 0.0;
-            }
-            if vertex.y < 0.0 {
-                vertex.y = 0.0;
-            }
-            if vertex.x > screen_size.0 as f32 {
-                vertex.x = screen_size.0 as f32;
-            }
-            if vertex.y > screen_size.1 as f32 {
-                vertex.y = screen_size.1 as f32;
-            }
-        }
-    }
-
-    pub fn should_deduplicate(&self, vertex_index: usize) -> Option<(f32, f32)> {
-        let margin = 5.0;
-        let vertex = &self.vertices[vertex_index];
-        for i in 0..self.vertices.len() {
-            if i == vertex_index {
-                continue;
-            }
-            let other_vertex = &self.vertices[i];
-            if (vertex.x - other_vertex.x).abs() < margin && (vertex.y - other_vertex.y).abs() < margin {
-                return Some((other_vertex.x, other_vertex.y));
-            }
-        }
-
-        None
-    }
-
-    pub fn deduplicate(&mut self) {
-        let mut i = 0;
-        while i < self.vertices.len() {
-            if self.should_deduplicate(i).is_some() {
-                self.vertices.remove(i);
-            } else {
-                i += 1;
-            }
-        }
-    }
-
-    pub fn set_from_bounds(&mut self, bounds: &Bounds) {
-        self.vertices = vec![
-            Vertex::new(bounds.x as f32, bounds.y as f32),
-            Vertex::new(bounds.x as f32 + bounds.width as f32, bounds.y as f32),
-            Vertex::new(bounds.x as f32 + bounds.width as f32, bounds.y as f32 + bounds.height as f32),
-            Vertex::new(bounds.x as f32, bounds.y as f32 + bounds.height as f32)
-        ];
-    }
-
-    pub fn get_edge_origin(&self, edge_index: usize) -> (f32, f32) {
-        let vertex1 = &self.vertices[edge_index];
-        let vertex2 = &self.vertices[(edge_index + 1) % self.vertices.len()];
-        let x = (vertex1.x + vertex2.x) / 2.0;
-        let y = (vertex1.y + vertex2.y) / 2.0;
-        (x, y)
-    }
-
-    pub fn set_edge_origin(&mut self, edge_index: usize, origin: (f32, f32)) {
-        let current_origin = self.get_edge_origin(edge_index);
-        let dx = origin.0 - current_origin.0;
-        let dy = origin.1 - current_origin.1;
-
-        let vertex1 = &mut self.vertices[edge_index];
-        vertex1.x += dx;
-        vertex1.y += dy;
-        
-        let vertices = self.vertices.len();
-        let vertex2 = &mut self.vertices[(edge_index + 1) % vertices];
-        vertex2.x += dx;
-        vertex2.y += dy;
-    }
-
-    pub fn as_gpu_vertices(&self) -> Vec<GPUVertex> {
-        self.vertices.iter().map(|v| v.as_gpu_vertex()).collect()
-    }
-
-    pub fn update(&mut self, delta: std::time::Duration) {
-        let vertices = self.vertices.len();
-        for (vertex, i) in self.vertices.iter_mut().zip(0..) {
-            let prev_vertex_index = (i + vertices - 1) % vertices;
-            vertex.update(delta, self.hovered_edge.is_some_and(|idx| idx == prev_vertex_index), self.hovered_vertex.is_some_and(|idx| idx == i));
-        }
-    }
-}
\ No newline at end of file
@@ -1,27 +1,77 @@
-use std::sync::{Arc, Mutex};
+use std::{path::PathBuf, sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex}};
 use include_dir::{include_dir, Dir};
 
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 
-use crate::INITIALIZATION_ERRORS;
+use crate::{anki::AnkiSettings, clipboard_watcher::ClipboardWatcherMode, llm::LlmSettings, text_formatting::{TesseractExportMode, TypographySettings}, upload::UploadSettings, INITIALIZATION_ERRORS};
 
 static SETTINGS_FILE_NAME: &str = "settings.bin";
 static TESSERACT_SETTNGS_FILE_NAME: &str = "tesseract_settings.toml";
 
 static DEFAULT_CONFIG_FILES: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/default_config_files");
 
+/// Which variant of a language's traineddata is loaded.
+#[derive(Debug, Serialize, Copy, Clone, Deserialize, PartialEq, Eq, Hash, Default)]
+pub enum ModelQuality {
+    /// `[code].traineddata` directly under /tessdata
+    #[default]
+    Standard,
+    /// The smaller, faster models from tessdata_fast, under /tessdata/fast
+    Fast,
+    /// The slower, more accurate models from tessdata_best, under /tessdata/best
+    Best
+}
+
+impl ModelQuality {
+    /// The directory under /tessdata the models are stored in, if any.
+    pub fn directory(&self) -> Option<&'static str> {
+        match self {
+            ModelQuality::Standard => None,
+            ModelQuality::Fast => Some("fast"),
+            ModelQuality::Best => Some("best")
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ModelQuality::Standard => "Standard",
+            ModelQuality::Fast => "Fast",
+            ModelQuality::Best => "Best"
+        }
+    }
+
+    fn next(&self) -> Self {
+        match self {
+            ModelQuality::Standard => ModelQuality::Fast,
+            ModelQuality::Fast => ModelQuality::Best,
+            ModelQuality::Best => ModelQuality::Standard
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct OCRLanguage {
     pub code: String,
     pub name: String,
+    #[serde(default)]
+    pub quality: ModelQuality,
 }
 
 impl OCRLanguage {
     pub fn new(code: &str, name: &str) -> Self {
         Self {
             code: code.to_string(),
-            name: name.to_string()
+            name: name.to_string(),
+            quality: ModelQuality::Standard
+        }
+    }
+
+    /// The model name Tesseract should load, relative to /tessdata.
+    pub fn model(&self) -> String {
+        match self.quality.directory() {
+            Some(directory) => format!("{}/{}", directory, self.code),
+            None => self.code.clone()
         }
     }
 }
@@ -68,16 +118,230 @@ impl Keybind {
     }
 }
 
+/// The colors of the overlay's panels, icons, text, and highlights.
+#[derive(Debug, Serialize, Copy, Clone, Deserialize, PartialEq, Eq, Hash, Default)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    /// Black panels with bright yellow icons and text, and a cyan accent
+    HighVisibility
+}
+
+/// RGBA colors for each part of the overlay. For panels and icons, the alpha is how much the color
+/// replaces the icon's own, so 0 keeps the icon atlas as it is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThemeColors {
+    pub panel: [f32; 4],
+    pub icon: [f32; 4],
+    pub hovered_icon: [f32; 4],
+    /// Selected icons
+    pub active_icon: [f32; 4],
+    pub text: [f32; 3],
+    /// The selection border's color, unless it has its own
+    pub accent: [f32; 3],
+    /// What the screen outside the selection is dimmed towards
+    pub dim: [f32; 3]
+}
+
+impl Theme {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::Dark => "Dark",
+            Theme::Light => "Light",
+            Theme::HighVisibility => "High visibility"
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Theme::Dark => Theme::Light,
+            Theme::Light => Theme::HighVisibility,
+            Theme::HighVisibility => Theme::Dark
+        }
+    }
+
+    pub fn colors(&self) -> ThemeColors {
+        match self {
+            Theme::Dark => ThemeColors {
+                panel: [0.0, 0.0, 0.0, 0.0],
+                icon: [0.0, 0.0, 0.0, 0.0],
+                hovered_icon: [0.0, 0.0, 0.0, 0.0],
+                active_icon: [0.482, 0.412, 0.745, 0.0],
+                text: [1.0, 1.0, 1.0],
+                accent: [0.482, 0.412, 0.745],
+                dim: [0.0, 0.0, 0.02]
+            },
+            Theme::Light => ThemeColors {
+                panel: [0.94, 0.94, 0.96, 1.0],
+                icon: [0.25, 0.25, 0.3, 1.0],
+                hovered_icon: [0.05, 0.05, 0.1, 1.0],
+                active_icon: [0.38, 0.3, 0.7, 1.0],
+                text: [0.1, 0.1, 0.12],
+                accent: [0.38, 0.3, 0.7],
+                dim: [0.96, 0.96, 0.98]
+            },
+            Theme::HighVisibility => ThemeColors {
+                panel: [0.0, 0.0, 0.0, 1.0],
+                icon: [1.0, 0.9, 0.0, 1.0],
+                hovered_icon: [1.0, 1.0, 1.0, 1.0],
+                active_icon: [0.0, 0.9, 1.0, 1.0],
+                text: [1.0, 0.9, 0.0],
+                accent: [0.0, 0.9, 1.0],
+                dim: [0.0, 0.0, 0.0]
+            }
+        }
+    }
+}
+
+/// Whether Windows' high contrast mode is on.
+#[cfg(windows)]
+pub fn system_high_contrast() -> bool {
+    use windows_sys::Win32::UI::{
+        Accessibility::{HCF_HIGHCONTRASTON, HIGHCONTRASTW},
+        WindowsAndMessaging::{SystemParametersInfoW, SPI_GETHIGHCONTRAST}
+    };
+
+    let mut high_contrast = HIGHCONTRASTW {
+        cbSize: std::mem::size_of::<HIGHCONTRASTW>() as u32,
+        dwFlags: 0,
+        lpszDefaultScheme: std::ptr::null_mut()
+    };
+    let succeeded = unsafe {
+        SystemParametersInfoW(SPI_GETHIGHCONTRAST, high_contrast.cbSize, &mut high_contrast as *mut HIGHCONTRASTW as *mut _, 0)
+    } != 0;
+    succeeded && high_contrast.dwFlags & HCF_HIGHCONTRASTON != 0
+}
+
+#[cfg(not(windows))]
+pub fn system_high_contrast() -> bool {
+    false
+}
+
+/// Parses a "#RRGGBB" color as RGB from 0 to 1.
+fn parse_hex_color(color: &str) -> Option<[f32; 3]> {
+    let hex = color.trim().strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok().map(|value| value as f32 / 255.);
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Which edge or corner of the screen the menubar is against.
+#[derive(Debug, Serialize, Copy, Clone, Deserialize, PartialEq, Eq, Hash, Default)]
+pub enum MenubarPosition {
+    TopLeft,
+    #[default]
+    TopCenter,
+    TopRight,
+    CenterLeft,
+    CenterRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight
+}
+
+impl MenubarPosition {
+    /// The position for a menubar dropped at `point`: the corner or edge of the third of the screen it's
+    /// in, or the closest edge if it's in the middle.
+    pub fn nearest(point: (f32, f32), screen_size: (f32, f32)) -> Self {
+        let (x, y) = (point.0 / screen_size.0, point.1 / screen_size.1);
+        let column = if x < 1. / 3. { 0 } else if x > 2. / 3. { 2 } else { 1 };
+        let row = if y < 1. / 3. { 0 } else if y > 2. / 3. { 2 } else { 1 };
+        match (row, column) {
+            (0, 0) => MenubarPosition::TopLeft,
+            (0, 1) => MenubarPosition::TopCenter,
+            (0, 2) => MenubarPosition::TopRight,
+            (1, 0) => MenubarPosition::CenterLeft,
+            (1, 2) => MenubarPosition::CenterRight,
+            (2, 0) => MenubarPosition::BottomLeft,
+            (2, 1) => MenubarPosition::BottomCenter,
+            (2, 2) => MenubarPosition::BottomRight,
+            _ => {
+                let edges = [(y, MenubarPosition::TopCenter), (1. - y, MenubarPosition::BottomCenter), (x, MenubarPosition::CenterLeft), (1. - x, MenubarPosition::CenterRight)];
+                edges.into_iter().min_by(|a, b| a.0.total_cmp(&b.0)).map_or(MenubarPosition::TopCenter, |(_, position)| position)
+            }
+        }
+    }
+}
+
+/// What's done to the screen outside the selection.
+#[derive(Debug, Serialize, Copy, Clone, Deserialize, PartialEq, Eq, Hash, Default)]
+pub enum BackgroundEffect {
+    /// Blurred and dimmed
+    #[default]
+    Blur,
+    Dim,
+    None
+}
+
+impl BackgroundEffect {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackgroundEffect::Blur => "Blur",
+            BackgroundEffect::Dim => "Dim",
+            BackgroundEffect::None => "None"
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            BackgroundEffect::Blur => BackgroundEffect::Dim,
+            BackgroundEffect::Dim => BackgroundEffect::None,
+            BackgroundEffect::None => BackgroundEffect::Blur
+        }
+    }
+}
+
+/// The range of the UI scale, in percent
+pub const MIN_UI_SCALE: u32 = 75;
+pub const MAX_UI_SCALE: u32 = 200;
+
+/// The largest blur radius the settings panel allows, since the blur's cost grows with its square
+pub const MAX_BLUR_RADIUS: u32 = 6;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SettingsManager {
     pub use_polygon: bool,
+    pub use_ellipse: bool,
     
     pub maintain_newline: bool,
     pub reformat_and_correct: bool,
-    pub background_blur_enabled: bool,
+    pub background_effect: BackgroundEffect,
+    /// In pixels, from 1 to `MAX_BLUR_RADIUS`
+    pub blur_radius: u32,
+    /// How dark the area outside the selection is, from 0 to 1
+    pub dim_opacity: f32,
     pub add_pilcrow_in_preview: bool,
     pub close_on_copy: bool,
     pub auto_copy: bool,
+    pub copy_image_without_text: bool,
+    pub code_mode: bool,
+    pub smart_paragraphs: bool,
+    pub collapse_spaces: bool,
+    pub trim_trailing_spaces: bool,
+    pub normalize_tabs: bool,
+    pub strip_noise: bool,
+    pub programmer_mode: bool,
+    pub copy_rtf: bool,
+    pub copy_image_with_text: bool,
+    pub llm_post_processing: bool,
+    pub use_processed_text: bool,
+    pub text_suggestions: bool,
+    pub snap_to_text_lines: bool,
+    pub snap_to_grid: bool,
+    pub border_corner_markers: bool,
+    pub border_marching_ants: bool,
+    pub magnifier: bool,
+    pub crosshair_guides: bool,
+    /// Thick selection outlines, larger icons, and preview text on a solid backing. Also on while Windows'
+    /// high contrast mode is, without changing this.
+    pub high_contrast: bool,
+    /// Whether icons, the preview, and feedback appear and disappear instantly instead of fading and sliding
+    pub reduced_motion: bool,
+    /// Whether the menubar collapses to a small pill until it's hovered or Q is pressed
+    pub compact_menubar: bool,
 
     /// Intended to be read-only by other modules -- use `set_open_keybind` to change.
     /// This is the case because we also set `open_keybind_string` so we don't need to
@@ -85,6 +349,8 @@ pub struct SettingsManager {
     pub open_keybind: Arc<Mutex<Keybind>>,
     #[serde(skip, default)]
     pub open_keybind_string: String,
+    /// Whether the global hotkeys are ignored, e.g. while gaming. Shared with the hotkey listener.
+    pub hotkeys_paused: Arc<AtomicBool>,
 
     // Don't seriaize with the other settings; it's loaded from a separate file
     #[serde(skip)]
@@ -94,12 +360,196 @@ pub struct SettingsManager {
     project_dirs: ProjectDirs
 }
 
-#[derive(Debug, Serialize, Copy, Clone, Deserialize)]
-pub enum TesseractExportMode {
-    UTF8,
-    HOCR,
-    Alto,
-    TSV
+/// The recognition backend used for OCR.
+#[derive(Debug, Serialize, Copy, Clone, Deserialize, PartialEq, Eq, Hash, Default)]
+pub enum OCREngine {
+    /// Tesseract with the currently selected language
+    #[default]
+    Tesseract,
+    /// Tesseract with a handwriting-trained model, a layout mode suited to notes, and contrast normalization
+    /// for whiteboard photos
+    Handwriting
+}
+
+/// The scheduling priority of the OCR worker thread.
+#[derive(Debug, Serialize, Copy, Clone, Deserialize, PartialEq, Eq, Default)]
+pub enum WorkerPriority {
+    #[default]
+    Normal,
+    /// Lets the overlay keep rendering smoothly while large selections are recognized
+    BelowNormal,
+    Lowest
+}
+
+/// When the selection is recognized while it's being dragged.
+#[derive(Debug, Serialize, Copy, Clone, Deserialize, PartialEq, Eq, Default)]
+pub enum OCRStrategy {
+    /// Recognize continuously while dragging, after the debounce time
+    #[default]
+    Continuous,
+    /// Only recognize once the mouse is released, which keeps dragging smooth on slow machines
+    OnRelease
+}
+
+/// Where copied results go.
+#[derive(Debug, Serialize, Copy, Clone, Deserialize, PartialEq, Eq, Default)]
+pub enum OutputTarget {
+    #[default]
+    Clipboard,
+    /// Write each result to a file named by `output_file_template`
+    File,
+    ClipboardAndFile,
+    /// Type the result into the window focused before the overlay, for remote desktops and VMs that don't share the clipboard
+    Type,
+    /// Append the result to a Markdown note, e.g. in an Obsidian vault
+    Note
+}
+
+/// The Markdown note results are appended to with the "Note" output target.
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Default)]
+pub struct NoteSettings {
+    /// The note's path, e.g. a file in an Obsidian vault
+    #[serde(default)]
+    pub path: String,
+    /// Where embedded images are saved, relative to the note's directory unless it's absolute
+    #[serde(default = "default_note_attachments_directory")]
+    pub attachments_directory: String,
+    #[serde(default)]
+    pub embed_image: bool,
+}
+
+fn default_note_attachments_directory() -> String {
+    "attachments".to_string()
+}
+
+impl OutputTarget {
+    pub fn uses_clipboard(&self) -> bool {
+        matches!(self, OutputTarget::Clipboard | OutputTarget::ClipboardAndFile)
+    }
+
+    pub fn uses_file(&self) -> bool {
+        matches!(self, OutputTarget::File | OutputTarget::ClipboardAndFile)
+    }
+}
+
+/// A named bundle of output options, so switching between e.g. plain text and TSV to a file is one key press.
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq)]
+pub struct OutputProfile {
+    pub name: String,
+    pub format: TesseractExportMode,
+    pub maintain_newlines: bool,
+    pub template: String,
+    pub destination: OutputTarget,
+    /// The typographic cleanup to switch to, or `None` to keep the current one
+    #[serde(default)]
+    pub typography: Option<TypographySettings>,
+}
+
+impl OutputProfile {
+    fn new(name: &str, format: TesseractExportMode, template: &str, destination: OutputTarget) -> Self {
+        Self { name: name.to_string(), format, maintain_newlines: true, template: template.to_string(), destination, typography: None }
+    }
+}
+
+/// A selection saved under a name, recalled from the settings panel or with Ctrl + its number in the overlay.
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq)]
+pub struct RegionPreset {
+    pub name: String,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// The vertices of a polygon or ellipse selection, or empty for a box
+    #[serde(default)]
+    pub polygon: Vec<(f32, f32)>,
+}
+
+fn default_output_profiles() -> Vec<OutputProfile> {
+    vec![
+        OutputProfile::new("Plain text", TesseractExportMode::UTF8, "{text}", OutputTarget::Clipboard),
+        OutputProfile::new("Markdown quote", TesseractExportMode::UTF8, "> {text}", OutputTarget::Clipboard),
+        OutputProfile::new("TSV to file", TesseractExportMode::TSV, "{text}", OutputTarget::File),
+    ]
+}
+
+fn default_history_size() -> usize {
+    10
+}
+
+fn default_output_file_template() -> String {
+    "{date}_{time}_{lang}.txt".to_string()
+}
+
+fn default_copy_templates() -> Vec<String> {
+    vec!["{text}".to_string(), "\"{text}\" — captured {date} from {lang}".to_string(), "> {text}".to_string()]
+}
+
+fn default_search_url() -> String {
+    "https://www.google.com/search?q={query}".to_string()
+}
+
+fn default_debounce_ms() -> u64 {
+    50
+}
+
+fn default_live_interval_ms() -> u64 {
+    500
+}
+
+fn default_csv_delimiter() -> char {
+    ','
+}
+
+fn default_pretty_json() -> bool {
+    true
+}
+
+/// The colors the settings panel cycles the selection border through, starting with the default
+const SELECTION_BORDER_COLORS: [&str; 6] = ["#7B69BE", "#FFFFFF", "#E0474C", "#4CC26B", "#F2C744", "#3FB8E6"];
+
+
+fn default_selection_border_width() -> u32 {
+    1
+}
+
+fn default_ui_scale() -> u32 {
+    100
+}
+
+fn default_system_fallback_fonts() -> bool {
+    true
+}
+
+fn default_interface_language() -> String {
+    crate::localization::DEFAULT_LOCALE.to_string()
+}
+
+/// How much the loupe can magnify the screen
+pub const MIN_MAGNIFIER_ZOOM: u32 = 4;
+pub const MAX_MAGNIFIER_ZOOM: u32 = 8;
+
+fn default_magnifier_zoom() -> u32 {
+    6
+}
+
+fn default_resize_step() -> u32 {
+    10
+}
+
+fn default_snap_grid_size() -> u32 {
+    20
+}
+
+fn default_undo_limit() -> u32 {
+    200
+}
+
+fn default_minimum_selection_size() -> u32 {
+    5
+}
+
+fn default_handwriting_language_code() -> String {
+    "eng_handwriting".to_string()
 }
 
 fn verify(settings: TesseractSettings) -> Result<TesseractSettings, String> {
@@ -114,11 +564,141 @@ fn verify(settings: TesseractSettings) -> Result<TesseractSettings, String> {
 pub struct TesseractSettings {
     pub ocr_language_code: String,
     pub export_mode: TesseractExportMode,
+    /// Where saved exports are written, or empty for the default directory
+    #[serde(default)]
+    pub export_directory: String,
+    /// The separator between fields in the "CSV" export mode
+    #[serde(default = "default_csv_delimiter")]
+    pub csv_delimiter: char,
+    /// Whether the "JSON" export mode is indented across lines rather than on a single line
+    #[serde(default = "default_pretty_json")]
+    pub pretty_json: bool,
+    #[serde(default)]
+    pub output_target: OutputTarget,
+    /// The name of the file results are written to, relative to the export directory
+    #[serde(default = "default_output_file_template")]
+    pub output_file_template: String,
+    /// Templates copied results are rendered with, of which `copy_template` is the active one
+    #[serde(default = "default_copy_templates")]
+    pub copy_templates: Vec<String>,
+    #[serde(default)]
+    pub copy_template: usize,
+    /// The index of the last applied output profile
+    #[serde(default)]
+    pub output_profile: usize,
+
+    /// How many copied results are kept in the history
+    #[serde(default = "default_history_size")]
+    pub history_size: usize,
+    #[serde(default)]
+    pub persist_history: bool,
+    /// Seconds after which copied text is cleared from the clipboard, or 0 to keep it
+    #[serde(default)]
+    pub clear_clipboard_seconds: u64,
+    /// The search engine used by "Search the web", with `{query}` where the text goes
+    #[serde(default = "default_search_url")]
+    pub search_url: String,
+    #[serde(default)]
+    pub clipboard_watcher: ClipboardWatcherMode,
+    /// Seconds after closing the overlay within which reopening it restores the previous session, or 0 to always start over
+    #[serde(default)]
+    pub session_restore_seconds: u64,
+
+    #[serde(default)]
+    pub engine: OCREngine,
+    #[serde(default = "default_handwriting_language_code")]
+    pub handwriting_language_code: String,
+
+    #[serde(default = "default_minimum_selection_size")]
+    pub minimum_selection_size: u32,
+    /// The aspect ratio new boxes are locked to, e.g. "16:9", or empty to draw them freely
+    #[serde(default)]
+    pub selection_aspect_ratio: String,
+    /// The selection border's color, as "#RRGGBB", or empty to use the theme's accent
+    #[serde(default)]
+    pub selection_border_color: String,
+    /// The colors of the overlay's panels, icons, and text
+    #[serde(default)]
+    pub theme: Theme,
+    /// Font files the preview falls back to for characters its own fonts don't have, tried before system fonts
+    #[serde(default)]
+    pub fallback_fonts: Vec<String>,
+    /// Whether the preview also falls back to system fonts for the OCR language's script, and for emoji
+    #[serde(default = "default_system_fallback_fonts")]
+    pub system_fallback_fonts: bool,
+    /// The code of the locale the overlay's labels and tooltips are shown in, e.g. "de" for `locales/de.toml`
+    #[serde(default = "default_interface_language")]
+    pub interface_language: String,
+    /// Where the menubar is, which can be changed by dragging it
+    #[serde(default)]
+    pub menubar_position: MenubarPosition,
+    /// How large the icons and text are, in percent, from `MIN_UI_SCALE` to `MAX_UI_SCALE`
+    #[serde(default = "default_ui_scale")]
+    pub ui_scale: u32,
+    /// A custom accent color for selected icons and the selection border, as "#RRGGBB", or empty for the theme's
+    #[serde(default)]
+    pub accent_color: String,
+    /// How thick the selection border is, from 1 to 4
+    #[serde(default = "default_selection_border_width")]
+    pub selection_border_width: u32,
+    /// How much the loupe shown while drawing or resizing the selection magnifies, from 4 to 8
+    #[serde(default = "default_magnifier_zoom")]
+    pub magnifier_zoom: u32,
+    /// How many pixels + and - grow or shrink the selection by on each side
+    #[serde(default = "default_resize_step")]
+    pub resize_step: u32,
+    /// The spacing of the grid selection edges snap to when grid snapping is on, in pixels; 0 disables the grid
+    #[serde(default = "default_snap_grid_size")]
+    pub snap_grid_size: u32,
+    /// How many changes to the selection can be undone, dropping the oldest past that
+    #[serde(default = "default_undo_limit")]
+    pub undo_limit: u32,
+
+    /// How long the selection has to stay still before it's recognized
+    #[serde(default = "default_debounce_ms")]
+    pub debounce_ms: u64,
+    #[serde(default)]
+    pub ocr_strategy: OCRStrategy,
+
+    /// How often live mode re-captures the selection
+    #[serde(default = "default_live_interval_ms")]
+    pub live_interval_ms: u64,
+
+    /// The most threads Tesseract may use, or 0 to let it decide. Only read at startup.
+    #[serde(default)]
+    pub thread_limit: u32,
+    #[serde(default)]
+    pub worker_priority: WorkerPriority,
+
+    /// Whether to register as the handler for `onscreenocr://` links at startup
+    #[serde(default)]
+    pub register_url_protocol: bool,
 
     pub ocr_languages: Vec<OCRLanguage>,
 
     pub tesseract_parameters: toml::Table,
 
+    #[serde(default)]
+    pub typography: TypographySettings,
+
+    #[serde(default)]
+    pub anki: AnkiSettings,
+
+    #[serde(default)]
+    pub note: NoteSettings,
+
+    #[serde(default)]
+    pub upload: UploadSettings,
+
+    #[serde(default)]
+    pub llm: LlmSettings,
+
+    #[serde(default = "default_output_profiles")]
+    pub output_profiles: Vec<OutputProfile>,
+
+    #[serde(default)]
+    pub region_presets: Vec<RegionPreset>,
+
     #[serde(skip, default="crate::settings::get_project_dirs")]
     project_dirs: ProjectDirs
 }
@@ -134,6 +714,22 @@ impl Default for TesseractSettings {
 }
 
 impl TesseractSettings {
+    /// Reads only `thread_limit` from the settings file, or 0 if it can't be read. Unlike loading the
+    /// settings, this never deletes an invalid file or reports errors, so it's safe before the application
+    /// knows whether it'll run at all.
+    pub fn read_thread_limit() -> u32 {
+        #[derive(Deserialize)]
+        struct ThreadLimit {
+            #[serde(default)]
+            thread_limit: u32
+        }
+
+        let path = get_project_dirs().config_dir().join(TESSERACT_SETTNGS_FILE_NAME);
+        std::fs::read_to_string(path).ok()
+            .and_then(|contents| toml::from_str::<ThreadLimit>(&contents).ok())
+            .map_or(0, |settings| settings.thread_limit)
+    }
+
     fn new() -> Self {
         let project_dirs = get_project_dirs();
         let tesseract_settings_path = project_dirs.config_dir().join(TESSERACT_SETTNGS_FILE_NAME);
@@ -170,6 +766,64 @@ impl TesseractSettings {
             ocr_language_code: "eng".to_string(),
             tesseract_parameters: toml::Table::new(),
             export_mode: TesseractExportMode::UTF8,
+            export_directory: String::new(),
+            csv_delimiter: default_csv_delimiter(),
+            pretty_json: default_pretty_json(),
+            output_target: OutputTarget::Clipboard,
+            output_file_template: default_output_file_template(),
+            copy_templates: default_copy_templates(),
+            copy_template: 0,
+            output_profile: 0,
+
+            history_size: default_history_size(),
+            persist_history: false,
+            clear_clipboard_seconds: 0,
+            search_url: default_search_url(),
+            clipboard_watcher: ClipboardWatcherMode::Off,
+            session_restore_seconds: 0,
+
+            engine: OCREngine::Tesseract,
+            handwriting_language_code: default_handwriting_language_code(),
+
+            minimum_selection_size: default_minimum_selection_size(),
+            selection_aspect_ratio: String::new(),
+            selection_border_color: String::new(),
+            theme: Theme::Dark,
+            fallback_fonts: Vec::new(),
+            system_fallback_fonts: default_system_fallback_fonts(),
+            interface_language: default_interface_language(),
+            ui_scale: default_ui_scale(),
+            menubar_position: MenubarPosition::TopCenter,
+            accent_color: String::new(),
+            selection_border_width: default_selection_border_width(),
+            magnifier_zoom: default_magnifier_zoom(),
+            resize_step: default_resize_step(),
+            snap_grid_size: default_snap_grid_size(),
+            undo_limit: default_undo_limit(),
+
+            debounce_ms: default_debounce_ms(),
+            ocr_strategy: OCRStrategy::Continuous,
+
+            live_interval_ms: default_live_interval_ms(),
+
+            thread_limit: 0,
+            worker_priority: WorkerPriority::Normal,
+
+            register_url_protocol: false,
+
+            typography: TypographySettings::default(),
+
+            anki: AnkiSettings::default(),
+
+            note: NoteSettings { attachments_directory: default_note_attachments_directory(), ..Default::default() },
+
+            upload: UploadSettings::default(),
+
+            llm: LlmSettings::default(),
+
+            output_profiles: default_output_profiles(),
+
+            region_presets: Vec::new(),
 
             ocr_languages: vec![
                 OCRLanguage::new("eng", "English"),
@@ -186,6 +840,17 @@ impl TesseractSettings {
     }
 
     fn save(&self) {
+        let encoded = self.to_commented_toml();
+
+        ensure_settings_dir(&self.project_dirs);
+
+        let tesseract_settings_path = self.project_dirs.config_dir().join(TESSERACT_SETTNGS_FILE_NAME);
+        std::fs::write(tesseract_settings_path, encoded).unwrap();
+    }
+
+    /// Serializes the settings with a comment explaining each option. Keys are matched at the start of a line,
+    /// since some are suffixes of others (e.g. `fallback_fonts` and `system_fallback_fonts`).
+    fn to_commented_toml(&self) -> String {
         let encoded = toml::to_string(&self).unwrap();
         // Not a perfect solution, but the comment isn't a huge deal
         let encoded = format!(r#"# The lower-level Tesseract configuration.
@@ -197,7 +862,8 @@ impl TesseractSettings {
 # There are some useful parameters here: https://tesseract-ocr.github.io/tessdoc/tess3/ControlParams.html
 # This is a (old) list of all parameters: http://www.sk-spell.sk.cx/tesseract-ocr-parameters-in-302-version
 [tesseract_parameters]"#);
-        let encoded = encoded.replace("export_mode = ", r#"
+        let encoded = encoded.replace("\nexport_mode = ", r#"
+
 # The export mode. Possible values:
 # "UTF8" - Normal text
 # "HOCR" - An OCR-specific HTML format with coordinates, layout information, etc.
@@ -205,19 +871,249 @@ impl TesseractSettings {
 # "Alto" - An XML format originally designed for OCR data
 #    More information available here: https://en.wikipedia.org/wiki/Analyzed_Layout_and_Text_Object
 # "TSV" - Tab-separated values
+# "CSV" - The same as "TSV", but as comma-separated values (see csv_delimiter) for spreadsheets
+# "JSON" - Blocks, lines, and words with their bounding boxes and confidences, for use in other tools
+# "Receipt" - The date, totals, and line items of a receipt or invoice as CSV, e.g. for expense tracking
 # Note that turning "preserve newlines" off and "Reformat and correct results" will only work with "UTF8"
 # If you don't know what to choose, "UTF8" is probably what you expect.
 export_mode = "#);
+        let encoded = encoded.replace("\nengine = ", r#"
+
+# The recognition engine. Possible values:
+# "Tesseract" - The normal engine, using the language selected in the overlay
+# "Handwriting" - Uses the handwriting model below for stylus notes and whiteboard photos (H in the overlay)
+engine = "#);
+        let encoded = encoded.replace("\nhandwriting_language_code = ", r#"
+
+# The model used by the "Handwriting" engine. Tesseract doesn't ship a handwriting model, so a
+# handwriting-trained [code].traineddata file needs to be added to /tessdata.
+handwriting_language_code = "#);
+        let encoded = encoded.replace("\nminimum_selection_size = ", r#"
+
+# Selections narrower or shorter than this many pixels aren't recognized or copied, since tiny regions
+# almost always produce garbage. A warning is shown while dragging a selection smaller than this.
+minimum_selection_size = "#);
+        let encoded = encoded.replace("\nselection_aspect_ratio = ", r#"
+
+# The aspect ratio new selections are locked to while dragging, as "width:height", e.g. "16:9" for consistent
+# captures of video frames. Leave this empty to draw freely. Holding Ctrl while drawing a box locks it to a square.
+selection_aspect_ratio = "#);
+        let encoded = encoded.replace("\nselection_border_color = ", r##"
+
+# The color of the selection's border, in hex, e.g. "#7B69BE", or empty to use the theme's accent color.
+# Corner markers and marching ants are toggled in the settings panel.
+selection_border_color = "##);
+        let encoded = encoded.replace("\ntheme = ", r#"
+
+# The colors of the overlay's panels, icons, and text. Possible values:
+# "Dark" - The default
+# "Light" - Light panels with dark icons and text, and the screen outside the selection lightened instead of darkened
+# "HighVisibility" - Black panels with yellow icons and text, and a cyan accent
+theme = "#);
+        let encoded = encoded.replace("\nfallback_fonts = ", r#"
+
+# Font files the OCR preview uses for characters its own font can't show, e.g. ["C:\\Fonts\\MyFont.ttf"],
+# tried in order before system fonts.
+fallback_fonts = "#);
+        let encoded = encoded.replace("\nsystem_fallback_fonts = ", r#"
+
+# Whether the OCR preview also uses installed system fonts suited to the OCR language, so results in Japanese,
+# Chinese, Korean, Arabic, Hebrew, Thai, or Hindi (and emoji) show properly instead of as empty boxes.
+system_fallback_fonts = "#);
+        let encoded = encoded.replace("\ninterface_language = ", r#"
+
+# The language of the overlay's labels and tooltips, as the name of a file in the locales directory without
+# ".toml", e.g. "de", or "en" for English. Locale files translate each English string in their [strings] table;
+# anything they leave out stays in English. This can also be picked in the settings panel.
+interface_language = "#);
+        let encoded = encoded.replace("\nmenubar_position = ", r#"
+
+# Which edge or corner of the screen the menubar is against. Dragging the menubar by its background changes this.
+# Possible values: "TopLeft", "TopCenter", "TopRight", "CenterLeft", "CenterRight", "BottomLeft", "BottomCenter", "BottomRight"
+menubar_position = "#);
+        let encoded = encoded.replace("\nui_scale = ", r#"
+
+# How large the overlay's icons and text are, in percent, from 75 to 200. Useful on 4K screens and small laptop panels.
+ui_scale = "#);
+        let encoded = encoded.replace("\naccent_color = ", r##"
+
+# A custom accent color for selected icons and the selection border, in hex, e.g. "#E0474C". Leave empty to use the theme's.
+accent_color = "##);
+        let encoded = encoded.replace("\nselection_border_width = ", r#"
+
+# How thick the selection's border is, from 1 (the default) to 4.
+selection_border_width = "#);
+        let encoded = encoded.replace("\nmagnifier_zoom = ", r#"
+
+# How many times the loupe shown next to the cursor while drawing or resizing the selection magnifies the screen,
+# from 4 to 8. The loupe itself is toggled in the settings panel.
+magnifier_zoom = "#);
+        let encoded = encoded.replace("\nresize_step = ", r#"
+
+# How many pixels + and - in the overlay grow or shrink the selection by on each side, around its center.
+resize_step = "#);
+        let encoded = encoded.replace("\nsnap_grid_size = ", r#"
+
+# The spacing, in pixels, of the grid selection edges snap to when "Snap to grid and screen" is on in the
+# settings panel. Edges also snap to the screen's edges, halves, and thirds. Set to 0 to only snap to the screen.
+snap_grid_size = "#);
+        let encoded = encoded.replace("\nundo_limit = ", r#"
+
+# How many changes to the selection Ctrl+Z can undo each time the overlay is opened. Older changes are forgotten.
+undo_limit = "#);
+        let encoded = encoded.replace("\ndebounce_ms = ", r#"
+
+# How long, in milliseconds, the selection has to stay still before it's recognized.
+# Raising this reduces how much OCR runs while adjusting a selection.
+debounce_ms = "#);
+        let encoded = encoded.replace("\nocr_strategy = ", r#"
+
+# When the selection is recognized while dragging. Possible values:
+# "Continuous" - Recognize while dragging, whenever the selection stays still for the debounce time
+# "OnRelease" - Only recognize once the mouse is released, which keeps dragging smooth on slow machines
+ocr_strategy = "#);
+        let encoded = encoded.replace("\nexport_directory = ", r#"
+
+# The directory results are saved to with Shift+S in the overlay, using the export mode's file extension.
+# Leave this empty to use the "exports" directory next to the application's other data.
+export_directory = "#);
+        let encoded = encoded.replace("\ncsv_delimiter = ", r#"
+
+# The separator between fields in the "CSV" export mode, e.g. ";" for spreadsheets in locales using decimal commas.
+csv_delimiter = "#);
+        let encoded = encoded.replace("\npretty_json = ", r#"
+
+# Whether the "JSON" export mode is indented across lines for reading, or written on a single line for other tools.
+pretty_json = "#);
+        let encoded = encoded.replace("\noutput_target = ", r#"
+
+# Where results go when copying (C in the overlay). Possible values:
+# "Clipboard" - Only the clipboard
+# "File" - Only a file in the export directory, named by output_file_template
+# "ClipboardAndFile" - Both
+# "Type" - Type the text into the previously focused window, for remote desktops and VMs that don't share the clipboard
+# "Note" - Append the text to the Markdown note configured under [note], e.g. in an Obsidian vault
+output_target = "#);
+        let encoded = encoded.replace("\noutput_file_template = ", r#"
+
+# The name of the file results are written to, relative to the export directory. Possible placeholders:
+# {date} - The local date, e.g. 2024-03-02
+# {time} - The local time, e.g. 14-05-09
+# {lang} - The language code, e.g. eng
+# Results written to a file that already exists are appended, so "{date}_{lang}.txt" collects a day's snippets in one file.
+output_file_template = "#);
+        let encoded = encoded.replace("\ncopy_templates = ", r#"
+
+# Templates copied results are rendered with, chosen in the settings panel. Possible placeholders:
+# {text} - The recognized text
+# {date} - The local date, e.g. 2024-03-02
+# {time} - The local time, e.g. 14:05:09
+# {lang} - The language code, e.g. eng
+copy_templates = "#);
+        let encoded = encoded.replace("\ncopy_template = ", r#"
+
+# The index of the active copy template, starting at 0.
+copy_template = "#);
+        let encoded = encoded.replace("\noutput_profile = ", r#"
+
+# The index of the active output profile, starting at 0. Switch profiles with P in the overlay.
+output_profile = "#);
+        let encoded = encoded.replace("\nhistory_size = ", r#"
+
+# How many copied results are kept in the history (Shift + H in the overlay, or the tray menu).
+history_size = "#);
+        let encoded = encoded.replace("\npersist_history = ", r#"
+
+# Whether the history is saved to disk, so it's kept after restarting the application.
+persist_history = "#);
+        let encoded = encoded.replace("\nclear_clipboard_seconds = ", r#"
+
+# Seconds after which text copied by OnScreenOCR is cleared from the clipboard, for OCRing passwords or tokens.
+# The clipboard is only cleared if it still holds the copied text. 0 keeps it.
+# When set, copies are also left out of the history and, on Windows, out of clipboard history and cloud sync.
+clear_clipboard_seconds = "#);
+        let encoded = encoded.replace("\nsearch_url = ", r#"
+
+# The search engine F in the overlay searches the text with. {query} is replaced with the URL-encoded text,
+# e.g. "https://duckduckgo.com/?q={query}" or "https://www.bing.com/search?q={query}".
+search_url = "#);
+        let encoded = encoded.replace("\nclipboard_watcher = ", r#"
+
+# What happens when another app copies an image, like a screenshot tool or a browser: "Off" ignores it,
+# "Replace" replaces it with its text, "Augment" adds the text alongside the image, and "Notify" shows a
+# notification with a button to copy the text. Only supported on Windows. Changes take effect after restarting.
+clipboard_watcher = "#);
+        let encoded = encoded.replace("\nsession_restore_seconds = ", r#"
+
+# If the overlay is reopened on the same monitor within this many seconds of closing it, the previous screenshot,
+# selection, preview, and undo history are restored instead of starting over, e.g. after closing it by accident.
+# 0 always starts over.
+session_restore_seconds = "#);
+        let encoded = encoded.replace("\nlive_interval_ms = ", r#"
+
+# How often, in milliseconds, live mode (L in the overlay) re-captures and recognizes the selection.
+live_interval_ms = "#);
+        let encoded = encoded.replace("\nthread_limit = ", r#"
+
+# The most threads Tesseract may use for recognition (OMP_THREAD_LIMIT), or 0 to let it decide.
+# Lowering this helps on machines with few cores. Changes take effect after restarting the application.
+thread_limit = "#);
+        let encoded = encoded.replace("\nworker_priority = ", r#"
+
+# The priority of the thread running OCR. Possible values: "Normal", "BelowNormal", "Lowest"
+# Lower priorities keep the overlay responsive while large selections are recognized.
+worker_priority = "#);
+        let encoded = encoded.replace("\nregister_url_protocol = ", r#"
+
+# Whether OnScreenOCR registers itself as the handler for onscreenocr:// links when it starts.
+# This replaces any other application registered for them.
+register_url_protocol = "#);
+        let encoded = encoded.replace("[typography]", r#"# Typographic cleanup applied to plain text ("UTF8") results, except in code mode, where it would corrupt source code.
+# quotes: "Unchanged", "Straight" (“” ‘’ become " '), or "Curly" (" ' become “” ‘’)
+# dashes: "Unchanged", "Normalized" (hyphen lookalikes become -), or "Ascii" (also – becomes - and — becomes --)
+# expand_ligatures: Whether ligatures like ﬁ and ﬂ are split into separate letters
+[typography]"#);
+        let encoded = encoded.replace("[anki]", r#"# Flashcards made with K in the overlay, added to Anki through the AnkiConnect add-on (Anki has to be running).
+# url: The AnkiConnect endpoint. deck, note_type: Where the card goes and its note type.
+# front_field, back_field: The note type's fields for each side of the card.
+# front, back: What goes on each side: "Image", "Text", "ImageAndText", or "Empty"
+[anki]"#);
+        let encoded = encoded.replace("[note]", r#"# The Markdown note results are appended to when output_target is "Note", each as a quote under a timestamp.
+# path: The note's file, e.g. "C:/Users/me/Vault/Inbox.md"
+# attachments_directory: Where embedded images are saved, relative to the note's directory unless it's absolute
+# embed_image: Whether the selection image is saved and embedded above the text
+[note]"#);
+        let encoded = encoded.replace("[upload]", r#"# Where the selection image is uploaded with U in the overlay. The resulting URL is copied to the clipboard.
+# host: "Imgur" (needs imgur_client_id from https://api.imgur.com/oauth2/addclient), "S3", or "Custom"
+# s3_*: Any S3-compatible storage (AWS S3, Cloudflare R2, MinIO, ...). s3_public_url is the bucket's public address
+#   if it differs from the endpoint, e.g. a CDN; leave it empty to use the endpoint.
+# custom_*: A multipart form upload of the image in custom_field to custom_url. custom_response_url is a JSON pointer
+#   to the URL in the response, e.g. "/data/url", or empty if the response is the URL itself.
+[upload]"#);
+        let encoded = encoded.replace("[llm]", r#"# The OpenAI-compatible chat completions endpoint results are sent to when "Post-process with a language model"
+# is on, e.g. OpenAI, or a local server like Ollama at "http://localhost:11434/v1/chat/completions".
+# api_key can be left empty for servers that don't need one. prompt tells the model what to do with the text,
+# e.g. "Fix OCR errors", "Summarize this text", or "Translate this text to German". G in the overlay switches
+# between the processed and the raw text, and whichever is shown is what gets copied.
+[llm]"#);
+        let encoded = encoded.replacen("[[output_profiles]]", r#"# Output profiles bundle output options under a name, and P in the overlay switches to the next one.
+# format is an export mode (see export_mode), maintain_newlines matches the "Maintain newlines" setting,
+# template is a copy template (see copy_templates), and destination is an output target (see output_target).
+# A profile can also switch the typographic cleanup by adding a typography table with the same keys as [typography].
+[[output_profiles]]"#, 1);
+        let encoded = encoded.replacen("[[region_presets]]", r#"# Selections saved with M in the overlay, recalled from the settings panel or with Ctrl + 1-9 in the order listed here.
+# x, y, width, and height are the selection's bounds in pixels. polygon holds the vertices of a polygon or ellipse
+# selection as [x, y] pairs, or is empty for a box.
+[[region_presets]]"#, 1);
         let encoded = encoded.replacen("[[ocr_languages]]", r#"# Each entry should be a language, with a corresponding [name].traineddata file under /tessdata.
 # Name is an arbitrary string shown in the UI, and code is the language code.
+# quality is "Standard", "Fast" (from tessdata_fast, under /tessdata/fast), or "Best" (from tessdata_best,
+# under /tessdata/best). Fast models trade accuracy for speed, and best models the opposite.
 # To support automatic correction for other languages, add associated dictionary text files
 # to correction_data, following the existing conventions. More documentation is to come.
 [[ocr_languages]]"#, 1);
 
-        ensure_settings_dir(&self.project_dirs);
-
-        let tesseract_settings_path = self.project_dirs.config_dir().join(TESSERACT_SETTNGS_FILE_NAME);
-        std::fs::write(tesseract_settings_path, encoded).unwrap();
+        encoded
     }
 
     pub fn absolute_path(&self) -> String {
@@ -281,7 +1177,148 @@ export_mode = "#);
         self.ocr_language_code = self.ocr_languages[(current_language_index + self.ocr_languages.len() - 1) % self.ocr_languages.len()].code.to_string();
     }
 
+    /// The active copy template, or one that copies the text unchanged if the index is out of range.
+    pub fn active_copy_template(&self) -> &str {
+        self.copy_templates.get(self.copy_template).map(|template| template.as_str()).unwrap_or("{text}")
+    }
+
+    pub fn copy_template_increment(&mut self) {
+        if !self.copy_templates.is_empty() {
+            self.copy_template = (self.copy_template + 1) % self.copy_templates.len();
+        }
+    }
+
+    pub fn copy_template_decrement(&mut self) {
+        if !self.copy_templates.is_empty() {
+            self.copy_template = (self.copy_template.min(self.copy_templates.len() - 1) + self.copy_templates.len() - 1) % self.copy_templates.len();
+        }
+    }
+
+    /// The selection border's color as RGB from 0 to 1, or the theme's accent if it isn't a valid "#RRGGBB" color.
+    pub fn selection_border_color(&self) -> [f32; 3] {
+        parse_hex_color(&self.selection_border_color).unwrap_or_else(|| self.theme_colors().accent)
+    }
+
+    /// Cycles through the preset border colors, then back to the theme's accent.
+    pub fn cycle_selection_border_color(&mut self) {
+        let current = SELECTION_BORDER_COLORS.iter().position(|color| color.eq_ignore_ascii_case(self.selection_border_color.trim()));
+        self.selection_border_color = match current {
+            Some(index) if index + 1 < SELECTION_BORDER_COLORS.len() => SELECTION_BORDER_COLORS[index + 1].to_string(),
+            Some(_) => String::new(),
+            None => SELECTION_BORDER_COLORS[0].to_string()
+        };
+    }
+
+    /// The theme's colors, with the custom accent color if there's a valid one.
+    pub fn theme_colors(&self) -> ThemeColors {
+        let mut colors = self.theme.colors();
+        if let Some([red, green, blue]) = parse_hex_color(&self.accent_color) {
+            colors.accent = [red, green, blue];
+            colors.active_icon = [red, green, blue, 1.0];
+        }
+        colors
+    }
+
+    pub fn cycle_selection_border_width(&mut self) {
+        self.selection_border_width = self.selection_border_width.clamp(1, 4) % 4 + 1;
+    }
+
+    /// Saves a region preset, replacing any with the same name, and saves the settings file.
+    pub fn add_region_preset(&mut self, preset: RegionPreset) {
+        match self.region_presets.iter_mut().find(|existing| existing.name == preset.name) {
+            Some(existing) => *existing = preset,
+            None => self.region_presets.push(preset)
+        }
+        self.save();
+    }
+
+    pub fn remove_region_preset(&mut self, index: usize) {
+        if index < self.region_presets.len() {
+            self.region_presets.remove(index);
+            self.save();
+        }
+    }
+
+    pub fn set_language(&mut self, code: &str) -> Result<(), String> {
+        if !self.ocr_languages.iter().any(|x| x.code == code) {
+            return Err(format!("Unknown OCR language '{}'", code));
+        }
+        self.ocr_language_code = code.to_string();
+        Ok(())
+    }
+
+    /// Adds a newly installed language to the list and saves the settings file.
+    pub fn add_language(&mut self, language: OCRLanguage) {
+        if !self.ocr_languages.iter().any(|x| x.code == language.code) {
+            self.ocr_languages.push(language);
+            self.save();
+        }
+    }
+
+    /// The next quality tier for the current language. It's only applied with `set_model_quality`,
+    /// since the model may need to be downloaded first.
+    pub fn next_model_quality(&self) -> ModelQuality {
+        self.get_ocr_language_data().quality.next()
+    }
+
+    pub fn set_model_quality(&mut self, code: &str, quality: ModelQuality) {
+        if let Some(language) = self.ocr_languages.iter_mut().find(|x| x.code == code) {
+            language.quality = quality;
+            self.save();
+        }
+    }
+
+    /// The directory saved exports are written to.
+    pub fn resolved_export_directory(&self) -> PathBuf {
+        if self.export_directory.trim().is_empty() {
+            self.project_dirs.data_dir().join("exports")
+        } else {
+            PathBuf::from(self.export_directory.trim())
+        }
+    }
+
+    /// The model that should actually be loaded, relative to /tessdata, taking the engine into account.
+    pub fn active_model(&self) -> String {
+        match self.engine {
+            OCREngine::Tesseract => self.get_ocr_language_data().model(),
+            OCREngine::Handwriting => self.handwriting_language_code.clone()
+        }
+    }
+
+    /// Switches to models that exist when the selected ones are missing from tessdata: the normal engine instead
+    /// of a missing handwriting model, and the standard model instead of a missing fast or best one.
+    /// Returns a message for each fallback.
+    pub fn apply_model_fallbacks(&mut self) -> Vec<String> {
+        let directory = get_project_dirs().config_dir().join("tessdata");
+        let mut warnings = Vec::new();
+        if self.engine == OCREngine::Handwriting && !directory.join(format!("{}.traineddata", self.handwriting_language_code)).exists() {
+            warnings.push(format!("Handwriting model {}.traineddata not found in tessdata", self.handwriting_language_code));
+            self.engine = OCREngine::Tesseract;
+        }
+        if self.engine == OCREngine::Tesseract && !directory.join(format!("{}.traineddata", self.active_model())).exists() {
+            let language_code = self.ocr_language_code.clone();
+            if let Some(language) = self.ocr_languages.iter_mut().find(|x| x.code == language_code && x.quality != ModelQuality::Standard) {
+                warnings.push(format!("{} model for {} not found in tessdata", language.quality.name(), language.code));
+                language.quality = ModelQuality::Standard;
+            }
+        }
+        warnings
+    }
+
+    pub fn toggle_handwriting(&mut self) {
+        self.engine = match self.engine {
+            OCREngine::Tesseract => OCREngine::Handwriting,
+            OCREngine::Handwriting => OCREngine::Tesseract
+        };
+    }
+
     pub fn configure_tesseract(&self, api: &mut leptess::tesseract::TessApi) {
+        if self.engine == OCREngine::Handwriting {
+            // Notes are usually a single uniform block of text rather than a full page layout
+            let _ = api.raw.set_variable(&std::ffi::CString::new("tessedit_pageseg_mode").unwrap(), &std::ffi::CString::new("6").unwrap());
+        }
+
+
         for (k, v) in &self.tesseract_parameters {
             let k = std::ffi::CString::new(k.to_string()).unwrap();
             let value_string = match v {
@@ -325,17 +1362,43 @@ impl SettingsManager {
         // Default settings
         Self {
             use_polygon: false,
+            use_ellipse: false,
             maintain_newline: true,
             reformat_and_correct: true,
-            background_blur_enabled: true,
+            background_effect: BackgroundEffect::Blur,
+            blur_radius: 2,
+            dim_opacity: 0.6,
             add_pilcrow_in_preview: true,
             close_on_copy: false,
             auto_copy: false,
+            copy_image_without_text: true,
+            code_mode: false,
+            smart_paragraphs: false,
+            collapse_spaces: true,
+            trim_trailing_spaces: true,
+            normalize_tabs: true,
+            strip_noise: false,
+            programmer_mode: false,
+            copy_rtf: false,
+            copy_image_with_text: false,
+            llm_post_processing: false,
+            use_processed_text: true,
+            text_suggestions: false,
+            snap_to_text_lines: true,
+            snap_to_grid: false,
+            border_corner_markers: false,
+            border_marching_ants: false,
+            magnifier: true,
+            crosshair_guides: false,
+            high_contrast: false,
+            reduced_motion: false,
+            compact_menubar: false,
 
             tesseract_settings: TesseractSettings::new(),
 
             open_keybind: Arc::new(Mutex::new(Keybind::default())),
             open_keybind_string: "Shift + Alt + Z".to_string(),
+            hotkeys_paused: Arc::new(AtomicBool::new(false)),
 
             project_dirs
         }
@@ -346,6 +1409,80 @@ impl SettingsManager {
         self.open_keybind_string = keybind.to_string();
     }
 
+    /// Pauses or resumes the global hotkeys, returning whether they're now paused.
+    pub fn toggle_hotkeys_paused(&mut self) -> bool {
+        !self.hotkeys_paused.fetch_xor(true, Ordering::Relaxed)
+    }
+
+    /// Sets a toggle by its field name. Used by scripts, which can't reach the fields directly.
+    pub fn set_toggle(&mut self, name: &str, value: bool) -> Result<(), String> {
+        let toggle = match name {
+            "maintain_newline" => &mut self.maintain_newline,
+            "reformat_and_correct" => &mut self.reformat_and_correct,
+            "add_pilcrow_in_preview" => &mut self.add_pilcrow_in_preview,
+            "close_on_copy" => &mut self.close_on_copy,
+            "auto_copy" => &mut self.auto_copy,
+            "copy_image_without_text" => &mut self.copy_image_without_text,
+            "code_mode" => &mut self.code_mode,
+            "smart_paragraphs" => &mut self.smart_paragraphs,
+            "collapse_spaces" => &mut self.collapse_spaces,
+            "trim_trailing_spaces" => &mut self.trim_trailing_spaces,
+            "normalize_tabs" => &mut self.normalize_tabs,
+            "strip_noise" => &mut self.strip_noise,
+            "programmer_mode" => &mut self.programmer_mode,
+            "copy_rtf" => &mut self.copy_rtf,
+            "copy_image_with_text" => &mut self.copy_image_with_text,
+            "llm_post_processing" => &mut self.llm_post_processing,
+            "use_processed_text" => &mut self.use_processed_text,
+            "text_suggestions" => &mut self.text_suggestions,
+            "snap_to_text_lines" => &mut self.snap_to_text_lines,
+            "snap_to_grid" => &mut self.snap_to_grid,
+            "border_corner_markers" => &mut self.border_corner_markers,
+            "border_marching_ants" => &mut self.border_marching_ants,
+            "magnifier" => &mut self.magnifier,
+            "crosshair_guides" => &mut self.crosshair_guides,
+            "high_contrast" => &mut self.high_contrast,
+            "reduced_motion" => &mut self.reduced_motion,
+            "compact_menubar" => &mut self.compact_menubar,
+            _ => return Err(format!("Unknown setting '{}'", name))
+        };
+        *toggle = value;
+        Ok(())
+    }
+
+    /// Applies the next output profile and returns its name, or None if no profiles are defined.
+    pub fn cycle_output_profile(&mut self) -> Option<String> {
+        let tesseract_settings = &mut self.tesseract_settings;
+        if tesseract_settings.output_profiles.is_empty() {
+            return None;
+        }
+
+        tesseract_settings.output_profile = (tesseract_settings.output_profile + 1) % tesseract_settings.output_profiles.len();
+        let profile = tesseract_settings.output_profiles[tesseract_settings.output_profile].clone();
+        tesseract_settings.export_mode = profile.format;
+        tesseract_settings.output_target = profile.destination;
+        // Profiles name their template directly, so it's added to the list if it isn't there yet
+        tesseract_settings.copy_template = match tesseract_settings.copy_templates.iter().position(|template| *template == profile.template) {
+            Some(index) => index,
+            None => {
+                tesseract_settings.copy_templates.push(profile.template);
+                tesseract_settings.copy_templates.len() - 1
+            }
+        };
+        if let Some(typography) = profile.typography {
+            tesseract_settings.typography = typography;
+        }
+        self.maintain_newline = profile.maintain_newlines;
+
+        Some(profile.name)
+    }
+
+    /// The name of the last applied output profile, if any are defined.
+    pub fn output_profile_name(&self) -> Option<&str> {
+        let tesseract_settings = &self.tesseract_settings;
+        tesseract_settings.output_profiles.get(tesseract_settings.output_profile).map(|profile| profile.name.as_str())
+    }
+
     pub fn save(&self) {
         ensure_settings_dir(&self.project_dirs);
 
@@ -378,5 +1515,51 @@ fn ensure_settings_dir(project_dirs: &ProjectDirs) {
         }
 
         println!("Default settings and configuration files have been created in {}", config_dir.to_string_lossy());
+        return;
     }
-}
\ No newline at end of file
+
+    // Translations are added over time, so copy any that an existing config directory doesn't have yet
+    for entry in DEFAULT_CONFIG_FILES.get_dir("locales").map(|dir| dir.files()).into_iter().flatten() {
+        let path = config_dir.join(entry.path());
+        if path.exists() {
+            continue;
+        }
+
+        if let Err(e) = std::fs::create_dir_all(config_dir.join("locales")).and_then(|_| std::fs::write(&path, entry.contents())) {
+            eprintln!("Unable to create {}: {}", path.to_string_lossy(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minimal_settings() -> TesseractSettings {
+        toml::from_str(r#"
+ocr_language_code = "eng"
+export_mode = "UTF8"
+ocr_languages = [{ code = "eng", name = "English" }]
+
+[tesseract_parameters]
+"#).unwrap()
+    }
+
+    #[test]
+    fn commented_toml_round_trips() {
+        let mut settings = minimal_settings();
+        settings.fallback_fonts = vec!["MyFont.ttf".to_string()];
+        settings.system_fallback_fonts = false;
+        settings.copy_template = 1;
+
+        let encoded = settings.to_commented_toml();
+        let decoded: TesseractSettings = toml::from_str(&encoded).expect("saved settings should parse");
+
+        assert_eq!(decoded.fallback_fonts, settings.fallback_fonts);
+        assert!(!decoded.system_fallback_fonts);
+        assert_eq!(decoded.copy_template, 1);
+        assert_eq!(decoded.copy_templates, settings.copy_templates);
+        assert_eq!(decoded.ocr_language_code, "eng");
+        assert_eq!(toml::to_string(&decoded).unwrap(), toml::to_string(&settings).unwrap());
+    }
+}
@@ -27,22 +27,80 @@ struct Vertex {
 }
 
 struct Locals {
-    @location(0) blur_enabled: u32,
+    // In pixels, or 0 to not blur
+    @location(0) blur_radius: f32,
+    @location(1) edge_handles: u32,
     @location(2) vertex_count: u32,
-    @location(3) vertices: array<Vertex>,
+    @location(3) suggestion_count: u32,
+    @location(4) border_color: vec4<f32>,
+    // A multiple of BORDER_WIDTH
+    @location(5) border_width: f32,
+    @location(6) corner_markers: u32,
+    @location(7) marching_ants: u32,
+    // Seconds since the overlay was created, for animating the marching ants
+    @location(8) time: f32,
+    // How dark the area outside the selection is, from 0 to 1
+    @location(9) dim_opacity: f32,
+    // How many times the loupe magnifies, or 0 to hide it
+    @location(10) loupe_zoom: f32,
+    // The middle of the pixel under the cursor, relative to the screen size
+    @location(11) cursor: vec2<f32>,
+    // The screenshot's offset relative to the screen size in xy, and its zoom in z. The vertices and
+    // suggestions are already on screen, so only sampling the screenshot uses this.
+    @location(12) view: vec4<f32>,
+    @location(13) crosshair: u32,
+    // How many vertices of the selection Ctrl+Z would go back to follow the selection's own
+    @location(14) ghost_vertex_count: u32,
+    // What the area outside the selection is dimmed towards, from the theme. The alpha is unused.
+    @location(15) dim_color: vec4<f32>,
+    // Left, top, right, and bottom of the solid backing behind the preview text, or all 0 for none
+    @location(16) text_backing: vec4<f32>,
+    // Left, top, right, and bottom of the word box clicked in the preview, or all 0 for none
+    @location(17) word_flash: vec4<f32>,
+    // The theme's accent, with an alpha that fades the word box out
+    @location(18) word_flash_color: vec4<f32>,
+    // Left, top, right, and bottom of each suggested text region
+    @location(19) suggestions: array<vec4<f32>, 16>,
+    @location(20) vertices: array<Vertex>,
 }
 @group(0) @binding(2) var<storage, read> r_locals: Locals;
 
-const BLUR_RADIUS = 2.0;
 const BLUR_ITERATIONS = 2.0;
-const OUT_OF_BOX_TINT = vec3<f32>(0.4, 0.4, 0.42);
 
 const BORDER_WIDTH = 0.6; // Note: not in pixels. Roughly 1.5x less than the actual pixel width, but depends on the situation.
-const BORDER_INNER_COLOR = vec4<f32>(0.482, 0.412, 0.745, 1.0);
-const BORDER_OUTER_COLOR = vec4<f32>(0.482, 0.412, 0.745, 0.0);
+
+const CORNER_MARKER_LENGTH = 14.0;
+const CORNER_MARKER_WIDTH = 3.0;
+
+const MARCHING_ANTS_LENGTH = 12.0;
+const MARCHING_ANTS_SPEED = 1.5; // Dashes per second
+const MARCHING_ANTS_GAP_COLOR = vec4<f32>(0.1, 0.1, 0.1, 1.0);
 
 const VERTEX_HANDLE_CIRCLE_RADIUS = 4.0;
 
+const LOUPE_RADIUS = 64.0;
+// How far the loupe is from the cursor, so it doesn't cover what's being selected
+const LOUPE_OFFSET = vec2<f32>(96.0, 96.0);
+const LOUPE_BORDER_WIDTH = 2.0;
+const LOUPE_BORDER_COLOR = vec4<f32>(1.0, 1.0, 1.0, 0.9);
+const LOUPE_GRID_COLOR = vec4<f32>(0.0, 0.0, 0.0, 0.3);
+const LOUPE_CURSOR_COLOR = vec4<f32>(1.0, 0.3, 0.3, 0.9);
+
+const CROSSHAIR_COLOR = vec4<f32>(1.0, 1.0, 1.0, 0.35);
+
+const GHOST_COLOR = vec4<f32>(1.0, 1.0, 1.0, 0.5);
+const GHOST_WIDTH = 1.5;
+
+const SUGGESTION_FILL_COLOR = vec4<f32>(1.0, 1.0, 1.0, 0.06);
+const SUGGESTION_BORDER_COLOR = vec4<f32>(1.0, 1.0, 1.0, 0.3);
+
+// Behind the preview text in high-contrast mode
+const TEXT_BACKING_COLOR = vec4<f32>(0.0, 0.0, 0.0, 1.0);
+
+// How opaque the inside of a flashed word box is compared to its border
+const WORD_FLASH_FILL_OPACITY = 0.3;
+const WORD_FLASH_BORDER_WIDTH = 2.0;
+
 struct SDFResult {
     distance: f32,
     index: i32,
@@ -121,6 +179,19 @@ fn vertex_signed_distance(point: vec2<f32>, screen_dimensions: vec2<f32>) -> SDF
             index = i;
         }
     }
+
+    // In box mode, the middle of each edge has a handle too; these come after the vertices
+    if(r_locals.edge_handles != 0u) {
+        for(var i = 0; i < num; i += 1) {
+            var midpoint: vec2<f32> = (r_locals.vertices[i].position + r_locals.vertices[(i + 1) % num].position) * 0.5 * screen_dimensions;
+
+            var new_dist: f32 = dot(screen_point - midpoint, screen_point - midpoint) - squared_radius;
+            if(new_dist < dist) {
+                dist = new_dist;
+                index = num + i;
+            }
+        }
+    }
     
     return SDFResult(sqrt(dist), index);
 }
@@ -172,36 +243,213 @@ fn get_edge_opacity(vertex_index: i32) -> f32 {
     return f32(r_locals.vertices[vertex_index].highlight & u32(0xFFFF)) / 65535.0;
 }
 
+fn suggestion_overlay(point: vec2<f32>, screen_dimensions: vec2<f32>) -> vec4<f32> {
+    var screen_point: vec2<f32> = point * screen_dimensions;
+    for(var i = 0u; i < r_locals.suggestion_count; i += 1u) {
+        var top_left: vec2<f32> = r_locals.suggestions[i].xy * screen_dimensions;
+        var bottom_right: vec2<f32> = r_locals.suggestions[i].zw * screen_dimensions;
+        if(all(screen_point >= top_left) && all(screen_point <= bottom_right)) {
+            var edge_distance: f32 = min(
+                min(screen_point.x - top_left.x, bottom_right.x - screen_point.x),
+                min(screen_point.y - top_left.y, bottom_right.y - screen_point.y)
+            );
+            if(edge_distance < 1.0) {
+                return SUGGESTION_BORDER_COLOR;
+            }
+            return SUGGESTION_FILL_COLOR;
+        }
+    }
+    return vec4<f32>(0.0, 0.0, 0.0, 0.0);
+}
+
+fn text_backing_overlay(point: vec2<f32>) -> vec4<f32> {
+    var backing = r_locals.text_backing;
+    if(backing.z > backing.x && all(point >= backing.xy) && all(point <= backing.zw)) {
+        return TEXT_BACKING_COLOR;
+    }
+    return vec4<f32>(0.0, 0.0, 0.0, 0.0);
+}
+
+fn word_flash_overlay(point: vec2<f32>, screen_dimensions: vec2<f32>) -> vec4<f32> {
+    var screen_point: vec2<f32> = point * screen_dimensions;
+    var top_left: vec2<f32> = r_locals.word_flash.xy * screen_dimensions;
+    var bottom_right: vec2<f32> = r_locals.word_flash.zw * screen_dimensions;
+    // The border goes around the box so it doesn't cover the word's pixels
+    if(bottom_right.x <= top_left.x || any(screen_point < top_left - WORD_FLASH_BORDER_WIDTH) || any(screen_point > bottom_right + WORD_FLASH_BORDER_WIDTH)) {
+        return vec4<f32>(0.0, 0.0, 0.0, 0.0);
+    }
+    if(all(screen_point >= top_left) && all(screen_point <= bottom_right)) {
+        return vec4<f32>(r_locals.word_flash_color.rgb, r_locals.word_flash_color.a * WORD_FLASH_FILL_OPACITY);
+    }
+    return r_locals.word_flash_color;
+}
+
+fn border_width() -> f32 {
+    return BORDER_WIDTH * r_locals.border_width;
+}
+
+// The border color at a point, alternating with dark gaps that move along the border for marching ants
+fn border_inner_color(point: vec2<f32>, screen_dimensions: vec2<f32>) -> vec4<f32> {
+    let color: vec4<f32> = vec4<f32>(r_locals.border_color.rgb, 1.0);
+    if(r_locals.marching_ants != 0u) {
+        var screen_point: vec2<f32> = point * screen_dimensions;
+        // Diagonal stripes look like dashes on both horizontal and vertical edges
+        if(fract((screen_point.x + screen_point.y) / (MARCHING_ANTS_LENGTH * 2.0) - r_locals.time * MARCHING_ANTS_SPEED / 2.0) >= 0.5) {
+            return MARCHING_ANTS_GAP_COLOR;
+        }
+    }
+    return color;
+}
+
+// How much a point is covered by the thick brackets drawn just outside each corner
+fn corner_marker_opacity(point: vec2<f32>, screen_dimensions: vec2<f32>, polygon_distance: f32) -> f32 {
+    var num = i32(r_locals.vertex_count);
+    if(r_locals.corner_markers == 0u || num == 0 || polygon_distance < 0.0 || polygon_distance > CORNER_MARKER_WIDTH) {
+        return 0.0;
+    }
+
+    var screen_point: vec2<f32> = point * screen_dimensions;
+    var closest: f32 = 1000000.0;
+    for(var i = 0; i < num; i += 1) {
+        closest = min(closest, distance(screen_point, r_locals.vertices[i].position * screen_dimensions));
+    }
+    return clamp(CORNER_MARKER_LENGTH - closest, 0.0, 1.0);
+}
+
+// Guide lines across the whole screen through the cursor
+fn crosshair_overlay(point: vec2<f32>, screen_dimensions: vec2<f32>) -> vec4<f32> {
+    if(r_locals.crosshair == 0u) {
+        return vec4<f32>(0.0, 0.0, 0.0, 0.0);
+    }
+
+    var offset: vec2<f32> = abs(point - r_locals.cursor) * screen_dimensions;
+    if(offset.x < 0.5 || offset.y < 0.5) {
+        return CROSSHAIR_COLOR;
+    }
+    return vec4<f32>(0.0, 0.0, 0.0, 0.0);
+}
+
+// A translucent outline of the selection Ctrl+Z would go back to
+fn ghost_overlay(point: vec2<f32>, screen_dimensions: vec2<f32>) -> vec4<f32> {
+    var num = i32(r_locals.ghost_vertex_count);
+    var first = i32(r_locals.vertex_count);
+    if(num == 0) {
+        return vec4<f32>(0.0, 0.0, 0.0, 0.0);
+    }
+
+    var screen_point: vec2<f32> = point * screen_dimensions;
+    var last: vec2<f32> = r_locals.vertices[first + num - 1].position * screen_dimensions;
+    for(var i = 0; i < num; i += 1) {
+        var current: vec2<f32> = r_locals.vertices[first + i].position * screen_dimensions;
+        var e: vec2<f32> = last - current;
+        var w: vec2<f32> = screen_point - current;
+        var b: vec2<f32> = w - e * clamp(dot(w, e) / max(dot(e, e), 0.0001), 0.0, 1.0);
+        if(length(b) < GHOST_WIDTH) {
+            return GHOST_COLOR;
+        }
+        last = current;
+    }
+    return vec4<f32>(0.0, 0.0, 0.0, 0.0);
+}
+
+// Where a point on screen is on the zoomed and panned screenshot
+fn screenshot_coord(tex_coord: vec2<f32>) -> vec2<f32> {
+    return r_locals.view.xy + tex_coord / r_locals.view.z;
+}
+
+// The loupe's center in pixels, below and to the right of the cursor unless that would go off screen
+fn loupe_center(screen_dimensions: vec2<f32>) -> vec2<f32> {
+    var cursor: vec2<f32> = r_locals.cursor * screen_dimensions;
+    var center: vec2<f32> = cursor + LOUPE_OFFSET;
+    if(center.x + LOUPE_RADIUS > screen_dimensions.x) {
+        center.x = cursor.x - LOUPE_OFFSET.x;
+    }
+    if(center.y + LOUPE_RADIUS > screen_dimensions.y) {
+        center.y = cursor.y - LOUPE_OFFSET.y;
+    }
+    return center;
+}
+
+// The magnified screenshot around the cursor with a grid between its pixels, or transparent outside the loupe
+fn loupe_overlay(point: vec2<f32>, screen_dimensions: vec2<f32>) -> vec4<f32> {
+    if(r_locals.loupe_zoom <= 0.0) {
+        return vec4<f32>(0.0, 0.0, 0.0, 0.0);
+    }
+
+    var screen_point: vec2<f32> = point * screen_dimensions;
+    var center: vec2<f32> = loupe_center(screen_dimensions);
+    var center_distance: f32 = distance(screen_point, center);
+    if(center_distance > LOUPE_RADIUS + LOUPE_BORDER_WIDTH) {
+        return vec4<f32>(0.0, 0.0, 0.0, 0.0);
+    }
+    if(center_distance > LOUPE_RADIUS) {
+        return LOUPE_BORDER_COLOR;
+    }
+
+    // The loupe magnifies what's on screen, which is already zoomed by the view
+    var cursor: vec2<f32> = screenshot_coord(r_locals.cursor) * screen_dimensions;
+    var source: vec2<f32> = cursor + (screen_point - center) / (r_locals.loupe_zoom * r_locals.view.z);
+    var pixel: vec2<f32> = clamp(floor(source), vec2<f32>(0.0, 0.0), screen_dimensions - 1.0);
+    var color: vec4<f32> = vec4<f32>(textureLoad(r_tex_color, vec2<i32>(pixel), 0).rgb, 1.0);
+
+    // How far the point is from the edge of its pixel, in pixels of the loupe
+    var within_pixel: vec2<f32> = fract(source);
+    var pixel_edge_distance: f32 = min(min(within_pixel.x, 1.0 - within_pixel.x), min(within_pixel.y, 1.0 - within_pixel.y)) * r_locals.loupe_zoom;
+    if(pixel_edge_distance < 1.0) {
+        if(all(pixel == floor(cursor))) {
+            return LOUPE_CURSOR_COLOR;
+        }
+        return vec4<f32>(alpha_mix(color.rgb, LOUPE_GRID_COLOR), 1.0);
+    }
+    return color;
+}
+
+fn get_handle_opacity(handle_index: i32) -> f32 {
+    var num = i32(r_locals.vertex_count);
+    if(handle_index < num) {
+        return get_vertex_opacity(handle_index);
+    }
+    // Edge handles light up with their edge, whose highlight is stored on the vertex it leads to
+    return get_edge_opacity((handle_index - num + 1) % num);
+}
+
 fn color_at_position(tex_coord: vec2<f32>, screen_dimensions: vec2<f32>, in_box_color: vec3<f32>, out_of_box_color: vec3<f32>) -> vec3<f32> {
     var in_box: f32 = 0.0;
     var in_handle: f32 = 0.0;
 
+    var border_inner: vec4<f32> = border_inner_color(tex_coord, screen_dimensions);
+    var border_outer: vec4<f32> = vec4<f32>(border_inner.rgb, 0.0);
+
     var sdf_result = polygon_signed_distance(tex_coord, screen_dimensions);
     var sdf_edge_opcity = get_edge_opacity(sdf_result.index);
     if(sdf_result.distance < 0.0) {
-        in_box = 1.0 * min(sdf_result.distance * -0.5 / BORDER_WIDTH, 1.);
+        in_box = 1.0 * min(sdf_result.distance * -0.5 / border_width(), 1.);
     }
     var vertex_sdf_result = vertex_signed_distance(tex_coord, screen_dimensions);
-    var sdf_vertex_opacity = get_vertex_opacity(vertex_sdf_result.index);
+    var sdf_vertex_opacity = get_handle_opacity(vertex_sdf_result.index);
     return mix(
         // Color for inside the vertex handle
         mix(
-            BORDER_INNER_COLOR.rgb,
+            r_locals.border_color.rgb,
             vec3(1.0, 1.0, 1.0),
             sdf_vertex_opacity
         ),
         // Color for non-vertex handle
         mix(
-            // Color for outside the main selection
-            alpha_mix(
-                // Background color
-                out_of_box_color * OUT_OF_BOX_TINT,
-                // Border color
-                mix(
-                    mix(BORDER_INNER_COLOR, vec4(1.0, 1.0, 1.0, 1.0), sdf_edge_opcity),
-                    BORDER_OUTER_COLOR,
-                    min(sdf_result.distance / BORDER_WIDTH / (sdf_edge_opcity + 1.), 1.)
-                )
+            // Color for outside the main selection, with the corner markers on top
+            mix(
+                alpha_mix(
+                    // Background color, with any text suggestions on top
+                    alpha_mix(mix(out_of_box_color, r_locals.dim_color.rgb, r_locals.dim_opacity), suggestion_overlay(tex_coord, screen_dimensions)),
+                    // Border color
+                    mix(
+                        mix(border_inner, vec4(1.0, 1.0, 1.0, 1.0), sdf_edge_opcity),
+                        border_outer,
+                        min(sdf_result.distance / border_width() / (sdf_edge_opcity + 1.), 1.)
+                    )
+                ),
+                r_locals.border_color.rgb,
+                corner_marker_opacity(tex_coord, screen_dimensions, sdf_result.distance)
             ),
             // Color for inside the main selection
             in_box_color,
@@ -217,16 +465,32 @@ fn fs_main(
 ) -> @location(0) vec4<f32> {
     let screen_dimensions = vec2<f32>(textureDimensions(r_tex_color));
 
-    let in_box_color = textureSample(r_tex_color, r_tex_sampler, tex_coord).rgb;
+    let screenshot_tex_coord = screenshot_coord(tex_coord);
+    let in_box_color = textureSample(r_tex_color, r_tex_sampler, screenshot_tex_coord).rgb;
     var out_of_box_color: vec3<f32> = vec3<f32>(0.0, 0.0, 0.0);
-    if(r_locals.blur_enabled != 0u) {
-        out_of_box_color = get_blurred_color(r_tex_color, r_tex_sampler, tex_coord, screen_dimensions, BLUR_RADIUS);
+    if(r_locals.blur_radius > 0.0) {
+        out_of_box_color = get_blurred_color(r_tex_color, r_tex_sampler, screenshot_tex_coord, screen_dimensions, r_locals.blur_radius);
     } else {
-        out_of_box_color = textureSample(r_tex_color, r_tex_sampler, tex_coord).rgb;
+        out_of_box_color = textureSample(r_tex_color, r_tex_sampler, screenshot_tex_coord).rgb;
     }
     
     return vec4<f32>(
-        color_at_position(tex_coord, screen_dimensions, in_box_color, out_of_box_color),
+        alpha_mix(
+            alpha_mix(
+                alpha_mix(
+                    alpha_mix(
+                        alpha_mix(
+                            color_at_position(tex_coord, screen_dimensions, in_box_color, out_of_box_color),
+                            ghost_overlay(tex_coord, screen_dimensions)
+                        ),
+                        word_flash_overlay(tex_coord, screen_dimensions)
+                    ),
+                    crosshair_overlay(tex_coord, screen_dimensions)
+                ),
+                text_backing_overlay(tex_coord)
+            ),
+            loupe_overlay(tex_coord, screen_dimensions)
+        ),
         1.0
     );
 }
\ No newline at end of file
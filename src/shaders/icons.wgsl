@@ -2,7 +2,8 @@
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) tex_coord: vec2<f32>,
-    @location(1) opacity: f32
+    @location(1) opacity: f32,
+    @location(2) tint: vec4<f32>
 }
 
 const ICON_Z: f32 = 0.1;
@@ -20,12 +21,14 @@ var<uniform> atlas_dimensions: vec2<u32>;
 fn vs_main(
     @location(0) position: vec2<f32>,
     @location(1) instance_position: vec4<f32>, // Icon x, icon y, icon width, icon height
-    @location(2) icon_state: vec3<f32> // Icon atlas position, icon opacity
+    @location(2) icon_state: vec3<f32>, // Icon atlas position, icon opacity
+    @location(3) tint: vec4<f32> // The theme's color, and how much it replaces the icon's own
 ) -> VertexOutput {
     var out: VertexOutput;
     out.tex_coord = position / vec2<f32>(atlas_dimensions) + icon_state.xy;
     out.clip_position = ortho.v * vec4<f32>(position * instance_position.zw + instance_position.xy, ICON_Z, 1.0);
     out.opacity = icon_state.z;
+    out.tint = tint;
     return out;
 }
 
@@ -36,9 +39,11 @@ fn vs_main(
 @fragment
 fn fs_main(
     @location(0) tex_coord: vec2<f32>,
-    @location(1) opacity: f32
+    @location(1) opacity: f32,
+    @location(2) tint: vec4<f32>
 ) -> @location(0) vec4<f32> {
     var color = textureSample(r_icon_texture, r_icon_sampler, tex_coord);
+    color = vec4<f32>(mix(color.rgb, tint.rgb, tint.a), color.a);
     color.a *= opacity;
     return color;
 }
\ No newline at end of file
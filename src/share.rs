@@ -0,0 +1,71 @@
+use image::DynamicImage;
+use winit::window::Window;
+
+/// The handler registered for the current share, removed before the next one so only the latest data is offered
+#[cfg(windows)]
+static DATA_REQUESTED_TOKEN: std::sync::Mutex<Option<windows::Foundation::EventRegistrationToken>> = std::sync::Mutex::new(None);
+
+/// Opens the Windows share sheet for the overlay, offering the text and image to installed apps like Mail,
+/// OneNote, or Teams through the share contract.
+#[cfg(windows)]
+pub(crate) fn share(window: &Window, text: String, image: Option<&DynamicImage>) -> Result<(), String> {
+    use image::ImageFormat;
+    use windows::{
+        core::{factory, HSTRING},
+        ApplicationModel::DataTransfer::{DataRequestedEventArgs, DataTransferManager},
+        Foundation::TypedEventHandler,
+        Storage::Streams::{DataWriter, InMemoryRandomAccessStream, RandomAccessStreamReference},
+        Win32::{Foundation::HWND, UI::Shell::IDataTransferManagerInterop}
+    };
+    use winit::raw_window_handle_05::{HasRawWindowHandle, RawWindowHandle};
+
+    let RawWindowHandle::Win32(handle) = window.raw_window_handle() else {
+        return Err("Unable to get the overlay's window handle".to_string());
+    };
+    let hwnd = HWND(handle.hwnd as isize);
+    let error = |e: windows::core::Error| format!("Unable to share: {}", e.message());
+
+    // The image is written to a stream up front, since the data has to be ready when the share target asks for it
+    let bitmap = match image {
+        Some(image) => {
+            let mut png = std::io::Cursor::new(Vec::new());
+            image.write_to(&mut png, ImageFormat::Png).map_err(|e| format!("Unable to encode the image: {}", e))?;
+
+            let stream = InMemoryRandomAccessStream::new().map_err(error)?;
+            let writer = DataWriter::CreateDataWriter(&stream).map_err(error)?;
+            writer.WriteBytes(&png.into_inner()).map_err(error)?;
+            writer.StoreAsync().map_err(error)?.get().map_err(error)?;
+            writer.DetachStream().map_err(error)?;
+            stream.Seek(0).map_err(error)?;
+            Some(RandomAccessStreamReference::CreateFromStream(&stream).map_err(error)?)
+        }
+        None => None
+    };
+
+    let interop = factory::<DataTransferManager, IDataTransferManagerInterop>().map_err(error)?;
+    let manager: DataTransferManager = unsafe { interop.GetForWindow(hwnd) }.map_err(error)?;
+
+    let mut token = DATA_REQUESTED_TOKEN.lock().unwrap();
+    if let Some(previous_token) = token.take() {
+        let _ = manager.RemoveDataRequested(previous_token);
+    }
+    *token = Some(manager.DataRequested(&TypedEventHandler::<DataTransferManager, DataRequestedEventArgs>::new(move |_, args| {
+        let Some(args) = args.as_ref() else {
+            return Ok(());
+        };
+        let data = args.Request()?.Data()?;
+        data.Properties()?.SetTitle(&HSTRING::from("OnScreenOCR capture"))?;
+        data.SetText(&HSTRING::from(text.as_str()))?;
+        if let Some(bitmap) = &bitmap {
+            data.SetBitmap(bitmap)?;
+        }
+        Ok(())
+    })).map_err(error)?);
+
+    unsafe { interop.ShowShareUIForWindow(hwnd) }.map_err(error)
+}
+
+#[cfg(not(windows))]
+pub(crate) fn share(_window: &Window, _text: String, _image: Option<&DynamicImage>) -> Result<(), String> {
+    Err("Sharing is only supported on Windows".to_string())
+}
@@ -0,0 +1,157 @@
+use std::{net::{TcpListener, TcpStream}, thread};
+
+use clipboard::{ClipboardContext, ClipboardProvider};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tungstenite::{handshake::server::{ErrorResponse, Request, Response}, http::StatusCode, Message};
+use winit::event_loop::EventLoopProxy;
+
+use crate::{automation::{send_command, AutomationCommand}, settings::get_project_dirs, AppEvent, INITIALIZATION_ERRORS};
+
+const PRESETS_FILE_NAME: &str = "capture_presets.toml";
+/// Only local connections are accepted, since anything connected can read text off the screen. Browsers
+/// can still connect to local addresses, so handshakes from web pages are rejected in `check_origin`.
+const LISTEN_ADDRESS: &str = "127.0.0.1:28196";
+/// Stream Deck keys only fit a few characters per line
+const TITLE_LENGTH: usize = 12;
+
+/// A named region of the screen that a key can capture.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct CapturePreset {
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PresetsFile {
+    #[serde(default)]
+    presets: Vec<CapturePreset>,
+}
+
+fn load_presets() -> Vec<CapturePreset> {
+    let Ok(contents) = std::fs::read_to_string(get_project_dirs().config_dir().join(PRESETS_FILE_NAME)) else {
+        return Vec::new();
+    };
+
+    match toml::from_str::<PresetsFile>(&contents) {
+        Ok(file) => file.presets,
+        Err(error) => {
+            eprintln!("Failed to parse {}: {}", PRESETS_FILE_NAME, error);
+            INITIALIZATION_ERRORS.lock().unwrap().push(format!("Failed to parse {}: {}", PRESETS_FILE_NAME, error));
+            Vec::new()
+        }
+    }
+}
+
+/// Starts the WebSocket server for Stream Deck plugins, if any capture presets are defined.
+///
+/// The protocol mirrors the Stream Deck SDK, so a plugin can forward its `keyDown` and `willAppear`
+/// events as-is and pass our `setTitle` replies straight back to the Stream Deck application.
+/// The preset a key captures is read from `payload.settings.preset`.
+pub(crate) fn start(loop_proxy: EventLoopProxy<AppEvent>) {
+    let presets = load_presets();
+    if presets.is_empty() {
+        return;
+    }
+
+    thread::spawn(move || {
+        let listener = match TcpListener::bind(LISTEN_ADDRESS) {
+            Ok(listener) => listener,
+            Err(error) => {
+                eprintln!("Unable to listen for Stream Deck plugins on {}: {}", LISTEN_ADDRESS, error);
+                return;
+            }
+        };
+        println!("Listening for Stream Deck plugins on ws://{}", LISTEN_ADDRESS);
+
+        for stream in listener.incoming().flatten() {
+            let loop_proxy = loop_proxy.clone();
+            let presets = presets.clone();
+            thread::spawn(move || {
+                if let Err(error) = handle_client(stream, &loop_proxy, &presets) {
+                    eprintln!("Stream Deck plugin disconnected: {}", error);
+                }
+            });
+        }
+    });
+}
+
+fn handle_client(stream: TcpStream, loop_proxy: &EventLoopProxy<AppEvent>, presets: &[CapturePreset]) -> Result<(), tungstenite::Error> {
+    let mut socket = tungstenite::accept_hdr(stream, check_origin).map_err(|error| match error {
+        tungstenite::HandshakeError::Failure(error) => error,
+        tungstenite::HandshakeError::Interrupted(_) => tungstenite::Error::ConnectionClosed
+    })?;
+
+    loop {
+        let message = socket.read()?;
+        let Message::Text(text) = message else {
+            continue;
+        };
+        let Ok(event) = serde_json::from_str::<Value>(&text) else {
+            continue;
+        };
+
+        let context = event["context"].clone();
+        let title = match event["event"].as_str() {
+            Some("keyDown") => {
+                let preset_name = event["payload"]["settings"]["preset"].as_str().unwrap_or_default();
+                match presets.iter().find(|preset| preset.name == preset_name) {
+                    Some(preset) => capture_preset(loop_proxy, preset),
+                    None => format!("No preset\n{}", preset_name)
+                }
+            }
+            Some("willAppear") => {
+                send_command(loop_proxy, AutomationCommand::GetLastResult).map(|text| key_title(&text)).unwrap_or_default()
+            }
+            _ => continue
+        };
+
+        let reply = json!({
+            "event": "setTitle",
+            "context": context,
+            "payload": { "title": title, "target": 0 }
+        });
+        socket.send(Message::Text(reply.to_string()))?;
+    }
+}
+
+/// Rejects handshakes with an `Origin` header. Browsers always send one, so this keeps web pages from
+/// capturing presets or reading the last result, while plugins, which don't send one, can connect.
+fn check_origin(request: &Request, response: Response) -> Result<Response, ErrorResponse> {
+    if request.headers().contains_key("Origin") {
+        let mut error = ErrorResponse::new(Some("Connections from web pages aren't allowed".to_string()));
+        *error.status_mut() = StatusCode::FORBIDDEN;
+        return Err(error);
+    }
+    Ok(response)
+}
+
+/// Captures the preset's region and copies the text. Returns the title to show on the key.
+fn capture_preset(loop_proxy: &EventLoopProxy<AppEvent>, preset: &CapturePreset) -> String {
+    let command = AutomationCommand::CaptureRegion { x: preset.x, y: preset.y, width: preset.width, height: preset.height };
+    match send_command(loop_proxy, command) {
+        Ok(text) => {
+            let copied = ClipboardProvider::new().and_then(|mut ctx: ClipboardContext| ctx.set_contents(text.trim().to_string()));
+            if let Err(error) = copied {
+                eprintln!("Unable to copy the result of preset '{}': {}", preset.name, error);
+            }
+            key_title(&text)
+        }
+        Err(error) => {
+            eprintln!("Unable to capture preset '{}': {}", preset.name, error);
+            "Error".to_string()
+        }
+    }
+}
+
+fn key_title(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+    let mut title: String = text.chars().take(TITLE_LENGTH).collect();
+    if text.chars().count() > TITLE_LENGTH {
+        title.push('…');
+    }
+    title
+}
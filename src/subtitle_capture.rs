@@ -0,0 +1,87 @@
+use std::{collections::VecDeque, path::PathBuf};
+
+use crate::settings::get_project_dirs;
+
+/// How many of the most recent lines new captures are compared against. Subtitles often
+/// alternate between two lines, so comparing against only the last one isn't enough.
+const RECENT_LINE_COUNT: usize = 6;
+/// How similar (0-1) two lines have to be to count as the same subtitle. OCR of the same
+/// subtitle varies slightly between frames, especially over moving video.
+const SIMILARITY_THRESHOLD: f32 = 0.8;
+
+/// Accumulates the lines of hard-subbed video as they're recognized in live mode,
+/// skipping captures that repeat a subtitle that's still on screen.
+pub(crate) struct SubtitleCapture {
+    lines: Vec<String>,
+    recent: VecDeque<String>,
+}
+
+impl SubtitleCapture {
+    pub fn new() -> Self {
+        Self {
+            lines: Vec::new(),
+            recent: VecDeque::new()
+        }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Adds the lines of a new capture that aren't repeats. Returns how many were added.
+    pub fn record(&mut self, text: &str) -> usize {
+        let mut added = 0;
+        for line in text.lines() {
+            let line = line.split_whitespace().collect::<Vec<&str>>().join(" ");
+            if line.is_empty() || self.recent.iter().any(|recent| similarity(recent, &line) >= SIMILARITY_THRESHOLD) {
+                continue;
+            }
+
+            self.lines.push(line.clone());
+            self.recent.push_back(line);
+            if self.recent.len() > RECENT_LINE_COUNT {
+                self.recent.pop_front();
+            }
+            added += 1;
+        }
+        added
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// Saves the captured lines to a text file and returns its path.
+    pub fn save(&self) -> Result<PathBuf, String> {
+        let directory = get_project_dirs().data_dir().join("subtitles");
+        std::fs::create_dir_all(&directory).map_err(|e| format!("Unable to create subtitles directory: {}", e))?;
+
+        let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs();
+        let path = directory.join(format!("subtitles-{}.txt", timestamp));
+        std::fs::write(&path, self.text() + "\n").map_err(|e| format!("Unable to write subtitles: {}", e))?;
+        Ok(path)
+    }
+}
+
+/// The normalized Levenshtein similarity of two lines, ignoring case.
+fn similarity(a: &str, b: &str) -> f32 {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, a_char) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + if a_char == b_char { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    1. - previous[b.len()] as f32 / longest as f32
+}
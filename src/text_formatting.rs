@@ -0,0 +1,428 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct FormatOptions {
+    pub reformat_and_correct: bool,
+    pub maintain_newlines: bool,
+    pub code_mode: bool,
+    pub smart_paragraphs: bool,
+    pub collapse_spaces: bool,
+    pub trim_trailing_spaces: bool,
+    pub normalize_tabs: bool,
+    pub typography: TypographySettings,
+    pub strip_noise: bool,
+    pub programmer_mode: bool,
+    pub csv_delimiter: char,
+    pub pretty_json: bool,
+    pub export_mode: TesseractExportMode,
+}
+
+#[derive(Debug, Serialize, Copy, Clone, Deserialize, PartialEq, Eq, Hash)]
+pub enum TesseractExportMode {
+    UTF8,
+    HOCR,
+    Alto,
+    TSV,
+    /// The TSV output converted to CSV, for spreadsheets
+    CSV,
+    /// Blocks, lines, and words with their bounding boxes and confidences
+    JSON,
+    /// Totals, dates, and line items pulled out of a receipt or invoice, as CSV
+    Receipt
+}
+
+impl TesseractExportMode {
+    /// The file extension used when the result is saved to a file.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TesseractExportMode::UTF8 => "txt",
+            TesseractExportMode::HOCR => "hocr",
+            TesseractExportMode::Alto => "xml",
+            TesseractExportMode::TSV => "tsv",
+            TesseractExportMode::CSV => "csv",
+            TesseractExportMode::JSON => "json",
+            TesseractExportMode::Receipt => "csv"
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TesseractExportMode::UTF8 => "Text",
+            TesseractExportMode::HOCR => "hOCR",
+            TesseractExportMode::Alto => "ALTO",
+            TesseractExportMode::TSV => "TSV",
+            TesseractExportMode::CSV => "CSV",
+            TesseractExportMode::JSON => "JSON",
+            TesseractExportMode::Receipt => "Receipt"
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            TesseractExportMode::UTF8 => TesseractExportMode::HOCR,
+            TesseractExportMode::HOCR => TesseractExportMode::Alto,
+            TesseractExportMode::Alto => TesseractExportMode::TSV,
+            TesseractExportMode::TSV => TesseractExportMode::CSV,
+            TesseractExportMode::CSV => TesseractExportMode::JSON,
+            TesseractExportMode::JSON => TesseractExportMode::Receipt,
+            TesseractExportMode::Receipt => TesseractExportMode::UTF8
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum QuoteStyle {
+    #[default]
+    Unchanged,
+    /// Converts curly quotes to " and '
+    Straight,
+    /// Converts straight quotes to curly quotes, guessing whether each one opens or closes
+    Curly
+}
+
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum DashStyle {
+    #[default]
+    Unchanged,
+    /// Converts hyphen lookalikes (‐, ‑, ‒, −) to -, but keeps en and em dashes
+    Normalized,
+    /// Converts every dash to ASCII: en dashes become - and em dashes become --
+    Ascii
+}
+
+/// Optional typographic cleanup for plain text output.
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TypographySettings {
+    #[serde(default)]
+    pub quotes: QuoteStyle,
+    #[serde(default)]
+    pub dashes: DashStyle,
+    #[serde(default = "default_expand_ligatures")]
+    pub expand_ligatures: bool,
+}
+
+fn default_expand_ligatures() -> bool {
+    true
+}
+
+impl Default for TypographySettings {
+    fn default() -> Self {
+        Self {
+            quotes: QuoteStyle::default(),
+            dashes: DashStyle::default(),
+            expand_ligatures: default_expand_ligatures()
+        }
+    }
+}
+
+/// Escapes the characters that are special in HTML and XML text and attribute values.
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Removes the whitespace between XML elements. If the XML can't be parsed, it's returned unchanged.
+pub(crate) fn compact_xml(xml_string: &str) -> String {
+    let mut reader = quick_xml::Reader::from_str(xml_string);
+    let mut writer = quick_xml::Writer::new(std::io::Cursor::new(Vec::new()));
+
+    loop {
+        match reader.read_event() {
+            Ok(quick_xml::events::Event::Text(_)) => {}, // Skip text events
+            Ok(quick_xml::events::Event::Eof) => break,
+            Ok(e) => {
+                if writer.write_event(e).is_err() {
+                    return xml_string.to_string();
+                }
+            }
+            Err(e) => {
+                eprintln!("Unable to compact XML; error at position {}: {:?}", reader.buffer_position(), e);
+                return xml_string.to_string();
+            }
+        }
+    }
+
+    String::from_utf8(writer.into_inner().into_inner()).unwrap_or_else(|_| xml_string.to_string())
+}
+
+/// Cleans up the spurious double spaces, trailing spaces, and tabs that Tesseract tends to produce.
+pub(crate) fn normalize_whitespace(mut text: String, format_options: &FormatOptions) -> String {
+    if format_options.normalize_tabs {
+        // Tabs are real indentation in code, but otherwise they're just oddly-detected spaces
+        text = text.replace('\t', if format_options.code_mode { "    " } else { " " });
+    }
+
+    // Code mode uses runs of spaces to reproduce alignment, so they aren't spurious there
+    let collapse_spaces = format_options.collapse_spaces && !format_options.code_mode;
+    if !collapse_spaces && !format_options.trim_trailing_spaces {
+        return text;
+    }
+
+    text.split('\n').map(|line| {
+        let mut line = line.to_string();
+        if collapse_spaces {
+            // Keep any indentation, but collapse every other run of spaces into one
+            let (indentation, content) = line.split_at(line.len() - line.trim_start_matches(' ').len());
+            let mut collapsed = indentation.to_string();
+            for c in content.chars() {
+                if c == ' ' && collapsed.ends_with(' ') && collapsed.len() > indentation.len() {
+                    continue;
+                }
+                collapsed.push(c);
+            }
+            line = collapsed;
+        }
+        if format_options.trim_trailing_spaces {
+            line.truncate(line.trim_end().len());
+        }
+        line
+    }).collect::<Vec<String>>().join("\n")
+}
+
+pub(crate) fn normalize_typography(text: String, settings: &TypographySettings) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut previous: Option<char> = None;
+    for c in text.chars() {
+        match c {
+            // Ligatures
+            'ﬀ' if settings.expand_ligatures => output.push_str("ff"),
+            'ﬁ' if settings.expand_ligatures => output.push_str("fi"),
+            'ﬂ' if settings.expand_ligatures => output.push_str("fl"),
+            'ﬃ' if settings.expand_ligatures => output.push_str("ffi"),
+            'ﬄ' if settings.expand_ligatures => output.push_str("ffl"),
+            'ﬅ' | 'ﬆ' if settings.expand_ligatures => output.push_str("st"),
+
+            // Quotes
+            '“' | '”' | '„' | '‟' if settings.quotes == QuoteStyle::Straight => output.push('"'),
+            '‘' | '’' | '‚' | '‛' if settings.quotes == QuoteStyle::Straight => output.push('\''),
+            '"' | '\'' if settings.quotes == QuoteStyle::Curly => {
+                // A quote at the start of a word opens; anything else (including apostrophes) closes
+                let opening = match previous {
+                    Some(p) => p.is_whitespace() || "([{-–—".contains(p),
+                    None => true
+                };
+                output.push(match (c, opening) {
+                    ('"', true) => '“',
+                    ('"', false) => '”',
+                    (_, true) => '‘',
+                    (_, false) => '’'
+                });
+            }
+
+            // Dashes
+            '‐' | '‑' | '‒' | '−' if settings.dashes != DashStyle::Unchanged => output.push('-'),
+            '–' if settings.dashes == DashStyle::Ascii => output.push('-'),
+            '—' | '―' if settings.dashes == DashStyle::Ascii => output.push_str("--"),
+
+            _ => output.push(c)
+        }
+        previous = Some(c);
+    }
+    output
+}
+
+/// Corrects the symbols OCR commonly gets wrong in source code. Brackets and quotes are replaced with their
+/// ASCII forms, since lookalikes like fullwidth parentheses or guillemets are never valid syntax, and the
+/// spaces Tesseract inserts around underscores are removed so snake_case identifiers stay intact.
+pub(crate) fn fix_code_symbols(text: String) -> String {
+    let mut output = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '（' | '❨' | '⁽' => output.push('('),
+            '）' | '❩' | '⁾' => output.push(')'),
+            '［' | '【' | '〚' => output.push('['),
+            '］' | '】' | '〛' => output.push(']'),
+            '｛' | '❴' => output.push('{'),
+            '｝' | '❵' => output.push('}'),
+            '＜' | '‹' | '〈' | '⟨' | '〈' => output.push('<'),
+            '＞' | '›' | '〉' | '⟩' | '〉' => output.push('>'),
+            '«' => output.push_str("<<"),
+            '»' => output.push_str(">>"),
+            '“' | '”' | '„' | '‟' | '＂' => output.push('"'),
+            '‘' | '’' | '‚' | '‛' | '＇' => output.push('\''),
+            '‐' | '‑' | '‒' | '−' | '–' | '—' => output.push('-'),
+            '；' => output.push(';'),
+            '：' => output.push(':'),
+            '＝' => output.push('='),
+            _ => output.push(c)
+        }
+    }
+
+    output.lines().map(join_split_identifiers).collect::<Vec<String>>().join("\n")
+}
+
+/// Removes a space next to an underscore between identifier characters, e.g. "foo_ bar" becomes "foo_bar".
+fn join_split_identifiers(line: &str) -> String {
+    let is_identifier_char = |c: Option<&char>| c.is_some_and(|c| c.is_alphanumeric() || *c == '_');
+    let chars: Vec<char> = line.chars().collect();
+    chars.iter().enumerate().filter(|(i, c)| {
+        if **c != ' ' || *i < 2 {
+            return true;
+        }
+        let (before, after) = (chars.get(i - 1), chars.get(i + 1));
+        let split_after_underscore = before == Some(&'_') && is_identifier_char(chars.get(i - 2)) && is_identifier_char(after);
+        let split_before_underscore = after == Some(&'_') && is_identifier_char(before) && is_identifier_char(chars.get(i + 2));
+        !split_after_underscore && !split_before_underscore
+    }).map(|(_, c)| *c).collect()
+}
+
+pub(crate) fn reformat_and_correct_text(text: String, format_options: &FormatOptions, hyphenated_words: &[String]) -> String {
+    let smart_paragraphs = format_options.smart_paragraphs;
+
+    // Bullets come out of Tesseract as all sorts of glyphs, so settle on one marker style up front
+    let text = normalize_list_markers(&text);
+
+    // Blank lines separate paragraphs, so we need to keep them if we're joining paragraphs
+    let paragraphs = if smart_paragraphs { split_paragraphs(&text) } else { vec![text] };
+
+    let paragraphs = paragraphs.into_iter().map(|paragraph| {
+        // 1. If a line ends with a hyphen and the word isn't detected to be a hyphenated word, remove the hyphen
+        let paragraph = fix_end_of_line_hyphens(paragraph, hyphenated_words);
+        // 2. Join lines that were only broken because they reached the edge of the text block
+        if smart_paragraphs { join_wrapped_lines(&paragraph) } else { paragraph }
+    }).collect::<Vec<String>>();
+    let mut text = paragraphs.join(if smart_paragraphs { "\n\n" } else { "" });
+
+    if !format_options.maintain_newlines {
+        text = collapse_newlines(&text);
+    }
+
+    return text;
+}
+
+/// Replaces newlines with spaces, except before list items so every item stays on its own line
+fn collapse_newlines(text: &str) -> String {
+    let mut output = String::new();
+    for line in text.lines().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+        if !output.is_empty() {
+            output.push(if is_list_item(line) { '\n' } else { ' ' });
+        }
+        output.push_str(line);
+    }
+    output
+}
+
+const BULLET_GLYPHS: [char; 9] = ['•', '·', '◦', '▪', '‣', '●', '■', '-', '*'];
+
+/// If the line starts with a bullet or number marker, returns the normalized marker and the rest of the line.
+/// Bullets become "-" and numbers become "1.", regardless of whether they were written as "1." or "1)".
+fn split_list_marker(line: &str) -> Option<(String, &str)> {
+    let trimmed = line.trim_start();
+    let first = trimmed.chars().next()?;
+    let (marker, rest) = if BULLET_GLYPHS.contains(&first) {
+        ("-".to_string(), &trimmed[first.len_utf8()..])
+    } else {
+        let digits = trimmed.find(|c: char| !c.is_ascii_digit())?;
+        if digits == 0 || digits > 3 {
+            return None;
+        }
+        let rest = &trimmed[digits..];
+        if !rest.starts_with('.') && !rest.starts_with(')') {
+            return None;
+        }
+        (format!("{}.", &trimmed[..digits]), &rest[1..])
+    };
+
+    // Markers are always followed by a space, which rules out things like negative numbers, decimals, and *emphasis*
+    if !rest.starts_with(char::is_whitespace) || rest.trim().is_empty() {
+        return None;
+    }
+    Some((marker, rest.trim_start()))
+}
+
+fn is_list_item(line: &str) -> bool {
+    split_list_marker(line).is_some()
+}
+
+fn normalize_list_markers(text: &str) -> String {
+    text.lines().map(|line| match split_list_marker(line) {
+        Some((marker, rest)) => {
+            let indentation = &line[..line.len() - line.trim_start().len()];
+            format!("{}{} {}\n", indentation, marker, rest)
+        }
+        None => format!("{}\n", line)
+    }).collect()
+}
+
+fn split_paragraphs(text: &str) -> Vec<String> {
+    let mut paragraphs = vec![String::new()];
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            if !paragraphs.last().unwrap().is_empty() {
+                paragraphs.push(String::new());
+            }
+            continue;
+        }
+        let paragraph = paragraphs.last_mut().unwrap();
+        paragraph.push_str(line);
+        paragraph.push('\n');
+    }
+    paragraphs.retain(|paragraph| !paragraph.is_empty());
+    paragraphs
+}
+
+fn fix_end_of_line_hyphens(text: String, hyphenated_words: &[String]) -> String {
+    let mut lines = text.lines().map(|x| format!("{}\n", x.to_string())).collect::<Vec<String>>();
+
+    // Remove empty lines. This may not be an ideal solution, but it works for now.
+    lines.retain(|x| !x.trim().is_empty());
+
+    let lines_loop = lines.clone();
+    for (line, i) in lines_loop.iter().zip(0..) {
+        if line.ends_with("-\n") {
+            // A new list item never continues the word from the line before it
+            if lines_loop.get(i + 1).is_some_and(|x| is_list_item(x)) {
+                continue;
+            }
+
+            // The last word of this line plus the first word of the next is our query
+            let last_word = line.split_whitespace().last().unwrap_or("");
+            let next_first_word = lines_loop.get(i + 1).map(|x| x.split_whitespace().next()).flatten().unwrap_or("");
+            let query = format!("{}{}", last_word, next_first_word);
+            if hyphenated_words.contains(&query) {
+                continue;
+            }
+
+            // Remove the hyphen and the newline
+            lines.get_mut(i).map(|x| {
+                let last = x.pop();
+                if last == Some('\n') {
+                    x.pop();
+                }
+            });
+            // Add a newline to the end of the word on the next line to roughly maintain the same formatting
+            lines.get_mut(i + 1).map(|x| {
+                let words = x.split_whitespace().collect::<Vec<&str>>();
+                if let Some(first) = words.first() {
+                    *x = format!("{}\n{}", first, x.trim_start()[first.len()..].trim_start());
+                }
+            });
+        }
+    }
+    lines.join("")
+}
+
+/// Lines at least this fraction of the paragraph's longest line are assumed to have wrapped
+const WRAPPED_LINE_RATIO: f32 = 0.75;
+
+fn join_wrapped_lines(paragraph: &str) -> String {
+    let lines = paragraph.lines().map(|line| line.trim_end()).filter(|line| !line.is_empty()).collect::<Vec<&str>>();
+    let longest_line = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+
+    let mut text = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        text.push_str(line);
+        if i == lines.len() - 1 {
+            break;
+        }
+
+        // A short line ends its paragraph (or is a heading), a trailing colon usually introduces a new block,
+        // and list items always start on their own line
+        let wrapped = line.chars().count() as f32 >= longest_line as f32 * WRAPPED_LINE_RATIO;
+        if wrapped && !line.ends_with(':') && !is_list_item(lines[i + 1]) {
+            text.push(' ');
+        } else {
+            text.push('\n');
+        }
+    }
+    text
+}
@@ -0,0 +1,149 @@
+use std::{sync::{mpsc, Arc, LazyLock, Mutex}, thread};
+
+use crate::{geometry::{Point, Rect}, ocr_handler::{configure_tesseract, text_layout}, screenshot::{crop_screenshot_to_bounds, Screenshot}, settings::TesseractSettings};
+
+/// How far around the cursor smart select looks for text, in pixels
+const SMART_SELECT_AREA: (f32, f32) = (1200., 600.);
+/// How close to a paragraph a click has to be to select it, since lines have gaps between them
+const SMART_SELECT_MARGIN: f32 = 8.;
+
+/// Where the text on an image is, in pixels of the image.
+#[derive(Debug, Clone, Default)]
+pub struct TextLayout {
+    pub paragraphs: Vec<Rect>,
+    pub lines: Vec<Rect>
+}
+
+type LayoutResult = Arc<Mutex<Option<Result<TextLayout, String>>>>;
+
+struct LayoutJob {
+    tesseract_settings: TesseractSettings,
+    screenshot: Screenshot,
+    result: LayoutResult
+}
+
+/// Layout analysis runs on one long-lived thread, so its Tesseract instance is only created again when the
+/// model changes rather than for every search.
+static LAYOUT_WORKER: LazyLock<mpsc::Sender<LayoutJob>> = LazyLock::new(|| {
+    let (tx, rx) = mpsc::channel::<LayoutJob>();
+    thread::spawn(move || {
+        let mut engine: Option<(String, leptess::tesseract::TessApi)> = None;
+        for job in rx {
+            // Nobody is waiting for this one anymore, e.g. because the overlay was closed
+            if Arc::strong_count(&job.result) == 1 {
+                continue;
+            }
+
+            let model = job.tesseract_settings.active_model();
+            if !engine.as_ref().is_some_and(|(engine_model, _)| *engine_model == model) {
+                let mut tess_api = configure_tesseract(job.tesseract_settings);
+                // Layout analysis needs automatic page segmentation, whatever recognition is configured to use
+                let _ = tess_api.raw.set_variable(c"tessedit_pageseg_mode", c"3");
+                engine = Some((model, tess_api));
+            }
+            let (_, tess_api) = engine.as_mut().unwrap();
+            *job.result.lock().unwrap() = Some(text_layout(tess_api, &job.screenshot));
+        }
+    });
+    tx
+});
+
+/// Finds the lines and paragraphs of an image in the background, in pixels of the image.
+fn detect_layout(tesseract_settings: TesseractSettings, screenshot: Screenshot) -> LayoutResult {
+    let result = Arc::new(Mutex::new(None));
+    let job = LayoutJob { tesseract_settings, screenshot, result: result.clone() };
+    if LAYOUT_WORKER.send(job).is_err() {
+        *result.lock().unwrap() = Some(Err("The layout analysis thread stopped".to_string()));
+    }
+    result
+}
+
+/// Finds the paragraph under a point in the background, using layout analysis only, which is fast
+/// enough to feel like a single click.
+pub struct SmartSelect {
+    /// The point, the area around it that's analysed, and the result
+    detection: Option<(Point, Rect, LayoutResult)>,
+}
+
+impl SmartSelect {
+    pub fn new() -> Self {
+        SmartSelect { detection: None }
+    }
+
+    /// Starts looking for text around the point. Only one search runs at a time.
+    pub fn start(&mut self, tesseract_settings: TesseractSettings, screenshot: &Screenshot, point: Point) -> Result<(), String> {
+        if self.detection.is_some() {
+            return Err("Still looking for text".to_string());
+        }
+
+        // Only the area around the point is analysed, which keeps this quick on large monitors
+        let (width, height) = (SMART_SELECT_AREA.0.min(screenshot.width as f32), SMART_SELECT_AREA.1.min(screenshot.height as f32));
+        let left = (point.x - width / 2.).clamp(0., screenshot.width as f32 - width);
+        let top = (point.y - height / 2.).clamp(0., screenshot.height as f32 - height);
+        let area = Rect::new(left, top, width, height);
+        let cropped = crop_screenshot_to_bounds(area, screenshot);
+
+        self.detection = Some((point, area, detect_layout(tesseract_settings, cropped)));
+        Ok(())
+    }
+
+    /// Returns the smallest paragraph under the point once the search finishes, or None in the result if
+    /// there's no text there.
+    pub fn poll(&mut self) -> Option<Result<Option<Rect>, String>> {
+        let (point, area, state) = self.detection.as_ref()?;
+        let (point, area) = (*point, *area);
+        let result = state.lock().unwrap().take()?;
+        self.detection = None;
+
+        Some(result.map(|layout| {
+            layout.paragraphs.into_iter()
+                .map(|paragraph| Rect::new(paragraph.x + area.x, paragraph.y + area.y, paragraph.width, paragraph.height))
+                .filter(|paragraph| {
+                    Rect::new(
+                        paragraph.x - SMART_SELECT_MARGIN,
+                        paragraph.y - SMART_SELECT_MARGIN,
+                        paragraph.width + SMART_SELECT_MARGIN * 2.,
+                        paragraph.height + SMART_SELECT_MARGIN * 2.
+                    ).contains(point)
+                })
+                .min_by(|a, b| (a.width * a.height).total_cmp(&(b.width * b.height)))
+        }))
+    }
+}
+
+/// Finds the text on the whole screen in the background when the overlay opens. Its paragraphs are
+/// suggested as selections, and selection edges snap to its lines.
+pub struct ScreenLayout {
+    /// The minimum selection size, below which paragraphs aren't suggested, and the result
+    detection: Option<(u32, LayoutResult)>,
+}
+
+impl ScreenLayout {
+    pub fn new() -> Self {
+        ScreenLayout { detection: None }
+    }
+
+    /// Starts looking for text on the screenshot, replacing any earlier search. Paragraphs smaller than
+    /// the minimum selection size aren't suggested.
+    pub fn start(&mut self, tesseract_settings: TesseractSettings, screenshot: Screenshot) {
+        let minimum_size = tesseract_settings.minimum_selection_size;
+        self.detection = Some((minimum_size, detect_layout(tesseract_settings, screenshot)));
+    }
+
+    /// Stops waiting for the current search, e.g. because the screenshot it's looking at is gone.
+    pub fn cancel(&mut self) {
+        self.detection = None;
+    }
+
+    /// Returns the layout once the search finishes.
+    pub fn poll(&mut self) -> Option<Result<TextLayout, String>> {
+        let (minimum_size, state) = self.detection.as_ref()?;
+        let minimum_size = *minimum_size;
+        let result = state.lock().unwrap().take()?;
+        self.detection = None;
+        Some(result.map(|mut layout| {
+            layout.paragraphs.retain(|paragraph| !paragraph.smaller_than(minimum_size));
+            layout
+        }))
+    }
+}
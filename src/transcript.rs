@@ -0,0 +1,155 @@
+use std::{path::PathBuf, time::{Duration, Instant}};
+
+use crate::settings::get_project_dirs;
+
+/// The shortest time a cue stays on screen; OCR results that change faster than this
+/// are usually intermediate frames rather than real text changes.
+const MINIMUM_CUE_DURATION: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone)]
+struct TranscriptCue {
+    start: Duration,
+    end: Option<Duration>,
+    text: String,
+}
+
+/// Records every distinct OCR result with the time it appeared, so a session
+/// can be exported as subtitles.
+#[derive(Debug)]
+pub(crate) struct Transcript {
+    session_start: Instant,
+    cues: Vec<TranscriptCue>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum SubtitleFormat {
+    SRT,
+    VTT
+}
+
+impl SubtitleFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            SubtitleFormat::SRT => "srt",
+            SubtitleFormat::VTT => "vtt"
+        }
+    }
+}
+
+impl Transcript {
+    pub fn new() -> Self {
+        Self {
+            session_start: Instant::now(),
+            cues: Vec::new()
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.session_start = Instant::now();
+        self.cues.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cues.is_empty()
+    }
+
+    /// Records a new OCR result. Empty text ends the current cue, and text identical
+    /// to the current cue is ignored.
+    pub fn record(&mut self, text: Option<&str>) {
+        let now = self.session_start.elapsed();
+        let text = text.map(|t| t.trim()).filter(|t| !t.is_empty());
+
+        if let Some(last) = self.cues.last_mut() {
+            if last.end.is_none() {
+                if text == Some(last.text.as_str()) {
+                    return;
+                }
+                last.end = Some(now.max(last.start + MINIMUM_CUE_DURATION));
+            }
+        }
+
+        if let Some(text) = text {
+            // If the previous cue was forced to last longer than it actually did, start after it
+            let start = self.cues.last().and_then(|cue| cue.end).map_or(now, |end| end.max(now));
+            self.cues.push(TranscriptCue {
+                start,
+                end: None,
+                text: text.to_string()
+            });
+        }
+    }
+
+    fn closed_cues(&self) -> Vec<TranscriptCue> {
+        let now = self.session_start.elapsed();
+        self.cues.iter().map(|cue| {
+            let mut cue = cue.clone();
+            if cue.end.is_none() {
+                cue.end = Some(now.max(cue.start + MINIMUM_CUE_DURATION));
+            }
+            cue
+        }).collect()
+    }
+
+    pub fn to_srt(&self) -> String {
+        let mut output = String::new();
+        for (i, cue) in self.closed_cues().iter().enumerate() {
+            output.push_str(&format!(
+                "{}\n{} --> {}\n{}\n\n",
+                i + 1,
+                format_timestamp(cue.start, ','),
+                format_timestamp(cue.end.unwrap(), ','),
+                cue_text(&cue.text)
+            ));
+        }
+        output
+    }
+
+    pub fn to_vtt(&self) -> String {
+        let mut output = String::from("WEBVTT\n\n");
+        for cue in self.closed_cues() {
+            output.push_str(&format!(
+                "{} --> {}\n{}\n\n",
+                format_timestamp(cue.start, '.'),
+                format_timestamp(cue.end.unwrap(), '.'),
+                cue_text(&cue.text)
+            ));
+        }
+        output
+    }
+
+    /// Writes the transcript to the transcripts directory and returns the path of the new file.
+    pub fn export(&self, format: SubtitleFormat) -> Result<PathBuf, String> {
+        let directory = get_transcript_directory();
+        std::fs::create_dir_all(&directory).map_err(|e| format!("Unable to create transcript directory: {}", e))?;
+
+        let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs();
+        let path = directory.join(format!("transcript-{}.{}", timestamp, format.extension()));
+
+        let contents = match format {
+            SubtitleFormat::SRT => self.to_srt(),
+            SubtitleFormat::VTT => self.to_vtt()
+        };
+        std::fs::write(&path, contents).map_err(|e| format!("Unable to write transcript: {}", e))?;
+
+        Ok(path)
+    }
+}
+
+pub fn get_transcript_directory() -> PathBuf {
+    get_project_dirs().data_dir().join("transcripts")
+}
+
+/// A cue's text without blank lines, since a blank line ends a cue in both SRT and WebVTT.
+fn cue_text(text: &str) -> String {
+    text.lines().filter(|line| !line.trim().is_empty()).collect::<Vec<&str>>().join("\n")
+}
+
+/// Formats a duration as `HH:MM:SS,mmm` (SRT) or `HH:MM:SS.mmm` (VTT).
+fn format_timestamp(duration: Duration, millisecond_separator: char) -> String {
+    let total_millis = duration.as_millis();
+    let hours = total_millis / 3_600_000;
+    let minutes = (total_millis / 60_000) % 60;
+    let seconds = (total_millis / 1000) % 60;
+    let millis = total_millis % 1000;
+    format!("{:02}:{:02}:{:02}{}{:03}", hours, minutes, seconds, millisecond_separator, millis)
+}
@@ -0,0 +1,265 @@
+use serde::Serialize;
+
+/// A single row of Tesseract's TSV output.
+/// Levels are 1 = page, 2 = block, 3 = paragraph, 4 = line, 5 = word.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TSVRow {
+    pub level: u32,
+    pub page_num: u32,
+    pub block_num: u32,
+    pub par_num: u32,
+    pub line_num: u32,
+    pub word_num: u32,
+    pub left: i32,
+    pub top: i32,
+    pub width: i32,
+    pub height: i32,
+    pub conf: f32,
+    pub text: String,
+}
+
+pub(crate) const TSV_BLOCK_LEVEL: u32 = 2;
+pub(crate) const TSV_LINE_LEVEL: u32 = 4;
+pub(crate) const TSV_WORD_LEVEL: u32 = 5;
+
+impl TSVRow {
+    /// Parses a single TSV line. Returns None for the header and malformed lines.
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut columns = line.splitn(12, '\t');
+        Some(Self {
+            level: columns.next()?.trim().parse().ok()?,
+            page_num: columns.next()?.trim().parse().ok()?,
+            block_num: columns.next()?.trim().parse().ok()?,
+            par_num: columns.next()?.trim().parse().ok()?,
+            line_num: columns.next()?.trim().parse().ok()?,
+            word_num: columns.next()?.trim().parse().ok()?,
+            left: columns.next()?.trim().parse().ok()?,
+            top: columns.next()?.trim().parse().ok()?,
+            width: columns.next()?.trim().parse().ok()?,
+            height: columns.next()?.trim().parse().ok()?,
+            conf: columns.next()?.trim().parse().ok()?,
+            text: columns.next().unwrap_or("").to_string(),
+        })
+    }
+
+    /// Identifies the line this row belongs to.
+    pub fn line_key(&self) -> (u32, u32, u32, u32) {
+        (self.page_num, self.block_num, self.par_num, self.line_num)
+    }
+}
+
+/// Tesseract's TSV output doesn't include the header row its file renderer writes.
+const TSV_HEADER: [&str; 12] = ["level", "page_num", "block_num", "par_num", "line_num", "word_num", "left", "top", "width", "height", "conf", "text"];
+
+/// Converts a TSV export to CSV with a header row, quoting any field that contains the delimiter,
+/// a quote, or a line break.
+pub(crate) fn tsv_to_csv(tsv: &str, delimiter: char) -> String {
+    let quote = |field: &str| {
+        if field.contains([delimiter, '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_string()
+        }
+    };
+    let separator = delimiter.to_string();
+
+    let header = TSV_HEADER.join(&separator);
+    let rows = tsv.lines()
+        .filter(|line| !line.is_empty() && !line.starts_with("level\t"))
+        .map(|line| line.split('\t').map(quote).collect::<Vec<String>>().join(&separator));
+    std::iter::once(header).chain(rows).collect::<Vec<String>>().join("\n")
+}
+
+pub(crate) fn parse_tsv(tsv: &str) -> Vec<TSVRow> {
+    tsv.lines().filter_map(TSVRow::parse).collect()
+}
+
+/// Returns the words of a TSV export grouped by line, in reading order.
+pub(crate) fn words_by_line(tsv: &str) -> Vec<Vec<TSVRow>> {
+    let mut lines: Vec<Vec<TSVRow>> = Vec::new();
+    for row in parse_tsv(tsv).into_iter().filter(|row| row.level == TSV_WORD_LEVEL && !row.text.trim().is_empty()) {
+        match lines.last_mut() {
+            Some(line) if line[0].line_key() == row.line_key() => line.push(row),
+            _ => lines.push(vec![row])
+        }
+    }
+    lines
+}
+
+/// Limits on the whitespace inserted by `layout_preserving_text`, so a bogus word position can't produce huge output
+const MAX_COLUMNS: usize = 500;
+const MAX_BLANK_LINES: usize = 10;
+
+/// Converts a horizontal distance in pixels to a number of spaces.
+fn columns(distance: f32, character_width: f32) -> usize {
+    ((distance / character_width).round() as usize).min(MAX_COLUMNS)
+}
+
+/// Rebuilds text from word positions, using spaces to approximate the horizontal layout.
+/// This keeps indentation and alignment that plain UTF-8 output throws away, which matters for code.
+pub(crate) fn layout_preserving_text(tsv: &str) -> String {
+    let lines = words_by_line(tsv);
+    if lines.is_empty() {
+        return String::new();
+    }
+
+    // Estimate the width of a single character from every word
+    let mut character_widths: Vec<f32> = lines.iter().flatten()
+        .filter(|word| word.width > 0)
+        .map(|word| word.width as f32 / word.text.chars().count().max(1) as f32)
+        .collect();
+    character_widths.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let character_width = character_widths.get(character_widths.len() / 2).copied().unwrap_or(1.).max(1.);
+
+    let mut line_heights: Vec<i32> = lines.iter().map(|line| line.iter().map(|word| word.height).max().unwrap_or(0)).collect();
+    line_heights.sort();
+    let line_height = line_heights[line_heights.len() / 2].max(1);
+
+    let min_left = lines.iter().map(|line| line[0].left).min().unwrap_or(0) as f32;
+
+    let mut output = Vec::with_capacity(lines.len());
+    let mut last_top: Option<i32> = None;
+    for line in lines {
+        // Blank lines don't show up in the TSV, so infer them from large vertical gaps
+        let top = line.iter().map(|word| word.top).min().unwrap_or(0);
+        if let Some(last_top) = last_top {
+            // Lines are usually spaced a bit more than their height apart
+            let line_pitches = ((top as f32 - last_top as f32) / (line_height as f32 * 1.6)).round() as usize;
+            for _ in 1..line_pitches.clamp(1, MAX_BLANK_LINES + 1) {
+                output.push(String::new());
+            }
+        }
+        last_top = Some(top);
+
+        let mut text = " ".repeat(columns(line[0].left as f32 - min_left, character_width));
+        let mut cursor = line[0].left as f32;
+        for (i, word) in line.iter().enumerate() {
+            if i > 0 {
+                text.push_str(&" ".repeat(columns(word.left as f32 - cursor, character_width).max(1)));
+            }
+            text.push_str(&word.text);
+            cursor = word.left as f32 + word.width as f32;
+        }
+        output.push(text);
+    }
+
+    output.join("\n")
+}
+
+/// Words this short with a confidence below `NOISE_MAX_CONFIDENCE` are dropped as noise
+const NOISE_MAX_WORD_LENGTH: usize = 2;
+const NOISE_MAX_CONFIDENCE: f32 = 40.;
+/// Lines where at least this fraction of the characters are punctuation are dropped as noise
+const NOISE_PUNCTUATION_RATIO: f32 = 0.6;
+
+fn is_noise_word(word: &TSVRow) -> bool {
+    word.text.trim().chars().count() <= NOISE_MAX_WORD_LENGTH && word.conf < NOISE_MAX_CONFIDENCE
+}
+
+fn is_mostly_punctuation(line: &str) -> bool {
+    let characters = line.chars().filter(|c| !c.is_whitespace()).collect::<Vec<char>>();
+    let punctuation = characters.iter().filter(|c| !c.is_alphanumeric()).count();
+    punctuation as f32 >= characters.len() as f32 * NOISE_PUNCTUATION_RATIO
+}
+
+/// Rebuilds plain text like Tesseract's UTF-8 output, but without the stray low-confidence
+/// characters and punctuation lines that watermarks, screen noise, and UI chrome produce.
+pub(crate) fn text_without_noise(tsv: &str) -> String {
+    let mut output = String::new();
+    let mut last_paragraph = None;
+    for line in words_by_line(tsv) {
+        let text = line.iter().filter(|word| !is_noise_word(word)).map(|word| word.text.trim()).collect::<Vec<&str>>().join(" ");
+        if text.is_empty() || is_mostly_punctuation(&text) {
+            continue;
+        }
+
+        // Paragraphs are separated by a blank line, the same as the UTF-8 output
+        let paragraph = (line[0].page_num, line[0].block_num, line[0].par_num);
+        if last_paragraph.is_some_and(|last| last != paragraph) {
+            output.push('\n');
+        }
+        last_paragraph = Some(paragraph);
+
+        output.push_str(&text);
+        output.push('\n');
+    }
+    output
+}
+
+#[derive(Serialize)]
+struct BoundingBox {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+}
+
+impl BoundingBox {
+    fn new(row: &TSVRow, origin: (f32, f32)) -> Self {
+        Self {
+            x: row.left as f32 + origin.0,
+            y: row.top as f32 + origin.1,
+            width: row.width as f32,
+            height: row.height as f32
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct StructuredWord {
+    text: String,
+    confidence: f32,
+    bbox: BoundingBox,
+}
+
+#[derive(Serialize)]
+struct StructuredLine {
+    text: String,
+    bbox: BoundingBox,
+    words: Vec<StructuredWord>,
+}
+
+#[derive(Serialize)]
+struct StructuredBlock {
+    bbox: BoundingBox,
+    lines: Vec<StructuredLine>,
+}
+
+/// Converts a TSV export into a JSON document of blocks, lines, and words with their bounding boxes and
+/// confidences, for tools that consume the result programmatically. Positions are moved by `origin` (x, y) so
+/// they line up with the full screenshot, the same as the word boxes.
+pub(crate) fn structured_json(tsv: &str, origin: (f32, f32), pretty: bool) -> String {
+    let mut blocks: Vec<StructuredBlock> = Vec::new();
+    for row in parse_tsv(tsv) {
+        match row.level {
+            TSV_BLOCK_LEVEL => blocks.push(StructuredBlock { bbox: BoundingBox::new(&row, origin), lines: Vec::new() }),
+            TSV_LINE_LEVEL => {
+                if let Some(block) = blocks.last_mut() {
+                    block.lines.push(StructuredLine { text: String::new(), bbox: BoundingBox::new(&row, origin), words: Vec::new() });
+                }
+            }
+            TSV_WORD_LEVEL if !row.text.trim().is_empty() => {
+                if let Some(line) = blocks.last_mut().and_then(|block| block.lines.last_mut()) {
+                    line.words.push(StructuredWord { text: row.text.trim().to_string(), confidence: row.conf, bbox: BoundingBox::new(&row, origin) });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Image regions and separators show up as blocks without any words
+    for block in &mut blocks {
+        block.lines.retain(|line| !line.words.is_empty());
+        for line in &mut block.lines {
+            line.text = line.words.iter().map(|word| word.text.as_str()).collect::<Vec<&str>>().join(" ");
+        }
+    }
+    blocks.retain(|block| !block.lines.is_empty());
+
+    let document = serde_json::json!({ "blocks": blocks });
+    let encoded = if pretty { serde_json::to_string_pretty(&document) } else { serde_json::to_string(&document) };
+    encoded.unwrap_or_else(|e| {
+        eprintln!("Unable to encode the OCR result as JSON: {}", e);
+        String::new()
+    })
+}
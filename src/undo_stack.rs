@@ -1,62 +1,337 @@
-use crate::selection::{Bounds, Polygon, Selection};
+use std::time::{Duration, Instant};
 
+use crate::{geometry::{Point, Rect}, selection::Selection};
+
+/// How soon after the last keyboard nudge another one is merged into the same undo step
+const NUDGE_MERGE_TIME: Duration = Duration::from_millis(600);
+
+/// Keeps the selection's history as the changes between its states rather than copies of each one, so
+/// moving a large polygon around only stores how far it moved. Only the last `limit` changes are kept.
 pub(crate) struct UndoStack {
-    stack: Vec<SelectionSnapshot>,
-    current_index: usize
+    /// The state the selection was in after the first `current_index` changes, or None until the first snapshot
+    current: Option<SelectionSnapshot>,
+    changes: Vec<SelectionChange>,
+    current_index: usize,
+    limit: usize,
+    /// When the change on top of the stack was last updated by a nudge, if it was
+    last_nudge: Option<Instant>
 }
 
 impl UndoStack {
     pub fn new() -> Self {
         UndoStack {
-            stack: Vec::new(),
-            current_index: 0
+            current: None,
+            changes: Vec::new(),
+            current_index: 0,
+            limit: usize::MAX,
+            last_nudge: None
         }
     }
 
     pub fn take_snapshot(&mut self, selection: &Selection) {
-        self.current_index += 1;
-        self.stack.truncate(self.current_index);
-        self.stack.push(SelectionSnapshot::from(selection));
+        self.last_nudge = None;
+        self.record(selection);
     }
 
-    pub fn undo(&mut self, selection: &mut Selection) -> Result<(), ()> {
-        if self.current_index > 0 {
+    /// Records how the selection changed since the last snapshot, returning whether it changed at all.
+    fn record(&mut self, selection: &Selection) -> bool {
+        let snapshot = SelectionSnapshot::from(selection);
+        let Some(current) = self.current.take() else {
+            self.current = Some(snapshot);
+            return false;
+        };
+        if current == snapshot {
+            self.current = Some(current);
+            return false;
+        }
+
+        self.changes.truncate(self.current_index);
+        self.changes.push(SelectionChange::between(&current, &snapshot));
+        self.current_index += 1;
+        self.current = Some(snapshot);
+
+        if self.changes.len() > self.limit {
+            self.changes.remove(0);
             self.current_index -= 1;
-            selection.bounds = self.stack[self.current_index].bounds.clone();
-            selection.polygon = self.stack[self.current_index].polygon.clone();
-            Ok(())
-        } else {
-            Err(())
         }
+        true
     }
 
-    pub fn redo(&mut self, selection: &mut Selection) -> Result<(), ()> {
-        if self.current_index < self.stack.len() - 1 {
-            self.current_index += 1;
-            selection.bounds = self.stack[self.current_index].bounds.clone();
-            selection.polygon = self.stack[self.current_index].polygon.clone();
-            Ok(())
-        } else {
-            Err(())
+    /// Takes a snapshot after a keyboard nudge. Nudges in quick succession, like from holding an arrow key,
+    /// replace each other's snapshot so they're undone in one step.
+    pub fn take_nudge_snapshot(&mut self, selection: &Selection) {
+        let merge = self.last_nudge.is_some_and(|last_nudge| last_nudge.elapsed() < NUDGE_MERGE_TIME)
+            && self.current_index == self.changes.len() && self.current_index > 0;
+        if merge {
+            // Undo the previous nudge without touching the selection, then record the combined change
+            if let (Some(mut change), Some(current)) = (self.changes.pop(), self.current.as_mut()) {
+                change.revert(current);
+                self.current_index -= 1;
+            }
+        }
+        // If the nudges cancelled out, there's nothing left on top to merge the next one into
+        self.last_nudge = self.record(selection).then(Instant::now);
+    }
+
+    /// The vertices the selection would go back to if undone, without undoing it.
+    pub fn peek_undo(&self) -> Option<Vec<Point>> {
+        let current = self.current.as_ref().filter(|_| self.current_index > 0)?;
+        match &self.changes[self.current_index - 1] {
+            SelectionChange::Moved(offset) => Some(current.vertices.iter().map(|vertex| *vertex - *offset).collect()),
+            SelectionChange::Reshaped(before) => Some(before.vertices.clone())
         }
     }
 
-    pub fn reset(&mut self) {
-        self.stack.clear();
+    pub fn undo(&mut self, selection: &mut Selection) -> Result<(), ()> {
+        let Some(current) = self.current.as_mut().filter(|_| self.current_index > 0) else {
+            return Err(());
+        };
+
+        self.last_nudge = None;
+        self.current_index -= 1;
+        self.changes[self.current_index].revert(current);
+        current.restore(selection);
+        Ok(())
+    }
+
+    pub fn redo(&mut self, selection: &mut Selection) -> Result<(), ()> {
+        let Some(current) = self.current.as_mut().filter(|_| self.current_index < self.changes.len()) else {
+            return Err(());
+        };
+
+        self.last_nudge = None;
+        self.changes[self.current_index].apply(current);
+        self.current_index += 1;
+        current.restore(selection);
+        Ok(())
+    }
+
+    /// Clears the history, keeping at most `limit` steps of it from now on.
+    pub fn reset(&mut self, limit: usize) {
+        self.current = None;
+        self.changes.clear();
         self.current_index = 0;
+        self.limit = limit;
+        self.last_nudge = None;
     }
 }
 
+#[derive(Clone, PartialEq)]
 struct SelectionSnapshot {
-    pub bounds: Bounds,
-    pub polygon: Polygon
+    pub bounds: Rect,
+    pub vertices: Vec<Point>
 }
 
 impl From<&Selection> for SelectionSnapshot {
     fn from(selection: &Selection) -> Self {
         SelectionSnapshot {
             bounds: selection.bounds.clone(),
-            polygon: selection.polygon.clone()
+            vertices: selection.polygon.points()
+        }
+    }
+}
+
+impl SelectionSnapshot {
+    fn restore(&self, selection: &mut Selection) {
+        selection.bounds = self.bounds.clone();
+        selection.polygon.set_from_points(&self.vertices);
+        // The hovered vertex or edge may not exist in the restored shape
+        selection.polygon.hovered_vertex = None;
+        selection.polygon.hovered_edge = None;
+    }
+}
+
+/// How the selection changed between two snapshots. Moves only store the offset; anything else stores
+/// the snapshot on the other side of the change, which is swapped with the current one to cross it.
+/// A change is only stored as a move if adding and subtracting the offset give back both snapshots
+/// exactly, so undoing and redoing never drifts because of float rounding.
+enum SelectionChange {
+    Moved(Point),
+    Reshaped(SelectionSnapshot)
+}
+
+impl SelectionChange {
+    fn between(before: &SelectionSnapshot, after: &SelectionSnapshot) -> Self {
+        if let (Some(first_before), Some(first_after)) = (before.vertices.first(), after.vertices.first()) {
+            let offset = *first_after - *first_before;
+            let moves_exactly = |a: Point, b: Point| a + offset == b && b - offset == a;
+            let moved = before.vertices.len() == after.vertices.len()
+                && (before.bounds.width, before.bounds.height) == (after.bounds.width, after.bounds.height)
+                && moves_exactly(Point::new(before.bounds.x, before.bounds.y), Point::new(after.bounds.x, after.bounds.y))
+                && before.vertices.iter().zip(&after.vertices).all(|(a, b)| moves_exactly(*a, *b));
+            if moved {
+                return SelectionChange::Moved(offset);
+            }
+        }
+
+        SelectionChange::Reshaped(before.clone())
+    }
+
+    fn apply(&mut self, snapshot: &mut SelectionSnapshot) {
+        match self {
+            SelectionChange::Moved(offset) => snapshot.move_by(*offset),
+            SelectionChange::Reshaped(other) => std::mem::swap(snapshot, other)
+        }
+    }
+
+    fn revert(&mut self, snapshot: &mut SelectionSnapshot) {
+        match self {
+            SelectionChange::Moved(offset) => snapshot.move_by(Point::new(-offset.x, -offset.y)),
+            SelectionChange::Reshaped(other) => std::mem::swap(snapshot, other)
+        }
+    }
+}
+
+impl SelectionSnapshot {
+    fn move_by(&mut self, offset: Point) {
+        self.bounds.x += offset.x;
+        self.bounds.y += offset.y;
+        for vertex in self.vertices.iter_mut() {
+            *vertex = *vertex + offset;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selection(bounds: Rect, vertices: &[(f32, f32)]) -> Selection {
+        let mut selection = Selection::default();
+        selection.bounds = bounds;
+        selection.polygon.set_from_points(&vertices.iter().map(|&(x, y)| Point::new(x, y)).collect::<Vec<Point>>());
+        selection
+    }
+
+    fn rectangle(x: f32, y: f32, width: f32, height: f32) -> Selection {
+        selection(Rect::new(x, y, width, height), &[(x, y), (x + width, y), (x + width, y + height), (x, y + height)])
+    }
+
+    fn assert_same(a: &Selection, b: &Selection) {
+        assert_eq!(a.bounds, b.bounds);
+        assert_eq!(a.polygon.points(), b.polygon.points());
+    }
+
+    #[test]
+    fn undo_and_redo_round_trip_exactly() {
+        // Offsets like these can't be added and subtracted back exactly in floating point
+        let states = [
+            rectangle(0.1, 0.2, 100.3, 50.7),
+            rectangle(0.3, 0.9, 100.3, 50.7),
+            rectangle(1e6 + 0.1, 3.3, 100.3, 50.7),
+            selection(Rect::new(5., 5., 20., 20.), &[(5., 5.), (25., 5.), (15., 25.)]),
+            selection(Rect::new(7.7, 5.1, 20., 20.), &[(7.7, 5.1), (27.7, 5.1), (17.7, 25.1)])
+        ];
+
+        let mut stack = UndoStack::new();
+        for state in &states {
+            stack.take_snapshot(state);
+        }
+
+        let mut current = states.last().unwrap().clone();
+        for _ in 0..3 {
+            for expected in states.iter().rev().skip(1) {
+                stack.undo(&mut current).unwrap();
+                assert_same(&current, expected);
+            }
+            assert!(stack.undo(&mut current).is_err());
+
+            for expected in states.iter().skip(1) {
+                stack.redo(&mut current).unwrap();
+                assert_same(&current, expected);
+            }
+            assert!(stack.redo(&mut current).is_err());
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn peek_undo_matches_undo() {
+        let mut stack = UndoStack::new();
+        stack.take_snapshot(&rectangle(0.1, 0.2, 10., 10.));
+        let mut current = rectangle(0.7, 0.4, 10., 10.);
+        stack.take_snapshot(&current);
+
+        let peeked = stack.peek_undo().unwrap();
+        stack.undo(&mut current).unwrap();
+        assert_eq!(peeked, current.polygon.points());
+    }
+
+    #[test]
+    fn snapshot_after_undo_drops_redo() {
+        let mut stack = UndoStack::new();
+        stack.take_snapshot(&rectangle(0., 0., 10., 10.));
+        let mut current = rectangle(5., 0., 10., 10.);
+        stack.take_snapshot(&current);
+
+        stack.undo(&mut current).unwrap();
+        stack.take_snapshot(&rectangle(0., 0., 20., 20.));
+        assert!(stack.redo(&mut current).is_err());
+    }
+
+    #[test]
+    fn oldest_changes_are_evicted_at_the_limit() {
+        let mut stack = UndoStack::new();
+        stack.reset(2);
+        let states: Vec<Selection> = (0..5).map(|i| rectangle(i as f32 * 3.3, 0., 10., 10. + i as f32)).collect();
+        for state in &states {
+            stack.take_snapshot(state);
+        }
+
+        let mut current = states[4].clone();
+        stack.undo(&mut current).unwrap();
+        assert_same(&current, &states[3]);
+        stack.undo(&mut current).unwrap();
+        assert_same(&current, &states[2]);
+        assert!(stack.undo(&mut current).is_err());
+        assert_same(&current, &states[2]);
+
+        stack.redo(&mut current).unwrap();
+        stack.redo(&mut current).unwrap();
+        assert_same(&current, &states[4]);
+    }
+
+    #[test]
+    fn quick_nudges_merge_into_one_step() {
+        let mut stack = UndoStack::new();
+        let start = rectangle(0.1, 0.1, 10., 10.);
+        stack.take_snapshot(&start);
+        stack.take_snapshot(&rectangle(20., 0.1, 10., 10.));
+        for i in 1..=5 {
+            stack.take_nudge_snapshot(&rectangle(20. + i as f32 * 0.1, 0.1, 10., 10.));
+        }
+
+        let mut current = rectangle(20.5, 0.1, 10., 10.);
+        stack.undo(&mut current).unwrap();
+        assert_same(&current, &rectangle(20., 0.1, 10., 10.));
+        stack.undo(&mut current).unwrap();
+        assert_same(&current, &start);
+        assert!(stack.undo(&mut current).is_err());
+    }
+
+    #[test]
+    fn nudges_that_cancel_out_leave_no_step() {
+        let mut stack = UndoStack::new();
+        let start = rectangle(0., 0., 10., 10.);
+        stack.take_snapshot(&start);
+        stack.take_nudge_snapshot(&rectangle(1., 0., 10., 10.));
+        stack.take_nudge_snapshot(&start);
+
+        let mut current = start.clone();
+        assert!(stack.undo(&mut current).is_err());
+    }
+
+    #[test]
+    fn a_regular_snapshot_ends_nudge_merging() {
+        let mut stack = UndoStack::new();
+        stack.take_snapshot(&rectangle(0., 0., 10., 10.));
+        stack.take_nudge_snapshot(&rectangle(1., 0., 10., 10.));
+        stack.take_snapshot(&rectangle(1., 0., 20., 10.));
+        stack.take_nudge_snapshot(&rectangle(2., 0., 20., 10.));
+
+        let mut current = rectangle(2., 0., 20., 10.);
+        stack.undo(&mut current).unwrap();
+        assert_same(&current, &rectangle(1., 0., 20., 10.));
+        stack.undo(&mut current).unwrap();
+        assert_same(&current, &rectangle(1., 0., 10., 10.));
+    }
+}
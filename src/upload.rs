@@ -0,0 +1,249 @@
+use std::{sync::{Arc, Mutex}, thread, time::Duration};
+
+use clipboard::{ClipboardContext, ClipboardProvider};
+use hmac::{Hmac, Mac};
+use image::{DynamicImage, ImageFormat};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::export::utc_now;
+
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where selection images are uploaded to.
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ImageHost {
+    #[default]
+    Imgur,
+    /// Any S3-compatible object storage, e.g. AWS S3, Cloudflare R2, or MinIO
+    S3,
+    /// An endpoint accepting a multipart form upload
+    Custom
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UploadSettings {
+    #[serde(default)]
+    pub host: ImageHost,
+
+    #[serde(default)]
+    pub imgur_client_id: String,
+
+    #[serde(default)]
+    pub s3_endpoint: String,
+    #[serde(default = "default_s3_region")]
+    pub s3_region: String,
+    #[serde(default)]
+    pub s3_bucket: String,
+    #[serde(default)]
+    pub s3_access_key: String,
+    #[serde(default)]
+    pub s3_secret_key: String,
+    /// The public address of the bucket, if it differs from the endpoint, e.g. a CDN
+    #[serde(default)]
+    pub s3_public_url: String,
+
+    #[serde(default)]
+    pub custom_url: String,
+    #[serde(default = "default_custom_field")]
+    pub custom_field: String,
+    /// A JSON pointer to the URL in the response, or empty if the response is the URL itself
+    #[serde(default)]
+    pub custom_response_url: String,
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
+fn default_custom_field() -> String {
+    "file".to_string()
+}
+
+impl Default for UploadSettings {
+    fn default() -> Self {
+        Self {
+            host: ImageHost::default(),
+            imgur_client_id: String::new(),
+            s3_endpoint: String::new(),
+            s3_region: default_s3_region(),
+            s3_bucket: String::new(),
+            s3_access_key: String::new(),
+            s3_secret_key: String::new(),
+            s3_public_url: String::new(),
+            custom_url: String::new(),
+            custom_field: default_custom_field(),
+            custom_response_url: String::new()
+        }
+    }
+}
+
+/// Uploads selection images in the background and copies the resulting URL to the clipboard.
+pub struct Uploader {
+    upload: Option<Arc<Mutex<Option<Result<String, String>>>>>,
+}
+
+impl Uploader {
+    pub fn new() -> Self {
+        Uploader { upload: None }
+    }
+
+    pub fn is_uploading(&self) -> bool {
+        self.upload.is_some()
+    }
+
+    /// Starts uploading the image. Only one image is uploaded at a time.
+    pub fn start(&mut self, settings: UploadSettings, image: DynamicImage) -> Result<(), String> {
+        if self.is_uploading() {
+            return Err("An image is already being uploaded".to_string());
+        }
+
+        let state = Arc::new(Mutex::new(None));
+        let thread_state = state.clone();
+        thread::spawn(move || {
+            // The URL is copied here so it reaches the clipboard even if the overlay closes in the meantime
+            let result = upload(&settings, &image).and_then(|url| {
+                let mut ctx: ClipboardContext = ClipboardProvider::new().map_err(|e| format!("Unable to open the clipboard: {}", e))?;
+                ctx.set_contents(url.clone()).map_err(|e| format!("Unable to copy the URL: {}", e))?;
+                Ok(url)
+            });
+            *thread_state.lock().unwrap() = Some(result);
+        });
+
+        self.upload = Some(state);
+        Ok(())
+    }
+
+    /// Returns the URL or error once an upload finishes.
+    pub fn poll(&mut self) -> Option<Result<String, String>> {
+        let result = self.upload.as_ref()?.lock().unwrap().take()?;
+        self.upload = None;
+        Some(result)
+    }
+}
+
+fn upload(settings: &UploadSettings, image: &DynamicImage) -> Result<String, String> {
+    let mut png = std::io::Cursor::new(Vec::new());
+    image.write_to(&mut png, ImageFormat::Png).map_err(|e| format!("Unable to encode the image: {}", e))?;
+    let png = png.into_inner();
+
+    let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_millis();
+    let file_name = format!("ocr-{}.png", timestamp);
+
+    match settings.host {
+        ImageHost::Imgur => {
+            if settings.imgur_client_id.trim().is_empty() {
+                return Err("Set imgur_client_id in the configuration to upload to Imgur".to_string());
+            }
+            let (content_type, body) = multipart_body("image", &file_name, &png);
+            let response = ureq::post("https://api.imgur.com/3/image")
+                .timeout(UPLOAD_TIMEOUT)
+                .set("Authorization", &format!("Client-ID {}", settings.imgur_client_id.trim()))
+                .set("Content-Type", &content_type)
+                .send_bytes(&body)
+                .map_err(|e| format!("Unable to upload to Imgur: {}", e))?
+                .into_string()
+                .map_err(|e| format!("Unable to read the Imgur response: {}", e))?;
+            url_from_response(&response, "/data/link")
+        }
+        ImageHost::S3 => upload_to_s3(settings, &file_name, &png),
+        ImageHost::Custom => {
+            if settings.custom_url.trim().is_empty() {
+                return Err("Set custom_url in the configuration to upload to a custom endpoint".to_string());
+            }
+            let (content_type, body) = multipart_body(&settings.custom_field, &file_name, &png);
+            let response = ureq::post(settings.custom_url.trim())
+                .timeout(UPLOAD_TIMEOUT)
+                .set("Content-Type", &content_type)
+                .send_bytes(&body)
+                .map_err(|e| format!("Unable to upload the image: {}", e))?
+                .into_string()
+                .map_err(|e| format!("Unable to read the upload response: {}", e))?;
+            url_from_response(&response, &settings.custom_response_url)
+        }
+    }
+}
+
+/// Reads the URL at the JSON pointer, or the whole response if the pointer is empty.
+fn url_from_response(response: &str, pointer: &str) -> Result<String, String> {
+    if pointer.is_empty() {
+        return Ok(response.trim().to_string());
+    }
+
+    let json: Value = serde_json::from_str(response).map_err(|e| format!("Unexpected upload response: {}", e))?;
+    json.pointer(pointer)
+        .and_then(|url| url.as_str())
+        .map(|url| url.to_string())
+        .ok_or_else(|| format!("The upload response has no URL at {}", pointer))
+}
+
+fn multipart_body(field: &str, file_name: &str, data: &[u8]) -> (String, Vec<u8>) {
+    let boundary = format!("----OnScreenOCR{}", std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_nanos());
+
+    let mut body = Vec::with_capacity(data.len() + 256);
+    body.extend_from_slice(format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: image/png\r\n\r\n",
+        boundary, field, file_name
+    ).as_bytes());
+    body.extend_from_slice(data);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+
+    (format!("multipart/form-data; boundary={}", boundary), body)
+}
+
+/// Uploads the object with a path-style PUT request signed with AWS Signature Version 4, which
+/// S3-compatible services accept as well.
+/// https://docs.aws.amazon.com/AmazonS3/latest/API/sig-v4-header-based-auth.html
+fn upload_to_s3(settings: &UploadSettings, file_name: &str, data: &[u8]) -> Result<String, String> {
+    let endpoint = settings.s3_endpoint.trim().trim_end_matches('/');
+    if endpoint.is_empty() || settings.s3_bucket.is_empty() || settings.s3_access_key.is_empty() || settings.s3_secret_key.is_empty() {
+        return Err("Set s3_endpoint, s3_bucket, s3_access_key, and s3_secret_key in the configuration to upload to S3".to_string());
+    }
+    let host = endpoint.split("://").last().unwrap_or(endpoint).split('/').next().unwrap_or_default();
+    let path = format!("/{}/{}", settings.s3_bucket, file_name);
+
+    let (date, time_of_day) = utc_now();
+    let date = date.replace('-', "");
+    let amz_date = format!("{}T{:02}{:02}{:02}Z", date, time_of_day / 3600, time_of_day / 60 % 60, time_of_day % 60);
+    let payload_hash = hex(&Sha256::digest(data));
+
+    let signed_headers = "content-type;host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+        "PUT\n{}\n\ncontent-type:image/png\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        path, host, payload_hash, amz_date, signed_headers, payload_hash
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, settings.s3_region);
+    let string_to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{}", amz_date, scope, hex(&Sha256::digest(canonical_request.as_bytes())));
+
+    let signing_key = [settings.s3_region.as_str(), "s3", "aws4_request"].iter()
+        .fold(hmac_sha256(format!("AWS4{}", settings.s3_secret_key).as_bytes(), date.as_bytes()), |key, part| hmac_sha256(&key, part.as_bytes()));
+    let signature = hex(&hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+    ureq::put(&format!("{}{}", endpoint, path))
+        .timeout(UPLOAD_TIMEOUT)
+        .set("Content-Type", "image/png")
+        .set("x-amz-content-sha256", &payload_hash)
+        .set("x-amz-date", &amz_date)
+        .set("Authorization", &format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            settings.s3_access_key, scope, signed_headers, signature
+        ))
+        .send_bytes(data)
+        .map_err(|e| format!("Unable to upload to S3: {}", e))?;
+
+    Ok(match settings.s3_public_url.trim().trim_end_matches('/') {
+        "" => format!("{}{}", endpoint, path),
+        public_url => format!("{}/{}", public_url, file_name)
+    })
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
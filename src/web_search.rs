@@ -0,0 +1,41 @@
+/// Fills the search engine template with the text, replacing `{query}` with it URL-encoded.
+pub(crate) fn search_url(template: &str, text: &str) -> String {
+    // Line breaks from multi-line selections become spaces, since search engines treat them as such anyway
+    let query = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+    template.replace("{query}", &url_encode(&query))
+}
+
+fn url_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            b' ' => "+".to_string(),
+            byte => format!("%{:02X}", byte)
+        })
+        .collect()
+}
+
+/// Opens the URL in the default browser.
+pub(crate) fn open_in_browser(url: &str) -> Result<(), String> {
+    #[cfg(windows)]
+    let mut command = {
+        // Unlike `cmd /C start`, this doesn't treat the & between query parameters as a command separator
+        let mut command = std::process::Command::new("rundll32");
+        command.args(["url.dll,FileProtocolHandler", url]);
+        command
+    };
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut command = std::process::Command::new("open");
+        command.arg(url);
+        command
+    };
+    #[cfg(not(any(windows, target_os = "macos")))]
+    let mut command = {
+        let mut command = std::process::Command::new("xdg-open");
+        command.arg(url);
+        command
+    };
+
+    command.spawn().map(|_| ()).map_err(|e| format!("Unable to open the browser: {}", e))
+}
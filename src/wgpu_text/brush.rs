@@ -136,6 +136,11 @@ where
         self.inner.fonts()
     }
 
+    /// Adds another font, returning the `FontId` sections can use it with.
+    pub fn add_font<I: Into<F>>(&mut self, font: I) -> glyph_brush::FontId {
+        self.inner.add_font(font)
+    }
+
     /// Draws all sections queued with [`queue`](#method.queue) function.
     #[inline]
     pub fn draw<'pass>(&'pass self, rpass: &mut wgpu::RenderPass<'pass>) {
@@ -0,0 +1,166 @@
+use std::{ffi::CStr, ops::Range};
+
+use tesseract_sys::{
+    TessBaseAPI, TessBaseAPIGetIterator, TessChoiceIteratorConfidence, TessChoiceIteratorDelete, TessChoiceIteratorGetUTF8Text,
+    TessChoiceIteratorNext, TessDeleteText, TessPageIteratorIsAtBeginningOf, TessPageIteratorLevel, TessPageIteratorLevel_RIL_SYMBOL,
+    TessPageIteratorLevel_RIL_WORD, TessResultIterator, TessResultIteratorConfidence, TessResultIteratorDelete,
+    TessResultIteratorGetChoiceIterator, TessResultIteratorGetPageIterator, TessResultIteratorGetUTF8Text, TessResultIteratorNext
+};
+
+/// Words recognized with a lower confidence than this (0-100) can be swapped for an alternative in the preview
+const UNCERTAIN_WORD_CONFIDENCE: f32 = 70.;
+const MAX_ALTERNATIVES: usize = 5;
+
+/// A low-confidence word in the OCR result, along with what else Tesseract thought it could be.
+#[derive(Debug, Clone)]
+pub(crate) struct UncertainWord {
+    /// The byte range of the word in the final (formatted) text
+    pub range: Range<usize>,
+    pub alternatives: Vec<String>,
+}
+
+struct WordChoices {
+    text: String,
+    /// How many bytes of `text` the symbols seen so far cover
+    consumed: usize,
+    alternatives: Vec<(String, f32)>,
+}
+
+/// Finds the low-confidence words of the last recognition and builds alternatives for each
+/// by swapping single characters for Tesseract's other choices, most confident first.
+pub(crate) fn uncertain_word_choices(api: &mut leptess::tesseract::TessApi) -> Vec<(String, Vec<String>)> {
+    let handle: *mut TessBaseAPI = *api.raw.as_ref();
+    let mut words = Vec::new();
+
+    unsafe {
+        let iterator = TessBaseAPIGetIterator(handle);
+        if iterator.is_null() {
+            return words;
+        }
+        let page_iterator = TessResultIteratorGetPageIterator(iterator);
+
+        let mut current: Option<WordChoices> = None;
+        loop {
+            if TessPageIteratorIsAtBeginningOf(page_iterator, TessPageIteratorLevel_RIL_WORD) != 0 {
+                if let Some(word) = current.take() {
+                    words.extend(finish_word(word));
+                }
+                let confidence = TessResultIteratorConfidence(iterator, TessPageIteratorLevel_RIL_WORD);
+                if confidence < UNCERTAIN_WORD_CONFIDENCE {
+                    if let Some(text) = iterator_text(iterator, TessPageIteratorLevel_RIL_WORD) {
+                        current = Some(WordChoices { text, consumed: 0, alternatives: Vec::new() });
+                    }
+                }
+            }
+
+            if let Some(word) = &mut current {
+                add_symbol_alternatives(iterator, word);
+            }
+
+            if TessResultIteratorNext(iterator, TessPageIteratorLevel_RIL_SYMBOL) == 0 {
+                break;
+            }
+        }
+        if let Some(word) = current.take() {
+            words.extend(finish_word(word));
+        }
+
+        TessResultIteratorDelete(iterator);
+    }
+
+    words
+}
+
+/// Adds an alternative to `word` for every other choice Tesseract had for the current symbol.
+unsafe fn add_symbol_alternatives(iterator: *mut TessResultIterator, word: &mut WordChoices) {
+    let Some(symbol) = iterator_text(iterator, TessPageIteratorLevel_RIL_SYMBOL) else {
+        return;
+    };
+    let symbol_range = word.consumed..(word.consumed + symbol.len()).min(word.text.len());
+    word.consumed = symbol_range.end;
+    if word.text.get(symbol_range.clone()) != Some(symbol.as_str()) {
+        return;
+    }
+
+    let choices = TessResultIteratorGetChoiceIterator(iterator);
+    if choices.is_null() {
+        return;
+    }
+
+    loop {
+        let choice = TessChoiceIteratorGetUTF8Text(choices);
+        if !choice.is_null() {
+            let choice = CStr::from_ptr(choice).to_string_lossy().into_owned();
+            if choice != symbol && !choice.trim().is_empty() {
+                let mut alternative = word.text.clone();
+                alternative.replace_range(symbol_range.clone(), &choice);
+                word.alternatives.push((alternative, TessChoiceIteratorConfidence(choices)));
+            }
+        }
+        if TessChoiceIteratorNext(choices) == 0 {
+            break;
+        }
+    }
+    TessChoiceIteratorDelete(choices);
+}
+
+fn finish_word(mut word: WordChoices) -> Option<(String, Vec<String>)> {
+    word.alternatives.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let mut alternatives: Vec<String> = Vec::new();
+    for (alternative, _) in word.alternatives {
+        if alternative != word.text && !alternatives.contains(&alternative) {
+            alternatives.push(alternative);
+        }
+    }
+    alternatives.truncate(MAX_ALTERNATIVES);
+
+    if alternatives.is_empty() {
+        None
+    } else {
+        Some((word.text, alternatives))
+    }
+}
+
+unsafe fn iterator_text(iterator: *mut TessResultIterator, level: TessPageIteratorLevel) -> Option<String> {
+    let text = TessResultIteratorGetUTF8Text(iterator, level);
+    if text.is_null() {
+        return None;
+    }
+    let result = CStr::from_ptr(text).to_string_lossy().into_owned();
+    TessDeleteText(text);
+    Some(result)
+}
+
+/// Locates each word in the final text, in order. Formatting can change or drop words,
+/// so any word that can't be found is skipped.
+pub(crate) fn locate_uncertain_words(text: &str, words: Vec<(String, Vec<String>)>) -> Vec<UncertainWord> {
+    let mut located = Vec::new();
+    let mut search_start = 0;
+    for (word, alternatives) in words {
+        let Some(offset) = find_whole_word(&text[search_start..], &word) else {
+            continue;
+        };
+        let start = search_start + offset;
+        search_start = start + word.len();
+        located.push(UncertainWord { range: start..search_start, alternatives });
+    }
+    located
+}
+
+/// Finds the byte offset of the first occurrence of a word that isn't part of a longer word, so e.g.
+/// "in" isn't found inside "thinking".
+pub(crate) fn find_whole_word(text: &str, word: &str) -> Option<usize> {
+    let joined = |a: Option<char>, b: Option<char>| a.zip(b).is_some_and(|(a, b)| a.is_alphanumeric() && b.is_alphanumeric());
+    let first = word.chars().next()?;
+    let mut search_start = 0;
+    while let Some(offset) = text[search_start..].find(word) {
+        let start = search_start + offset;
+        let end = start + word.len();
+        if !joined(text[..start].chars().next_back(), Some(first)) && !joined(word.chars().next_back(), text[end..].chars().next()) {
+            return Some(start);
+        }
+        // Matches can overlap, so only skip past the first character
+        search_start = start + first.len_utf8();
+    }
+    None
+}